@@ -17,7 +17,15 @@ use schemars::{
   schema_for,
 };
 use tauri_utils::{
-  acl::{build::CapabilityFile, capability::Capability, plugin::Manifest},
+  acl::{
+    build::{
+      autogenerate_command_permissions, generate_docs, parse_permission_files, CapabilityFile,
+      AUTOGENERATED_FOLDER_NAME,
+    },
+    capability::{Capability, CapabilityContext},
+    plugin::Manifest,
+    RemoteUrlPattern, APP_ACL_KEY,
+  },
   platform::Target,
 };
 
@@ -26,20 +34,61 @@ const CAPABILITIES_SCHEMA_FILE_NAME: &str = "schema.json";
 const CAPABILITIES_SCHEMA_FOLDER_PATH: &str = "gen/schemas";
 const CAPABILITIES_FILE_NAME: &str = "capabilities.json";
 const PLUGIN_MANIFESTS_FILE_NAME: &str = "plugin-manifests.json";
+/// Path of the folder containing the application permissions.
+const APP_PERMISSIONS_FOLDER_PATH: &str = "permissions";
+
+/// Definition of the application commands, used to generate the application permissions.
+///
+/// Application permissions are referenced in capabilities without a prefix, e.g. `allow-my-command`.
+/// They are only enforced when the `app > security > appAcl` configuration option is enabled.
+#[derive(Debug, Default)]
+pub struct AppManifest {
+  commands: &'static [&'static str],
+}
+
+impl AppManifest {
+  /// Creates a new, empty application manifest.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Sets the commands registered on the app invoke handler.
+  ///
+  /// An `allow-$command` and a `deny-$command` permission is generated for each command
+  /// in the `permissions/autogenerated` folder.
+  ///
+  /// When the application has permissions, [`tauri::generate_handler!`] fails to compile
+  /// if one of its commands is not allowed by any of them, so a command missing from this list is caught at build time.
+  ///
+  /// [`tauri::generate_handler!`]: https://docs.rs/tauri/latest/tauri/macro.generate_handler.html
+  #[must_use]
+  pub fn commands(mut self, commands: &'static [&'static str]) -> Self {
+    self.commands = commands;
+    self
+  }
+}
+
+/// The identifier of a permission as referenced in a capability.
+fn permission_identifier(plugin: &str, id: &str) -> String {
+  if plugin == APP_ACL_KEY {
+    id.to_string()
+  } else {
+    format!("{plugin}:{id}")
+  }
+}
 
 fn capabilities_schema(plugin_manifests: &BTreeMap<String, Manifest>) -> RootSchema {
   let mut schema = schema_for!(CapabilityFile);
 
   fn schema_from(plugin: &str, id: &str, description: Option<&str>) -> Schema {
+    let identifier = permission_identifier(plugin, id);
     Schema::Object(SchemaObject {
       metadata: Some(Box::new(Metadata {
-        description: description
-          .as_ref()
-          .map(|d| format!("{plugin}:{id} -> {d}")),
+        description: description.as_ref().map(|d| format!("{identifier} -> {d}")),
         ..Default::default()
       })),
       instance_type: Some(InstanceType::String.into()),
-      enum_values: Some(vec![serde_json::Value::String(format!("{plugin}:{id}"))]),
+      enum_values: Some(vec![serde_json::Value::String(identifier)]),
       ..Default::default()
     })
   }
@@ -234,6 +283,31 @@ pub fn get_plugin_manifests() -> Result<BTreeMap<String, Manifest>> {
   Ok(processed)
 }
 
+/// Generates the application command permissions and reads the application permission manifest.
+pub fn app_manifest(app_manifest: AppManifest) -> Result<Option<Manifest>> {
+  let permissions_dir = PathBuf::from(APP_PERMISSIONS_FOLDER_PATH);
+  let autogenerated = permissions_dir.join(AUTOGENERATED_FOLDER_NAME);
+
+  if !app_manifest.commands.is_empty() {
+    autogenerate_command_permissions(&autogenerated.join("commands"), app_manifest.commands, "");
+  }
+
+  if !permissions_dir.exists() {
+    return Ok(None);
+  }
+
+  println!("cargo:rerun-if-changed={APP_PERMISSIONS_FOLDER_PATH}");
+
+  let permission_files = parse_permission_files(&format!("./{APP_PERMISSIONS_FOLDER_PATH}/**/*"))
+    .context("failed to read application permissions")?;
+  tauri_utils::acl::build::generate_schema(&permission_files, &permissions_dir)?;
+  if autogenerated.exists() {
+    generate_docs(&permission_files, &autogenerated)?;
+  }
+
+  Ok(Some(Manifest::new(permission_files, None)))
+}
+
 pub fn validate_capabilities(
   plugin_manifests: &BTreeMap<String, Manifest>,
   capabilities: &BTreeMap<String, Capability>,
@@ -245,41 +319,56 @@ pub fn validate_capabilities(
       continue;
     }
 
+    if let CapabilityContext::Remote { urls } = &capability.context {
+      for url in urls {
+        url.parse::<RemoteUrlPattern>().with_context(|| {
+          format!(
+            "invalid remote URL pattern on capability {}",
+            capability.identifier
+          )
+        })?;
+      }
+    }
+
     for permission_entry in &capability.permissions {
       let permission_id = permission_entry.identifier();
-      if let Some((plugin_name, permission_name)) = permission_id.get().split_once(':') {
-        let permission_exists = plugin_manifests
-          .get(plugin_name)
-          .map(|manifest| {
-            if permission_name == "default" {
-              manifest.default_permission.is_some()
-            } else {
-              manifest.permissions.contains_key(permission_name)
-                || manifest.permission_sets.contains_key(permission_name)
-            }
-          })
-          .unwrap_or(false);
-
-        if !permission_exists {
-          let mut available_permissions = Vec::new();
-          for (plugin, manifest) in plugin_manifests {
-            if manifest.default_permission.is_some() {
-              available_permissions.push(format!("{plugin}:default"));
-            }
-            for p in manifest.permissions.keys() {
-              available_permissions.push(format!("{plugin}:{p}"));
-            }
-            for p in manifest.permission_sets.keys() {
-              available_permissions.push(format!("{plugin}:{p}"));
-            }
+      // permissions without a prefix reference the application permissions
+      let (plugin_name, permission_name) = permission_id
+        .get()
+        .split_once(':')
+        .unwrap_or((APP_ACL_KEY, permission_id.get()));
+
+      let permission_exists = plugin_manifests
+        .get(plugin_name)
+        .map(|manifest| {
+          if permission_name == "default" {
+            manifest.default_permission.is_some()
+          } else {
+            manifest.permissions.contains_key(permission_name)
+              || manifest.permission_sets.contains_key(permission_name)
+          }
+        })
+        .unwrap_or(false);
+
+      if !permission_exists {
+        let mut available_permissions = Vec::new();
+        for (plugin, manifest) in plugin_manifests {
+          if manifest.default_permission.is_some() {
+            available_permissions.push(permission_identifier(plugin, "default"));
+          }
+          for p in manifest.permissions.keys() {
+            available_permissions.push(permission_identifier(plugin, p));
+          }
+          for p in manifest.permission_sets.keys() {
+            available_permissions.push(permission_identifier(plugin, p));
           }
-
-          anyhow::bail!(
-            "Permission {} not found, expected one of {}",
-            permission_id.get(),
-            available_permissions.join(", ")
-          );
         }
+
+        anyhow::bail!(
+          "Permission {} not found, expected one of {}",
+          permission_id.get(),
+          available_permissions.join(", ")
+        );
       }
     }
   }
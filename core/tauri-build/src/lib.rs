@@ -18,7 +18,7 @@ use cargo_toml::Manifest;
 use heck::AsShoutySnakeCase;
 
 use tauri_utils::{
-  acl::build::parse_capabilities,
+  acl::{build::parse_capabilities, APP_ACL_KEY},
   config::{BundleResources, Config, WebviewInstallMode},
   resources::{external_binaries, ResourcePaths},
 };
@@ -40,6 +40,8 @@ mod static_vcruntime;
 #[cfg_attr(docsrs, doc(cfg(feature = "codegen")))]
 pub use codegen::context::CodegenContext;
 
+pub use acl::AppManifest;
+
 const PLUGIN_MANIFESTS_FILE_NAME: &str = "plugin-manifests.json";
 const CAPABILITIES_FILE_NAME: &str = "capabilities.json";
 
@@ -339,6 +341,7 @@ pub struct Attributes {
   capabilities_path_pattern: Option<&'static str>,
   #[cfg(feature = "codegen")]
   codegen: Option<codegen::context::CodegenContext>,
+  app_manifest: AppManifest,
 }
 
 impl Attributes {
@@ -365,6 +368,15 @@ impl Attributes {
     self
   }
 
+  /// Sets the application manifest for the Access Control List.
+  ///
+  /// See [`AppManifest`] for more information.
+  #[must_use]
+  pub fn app_manifest(mut self, manifest: AppManifest) -> Self {
+    self.app_manifest = manifest;
+    self
+  }
+
   #[cfg(feature = "codegen")]
   #[cfg_attr(docsrs, doc(cfg(feature = "codegen")))]
   #[must_use]
@@ -473,7 +485,10 @@ pub fn try_build(attributes: Attributes) -> Result<()> {
   let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
 
   manifest::check(&config, &mut manifest)?;
-  let plugin_manifests = acl::get_plugin_manifests()?;
+  let mut plugin_manifests = acl::get_plugin_manifests()?;
+  if let Some(app_manifest) = acl::app_manifest(attributes.app_manifest)? {
+    plugin_manifests.insert(APP_ACL_KEY.into(), app_manifest);
+  }
   std::fs::write(
     out_dir.join(PLUGIN_MANIFESTS_FILE_NAME),
     serde_json::to_string(&plugin_manifests)?,
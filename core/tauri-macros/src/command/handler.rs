@@ -2,23 +2,107 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use std::{collections::BTreeMap, path::PathBuf};
+
 use quote::format_ident;
 use syn::{
   parse::{Parse, ParseBuffer, ParseStream},
-  Attribute, Ident, Path, Token,
+  spanned::Spanned,
+  Attribute, Ident, LitStr, Path, Token,
 };
+use tauri_utils::acl::{plugin::Manifest, APP_ACL_KEY};
+
+/// The file where `tauri-build` saves the plugin and application permissions.
+const PLUGIN_MANIFESTS_FILE_NAME: &str = "plugin-manifests.json";
 
 struct CommandDef {
   path: Path,
   attrs: Vec<Attribute>,
 }
 
-impl Parse for CommandDef {
+/// The commands of an `impl` block, declared as `impl Type` or `impl Type as "prefix"`.
+struct CommandGroupDef {
+  ty: Path,
+  prefix: Option<LitStr>,
+  attrs: Vec<Attribute>,
+}
+
+enum Def {
+  Command(CommandDef),
+  Group(CommandGroupDef),
+}
+
+impl Parse for Def {
   fn parse(input: ParseStream) -> syn::Result<Self> {
     let attrs = input.call(Attribute::parse_outer)?;
+    if input.parse::<Option<Token![impl]>>()?.is_some() {
+      let ty = input.parse()?;
+      let prefix = if input.parse::<Option<Token![as]>>()?.is_some() {
+        Some(input.parse()?)
+      } else {
+        None
+      };
+      return Ok(Def::Group(CommandGroupDef { ty, prefix, attrs }));
+    }
     let path = input.parse()?;
 
-    Ok(CommandDef { path, attrs })
+    Ok(Def::Command(CommandDef { path, attrs }))
+  }
+}
+
+/// Reads the application permissions generated by `tauri-build`, if the crate has any.
+fn app_manifest() -> syn::Result<Option<Manifest>> {
+  let Ok(out_dir) = std::env::var("OUT_DIR") else {
+    return Ok(None);
+  };
+  let path = PathBuf::from(out_dir).join(PLUGIN_MANIFESTS_FILE_NAME);
+  if !path.exists() {
+    return Ok(None);
+  }
+  let mut manifests: BTreeMap<String, Manifest> = std::fs::read_to_string(&path)
+    .map_err(|e| e.to_string())
+    .and_then(|manifests| serde_json::from_str(&manifests).map_err(|e| e.to_string()))
+    .map_err(|e| {
+      syn::Error::new(
+        proc_macro2::Span::call_site(),
+        format!("failed to read {}: {e}", path.display()),
+      )
+    })?;
+  Ok(manifests.remove(APP_ACL_KEY))
+}
+
+/// Checks that every command has a permission allowing it in the application permissions,
+/// otherwise no capability can allow it when the application ACL is enabled.
+fn check_app_permissions(command_defs: &[CommandDef], commands: &[Ident]) -> syn::Result<()> {
+  let Some(manifest) = app_manifest()? else {
+    return Ok(());
+  };
+
+  let missing = command_defs
+    .iter()
+    .zip(commands)
+    .filter(|(_, command)| {
+      let name = command.to_string();
+      !manifest
+        .permissions
+        .values()
+        .any(|permission| permission.commands.allow.contains(&name))
+    })
+    .collect::<Vec<_>>();
+
+  match missing.first() {
+    Some((def, _)) => Err(syn::Error::new(
+      def.path.span(),
+      format!(
+        "commands without an application permission: {}, add them to `tauri_build::AppManifest::commands` or allow them in a file of the `permissions` folder",
+        missing
+          .iter()
+          .map(|(_, command)| format!("`{command}`"))
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+    )),
+    None => Ok(()),
   }
 }
 
@@ -27,14 +111,22 @@ pub struct Handler {
   command_defs: Vec<CommandDef>,
   commands: Vec<Ident>,
   wrappers: Vec<Path>,
+  groups: Vec<CommandGroupDef>,
 }
 
 impl Parse for Handler {
   fn parse(input: &ParseBuffer<'_>) -> syn::Result<Self> {
-    let command_defs = input.parse_terminated(CommandDef::parse, Token![,])?;
+    let mut command_defs = Vec::new();
+    let mut groups = Vec::new();
+    for def in input.parse_terminated(Def::parse, Token![,])? {
+      match def {
+        Def::Command(def) => command_defs.push(def),
+        Def::Group(def) => groups.push(def),
+      }
+    }
 
     // parse the command names and wrappers from the passed paths
-    let (commands, wrappers) = command_defs
+    let (commands, wrappers): (Vec<_>, Vec<_>) = command_defs
       .iter()
       .map(|command_def| {
         let mut wrapper = command_def.path.clone();
@@ -50,10 +142,13 @@ impl Parse for Handler {
       })
       .unzip();
 
+    check_app_permissions(&command_defs, &commands)?;
+
     Ok(Self {
-      command_defs: command_defs.into_iter().collect(), // remove punctuation separators
+      command_defs,
       commands,
       wrappers,
+      groups,
     })
   }
 }
@@ -64,6 +159,7 @@ impl From<Handler> for proc_macro::TokenStream {
       command_defs,
       commands,
       wrappers,
+      groups,
     }: Handler,
   ) -> Self {
     let cmd = format_ident!("__tauri_cmd__");
@@ -72,14 +168,33 @@ impl From<Handler> for proc_macro::TokenStream {
       .into_iter()
       .map(|def| (def.path, def.attrs))
       .unzip();
+
+    // each group runs the command matching one of its methods, otherwise the next one is tried
+    let groups = groups.into_iter().map(|group| {
+      let CommandGroupDef { ty, prefix, attrs } = group;
+      let mut macro_path = ty.clone();
+      let last = super::path_to_command(&mut macro_path);
+      last.ident = super::format_command_group(&last.ident);
+      last.arguments = Default::default();
+      let run = match prefix {
+        Some(prefix) => quote::quote!(
+          if let ::std::option::Option::Some(#cmd) = #cmd.strip_prefix(#prefix) {
+            #macro_path!(#ty, #cmd, #invoke);
+          }
+        ),
+        None => quote::quote!(#macro_path!(#ty, #cmd, #invoke);),
+      };
+      quote::quote!(#(#attrs)* { #run })
+    });
+
     quote::quote!(move |#invoke| {
       let #cmd = #invoke.message.command();
       match #cmd {
         #(#(#attrs)* stringify!(#commands) => #wrappers!(#paths, #invoke),)*
-        _ => {
-          return false;
-        },
+        _ => {},
       }
+      #(#groups)*
+      false
     })
     .into()
   }
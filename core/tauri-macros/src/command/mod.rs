@@ -5,7 +5,10 @@
 use proc_macro2::Ident;
 use syn::{Path, PathSegment};
 
-pub use self::{handler::Handler, wrapper::wrapper};
+pub use self::{
+  handler::Handler,
+  wrapper::{commands, wrapper},
+};
 
 mod handler;
 mod wrapper;
@@ -15,6 +18,11 @@ fn format_command_wrapper(function: &Ident) -> Ident {
   quote::format_ident!("__cmd__{}", function)
 }
 
+/// The autogenerated ident of the macro running the commands of an `impl` block.
+fn format_command_group(ty: &Ident) -> Ident {
+  quote::format_ident!("__cmds__{}", ty)
+}
+
 /// This function will panic if the passed [`syn::Path`] does not have any segments.
 fn path_to_command(path: &mut Path) -> &mut PathSegment {
   path
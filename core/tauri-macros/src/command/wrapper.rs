@@ -4,17 +4,19 @@
 
 use std::env::var;
 
+use crate::validate::{self, Rule};
 use heck::{ToLowerCamelCase, ToSnakeCase};
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
-use quote::{format_ident, quote, quote_spanned};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
   ext::IdentExt,
   parse::{Parse, ParseStream},
   parse_macro_input,
   punctuated::Punctuated,
   spanned::Spanned,
-  Expr, ExprLit, FnArg, ItemFn, Lit, Meta, Pat, Token, Visibility,
+  Expr, ExprLit, FnArg, ImplItem, ItemFn, ItemImpl, Lit, LitInt, LitStr, Meta, MetaNameValue, Pat,
+  Receiver, Signature, Token, Type, Visibility,
 };
 
 enum WrapperAttributeKind {
@@ -38,6 +40,40 @@ struct WrapperAttributes {
   root: TokenStream2,
   execution_context: ExecutionContext,
   argument_case: ArgumentCase,
+  /// The timeout of the async command, in milliseconds.
+  timeout: Option<LitInt>,
+  /// Whether the command is deprecated, with the version and reason of the deprecation.
+  deprecated: Option<Deprecation>,
+}
+
+/// The `deprecated(since = "...", note = "...")` option of the command.
+#[derive(Default)]
+struct Deprecation {
+  since: Option<LitStr>,
+  note: Option<LitStr>,
+}
+
+impl Deprecation {
+  fn parse_option(&mut self, option: MetaNameValue) -> syn::Result<()> {
+    let value = match option.value {
+      Expr::Lit(ExprLit {
+        lit: Lit::Str(value),
+        attrs: _,
+      }) => value,
+      value => return Err(syn::Error::new(value.span(), "expected a string literal")),
+    };
+    if option.path.is_ident("since") {
+      self.since.replace(value);
+    } else if option.path.is_ident("note") {
+      self.note.replace(value);
+    } else {
+      return Err(syn::Error::new(
+        option.path.span(),
+        "unexpected input, expected one of `since`, `note`",
+      ));
+    }
+    Ok(())
+  }
 }
 
 impl Parse for WrapperAttributes {
@@ -46,11 +82,22 @@ impl Parse for WrapperAttributes {
       root: quote!(::tauri),
       execution_context: ExecutionContext::Blocking,
       argument_case: ArgumentCase::Camel,
+      timeout: None,
+      deprecated: None,
     };
 
     let attrs = Punctuated::<WrapperAttributeKind, Token![,]>::parse_terminated(input)?;
     for attr in attrs {
       match attr {
+        WrapperAttributeKind::Meta(Meta::List(list)) if list.path.is_ident("deprecated") => {
+          let mut deprecation = Deprecation::default();
+          for option in
+            list.parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)?
+          {
+            deprecation.parse_option(option)?;
+          }
+          wrapper_attributes.deprecated.replace(deprecation);
+        }
         WrapperAttributeKind::Meta(Meta::List(_)) => {
           return Err(syn::Error::new(input.span(), "unexpected list input"));
         }
@@ -72,6 +119,20 @@ impl Parse for WrapperAttributes {
                 }
               };
             }
+          } else if v.path.is_ident("timeout") {
+            if let Expr::Lit(ExprLit {
+              lit: Lit::Int(timeout),
+              attrs: _,
+            }) = v.value
+            {
+              timeout.base10_parse::<u64>()?;
+              wrapper_attributes.timeout.replace(timeout);
+            } else {
+              return Err(syn::Error::new(
+                v.value.span(),
+                "expected the timeout in milliseconds",
+              ));
+            }
           } else if v.path.is_ident("root") {
             if let Expr::Lit(ExprLit {
               lit: Lit::Str(s),
@@ -89,10 +150,15 @@ impl Parse for WrapperAttributes {
             }
           }
         }
+        WrapperAttributeKind::Meta(Meta::Path(path)) if path.is_ident("deprecated") => {
+          wrapper_attributes
+            .deprecated
+            .replace(Deprecation::default());
+        }
         WrapperAttributeKind::Meta(Meta::Path(_)) => {
           return Err(syn::Error::new(
             input.span(),
-            "unexpected input, expected one of `rename_all`, `root`, `async`",
+            "unexpected input, expected one of `rename_all`, `root`, `async`, `timeout`, `deprecated`",
           ));
         }
         WrapperAttributeKind::Async => {
@@ -106,6 +172,7 @@ impl Parse for WrapperAttributes {
 }
 
 /// The execution context of the command.
+#[derive(Clone, Copy)]
 enum ExecutionContext {
   Async,
   Blocking,
@@ -127,83 +194,302 @@ struct Invoke {
 
 /// Create a new [`Wrapper`] from the function and the generated code parsed from the function.
 pub fn wrapper(attributes: TokenStream, item: TokenStream) -> TokenStream {
-  let mut attrs = parse_macro_input!(attributes as WrapperAttributes);
-  let function = parse_macro_input!(item as ItemFn);
+  let attrs = parse_macro_input!(attributes as WrapperAttributes);
+  let mut function = parse_macro_input!(item as ItemFn);
   let wrapper = super::format_command_wrapper(&function.sig.ident);
   let visibility = &function.vis;
 
-  if function.sig.asyncness.is_some() {
-    attrs.execution_context = ExecutionContext::Async;
-  }
-
   // macros used with `pub use my_macro;` need to be exported with `#[macro_export]`
   let maybe_macro_export = match &function.vis {
     Visibility::Public(_) | Visibility::Restricted(_) => quote!(#[macro_export]),
     _ => TokenStream2::default(),
   };
 
-  let invoke = Invoke {
-    message: format_ident!("__tauri_message__"),
-    resolver: format_ident!("__tauri_resolver__"),
-    acl: format_ident!("__tauri_acl__"),
+  let async_command_check = match async_command_check(&function.sig) {
+    Ok(Some((attrs, check))) => quote!(#attrs const _: () = #check;),
+    Ok(None) => TokenStream2::new(),
+    Err(e) => return e.into_compile_error().into(),
   };
 
-  // Tauri currently doesn't support async commands that take a reference as input and don't return
-  // a result. See: https://github.com/tauri-apps/tauri/issues/2533
-  //
-  // For now, we provide an informative error message to the user in that case. Once #2533 is
-  // resolved, this check can be removed.
-  let mut async_command_check = TokenStream2::new();
-  if function.sig.asyncness.is_some() {
-    // This check won't catch all possible problems but it should catch the most common ones.
-    let mut ref_argument_span = None;
-
-    for arg in &function.sig.inputs {
-      if let syn::FnArg::Typed(pat) = arg {
-        match &*pat.ty {
-          syn::Type::Reference(_) => {
-            ref_argument_span = Some(pat.span());
-          }
-          syn::Type::Path(path) => {
-            // Check if the type contains a lifetime argument
-            let last = path.path.segments.last().unwrap();
-            if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
-              if args
-                .args
-                .iter()
-                .any(|arg| matches!(arg, syn::GenericArgument::Lifetime(_)))
-              {
-                ref_argument_span = Some(pat.span());
-              }
+  let rules = match take_rules(&mut function.sig) {
+    Ok(rules) => rules,
+    Err(e) => return e.into_compile_error().into(),
+  };
+
+  let command = command_block(
+    &function.sig,
+    function.span(),
+    quote!($path),
+    None,
+    rules,
+    &attrs,
+  );
+
+  // Rely on rust 2018 edition to allow importing a macro from a path.
+  quote!(
+    #async_command_check
+
+    #function
+
+    #maybe_macro_export
+    #[doc(hidden)]
+    macro_rules! #wrapper {
+        // double braces because the item is expected to be a block expression
+        ($path:path, $invoke:ident) => {#command};
+    }
+
+    // allow the macro to be resolved with the same path as the command function
+    #[allow(unused_imports)]
+    #visibility use #wrapper;
+  )
+  .into()
+}
+
+/// Create the commands of the methods of an `impl` block, taking `&self` from the managed state.
+pub fn commands(attributes: TokenStream, item: TokenStream) -> TokenStream {
+  let attrs = parse_macro_input!(attributes as WrapperAttributes);
+  let item = parse_macro_input!(item as ItemImpl);
+  commands_group(&attrs, item)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+fn commands_group(attrs: &WrapperAttributes, mut item: ItemImpl) -> syn::Result<TokenStream2> {
+  if let Some((_, path, _)) = &item.trait_ {
+    return Err(syn::Error::new(
+      path.span(),
+      "commands must be declared in an inherent `impl` block",
+    ));
+  }
+  if !item.generics.params.is_empty() {
+    return Err(syn::Error::new(
+      item.generics.span(),
+      "commands cannot be declared in a generic `impl` block",
+    ));
+  }
+  let Type::Path(self_ty) = &*item.self_ty else {
+    return Err(syn::Error::new(
+      item.self_ty.span(),
+      "commands must be declared on a named type",
+    ));
+  };
+  let group = super::format_command_group(&super::path_to_command(&mut self_ty.path.clone()).ident);
+
+  let mut names = Vec::new();
+  let mut commands = Vec::new();
+  let mut async_command_checks = Vec::new();
+  // the group macro is exported like the `__cmd__` macros, so every method must be reachable with its visibility
+  let mut visibility: Option<Visibility> = None;
+  for method in item.items.iter_mut().filter_map(|item| match item {
+    ImplItem::Fn(method) => Some(method),
+    _ => None,
+  }) {
+    match method.sig.receiver() {
+      Some(Receiver {
+        reference: Some(_),
+        mutability: None,
+        ..
+      }) => (),
+      _ => {
+        return Err(syn::Error::new(
+          method.sig.span(),
+          "command methods must take `&self`, which is the managed state of the type",
+        ))
+      }
+    }
+
+    match &visibility {
+      Some(visibility)
+        if visibility.to_token_stream().to_string() != method.vis.to_token_stream().to_string() =>
+      {
+        return Err(syn::Error::new(
+          method.sig.ident.span(),
+          "command methods of an `impl` block must have the same visibility",
+        ))
+      }
+      Some(_) => (),
+      None => visibility = Some(method.vis.clone()),
+    }
+
+    let rules = take_rules(&mut method.sig)?;
+    let ident = method.sig.ident.clone();
+    if let Some((attrs, check)) = async_command_check(&method.sig)? {
+      // an associated constant since the return type can refer to `Self`
+      let name = format_ident!("__tauri_async_command_check_{}__", ident);
+      async_command_checks.push(quote!(
+        #attrs
+        #[allow(dead_code, non_upper_case_globals)]
+        const #name: () = #check;
+      ));
+    }
+    commands.push(command_block(
+      &method.sig,
+      method.span(),
+      quote!(<$ty>::#ident),
+      Some(quote!($ty)),
+      rules,
+      attrs,
+    ));
+    names.push(ident);
+  }
+
+  let visibility = visibility.unwrap_or(Visibility::Inherited);
+  // macros used with `pub use my_macro;` need to be exported with `#[macro_export]`
+  let maybe_macro_export = match &visibility {
+    Visibility::Public(_) | Visibility::Restricted(_) => quote!(#[macro_export]),
+    _ => TokenStream2::default(),
+  };
+
+  let self_ty = &item.self_ty;
+  Ok(quote!(
+    #item
+
+    #[doc(hidden)]
+    impl #self_ty {
+      #(#async_command_checks)*
+    }
+
+    #maybe_macro_export
+    #[doc(hidden)]
+    macro_rules! #group {
+      ($ty:ty, $cmd:expr, $invoke:ident) => {
+        match $cmd {
+          #(stringify!(#names) => {#commands},)*
+          _ => {}
+        }
+      };
+    }
+
+    // allow the macro to be resolved with the same path as the type
+    #[allow(unused_imports)]
+    #visibility use #group;
+  ))
+}
+
+/// Tauri currently doesn't support async commands that take a reference as input and don't return
+/// a result. See: https://github.com/tauri-apps/tauri/issues/2533
+///
+/// For now, we provide an informative error message to the user in that case. Once #2533 is
+/// resolved, this check can be removed.
+///
+/// Returns the attributes and expression of a constant checking that the command returns a `Result`.
+fn async_command_check(sig: &Signature) -> syn::Result<Option<(TokenStream2, TokenStream2)>> {
+  if sig.asyncness.is_none() {
+    return Ok(None);
+  }
+
+  // This check won't catch all possible problems but it should catch the most common ones.
+  let mut ref_argument_span = None;
+  let mut check = None;
+
+  for arg in &sig.inputs {
+    match arg {
+      // the `&self` of command methods is a reference to the managed state
+      syn::FnArg::Receiver(receiver) => {
+        if receiver.reference.is_some() {
+          ref_argument_span = Some(receiver.span());
+        }
+      }
+      syn::FnArg::Typed(pat) => match &*pat.ty {
+        syn::Type::Reference(_) => {
+          ref_argument_span = Some(pat.span());
+        }
+        syn::Type::Path(path) => {
+          // Check if the type contains a lifetime argument
+          let last = path.path.segments.last().unwrap();
+          if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+            if args
+              .args
+              .iter()
+              .any(|arg| matches!(arg, syn::GenericArgument::Lifetime(_)))
+            {
+              ref_argument_span = Some(pat.span());
             }
           }
-          _ => {}
         }
+        _ => {}
+      },
+    }
 
-        if let Some(span) = ref_argument_span {
-          if let syn::ReturnType::Type(_, return_type) = &function.sig.output {
-            // To check if the return type is `Result` we require it to check a trait that is
-            // only implemented by `Result`. That way we don't exclude renamed result types
-            // which we wouldn't otherwise be able to detect purely from the token stream.
-            // The "error message" displayed to the user is simply the trait name.
-            async_command_check = quote_spanned! {return_type.span() =>
-              #[allow(unreachable_code, clippy::diverging_sub_expression)]
-              const _: () = if false {
-                trait AsyncCommandMustReturnResult {}
-                impl<A, B> AsyncCommandMustReturnResult for ::std::result::Result<A, B> {}
-                let _check: #return_type = unreachable!();
-                let _: &dyn AsyncCommandMustReturnResult = &_check;
-              };
-            };
-          } else {
-            return quote_spanned! {
-              span => compile_error!("async commands that contain references as inputs must return a `Result`");
-            }.into();
+    if let Some(span) = ref_argument_span {
+      if let syn::ReturnType::Type(_, return_type) = &sig.output {
+        // `impl Trait` is not allowed in a `let` binding, and only the outer `Result` matters
+        let mut return_type = return_type.clone();
+        erase_impl_trait(&mut return_type);
+        // To check if the return type is `Result` we require it to check a trait that is
+        // only implemented by `Result`. That way we don't exclude renamed result types
+        // which we wouldn't otherwise be able to detect purely from the token stream.
+        // The "error message" displayed to the user is simply the trait name.
+        check = Some((
+          quote!(#[allow(unreachable_code, clippy::diverging_sub_expression)]),
+          quote_spanned! {return_type.span() =>
+            if false {
+              trait AsyncCommandMustReturnResult {}
+              impl<A, B> AsyncCommandMustReturnResult for ::std::result::Result<A, B> {}
+              let _check: #return_type = unreachable!();
+              let _: &dyn AsyncCommandMustReturnResult = &_check;
+            }
+          },
+        ));
+      } else {
+        return Err(syn::Error::new(
+          span,
+          "async commands that contain references as inputs must return a `Result`",
+        ));
+      }
+    }
+  }
+
+  Ok(check)
+}
+
+/// Replaces the `impl Trait` types nested in the type with the unit type.
+fn erase_impl_trait(ty: &mut Type) {
+  match ty {
+    Type::ImplTrait(_) => *ty = syn::parse_quote!(()),
+    Type::Path(path) => {
+      for segment in &mut path.path.segments {
+        if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+          for arg in &mut args.args {
+            if let syn::GenericArgument::Type(ty) = arg {
+              erase_impl_trait(ty);
+            }
           }
         }
       }
     }
+    Type::Reference(reference) => erase_impl_trait(&mut reference.elem),
+    Type::Paren(paren) => erase_impl_trait(&mut paren.elem),
+    Type::Group(group) => erase_impl_trait(&mut group.elem),
+    Type::Array(array) => erase_impl_trait(&mut array.elem),
+    Type::Slice(slice) => erase_impl_trait(&mut slice.elem),
+    Type::Tuple(tuple) => tuple.elems.iter_mut().for_each(erase_impl_trait),
+    _ => {}
   }
+}
+
+/// Generates the block expression that runs a command from the `$invoke` of the wrapper macro.
+///
+/// The command is called through `call`, and `self_ty` is the managed state type of command methods.
+fn command_block(
+  sig: &Signature,
+  span: Span,
+  call: TokenStream2,
+  self_ty: Option<TokenStream2>,
+  rules: Vec<Vec<Rule>>,
+  attrs: &WrapperAttributes,
+) -> TokenStream2 {
+  let execution_context = if sig.asyncness.is_some() {
+    ExecutionContext::Async
+  } else {
+    attrs.execution_context
+  };
+
+  let invoke = Invoke {
+    message: format_ident!("__tauri_message__"),
+    resolver: format_ident!("__tauri_resolver__"),
+    acl: format_ident!("__tauri_acl__"),
+  };
 
   let plugin_name = var("CARGO_PKG_NAME")
     .expect("missing `CARGO_PKG_NAME` environment variable")
@@ -211,10 +497,21 @@ pub fn wrapper(attributes: TokenStream, item: TokenStream) -> TokenStream {
     .map(|name| quote!(::core::option::Option::Some(#name)))
     .unwrap_or_else(|| quote!(::core::option::Option::None));
 
-  let body = match attrs.execution_context {
-    ExecutionContext::Async => body_async(&plugin_name, &function, &invoke, &attrs)
+  let command = Command {
+    sig,
+    call,
+    self_ty,
+    rules,
+  };
+  let body = match execution_context {
+    ExecutionContext::Blocking if attrs.timeout.is_some() => syn::Error::new(
+      attrs.timeout.span(),
+      "`timeout` is only supported on async commands, declare the command `async` or use `#[tauri::command(async)]`",
+    )
+    .into_compile_error(),
+    ExecutionContext::Async => body_async(&plugin_name, &command, &invoke, attrs)
       .unwrap_or_else(syn::Error::into_compile_error),
-    ExecutionContext::Blocking => body_blocking(&plugin_name, &function, &invoke, &attrs)
+    ExecutionContext::Blocking => body_blocking(&plugin_name, &command, &invoke, attrs)
       .unwrap_or_else(syn::Error::into_compile_error),
   };
 
@@ -224,15 +521,15 @@ pub fn wrapper(attributes: TokenStream, item: TokenStream) -> TokenStream {
     acl,
   } = invoke;
 
-  let root = attrs.root;
+  let root = &attrs.root;
 
-  let kind = match attrs.execution_context {
-    ExecutionContext::Async if function.sig.asyncness.is_none() => "sync_threadpool",
+  let kind = match execution_context {
+    ExecutionContext::Async if sig.asyncness.is_none() => "sync_threadpool",
     ExecutionContext::Async => "async",
     ExecutionContext::Blocking => "sync",
   };
 
-  let loc = function.span().start();
+  let loc = span.start();
   let line = loc.line;
   let col = loc.column;
 
@@ -252,34 +549,119 @@ pub fn wrapper(attributes: TokenStream, item: TokenStream) -> TokenStream {
     quote!()
   };
 
-  // Rely on rust 2018 edition to allow importing a macro from a path.
-  quote!(
-    #async_command_check
+  let maybe_deprecation_warning = attrs.deprecated.as_ref().map(|deprecation| {
+    let since = opt_str_lit(deprecation.since.as_ref());
+    let note = opt_str_lit(deprecation.note.as_ref());
+    quote!(
+      // warns once per command, only in development
+      #[cfg(debug_assertions)]
+      {
+        static WARNED: ::std::sync::Once = ::std::sync::Once::new();
+        WARNED.call_once(|| warn_deprecated_command(#message.command(), #since, #note));
+      }
+    )
+  });
 
-    #function
+  quote!({
+    #[allow(unused_imports)]
+    use #root::ipc::private::*;
+    // prevent warnings when the body is a `compile_error!` or if the command has no arguments
+    #[allow(unused_variables)]
+    let #root::ipc::Invoke { message: #message, resolver: #resolver, acl: #acl } = $invoke;
 
-    #maybe_macro_export
-    #[doc(hidden)]
-    macro_rules! #wrapper {
-        // double braces because the item is expected to be a block expression
-        ($path:path, $invoke:ident) => {{
-          #[allow(unused_imports)]
-          use #root::ipc::private::*;
-          // prevent warnings when the body is a `compile_error!` or if the command has no arguments
-          #[allow(unused_variables)]
-          let #root::ipc::Invoke { message: #message, resolver: #resolver, acl: #acl } = $invoke;
+    #maybe_span
 
-          #maybe_span
+    #maybe_deprecation_warning
 
-          #body
-      }};
-    }
+    #body
+  })
+}
 
-    // allow the macro to be resolved with the same path as the command function
-    #[allow(unused_imports)]
-    #visibility use #wrapper;
-  )
-  .into()
+fn opt_str_lit(lit: Option<&LitStr>) -> TokenStream2 {
+  match lit {
+    Some(lit) => quote!(::core::option::Option::Some(#lit)),
+    None => quote!(::core::option::Option::None),
+  }
+}
+
+/// The command called by the generated code.
+struct Command<'a> {
+  sig: &'a Signature,
+  /// The path of the function, or the method on the `$ty` of the wrapper macro.
+  call: TokenStream2,
+  /// The managed state type the `&self` of command methods is taken from.
+  self_ty: Option<TokenStream2>,
+  /// The validation rules of each argument.
+  rules: Vec<Vec<Rule>>,
+}
+
+/// Parses and removes the `#[validate(...)]` attributes of the arguments.
+fn take_rules(sig: &mut Signature) -> syn::Result<Vec<Vec<Rule>>> {
+  sig
+    .inputs
+    .iter_mut()
+    .map(|arg| {
+      let attrs = match arg {
+        FnArg::Typed(arg) => &mut arg.attrs,
+        FnArg::Receiver(arg) => &mut arg.attrs,
+      };
+      let rules = validate::parse_rules(attrs)?;
+      validate::strip_rules(attrs);
+      if let (FnArg::Receiver(receiver), false) = (&arg, rules.is_empty()) {
+        return Err(syn::Error::new(
+          receiver.span(),
+          "`self` cannot have validation rules",
+        ));
+      }
+      Ok(rules)
+    })
+    .collect()
+}
+
+/// Generates the call of the command, validating its arguments first if they have rules.
+///
+/// `extract` returns the expression extracting an argument, and `reject` rejects the `error` of the validation.
+fn call_command(
+  command: &Command<'_>,
+  args: &[(TokenStream2, Option<String>)],
+  message: &Ident,
+  attributes: &WrapperAttributes,
+  extract: impl Fn(&TokenStream2) -> TokenStream2,
+  reject: TokenStream2,
+) -> TokenStream2 {
+  let call = &command.call;
+  if command.rules.iter().all(Vec::is_empty) {
+    let args = args.iter().map(|(arg, _)| extract(arg));
+    return quote!(#call(#(#args),*));
+  }
+
+  let root = &attributes.root;
+  let errors = format_ident!("__tauri_validation_errors__");
+  let locals = (0..args.len())
+    .map(|i| format_ident!("__tauri_arg_{}__", i))
+    .collect::<Vec<_>>();
+  let extracted = args.iter().map(|(arg, _)| extract(arg));
+  let checks =
+    locals
+      .iter()
+      .zip(args)
+      .zip(&command.rules)
+      .filter_map(|((local, (_, key)), rules)| {
+        key
+          .as_ref()
+          .map(|key| validate::checks(root, &quote!(&#local), &quote!(#key), &errors, rules))
+      });
+
+  quote!({
+    #(let #locals = #extracted;)*
+    let #errors = &mut #root::ipc::ValidationErrors::default();
+    #(#checks)*
+    if !#errors.is_empty() {
+      let error = ::std::mem::take(#errors).into_command_error(#message.command());
+      #reject
+    }
+    #call(#(#locals),*)
+  })
 }
 
 /// Generates an asynchronous command response from the arguments and return value of a function.
@@ -289,7 +671,7 @@ pub fn wrapper(attributes: TokenStream, item: TokenStream) -> TokenStream {
 /// [`tauri::command`]: https://docs.rs/tauri/*/tauri/runtime/index.html
 fn body_async(
   plugin_name: &TokenStream2,
-  function: &ItemFn,
+  command: &Command<'_>,
   invoke: &Invoke,
   attributes: &WrapperAttributes,
 ) -> syn::Result<TokenStream2> {
@@ -298,16 +680,29 @@ fn body_async(
     resolver,
     acl,
   } = invoke;
-  parse_args(plugin_name, function, message, acl, attributes).map(|args| {
+  parse_args(plugin_name, command, message, acl, attributes).map(|args| {
+    let call = call_command(
+      command,
+      &args,
+      message,
+      attributes,
+      |arg| quote!(#arg?),
+      quote!(return ::std::result::Result::Err(error.into());),
+    );
+    let maybe_timeout = attributes.timeout.as_ref().map(|timeout| {
+      quote!(let #resolver = #resolver.with_timeout(::std::time::Duration::from_millis(#timeout));)
+    });
+
     #[cfg(feature = "tracing")]
     quote! {
       use tracing::Instrument;
 
       let span = tracing::debug_span!("ipc::request::run");
+      #maybe_timeout
       #resolver.respond_async_serialized(async move {
-        let result = $path(#(#args?),*);
+        let result = #call;
         let kind = (&result).async_kind();
-        kind.future(result).await
+        kind.future(result, &#message).await
       }
       .instrument(span));
       return true;
@@ -315,10 +710,11 @@ fn body_async(
 
     #[cfg(not(feature = "tracing"))]
     quote! {
+      #maybe_timeout
       #resolver.respond_async_serialized(async move {
-        let result = $path(#(#args?),*);
+        let result = #call;
         let kind = (&result).async_kind();
-        kind.future(result).await
+        kind.future(result, &#message).await
       });
       return true;
     }
@@ -332,7 +728,7 @@ fn body_async(
 /// [`tauri::command`]: https://docs.rs/tauri/*/tauri/runtime/index.html
 fn body_blocking(
   plugin_name: &TokenStream2,
-  function: &ItemFn,
+  command: &Command<'_>,
   invoke: &Invoke,
   attributes: &WrapperAttributes,
 ) -> syn::Result<TokenStream2> {
@@ -341,13 +737,21 @@ fn body_blocking(
     resolver,
     acl,
   } = invoke;
-  let args = parse_args(plugin_name, function, message, acl, attributes)?;
+  let args = parse_args(plugin_name, command, message, acl, attributes)?;
 
   // the body of a `match` to early return any argument that wasn't successful in parsing.
   let match_body = quote!({
     Ok(arg) => arg,
     Err(err) => { #resolver.invoke_error(err); return true },
   });
+  let call = call_command(
+    command,
+    &args,
+    message,
+    attributes,
+    |arg| quote!(match #arg #match_body),
+    quote!(#resolver.invoke_error(error.into()); return true;),
+  );
 
   let maybe_span = if cfg!(feature = "tracing") {
     quote!(let _span = tracing::debug_span!("ipc::request::run").entered();)
@@ -357,7 +761,7 @@ fn body_blocking(
 
   Ok(quote! {
     #maybe_span
-    let result = $path(#(match #args #match_body),*);
+    let result = #call;
     let kind = (&result).blocking_kind();
     kind.block(result, #resolver);
     return true;
@@ -367,19 +771,20 @@ fn body_blocking(
 /// Parse all arguments for the command wrapper to use from the signature of the command function.
 fn parse_args(
   plugin_name: &TokenStream2,
-  function: &ItemFn,
+  command: &Command<'_>,
   message: &Ident,
   acl: &Ident,
   attributes: &WrapperAttributes,
-) -> syn::Result<Vec<TokenStream2>> {
-  function
+) -> syn::Result<Vec<(TokenStream2, Option<String>)>> {
+  command
     .sig
     .inputs
     .iter()
     .map(|arg| {
       parse_arg(
         plugin_name,
-        &function.sig.ident,
+        &command.sig.ident,
+        command.self_ty.as_ref(),
         arg,
         message,
         acl,
@@ -393,15 +798,35 @@ fn parse_args(
 fn parse_arg(
   plugin_name: &TokenStream2,
   command: &Ident,
+  self_ty: Option<&TokenStream2>,
   arg: &FnArg,
   message: &Ident,
   acl: &Ident,
   attributes: &WrapperAttributes,
-) -> syn::Result<TokenStream2> {
-  // we have no use for self arguments
-  let mut arg = match arg {
-    FnArg::Typed(arg) => arg.pat.as_ref().clone(),
-    FnArg::Receiver(arg) => {
+) -> syn::Result<(TokenStream2, Option<String>)> {
+  let root = &attributes.root;
+
+  // the `&self` of command methods is borrowed from the managed state
+  let mut arg = match (arg, self_ty) {
+    (FnArg::Typed(arg), _) => arg.pat.as_ref().clone(),
+    (FnArg::Receiver(_), Some(self_ty)) => {
+      return Ok((
+        quote!(
+          <#root::State<'_, #self_ty> as #root::ipc::CommandArg<_>>::from_command(
+            #root::ipc::CommandItem {
+              plugin: #plugin_name,
+              name: stringify!(#command),
+              key: "self",
+              message: &#message,
+              acl: &#acl,
+            }
+          )
+          .map(|state| state.inner())
+        ),
+        None,
+      ));
+    }
+    (FnArg::Receiver(arg), None) => {
       return Err(syn::Error::new(
         arg.span(),
         "unable to use self as a command function parameter",
@@ -440,9 +865,7 @@ fn parse_arg(
     }
   }
 
-  let root = &attributes.root;
-
-  Ok(quote!(#root::ipc::CommandArg::from_command(
+  let arg = quote!(#root::ipc::CommandArg::from_command(
     #root::ipc::CommandItem {
       plugin: #plugin_name,
       name: stringify!(#command),
@@ -450,5 +873,7 @@ fn parse_arg(
       message: &#message,
       acl: &#acl,
     }
-  )))
+  ));
+
+  Ok((arg, Some(key)))
 }
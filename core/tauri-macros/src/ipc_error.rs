@@ -0,0 +1,137 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use heck::ToShoutySnakeCase;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{spanned::Spanned, Attribute, Data, DeriveInput, Fields, LitStr};
+
+/// The prefix of the error codes reserved for errors produced by Tauri.
+const RESERVED_CODE_PREFIX: &str = "TAURI_";
+
+/// Generates the `IpcError` implementation of an error enum and its conversion to `InvokeError`.
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+  let mut root = quote!(::tauri);
+  for attr in ipc_error_attributes(&input.attrs) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("root") {
+        let lit: LitStr = meta.value()?.parse()?;
+        root = if lit.value() == "crate" {
+          quote!(crate)
+        } else {
+          let ident = Ident::new(&lit.value(), Span::call_site());
+          quote!(#ident)
+        };
+        Ok(())
+      } else {
+        Err(meta.error("unexpected input, expected `root`"))
+      }
+    })?;
+  }
+
+  let Data::Enum(data) = &input.data else {
+    return Err(syn::Error::new(
+      input.span(),
+      "IpcError can only be derived for enums",
+    ));
+  };
+
+  let mut code_arms = Vec::new();
+  let mut data_arms = Vec::new();
+  for variant in &data.variants {
+    let ident = &variant.ident;
+
+    let mut code = ident.to_string().to_shouty_snake_case();
+    let mut code_span = ident.span();
+    for attr in ipc_error_attributes(&variant.attrs) {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("code") {
+          let lit: LitStr = meta.value()?.parse()?;
+          code = lit.value();
+          code_span = lit.span();
+          Ok(())
+        } else {
+          Err(meta.error("unexpected input, expected `code`"))
+        }
+      })?;
+    }
+    if code.starts_with(RESERVED_CODE_PREFIX) {
+      return Err(syn::Error::new(
+        code_span,
+        format!("error codes starting with `{RESERVED_CODE_PREFIX}` are reserved"),
+      ));
+    }
+
+    let pattern = match &variant.fields {
+      Fields::Named(_) => quote!(Self::#ident { .. }),
+      Fields::Unnamed(_) => quote!(Self::#ident(..)),
+      Fields::Unit => quote!(Self::#ident),
+    };
+    code_arms.push(quote!(#pattern => #code));
+
+    let mut data_field = None;
+    for (index, field) in variant.fields.iter().enumerate() {
+      for attr in ipc_error_attributes(&field.attrs) {
+        attr.parse_nested_meta(|meta| {
+          if !meta.path.is_ident("data") {
+            return Err(meta.error("unexpected input, expected `data`"));
+          }
+          if data_field.is_some() {
+            return Err(
+              meta.error("only one field of a variant can be marked with `#[ipc_error(data)]`"),
+            );
+          }
+          data_field.replace((index, field));
+          Ok(())
+        })?;
+      }
+    }
+    if let Some((index, field)) = data_field {
+      let pattern = match &field.ident {
+        Some(name) => quote!(Self::#ident { #name: data, .. }),
+        None => {
+          let skipped = (0..index).map(|_| quote!(_));
+          quote!(Self::#ident(#(#skipped,)* data, ..))
+        }
+      };
+      data_arms.push(quote!(#pattern => #root::ipc::private::serde_json::to_value(data).ok()));
+    }
+  }
+
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  let code_match = if code_arms.is_empty() {
+    quote!(match *self {})
+  } else {
+    quote!(match self { #(#code_arms,)* })
+  };
+
+  Ok(quote! {
+    impl #impl_generics #root::ipc::IpcError for #name #ty_generics #where_clause {
+      fn code(&self) -> &str {
+        #code_match
+      }
+
+      fn data(&self) -> ::std::option::Option<#root::ipc::private::serde_json::Value> {
+        #[allow(unreachable_patterns)]
+        match self {
+          #(#data_arms,)*
+          _ => ::std::option::Option::None,
+        }
+      }
+    }
+
+    impl #impl_generics ::std::convert::From<#name #ty_generics> for #root::ipc::InvokeError #where_clause {
+      fn from(error: #name #ty_generics) -> Self {
+        #root::ipc::CommandError::from(error).into()
+      }
+    }
+  })
+}
+
+fn ipc_error_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+  attrs
+    .iter()
+    .filter(|attr| attr.path().is_ident("ipc_error"))
+}
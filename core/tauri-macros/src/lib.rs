@@ -16,15 +16,22 @@ use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
 mod command;
+mod ipc_error;
 mod menu;
 mod mobile;
 mod runtime;
+mod validate;
 
 #[macro_use]
 mod context;
 
 /// Mark a function as a command handler. It creates a wrapper function with the necessary glue code.
 ///
+/// Async commands accept a `timeout = <milliseconds>` option, after which the command is dropped and its call rejected.
+///
+/// A command can be marked with `deprecated(since = "1.2.0", note = "use other_command instead")`,
+/// both values being optional. Debug builds log a warning the first time a deprecated command is called.
+///
 /// # Stability
 /// The output of this macro is managed internally by Tauri,
 /// and should not be accessed directly on normal applications.
@@ -34,6 +41,46 @@ pub fn command(attributes: TokenStream, item: TokenStream) -> TokenStream {
   command::wrapper(attributes, item)
 }
 
+/// Mark the methods of an `impl` block as commands, taking `&self` from the state managed with
+/// [`Manager::manage`](https://docs.rs/tauri/2/tauri/trait.Manager.html#method.manage).
+///
+/// Every method must take `&self`, so helper methods and constructors belong in another `impl` block.
+/// The methods must share the same visibility, which is also the visibility of the group,
+/// so `pub` methods can be registered from another crate.
+/// The attribute accepts the same `rename_all`, `root`, `async`, `timeout` and `deprecated` options as
+/// [`macro@command`].
+/// Register the whole group with `impl Type` in [`generate_handler!`], optionally prefixing the
+/// command names with `impl Type as "prefix_"`.
+///
+/// # Examples
+/// ```rust,ignore
+/// struct Db;
+///
+/// #[tauri::commands]
+/// impl Db {
+///   fn count(&self) -> u32 {
+///     0
+///   }
+///
+///   async fn query(&self, sql: String) -> Result<Vec<String>, String> {
+///     Ok(vec![sql])
+///   }
+/// }
+///
+/// fn main() {
+///   // registers the `db_count` and `db_query` commands
+///   let _handler = tauri::generate_handler![impl Db as "db_"];
+/// }
+/// ```
+/// # Stability
+/// The output of this macro is managed internally by Tauri,
+/// and should not be accessed directly on normal applications.
+/// It may have breaking changes in the future.
+#[proc_macro_attribute]
+pub fn commands(attributes: TokenStream, item: TokenStream) -> TokenStream {
+  command::commands(attributes, item)
+}
+
 #[proc_macro_attribute]
 pub fn mobile_entry_point(attributes: TokenStream, item: TokenStream) -> TokenStream {
   mobile::entry_point(attributes, item)
@@ -56,6 +103,16 @@ pub fn mobile_entry_point(attributes: TokenStream, item: TokenStream) -> TokenSt
 ///   let _handler = generate_handler![command_one, command_two];
 /// }
 /// ```
+///
+/// The methods of an `impl` block marked with [`macro@commands`] are registered with `impl Type`,
+/// and `impl Type as "prefix_"` prefixes their command names:
+/// ```rust,ignore
+/// let _handler = generate_handler![command_one, impl Db, impl Cache as "cache_"];
+/// ```
+///
+/// When the application has permissions generated by `tauri-build`, every command function
+/// must be allowed by one of them, see `tauri_build::AppManifest::commands`.
+///
 /// # Stability
 /// The output of this macro is managed internally by Tauri,
 /// and should not be accessed directly on normal applications.
@@ -91,6 +148,67 @@ pub fn default_runtime(attributes: TokenStream, input: TokenStream) -> TokenStre
   runtime::default_runtime(attributes, input).into()
 }
 
+/// Derives `tauri::ipc::IpcError` for an error enum, so it is sent to the frontend
+/// as a `tauri::ipc::CommandError` with a stable code per variant.
+///
+/// The code defaults to the variant name in `SCREAMING_SNAKE_CASE`, and codes starting with `TAURI_` are reserved.
+/// The enum must implement [`std::fmt::Display`], which is used as the error message,
+/// and must not implement `serde::Serialize` since this also derives the conversion to `tauri::ipc::InvokeError`.
+///
+/// # Attributes
+///
+/// - `#[ipc_error(code = "CODE")]` on a variant overrides its code.
+/// - `#[ipc_error(data)]` on a field of a variant sends its serialized value as the error data.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, tauri::ipc::IpcError)]
+/// enum Error {
+///   #[error("file {path} not found")]
+///   NotFound {
+///     #[ipc_error(data)]
+///     path: String,
+///   },
+///   #[error("the file is too large")]
+///   #[ipc_error(code = "FILE_TOO_LARGE")]
+///   TooLarge,
+/// }
+/// ```
+#[proc_macro_derive(IpcError, attributes(ipc_error))]
+pub fn ipc_error(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as syn::DeriveInput);
+  ipc_error::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Derives `tauri::ipc::Validate` for a struct from the `#[validate(...)]` rules of its fields.
+///
+/// The field paths follow the `#[serde(rename)]` and `#[serde(rename_all)]` attributes.
+/// See the `tauri::ipc::validation` module for the available rules.
+/// The `regex` rule requires the `validate-regex` feature.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(serde::Deserialize, tauri::ipc::Validate)]
+/// #[serde(rename_all = "camelCase")]
+/// struct Signup {
+///   #[validate(regex = "^[a-z0-9_]+$", length(min = 3, max = 16))]
+///   user_name: String,
+///   #[validate(range(min = 13))]
+///   age: u8,
+/// }
+/// ```
+#[proc_macro_derive(Validate, attributes(validate))]
+pub fn validate(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as syn::DeriveInput);
+  validate::derive(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
 /// Accepts a closure-like syntax to call arbitrary code on a menu item
 /// after matching against `kind` and retrieving it from `resources_table` using `rid`.
 ///
@@ -0,0 +1,280 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use heck::{
+  ToKebabCase, ToLowerCamelCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
+  ToUpperCamelCase,
+};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Attribute, Data, DeriveInput, Expr, LitStr, Path};
+
+/// A rule of the `#[validate(...)]` attribute.
+pub enum Rule {
+  Range {
+    min: Option<Expr>,
+    max: Option<Expr>,
+  },
+  Length {
+    min: Option<Expr>,
+    max: Option<Expr>,
+  },
+  #[cfg(feature = "validate-regex")]
+  Regex(LitStr),
+  Custom(Path),
+  Nested,
+}
+
+/// Parses the rules of the `#[validate(...)]` attributes.
+pub fn parse_rules(attrs: &[Attribute]) -> syn::Result<Vec<Rule>> {
+  let mut rules = Vec::new();
+  for attr in attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("range") {
+        let (mut min, mut max) = (None, None);
+        meta.parse_nested_meta(|bound| {
+          if bound.path.is_ident("min") {
+            min.replace(bound.value()?.parse()?);
+          } else if bound.path.is_ident("max") {
+            max.replace(bound.value()?.parse()?);
+          } else {
+            return Err(bound.error("unexpected input, expected `min` or `max`"));
+          }
+          Ok(())
+        })?;
+        rules.push(Rule::Range { min, max });
+      } else if meta.path.is_ident("length") {
+        let (mut min, mut max) = (None, None);
+        meta.parse_nested_meta(|bound| {
+          if bound.path.is_ident("min") {
+            min.replace(bound.value()?.parse()?);
+          } else if bound.path.is_ident("max") {
+            max.replace(bound.value()?.parse()?);
+          } else {
+            return Err(bound.error("unexpected input, expected `min` or `max`"));
+          }
+          Ok(())
+        })?;
+        rules.push(Rule::Length { min, max });
+      } else if meta.path.is_ident("regex") {
+        let pattern: LitStr = meta.value()?.parse()?;
+        #[cfg(feature = "validate-regex")]
+        {
+          if let Err(e) = regex::Regex::new(&pattern.value()) {
+            return Err(syn::Error::new(pattern.span(), e));
+          }
+          rules.push(Rule::Regex(pattern));
+        }
+        #[cfg(not(feature = "validate-regex"))]
+        return Err(syn::Error::new(
+          pattern.span(),
+          "the `regex` rule requires the `validate-regex` feature of tauri",
+        ));
+      } else if meta.path.is_ident("custom") {
+        rules.push(Rule::Custom(meta.value()?.parse()?));
+      } else if meta.path.is_ident("nested") {
+        rules.push(Rule::Nested);
+      } else if meta.path.is_ident("root") {
+        return Err(meta.error("`root` is only allowed on the struct"));
+      } else {
+        return Err(meta.error(
+          "unexpected input, expected one of `range`, `length`, `regex`, `custom`, `nested`",
+        ));
+      }
+      Ok(())
+    })?;
+  }
+  Ok(rules)
+}
+
+/// Removes the `#[validate(...)]` attributes, which are not known by the compiler.
+pub fn strip_rules(attrs: &mut Vec<Attribute>) {
+  attrs.retain(|attr| !attr.path().is_ident("validate"));
+}
+
+/// Generates the statements checking the rules of a value.
+///
+/// `value` is a reference to the value, `path` its `&str` path,
+/// and `errors` the `ValidationErrors` recording the broken rules.
+pub fn checks(
+  root: &TokenStream2,
+  value: &TokenStream2,
+  path: &TokenStream2,
+  errors: &Ident,
+  rules: &[Rule],
+) -> TokenStream2 {
+  let validation = quote!(#root::ipc::validation);
+  let option = |bound: &Option<_>| match bound {
+    Some(bound) => quote!(::std::option::Option::Some(#bound)),
+    None => quote!(::std::option::Option::None),
+  };
+  let checks = rules.iter().map(|rule| match rule {
+    Rule::Range { min, max } => {
+      let (min, max) = (option(min), option(max));
+      quote!(#errors.check(#path, "range", #validation::rules::range(#value, #min, #max));)
+    }
+    Rule::Length { min, max } => {
+      let (min, max) = (option(min), option(max));
+      quote!(#errors.check(#path, "length", #validation::rules::length(#value, #min, #max));)
+    }
+    #[cfg(feature = "validate-regex")]
+    Rule::Regex(pattern) => quote!({
+      static PATTERN: ::std::sync::OnceLock<#root::ipc::private::regex::Regex> =
+        ::std::sync::OnceLock::new();
+      // the pattern is checked when the macro is expanded
+      let pattern = PATTERN.get_or_init(|| #root::ipc::private::regex::Regex::new(#pattern).unwrap());
+      #errors.check(#path, "regex", #validation::rules::regex(#value, pattern));
+    }),
+    Rule::Custom(function) => {
+      let name = function
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default();
+      quote!(#errors.check(#path, #name, #function(#value));)
+    }
+    Rule::Nested => quote!(#root::ipc::Validate::validate(#value, #path, #errors);),
+  });
+  quote!(#(#checks)*)
+}
+
+/// Generates the `Validate` implementation of a struct from the rules of its fields.
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+  let mut root = quote!(::tauri);
+  for attr in input
+    .attrs
+    .iter()
+    .filter(|attr| attr.path().is_ident("validate"))
+  {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("root") {
+        let lit: LitStr = meta.value()?.parse()?;
+        root = if lit.value() == "crate" {
+          quote!(crate)
+        } else {
+          let ident = Ident::new(&lit.value(), Span::call_site());
+          quote!(#ident)
+        };
+        Ok(())
+      } else {
+        Err(meta.error("unexpected input, expected `root`"))
+      }
+    })?;
+  }
+
+  let Data::Struct(data) = &input.data else {
+    return Err(syn::Error::new(
+      input.span(),
+      "Validate can only be derived for structs",
+    ));
+  };
+
+  let rename_all = serde_rename_all(&input.attrs)?;
+  let path = format_ident!("path");
+  let errors = format_ident!("errors");
+  let mut validations = Vec::new();
+  for (index, field) in data.fields.iter().enumerate() {
+    let rules = parse_rules(&field.attrs)?;
+    if rules.is_empty() {
+      continue;
+    }
+
+    let (member, name) = match &field.ident {
+      Some(ident) => {
+        let name = serde_rename(&field.attrs)?.unwrap_or_else(|| {
+          let name = ident.to_string();
+          let name = name.strip_prefix("r#").unwrap_or(&name);
+          rename_all
+            .map(|case| case(name))
+            .unwrap_or_else(|| name.into())
+        });
+        (quote!(#ident), name)
+      }
+      None => {
+        let index = syn::Index::from(index);
+        (quote!(#index), index.index.to_string())
+      }
+    };
+    let field_checks = checks(
+      &root,
+      &quote!(&self.#member),
+      &quote!(&field_path),
+      &errors,
+      &rules,
+    );
+    validations.push(quote!({
+      let field_path = #root::ipc::validation::field_path(#path, #name);
+      #field_checks
+    }));
+  }
+
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+  Ok(quote! {
+    impl #impl_generics #root::ipc::Validate for #name #ty_generics #where_clause {
+      #[allow(unused_variables)]
+      fn validate(&self, #path: &str, #errors: &mut #root::ipc::ValidationErrors) {
+        #(#validations)*
+      }
+    }
+  })
+}
+
+/// The `#[serde(rename = "...")]` of a field.
+fn serde_rename(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+  let mut rename = None;
+  for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("rename") && meta.input.peek(syn::Token![=]) {
+        let lit: LitStr = meta.value()?.parse()?;
+        rename.replace(lit.value());
+      } else {
+        skip_meta(&meta)?;
+      }
+      Ok(())
+    })?;
+  }
+  Ok(rename)
+}
+
+type Case = fn(&str) -> String;
+
+/// The `#[serde(rename_all = "...")]` of a struct.
+fn serde_rename_all(attrs: &[Attribute]) -> syn::Result<Option<Case>> {
+  let mut rename_all = None;
+  for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("rename_all") && meta.input.peek(syn::Token![=]) {
+        let lit: LitStr = meta.value()?.parse()?;
+        let case: Case = match lit.value().as_str() {
+          "lowercase" => |s| s.to_lowercase(),
+          "UPPERCASE" => |s| s.to_uppercase(),
+          "PascalCase" => |s| s.to_upper_camel_case(),
+          "camelCase" => |s| s.to_lower_camel_case(),
+          "snake_case" => |s| s.to_snake_case(),
+          "SCREAMING_SNAKE_CASE" => |s| s.to_shouty_snake_case(),
+          "kebab-case" => |s| s.to_kebab_case(),
+          "SCREAMING-KEBAB-CASE" => |s| s.to_shouty_kebab_case(),
+          _ => return Err(syn::Error::new(lit.span(), "unknown rename rule")),
+        };
+        rename_all.replace(case);
+      } else {
+        skip_meta(&meta)?;
+      }
+      Ok(())
+    })?;
+  }
+  Ok(rename_all)
+}
+
+/// Skips the value of a serde attribute this macro does not use.
+fn skip_meta(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<()> {
+  if meta.input.peek(syn::Token![=]) {
+    meta.value()?.parse::<Expr>()?;
+  } else if meta.input.peek(syn::token::Paren) {
+    meta.parse_nested_meta(|meta| skip_meta(&meta))?;
+  }
+  Ok(())
+}
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::path::{Path, PathBuf};
+use std::{
+  collections::HashMap,
+  num::NonZeroU64,
+  path::{Path, PathBuf},
+};
 
 use anyhow::Result;
 use tauri_utils::acl::{self, Error};
@@ -30,6 +34,7 @@ pub fn plugin_config<T: DeserializeOwned>(name: &str) -> Option<T> {
 
 pub struct Builder<'a> {
   commands: &'a [&'static str],
+  command_versions: HashMap<&'static str, NonZeroU64>,
   global_scope_schema: Option<schemars::schema::RootSchema>,
   android_path: Option<PathBuf>,
   ios_path: Option<PathBuf>,
@@ -39,12 +44,22 @@ impl<'a> Builder<'a> {
   pub fn new(commands: &'a [&'static str]) -> Self {
     Self {
       commands,
+      command_versions: HashMap::new(),
       global_scope_schema: None,
       android_path: None,
       ios_path: None,
     }
   }
 
+  /// Sets the version of a command, used as the version of its autogenerated permissions.
+  ///
+  /// Bump it when the command changes in a way capabilities must opt into,
+  /// so they can require it with `minVersion`.
+  pub fn command_version(mut self, command: &'static str, version: NonZeroU64) -> Self {
+    self.command_versions.insert(command, version);
+    self
+  }
+
   /// Sets the global scope JSON schema.
   pub fn global_scope_schema(mut self, schema: schemars::schema::RootSchema) -> Self {
     self.global_scope_schema.replace(schema);
@@ -94,8 +109,21 @@ impl<'a> Builder<'a> {
 
     std::fs::create_dir_all(&autogenerated).expect("unable to create permissions dir");
 
+    if let Some(command) = self
+      .command_versions
+      .keys()
+      .find(|command| !self.commands.contains(command))
+    {
+      anyhow::bail!("cannot set the version of unknown command {command}");
+    }
+
     if !self.commands.is_empty() {
-      acl::build::autogenerate_command_permissions(&commands_dir, self.commands, "");
+      acl::build::autogenerate_versioned_command_permissions(
+        &commands_dir,
+        self.commands,
+        &self.command_versions,
+        "",
+      );
     }
 
     println!("cargo:rerun-if-changed=permissions");
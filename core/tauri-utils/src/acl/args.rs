@@ -0,0 +1,427 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Declarative constraints on the arguments of a command.
+//!
+//! Unlike [scopes](super::Scopes), which are handled by the command itself,
+//! argument constraints are enforced by Tauri before the command is executed.
+
+use std::collections::BTreeMap;
+
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{Number, Value};
+
+/// Constraints of each command argument, keyed by the argument name as sent by the frontend.
+pub type ArgConstraints = BTreeMap<String, ArgConstraint>;
+
+/// Constraints on a single command argument. The argument must satisfy all of them.
+///
+/// # Examples
+///
+/// ```toml
+/// [[permission]]
+/// identifier = "allow-read-app-data"
+/// commands.allow = ["read"]
+///
+/// [permission.args.path]
+/// glob = ["/home/*/.local/share/app/**"]
+///
+/// [permission.args.encoding]
+/// oneOf = ["utf8", "base64"]
+/// optional = true
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ArgConstraint {
+  /// Allows the argument to be omitted or `null`.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub optional: bool,
+  /// The argument must be a string matching one of these glob patterns.
+  ///
+  /// A `*` does not match path separators, use `**` to match nested paths.
+  /// Values containing `..` path components never match.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub glob: Vec<String>,
+  /// The argument must be equal to one of these values.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub one_of: Vec<Value>,
+  /// The argument must be a number greater than or equal to this value.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub min: Option<Number>,
+  /// The argument must be a number less than or equal to this value.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max: Option<Number>,
+}
+
+/// The constraint that an argument violated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArgConstraintKind {
+  /// The argument is missing.
+  Required,
+  /// The argument does not match any of the glob patterns.
+  Glob,
+  /// The argument is not one of the allowed values.
+  OneOf,
+  /// The argument is lower than the minimum.
+  Min,
+  /// The argument is greater than the maximum.
+  Max,
+}
+
+/// An argument that does not satisfy its constraints.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[error("argument `{argument}` {message}")]
+pub struct ArgViolation {
+  /// The argument name.
+  pub argument: String,
+  /// The violated constraint.
+  pub constraint: ArgConstraintKind,
+  /// A human-readable description of the violation.
+  pub message: String,
+}
+
+/// The arguments of an IPC call are not allowed by the Access Control List.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[error("arguments of command {command} not allowed: {}", display_violations(.violations))]
+pub struct ArgsError {
+  /// The command name.
+  pub command: String,
+  /// The arguments that do not satisfy their constraints.
+  pub violations: Vec<ArgViolation>,
+}
+
+fn display_violations(violations: &[ArgViolation]) -> String {
+  violations
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+impl ArgConstraint {
+  /// Checks that the glob patterns are valid.
+  pub fn check_patterns(&self) -> Result<(), glob::PatternError> {
+    for glob in &self.glob {
+      Pattern::new(glob)?;
+    }
+    Ok(())
+  }
+
+  /// Checks the value of the argument `name`, `None` if it was not provided.
+  pub fn validate(
+    &self,
+    name: &str,
+    value: Option<&serde_json::Value>,
+  ) -> Result<(), ArgViolation> {
+    let violation = |constraint, message: String| ArgViolation {
+      argument: name.into(),
+      constraint,
+      message,
+    };
+
+    let value = match value {
+      None | Some(serde_json::Value::Null) if self.optional => return Ok(()),
+      None => return Err(violation(ArgConstraintKind::Required, "is required".into())),
+      Some(value) => value,
+    };
+
+    if !self.glob.is_empty() {
+      let matches = value.as_str().is_some_and(|value| {
+        !has_parent_dir_component(value)
+          && self.glob.iter().any(|glob| {
+            Pattern::new(glob)
+              .map(|pattern| {
+                pattern.matches_with(
+                  value,
+                  MatchOptions {
+                    require_literal_separator: true,
+                    ..Default::default()
+                  },
+                )
+              })
+              .unwrap_or(false)
+          })
+      });
+      if !matches {
+        return Err(violation(
+          ArgConstraintKind::Glob,
+          format!("must match one of {}", self.glob.join(", ")),
+        ));
+      }
+    }
+
+    if !self.one_of.is_empty()
+      && !self
+        .one_of
+        .iter()
+        .any(|allowed| json_eq(&serde_json::Value::from(allowed.clone()), value))
+    {
+      return Err(violation(
+        ArgConstraintKind::OneOf,
+        format!(
+          "must be one of {}",
+          serde_json::Value::from(Value::List(self.one_of.clone()))
+        ),
+      ));
+    }
+
+    if self.min.is_some() || self.max.is_some() {
+      let number = value.as_f64();
+      if let Some(min) = self.min {
+        if !number.is_some_and(|n| n >= number_f64(min)) {
+          return Err(violation(
+            ArgConstraintKind::Min,
+            format!(
+              "must be a number greater than or equal to {}",
+              number_f64(min)
+            ),
+          ));
+        }
+      }
+      if let Some(max) = self.max {
+        if !number.is_some_and(|n| n <= number_f64(max)) {
+          return Err(violation(
+            ArgConstraintKind::Max,
+            format!("must be a number less than or equal to {}", number_f64(max)),
+          ));
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Checks the arguments of an IPC call against a set of constraints, collecting all violations.
+pub fn validate_args(
+  constraints: &ArgConstraints,
+  args: &serde_json::Value,
+) -> Result<(), Vec<ArgViolation>> {
+  let violations = constraints
+    .iter()
+    .filter_map(|(name, constraint)| constraint.validate(name, args.get(name)).err())
+    .collect::<Vec<_>>();
+
+  if violations.is_empty() {
+    Ok(())
+  } else {
+    Err(violations)
+  }
+}
+
+fn has_parent_dir_component(value: &str) -> bool {
+  value.split(['/', '\\']).any(|component| component == "..")
+}
+
+fn number_f64(number: Number) -> f64 {
+  match number {
+    Number::Int(i) => i as f64,
+    Number::Float(f) => f,
+  }
+}
+
+/// Compares JSON values, treating integers and floats with the same value as equal.
+fn json_eq(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+  match (a, b) {
+    (serde_json::Value::Number(a), serde_json::Value::Number(b)) => a.as_f64() == b.as_f64(),
+    _ => a == b,
+  }
+}
+
+#[cfg(feature = "build")]
+mod build {
+  use proc_macro2::TokenStream;
+  use quote::{quote, ToTokens, TokenStreamExt};
+  use std::convert::identity;
+
+  use super::*;
+  use crate::{literal_struct, tokens::*};
+
+  impl ToTokens for ArgConstraint {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let optional = self.optional;
+      let glob = vec_lit(&self.glob, str_lit);
+      let one_of = vec_lit(&self.one_of, identity);
+      let min = opt_lit(self.min.as_ref());
+      let max = opt_lit(self.max.as_ref());
+      literal_struct!(
+        tokens,
+        ::tauri::utils::acl::args::ArgConstraint,
+        optional,
+        glob,
+        one_of,
+        min,
+        max
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn constraints(toml: &str) -> ArgConstraints {
+    toml::from_str(toml).unwrap()
+  }
+
+  #[test]
+  fn required() {
+    let constraints = constraints(
+      r#"
+      path = { glob = ["/data/**"] }
+      encoding = { oneOf = ["utf8"], optional = true }
+      "#,
+    );
+
+    assert!(validate_args(&constraints, &json!({ "path": "/data/file" })).is_ok());
+    assert!(validate_args(
+      &constraints,
+      &json!({ "path": "/data/file", "encoding": null })
+    )
+    .is_ok());
+
+    let violations = validate_args(&constraints, &json!({})).unwrap_err();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].argument, "path");
+    assert_eq!(violations[0].constraint, ArgConstraintKind::Required);
+  }
+
+  #[test]
+  fn glob() {
+    let constraint = ArgConstraint {
+      glob: vec!["/data/*".into(), "/cache/**".into()],
+      ..Default::default()
+    };
+
+    assert!(constraint
+      .validate("path", Some(&json!("/data/file")))
+      .is_ok());
+    assert!(constraint
+      .validate("path", Some(&json!("/cache/nested/file")))
+      .is_ok());
+
+    for value in [
+      json!("/data/nested/file"),
+      json!("/data/../etc/passwd"),
+      json!("/cache/..\\secret"),
+      json!("/other/file"),
+      json!(1),
+    ] {
+      assert_eq!(
+        constraint
+          .validate("path", Some(&value))
+          .unwrap_err()
+          .constraint,
+        ArgConstraintKind::Glob
+      );
+    }
+  }
+
+  #[test]
+  fn one_of() {
+    let constraint = ArgConstraint {
+      one_of: vec![Value::String("info".into()), Value::Number(Number::Int(1))],
+      ..Default::default()
+    };
+
+    assert!(constraint.validate("level", Some(&json!("info"))).is_ok());
+    assert!(constraint.validate("level", Some(&json!(1.0))).is_ok());
+    assert_eq!(
+      constraint
+        .validate("level", Some(&json!("trace")))
+        .unwrap_err()
+        .constraint,
+      ArgConstraintKind::OneOf
+    );
+  }
+
+  #[test]
+  fn range() {
+    let constraint = ArgConstraint {
+      min: Some(Number::Int(1)),
+      max: Some(Number::Float(10.5)),
+      ..Default::default()
+    };
+
+    assert!(constraint.validate("count", Some(&json!(1))).is_ok());
+    assert!(constraint.validate("count", Some(&json!(10.5))).is_ok());
+    assert_eq!(
+      constraint
+        .validate("count", Some(&json!(0)))
+        .unwrap_err()
+        .constraint,
+      ArgConstraintKind::Min
+    );
+    assert_eq!(
+      constraint
+        .validate("count", Some(&json!(11)))
+        .unwrap_err()
+        .constraint,
+      ArgConstraintKind::Max
+    );
+    assert_eq!(
+      constraint
+        .validate("count", Some(&json!("5")))
+        .unwrap_err()
+        .constraint,
+      ArgConstraintKind::Min
+    );
+  }
+
+  #[test]
+  fn resolved_command_any_set() {
+    let reference = crate::acl::resolved::ResolvedCommandReference {
+      capability: "main".into(),
+      permission: "fs:allow-write".into(),
+      windows: vec!["main".parse().unwrap()],
+      limits: None,
+      args: vec![
+        constraints(
+          r#"
+          path = { glob = ["/data/**"] }
+          mode = { oneOf = ["overwrite"], optional = true }
+          "#,
+        ),
+        constraints(
+          r#"
+          path = { glob = ["/logs/*"] }
+          mode = { oneOf = ["append"] }
+          "#,
+        ),
+      ],
+    };
+    let command = crate::acl::resolved::ResolvedCommand {
+      referenced_by: vec![reference],
+      ..Default::default()
+    };
+
+    assert!(command
+      .validate_args("main", &json!({ "path": "/data/a/b" }))
+      .is_ok());
+    assert!(command
+      .validate_args("main", &json!({ "path": "/logs/a", "mode": "append" }))
+      .is_ok());
+
+    // reports the violations of the closest constraint set
+    let violations = command
+      .validate_args("main", &json!({ "path": "/logs/a", "mode": "truncate" }))
+      .unwrap_err();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].constraint, ArgConstraintKind::OneOf);
+
+    assert!(crate::acl::resolved::ResolvedCommand::default()
+      .validate_args("main", &json!(null))
+      .is_ok());
+  }
+}
@@ -8,6 +8,7 @@ use std::{
   collections::{BTreeMap, HashMap},
   env::{current_dir, vars_os},
   fs::{create_dir_all, read_to_string, write},
+  num::NonZeroU64,
   path::{Path, PathBuf},
 };
 
@@ -16,8 +17,8 @@ use schemars::{
   schema::{InstanceType, Metadata, RootSchema, Schema, SchemaObject, SubschemaValidation},
   schema_for,
 };
-use serde::Deserialize;
 
+pub use super::capability::CapabilityFile;
 use super::{capability::Capability, plugin::PermissionFile};
 
 /// Known name of the folder containing autogenerated permissions.
@@ -49,38 +50,13 @@ const CAPABILITIES_SCHEMA_FOLDER_NAME: &str = "schemas";
 
 const CORE_PLUGIN_PERMISSIONS_TOKEN: &str = "__CORE_PLUGIN__";
 
-/// Capability formats accepted in a capability file.
-#[derive(Deserialize, schemars::JsonSchema)]
-#[serde(untagged)]
-pub enum CapabilityFile {
-  /// A single capability.
-  Capability(Capability),
-  /// A list of capabilities.
-  List {
-    /// The list of capabilities.
-    capabilities: Vec<Capability>,
-  },
-}
-
 /// Write the permissions to a temporary directory and pass it to the immediate consuming crate.
 pub fn define_permissions(
   pattern: &str,
   pkg_name: &str,
   out_dir: &Path,
 ) -> Result<Vec<PermissionFile>, Error> {
-  let permission_files = glob::glob(pattern)?
-    .flatten()
-    .flat_map(|p| p.canonicalize())
-    // filter extension
-    .filter(|p| {
-      p.extension()
-        .and_then(|e| e.to_str())
-        .map(|e| PERMISSION_FILE_EXTENSIONS.contains(&e))
-        .unwrap_or_default()
-    })
-    // filter schemas
-    .filter(|p| p.parent().unwrap().file_name().unwrap() != PERMISSION_SCHEMAS_FOLDER_NAME)
-    .collect::<Vec<PathBuf>>();
+  let permission_files = find_permission_files(pattern)?;
 
   let permission_files_path = out_dir.join(format!("{}-permission-files", pkg_name));
   std::fs::write(
@@ -104,6 +80,30 @@ pub fn define_permissions(
   parse_permissions(permission_files)
 }
 
+/// Parses all permission files with the given glob pattern.
+///
+/// Used to read the application permissions, which are not passed through a dependency like plugin permissions.
+pub fn parse_permission_files(pattern: &str) -> Result<Vec<PermissionFile>, Error> {
+  parse_permissions(find_permission_files(pattern)?)
+}
+
+fn find_permission_files(pattern: &str) -> Result<Vec<PathBuf>, Error> {
+  let permission_files = glob::glob(pattern)?
+    .flatten()
+    .flat_map(|p| p.canonicalize())
+    // filter extension
+    .filter(|p| {
+      p.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| PERMISSION_FILE_EXTENSIONS.contains(&e))
+        .unwrap_or_default()
+    })
+    // filter schemas
+    .filter(|p| p.parent().unwrap().file_name().unwrap() != PERMISSION_SCHEMAS_FOLDER_NAME)
+    .collect::<Vec<PathBuf>>();
+  Ok(permission_files)
+}
+
 /// Define the global scope schema JSON file path if it exists and pass it to the immediate consuming crate.
 pub fn define_global_scope_schema(
   schema: schemars::schema::RootSchema,
@@ -378,6 +378,19 @@ fn parse_permissions(paths: Vec<PathBuf>) -> Result<Vec<PermissionFile>, Error>
 
 /// Autogenerate permission files for a list of commands.
 pub fn autogenerate_command_permissions(path: &Path, commands: &[&str], license_header: &str) {
+  autogenerate_versioned_command_permissions(path, commands, &HashMap::new(), license_header)
+}
+
+/// Autogenerate permission files for a list of commands,
+/// using the command version as the version of its `allow-` and `deny-` permissions.
+///
+/// Capabilities can then require a minimum command version with the `minVersion` of the permission entry.
+pub fn autogenerate_versioned_command_permissions(
+  path: &Path,
+  commands: &[&str],
+  versions: &HashMap<&str, NonZeroU64>,
+  license_header: &str,
+) {
   if !path.exists() {
     create_dir_all(path).expect("unable to create autogenerated commands dir");
   }
@@ -392,18 +405,22 @@ pub fn autogenerate_command_permissions(path: &Path, commands: &[&str], license_
 
   for command in commands {
     let slugified_command = command.replace('_', "-");
+    let version = versions
+      .get(command)
+      .map(|version| format!("version = {version}\n"))
+      .unwrap_or_default();
     let toml = format!(
       r###"{license_header}# Automatically generated - DO NOT EDIT!
 
 "$schema" = "{schema_path}"
 
 [[permission]]
-identifier = "allow-{slugified_command}"
+{version}identifier = "allow-{slugified_command}"
 description = "Enables the {command} command without any pre-configured scope."
 commands.allow = ["{command}"]
 
 [[permission]]
-identifier = "deny-{slugified_command}"
+{version}identifier = "deny-{slugified_command}"
 description = "Denies the {command} command without any pre-configured scope."
 commands.deny = ["{command}"]
 "###,
@@ -422,3 +439,29 @@ commands.deny = ["{command}"]
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn versioned_command_permissions() {
+    let path = std::env::temp_dir().join(format!("tauri-utils-commands-{}", std::process::id()));
+    let versions = HashMap::from([("write", NonZeroU64::new(2).unwrap())]);
+    autogenerate_versioned_command_permissions(&path, &["read", "write"], &versions, "");
+
+    let version = |command: &str| {
+      let file: PermissionFile =
+        toml::from_str(&read_to_string(path.join(format!("{command}.toml"))).unwrap()).unwrap();
+      file
+        .permission
+        .iter()
+        .map(|permission| permission.version.map(NonZeroU64::get))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(version("read"), [None, None]);
+    assert_eq!(version("write"), [Some(2), Some(2)]);
+
+    std::fs::remove_dir_all(path).unwrap();
+  }
+}
@@ -4,6 +4,8 @@
 
 //! End-user abstraction for selecting permissions a window has access to.
 
+use std::num::NonZeroU64;
+
 use crate::{acl::Identifier, platform::Target};
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +26,16 @@ pub enum PermissionEntry {
     /// Scope to append to the existing permission scope.
     #[serde(default, flatten)]
     scope: Scopes,
+    /// Minimum version of the referenced permissions.
+    ///
+    /// Resolving the capability fails if a referenced permission has a lower version.
+    /// Permissions without a version are considered to be at version 1.
+    #[serde(
+      default,
+      rename = "minVersion",
+      skip_serializing_if = "Option::is_none"
+    )]
+    min_version: Option<NonZeroU64>,
   },
 }
 
@@ -32,10 +44,15 @@ impl PermissionEntry {
   pub fn identifier(&self) -> &Identifier {
     match self {
       Self::PermissionRef(identifier) => identifier,
-      Self::ExtendedPermission {
-        identifier,
-        scope: _,
-      } => identifier,
+      Self::ExtendedPermission { identifier, .. } => identifier,
+    }
+  }
+
+  /// The minimum version of the permissions referenced in this entry.
+  pub fn min_version(&self) -> Option<NonZeroU64> {
+    match self {
+      Self::PermissionRef(_) => None,
+      Self::ExtendedPermission { min_version, .. } => *min_version,
     }
   }
 }
@@ -68,6 +85,20 @@ pub struct Capability {
   pub platforms: Vec<Target>,
 }
 
+/// Capability formats accepted in a capability file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum CapabilityFile {
+  /// A single capability.
+  Capability(Capability),
+  /// A list of capabilities.
+  List {
+    /// The list of capabilities.
+    capabilities: Vec<Capability>,
+  },
+}
+
 fn default_platforms() -> Vec<Target> {
   vec![
     Target::Linux,
@@ -88,7 +119,12 @@ pub enum CapabilityContext {
   Local,
   /// Capability refers to remote usage.
   Remote {
-    /// Remote domains this capability refers to. Can use glob patterns.
-    domains: Vec<String>,
+    /// Remote URL patterns this capability refers to.
+    ///
+    /// Patterns have the format `[scheme://]host[:port][/path]`, where the scheme and host can use glob patterns,
+    /// the port can be `*` to match any port and the path is matched as a prefix.
+    /// Without a scheme both `http` and `https` are allowed, and without a port only the scheme default port is allowed.
+    #[serde(alias = "domains")]
+    urls: Vec<String>,
   },
 }
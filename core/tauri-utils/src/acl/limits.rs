@@ -0,0 +1,167 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Limits on how often a webview can call a command.
+
+use std::num::{NonZeroU32, NonZeroU64};
+
+use serde::{Deserialize, Serialize};
+
+/// Limits applied to each webview calling the allowed commands of a permission.
+///
+/// # Examples
+///
+/// ```toml
+/// [[permission]]
+/// identifier = "allow-search"
+/// commands.allow = ["search"]
+///
+/// [permission.limits]
+/// maxConcurrent = 2
+/// rate = { calls = 10, periodMs = 1000 }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CommandLimits {
+  /// Maximum number of calls a webview can have in flight at the same time.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_concurrent: Option<NonZeroU32>,
+  /// Maximum number of calls a webview can make in a period of time.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rate: Option<RateLimit>,
+}
+
+/// A number of calls allowed in a period of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RateLimit {
+  /// The number of calls allowed in the period.
+  pub calls: NonZeroU32,
+  /// The length of the period in milliseconds.
+  pub period_ms: NonZeroU64,
+}
+
+impl CommandLimits {
+  /// Merges the limits of two permissions allowing the same command, keeping the least restrictive of each limit.
+  pub fn merge(self, other: Self) -> Self {
+    Self {
+      max_concurrent: self
+        .max_concurrent
+        .zip(other.max_concurrent)
+        .map(|(a, b)| a.max(b)),
+      rate: self.rate.zip(other.rate).map(|(a, b)| {
+        // compare calls per millisecond without dividing
+        if a.calls.get() as u128 * b.period_ms.get() as u128
+          >= b.calls.get() as u128 * a.period_ms.get() as u128
+        {
+          a
+        } else {
+          b
+        }
+      }),
+    }
+  }
+
+  /// Whether no limit is defined.
+  pub fn is_empty(&self) -> bool {
+    self.max_concurrent.is_none() && self.rate.is_none()
+  }
+}
+
+#[cfg(feature = "build")]
+mod build {
+  use proc_macro2::TokenStream;
+  use quote::{quote, ToTokens, TokenStreamExt};
+
+  use super::*;
+  use crate::{literal_struct, tokens::*};
+
+  impl ToTokens for CommandLimits {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let max_concurrent = opt_lit_owned(self.max_concurrent.map(|max| {
+        let max = max.get();
+        quote!(::core::num::NonZeroU32::new(#max).unwrap())
+      }));
+      let rate = opt_lit(self.rate.as_ref());
+      literal_struct!(
+        tokens,
+        ::tauri::utils::acl::limits::CommandLimits,
+        max_concurrent,
+        rate
+      )
+    }
+  }
+
+  impl ToTokens for RateLimit {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let calls = self.calls.get();
+      let period_ms = self.period_ms.get();
+      let calls = quote!(::core::num::NonZeroU32::new(#calls).unwrap());
+      let period_ms = quote!(::core::num::NonZeroU64::new(#period_ms).unwrap());
+      literal_struct!(
+        tokens,
+        ::tauri::utils::acl::limits::RateLimit,
+        calls,
+        period_ms
+      )
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn limits(toml: &str) -> CommandLimits {
+    toml::from_str(toml).unwrap()
+  }
+
+  #[test]
+  fn parse() {
+    let parsed = limits(
+      r#"
+      maxConcurrent = 2
+      rate = { calls = 10, periodMs = 1000 }
+      "#,
+    );
+    assert_eq!(parsed.max_concurrent, NonZeroU32::new(2));
+    assert_eq!(
+      parsed.rate,
+      Some(RateLimit {
+        calls: NonZeroU32::new(10).unwrap(),
+        period_ms: NonZeroU64::new(1000).unwrap(),
+      })
+    );
+
+    assert!(toml::from_str::<CommandLimits>("maxConcurrent = 0").is_err());
+    assert!(toml::from_str::<CommandLimits>("maxCalls = 1").is_err());
+  }
+
+  #[test]
+  fn merge_least_restrictive() {
+    let a = limits(
+      r#"
+      maxConcurrent = 1
+      rate = { calls = 10, periodMs = 1000 }
+      "#,
+    );
+    let b = limits(
+      r#"
+      maxConcurrent = 3
+      rate = { calls = 50, periodMs = 60000 }
+      "#,
+    );
+
+    let merged = a.merge(b);
+    assert_eq!(merged.max_concurrent, NonZeroU32::new(3));
+    assert_eq!(merged.rate, a.rate);
+
+    let unlimited_concurrency = limits("rate = { calls = 1, periodMs = 1000 }");
+    let merged = a.merge(unlimited_concurrency);
+    assert_eq!(merged.max_concurrent, None);
+    assert_eq!(merged.rate, a.rate);
+  }
+}
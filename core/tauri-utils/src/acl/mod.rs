@@ -4,21 +4,29 @@
 
 //! Access Control List types.
 
-use glob::Pattern;
 use serde::{Deserialize, Serialize};
-use std::num::NonZeroU64;
+use std::{collections::BTreeMap, num::NonZeroU64};
 use thiserror::Error;
 
-pub use self::{identifier::*, value::*};
+pub use self::{args::ArgConstraints, identifier::*, remote::RemoteUrlPattern, value::*};
 
+pub mod args;
 #[cfg(feature = "build")]
 pub mod build;
 pub mod capability;
 pub mod identifier;
+pub mod limits;
 pub mod plugin;
+pub mod remote;
+pub mod report;
 pub mod resolved;
 pub mod value;
 
+/// The key of the application permission manifest on the ACL maps.
+///
+/// Application permissions are referenced in capabilities without a prefix.
+pub const APP_ACL_KEY: &str = "__app-acl__";
+
 /// Possible errors while processing ACL files.
 #[derive(Debug, Error)]
 pub enum Error {
@@ -100,6 +108,28 @@ pub enum Error {
     available: String,
   },
 
+  /// Unknown application permission.
+  #[error("unknown application permission {permission}")]
+  UnknownAppPermission {
+    /// Permission identifier.
+    permission: String,
+  },
+
+  /// Permission with a lower version than the capability requires.
+  #[error("capability {capability} requires version {required} of permission {permission}, but {found} is the version of {resolved}")]
+  PermissionVersion {
+    /// Capability identifier.
+    capability: String,
+    /// Permission identifier referenced in the capability.
+    permission: String,
+    /// Permission identifier with the lower version, which can be part of the referenced set.
+    resolved: String,
+    /// Minimum version required by the capability.
+    required: NonZeroU64,
+    /// Version of the permission, 1 when it is not set.
+    found: NonZeroU64,
+  },
+
   /// Unknown permission.
   #[error("unknown permission {permission} for plugin {plugin}")]
   UnknownPermission {
@@ -114,7 +144,7 @@ pub enum Error {
 /// Allowed and denied commands inside a permission.
 ///
 /// If two commands clash inside of `allow` and `deny`, it should be denied by default.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Commands {
   /// Allowed command.
@@ -147,7 +177,7 @@ pub struct Scopes {
 /// It can enable commands to be accessible in the frontend of the application.
 ///
 /// If the scope is defined it can be used to fine grain control the access of individual or multiple commands.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Permission {
   /// The version of the permission.
@@ -166,10 +196,18 @@ pub struct Permission {
   /// Allowed or denied scoped when using this permission.
   #[serde(default)]
   pub scope: Scopes,
+
+  /// Constraints on the arguments of the allowed commands, enforced before the command is executed.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub args: ArgConstraints,
+
+  /// Limits on how often each webview can call the allowed commands.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub limits: Option<limits::CommandLimits>,
 }
 
 /// A set of direct permissions grouped together under a new name.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PermissionSet {
   /// A unique identifier for the permission.
@@ -189,11 +227,20 @@ pub enum ExecutionContext {
   Local,
   /// Remote URL is tring to use the IPC.
   Remote {
-    /// The domain trying to access the IPC (glob pattern).
-    domain: Pattern,
+    /// The URL pattern allowed to access the IPC.
+    url: RemoteUrlPattern,
   },
 }
 
+impl std::fmt::Display for ExecutionContext {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Local => write!(f, "local"),
+      Self::Remote { url } => write!(f, "remote: {url}"),
+    }
+  }
+}
+
 #[cfg(feature = "build")]
 mod build_ {
   use std::convert::identity;
@@ -212,9 +259,9 @@ mod build_ {
         Self::Local => {
           quote! { #prefix::Local }
         }
-        Self::Remote { domain } => {
-          let domain = domain.as_str();
-          quote! { #prefix::Remote { domain: #domain.parse().unwrap() } }
+        Self::Remote { url } => {
+          let url = url.as_str();
+          quote! { #prefix::Remote { url: #url.parse().unwrap() } }
         }
       });
     }
@@ -246,6 +293,13 @@ mod build_ {
       let description = opt_str_lit(self.description.as_ref());
       let commands = &self.commands;
       let scope = &self.scope;
+      let args = map_lit(
+        quote! { ::std::collections::BTreeMap },
+        &self.args,
+        str_lit,
+        identity,
+      );
+      let limits = opt_lit(self.limits.as_ref());
       literal_struct!(
         tokens,
         ::tauri::utils::acl::Permission,
@@ -253,7 +307,9 @@ mod build_ {
         identifier,
         description,
         commands,
-        scope
+        scope,
+        args,
+        limits
       )
     }
   }
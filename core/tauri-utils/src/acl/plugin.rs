@@ -42,7 +42,7 @@ pub struct PermissionFile {
 }
 
 /// Plugin manifest.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifest {
   /// Default permission.
   pub default_permission: Option<PermissionSet>,
@@ -0,0 +1,251 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! URL patterns used to match remote origins accessing the IPC.
+
+use std::{fmt, str::FromStr};
+
+use glob::Pattern;
+use thiserror::Error;
+use url::Url;
+
+const SCHEME_SEPARATOR: &str = "://";
+
+/// Errors that can happen when parsing a remote URL pattern.
+#[derive(Debug, Error)]
+pub enum ParseRemoteUrlPatternError {
+  /// The pattern is empty.
+  #[error("remote URL patterns cannot be empty")]
+  Empty,
+
+  /// The pattern does not have a host.
+  #[error("remote URL pattern {0} does not have a host")]
+  MissingHost(String),
+
+  /// The port is not a number nor a wildcard.
+  #[error("remote URL pattern {pattern} has an invalid port {port}, expected a number or `*`")]
+  InvalidPort {
+    /// The pattern.
+    pattern: String,
+    /// The invalid port.
+    port: String,
+  },
+
+  /// The IPv6 host is not closed.
+  #[error("remote URL pattern {0} has an unterminated IPv6 host")]
+  UnterminatedIpv6(String),
+
+  /// Invalid glob on the scheme or host.
+  #[error("remote URL pattern {pattern} has an invalid glob: {error}")]
+  Glob {
+    /// The pattern.
+    pattern: String,
+    /// The glob error.
+    error: glob::PatternError,
+  },
+}
+
+/// Port matcher of a [`RemoteUrlPattern`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum PortPattern {
+  /// Matches the scheme default port.
+  Default,
+  /// Matches any port.
+  Any,
+  /// Matches the given port.
+  Exact(u16),
+}
+
+/// A pattern matching remote URLs allowed to access the IPC.
+///
+/// The pattern has the format `[scheme://]host[:port][/path]`:
+///
+/// - `scheme` is a glob pattern. When omitted both `http` and `https` are allowed.
+/// - `host` is a glob pattern, e.g. `*.tauri.app`.
+/// - `port` is either a number or `*` to match any port. When omitted only the scheme default port is allowed.
+/// - `path` is a path prefix matched on segment boundaries, so `/api` matches `/api` and `/api/users` but not `/apis`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RemoteUrlPattern {
+  original: String,
+  scheme: Option<Pattern>,
+  host: Pattern,
+  port: PortPattern,
+  path: String,
+}
+
+impl RemoteUrlPattern {
+  /// The pattern string this matcher was parsed from.
+  pub fn as_str(&self) -> &str {
+    &self.original
+  }
+
+  /// Checks whether the given URL matches this pattern.
+  pub fn matches(&self, url: &Url) -> bool {
+    let scheme_matches = match &self.scheme {
+      Some(scheme) => scheme.matches(url.scheme()),
+      None => matches!(url.scheme(), "http" | "https"),
+    };
+    if !scheme_matches {
+      return false;
+    }
+
+    let host_matches = url
+      .host_str()
+      .map(|host| self.host.matches(host))
+      .unwrap_or(false);
+    if !host_matches {
+      return false;
+    }
+
+    let port_matches = match self.port {
+      // `Url::port` is `None` when the URL uses the scheme default port
+      PortPattern::Default => url.port().is_none(),
+      PortPattern::Any => true,
+      PortPattern::Exact(port) => url.port_or_known_default() == Some(port),
+    };
+    if !port_matches {
+      return false;
+    }
+
+    let prefix = self.path.trim_end_matches('/');
+    match url.path().strip_prefix(prefix) {
+      Some(rest) => rest.is_empty() || rest.starts_with('/'),
+      None => false,
+    }
+  }
+}
+
+impl FromStr for RemoteUrlPattern {
+  type Err = ParseRemoteUrlPatternError;
+
+  fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+    if pattern.is_empty() {
+      return Err(ParseRemoteUrlPatternError::Empty);
+    }
+
+    let glob = |value: &str| {
+      Pattern::new(value).map_err(|error| ParseRemoteUrlPatternError::Glob {
+        pattern: pattern.into(),
+        error,
+      })
+    };
+
+    let (scheme, rest) = match pattern.split_once(SCHEME_SEPARATOR) {
+      Some((scheme, rest)) => (Some(glob(scheme)?), rest),
+      None => (None, pattern),
+    };
+
+    let (authority, path) = match rest.find('/') {
+      Some(index) => rest.split_at(index),
+      None => (rest, ""),
+    };
+
+    let (host, port) = if let Some(ipv6) = authority.strip_prefix('[') {
+      let end = ipv6
+        .find(']')
+        .ok_or_else(|| ParseRemoteUrlPatternError::UnterminatedIpv6(pattern.into()))?;
+      // brackets are glob character classes, so the address is matched literally
+      let host = Pattern::escape(&authority[..end + 2]);
+      (host, authority[end + 2..].strip_prefix(':'))
+    } else {
+      match authority.split_once(':') {
+        Some((host, port)) => (host.to_string(), Some(port)),
+        None => (authority.to_string(), None),
+      }
+    };
+
+    if host.is_empty() {
+      return Err(ParseRemoteUrlPatternError::MissingHost(pattern.into()));
+    }
+
+    let port = match port {
+      None => PortPattern::Default,
+      Some("*") => PortPattern::Any,
+      Some(port) => port.parse().map(PortPattern::Exact).map_err(|_| {
+        ParseRemoteUrlPatternError::InvalidPort {
+          pattern: pattern.into(),
+          port: port.into(),
+        }
+      })?,
+    };
+
+    Ok(Self {
+      original: pattern.into(),
+      scheme,
+      host: glob(&host)?,
+      port,
+      path: path.into(),
+    })
+  }
+}
+
+impl fmt::Display for RemoteUrlPattern {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.original)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn matches(pattern: &str, url: &str) -> bool {
+    pattern
+      .parse::<RemoteUrlPattern>()
+      .unwrap()
+      .matches(&url.parse().unwrap())
+  }
+
+  #[test]
+  fn parse() {
+    assert!("tauri.app".parse::<RemoteUrlPattern>().is_ok());
+    assert!("https://*.tauri.app:*/api"
+      .parse::<RemoteUrlPattern>()
+      .is_ok());
+    assert!("http://[::1]:8080".parse::<RemoteUrlPattern>().is_ok());
+
+    // bad
+    assert!("".parse::<RemoteUrlPattern>().is_err());
+    assert!("https://".parse::<RemoteUrlPattern>().is_err());
+    assert!("https://:8080".parse::<RemoteUrlPattern>().is_err());
+    assert!("tauri.app:port".parse::<RemoteUrlPattern>().is_err());
+    assert!("tauri.app:70000".parse::<RemoteUrlPattern>().is_err());
+    assert!("http://[::1:8080".parse::<RemoteUrlPattern>().is_err());
+  }
+
+  #[test]
+  fn scheme() {
+    assert!(matches("tauri.app", "https://tauri.app"));
+    assert!(matches("tauri.app", "http://tauri.app"));
+    assert!(!matches("tauri.app", "ftp://tauri.app"));
+    assert!(matches("https://tauri.app", "https://tauri.app"));
+    assert!(!matches("https://tauri.app", "http://tauri.app"));
+  }
+
+  #[test]
+  fn host() {
+    assert!(matches("*.tauri.app", "https://v2.tauri.app"));
+    assert!(!matches("*.tauri.app", "https://tauri.studio"));
+    assert!(matches("http://[::1]:8080", "http://[::1]:8080"));
+  }
+
+  #[test]
+  fn port() {
+    assert!(matches("tauri.app", "https://tauri.app:443"));
+    assert!(!matches("tauri.app", "https://tauri.app:8080"));
+    assert!(matches("tauri.app:8080", "https://tauri.app:8080"));
+    assert!(!matches("tauri.app:8080", "https://tauri.app:8081"));
+    assert!(matches("tauri.app:*", "https://tauri.app:8081"));
+    assert!(matches("https://tauri.app:443", "https://tauri.app"));
+  }
+
+  #[test]
+  fn path() {
+    assert!(matches("tauri.app/", "https://tauri.app/docs"));
+    assert!(matches("tauri.app/docs", "https://tauri.app/docs"));
+    assert!(matches("tauri.app/docs/", "https://tauri.app/docs/api"));
+    assert!(!matches("tauri.app/docs", "https://tauri.app/docsite"));
+    assert!(!matches("tauri.app/docs", "https://tauri.app/blog"));
+  }
+}
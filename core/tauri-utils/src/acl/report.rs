@@ -0,0 +1,379 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Report of the access control list, describing what each window is allowed to do and why.
+//!
+//! Unlike [`Resolved`](super::resolved::Resolved), which is optimized for runtime lookups,
+//! the report keeps track of the capability and permission that granted each command and scope.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::platform::Target;
+
+use super::{
+  capability::Capability,
+  plugin::Manifest,
+  resolved::{capability_permissions, Resolved},
+  Error, Value, APP_ACL_KEY,
+};
+
+/// Whether a command is allowed or denied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+  /// The command is allowed.
+  Allow,
+  /// The command is denied. Takes precedence over allowed commands.
+  Deny,
+}
+
+/// The capability and permission that granted a command or scope.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Grant {
+  /// Identifier of the capability.
+  pub capability: String,
+  /// Identifier of the permission, as referenced by the capability.
+  pub permission: String,
+}
+
+/// A scope merged from all permissions referencing a command or plugin.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportScope {
+  /// Data that defines what is allowed by the scope.
+  pub allow: Vec<Value>,
+  /// Data that defines what is denied by the scope.
+  pub deny: Vec<Value>,
+}
+
+impl ReportScope {
+  /// Whether the scope does not allow nor deny anything.
+  pub fn is_empty(&self) -> bool {
+    self.allow.is_empty() && self.deny.is_empty()
+  }
+}
+
+/// A command available to a window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportCommand {
+  /// The full command name.
+  pub name: String,
+  /// Whether the command is allowed or denied.
+  pub decision: Decision,
+  /// The scope of the command.
+  ///
+  /// Matches the runtime behavior: the scope is merged from every capability allowing this command on the same context.
+  /// Denied commands do not have a scope.
+  pub scope: ReportScope,
+  /// The capabilities and permissions that allowed or denied this command.
+  pub granted_by: Vec<Grant>,
+}
+
+/// The commands available to a window label pattern on an execution context.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportEntry {
+  /// The window label pattern, as defined in the capabilities.
+  pub window: String,
+  /// The execution context, either `local` or `remote: <URL pattern>`.
+  pub context: String,
+  /// The allowed and denied commands.
+  pub commands: Vec<ReportCommand>,
+}
+
+/// The global scope of a plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportGlobalScope {
+  /// The plugin name, or [`APP_ACL_KEY`] for the application.
+  pub plugin: String,
+  /// The merged global scope.
+  pub scope: ReportScope,
+  /// The capabilities and permissions that defined this scope.
+  pub granted_by: Vec<Grant>,
+}
+
+/// A report of the access control list.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+  /// The commands of each window label pattern and execution context.
+  pub entries: Vec<ReportEntry>,
+  /// The global scope of each plugin.
+  pub global_scopes: Vec<ReportGlobalScope>,
+}
+
+/// A change between two reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum ReportChange {
+  /// A command is now available to the window.
+  Added {
+    /// The window label pattern.
+    window: String,
+    /// The execution context.
+    context: String,
+    /// The new command.
+    command: ReportCommand,
+  },
+  /// A command is no longer available to the window.
+  Removed {
+    /// The window label pattern.
+    window: String,
+    /// The execution context.
+    context: String,
+    /// The removed command.
+    command: ReportCommand,
+  },
+  /// The decision, scope or grants of a command changed.
+  Changed {
+    /// The window label pattern.
+    window: String,
+    /// The execution context.
+    context: String,
+    /// The command before the change.
+    before: ReportCommand,
+    /// The command after the change.
+    after: ReportCommand,
+  },
+  /// The global scope of a plugin changed.
+  GlobalScope {
+    /// The plugin name.
+    plugin: String,
+    /// The global scope before the change.
+    before: Option<ReportGlobalScope>,
+    /// The global scope after the change.
+    after: Option<ReportGlobalScope>,
+  },
+}
+
+impl Report {
+  /// Generates the report for the given plugin permissions and app capabilities.
+  ///
+  /// The commands and scopes are taken from [`Resolved::resolve`], so the report matches the runtime behavior.
+  pub fn new(
+    acl: &BTreeMap<String, Manifest>,
+    capabilities: &BTreeMap<String, Capability>,
+    target: Target,
+  ) -> Result<Self, Error> {
+    let resolved = Resolved::resolve(acl.clone(), capabilities.clone(), target)?;
+
+    // (window, context) -> command name -> command
+    let mut entries: BTreeMap<(String, String), BTreeMap<String, ReportCommand>> = BTreeMap::new();
+    for (decision, commands) in [
+      (Decision::Allow, &resolved.allowed_commands),
+      (Decision::Deny, &resolved.denied_commands),
+    ] {
+      for (key, command) in commands {
+        let context = key.context.to_string();
+        // the scope is merged from every capability allowing this command on the same context
+        let scope = match decision {
+          Decision::Allow => command
+            .scope
+            .and_then(|scope| resolved.command_scope.get(&scope))
+            .map(|scope| ReportScope {
+              allow: scope.allow.clone(),
+              deny: scope.deny.clone(),
+            })
+            .unwrap_or_default(),
+          Decision::Deny => ReportScope::default(),
+        };
+
+        for reference in &command.referenced_by {
+          let grant = Grant {
+            capability: reference.capability.clone(),
+            permission: reference.permission.clone(),
+          };
+          for window in &reference.windows {
+            let report_command = entries
+              .entry((window.as_str().to_string(), context.clone()))
+              .or_default()
+              .entry(key.name.clone())
+              .or_insert_with(|| ReportCommand {
+                name: key.name.clone(),
+                decision,
+                scope: scope.clone(),
+                granted_by: Vec::new(),
+              });
+            // denied commands take precedence
+            if decision == Decision::Deny {
+              report_command.decision = Decision::Deny;
+              report_command.scope = ReportScope::default();
+            }
+            if !report_command.granted_by.contains(&grant) {
+              report_command.granted_by.push(grant.clone());
+            }
+          }
+        }
+      }
+    }
+
+    let entries = entries
+      .into_iter()
+      .map(|((window, context), commands)| ReportEntry {
+        window,
+        context,
+        commands: commands
+          .into_values()
+          .map(|mut command| {
+            command.granted_by.sort();
+            command
+          })
+          .collect(),
+      })
+      .collect();
+
+    // the resolved global scopes do not keep track of the permissions that defined them
+    let mut global_grants: BTreeMap<&str, BTreeSet<Grant>> = BTreeMap::new();
+    for entry in capability_permissions(acl, capabilities, target)? {
+      if entry.commands.allow.is_empty() && entry.commands.deny.is_empty() {
+        global_grants
+          .entry(entry.plugin_name.unwrap_or(APP_ACL_KEY))
+          .or_default()
+          .insert(Grant {
+            capability: entry.capability.identifier.clone(),
+            permission: entry.permission_id.get().to_string(),
+          });
+      }
+    }
+
+    let global_scopes = resolved
+      .global_scope
+      .into_iter()
+      .map(|(plugin, scope)| ReportGlobalScope {
+        granted_by: global_grants
+          .remove(plugin.as_str())
+          .unwrap_or_default()
+          .into_iter()
+          .collect(),
+        plugin,
+        scope: ReportScope {
+          allow: scope.allow,
+          deny: scope.deny,
+        },
+      })
+      .collect();
+
+    Ok(Self {
+      entries,
+      global_scopes,
+    })
+  }
+
+  /// The commands available to the given window label on each execution context.
+  ///
+  /// Merges the entries of every window pattern matching the label, so a command denied by any of them is denied.
+  pub fn window(&self, label: &str) -> Vec<ReportEntry> {
+    let mut contexts: BTreeMap<&str, BTreeMap<&str, ReportCommand>> = BTreeMap::new();
+
+    for entry in &self.entries {
+      let matches = glob::Pattern::new(&entry.window)
+        .map(|pattern| pattern.matches(label))
+        .unwrap_or(false);
+      if !matches {
+        continue;
+      }
+
+      let commands = contexts.entry(&entry.context).or_default();
+      for command in &entry.commands {
+        match commands.get_mut(command.name.as_str()) {
+          Some(existing) => {
+            if command.decision == Decision::Deny {
+              existing.decision = Decision::Deny;
+              existing.scope = ReportScope::default();
+            }
+            for grant in &command.granted_by {
+              if !existing.granted_by.contains(grant) {
+                existing.granted_by.push(grant.clone());
+              }
+            }
+            existing.granted_by.sort();
+          }
+          None => {
+            commands.insert(&command.name, command.clone());
+          }
+        }
+      }
+    }
+
+    contexts
+      .into_iter()
+      .map(|(context, commands)| ReportEntry {
+        window: label.into(),
+        context: context.into(),
+        commands: commands.into_values().collect(),
+      })
+      .collect()
+  }
+
+  /// Lists the changes needed to go from this report to the `other` report.
+  pub fn diff(&self, other: &Report) -> Vec<ReportChange> {
+    fn commands(report: &Report) -> BTreeMap<(&str, &str, &str), &ReportCommand> {
+      report
+        .entries
+        .iter()
+        .flat_map(|entry| {
+          entry.commands.iter().map(|command| {
+            (
+              (
+                entry.window.as_str(),
+                entry.context.as_str(),
+                command.name.as_str(),
+              ),
+              command,
+            )
+          })
+        })
+        .collect()
+    }
+
+    let before = commands(self);
+    let after = commands(other);
+    let mut changes = Vec::new();
+
+    for (&(window, context, name), &command) in &before {
+      match after.get(&(window, context, name)) {
+        None => changes.push(ReportChange::Removed {
+          window: window.into(),
+          context: context.into(),
+          command: command.clone(),
+        }),
+        Some(&after) if after != command => changes.push(ReportChange::Changed {
+          window: window.into(),
+          context: context.into(),
+          before: command.clone(),
+          after: after.clone(),
+        }),
+        _ => {}
+      }
+    }
+    for (&(window, context, name), &command) in &after {
+      if !before.contains_key(&(window, context, name)) {
+        changes.push(ReportChange::Added {
+          window: window.into(),
+          context: context.into(),
+          command: command.clone(),
+        });
+      }
+    }
+
+    let plugins = self
+      .global_scopes
+      .iter()
+      .chain(&other.global_scopes)
+      .map(|s| s.plugin.as_str())
+      .collect::<BTreeSet<_>>();
+    for plugin in plugins {
+      let before = self.global_scopes.iter().find(|s| s.plugin == plugin);
+      let after = other.global_scopes.iter().find(|s| s.plugin == plugin);
+      if before != after {
+        changes.push(ReportChange::GlobalScope {
+          plugin: plugin.into(),
+          before: before.cloned(),
+          after: after.cloned(),
+        });
+      }
+    }
+
+    changes
+  }
+}
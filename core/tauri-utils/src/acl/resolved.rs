@@ -8,36 +8,68 @@ use std::{
   collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
   fmt,
   hash::{Hash, Hasher},
+  num::NonZeroU64,
 };
 
-use glob::Pattern;
-
 use crate::platform::Target;
 
 use super::{
+  args::{validate_args, ArgViolation},
   capability::{Capability, CapabilityContext, PermissionEntry},
+  identifier::Identifier,
+  limits::CommandLimits,
   plugin::Manifest,
-  Commands, Error, ExecutionContext, Permission, PermissionSet, Scopes, Value,
+  ArgConstraints, Commands, Error, ExecutionContext, Permission, PermissionSet, Scopes, Value,
+  APP_ACL_KEY,
 };
 
 /// A key for a scope, used to link a [`ResolvedCommand#structfield.scope`] to the store [`Resolved#structfield.scopes`].
 pub type ScopeKey = u64;
 
 /// Metadata for what referenced a [`ResolvedCommand`].
-#[cfg(debug_assertions)]
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Clone, PartialEq)]
 pub struct ResolvedCommandReference {
   /// Identifier of the capability.
   pub capability: String,
-  /// Identifier of the permission.
+  /// Identifier of the permission, including its plugin prefix, e.g. `fs:allow-read`.
   pub permission: String,
+  /// The window label patterns of the capability.
+  pub windows: Vec<glob::Pattern>,
+  /// The argument constraints of each permission allowing the command through this reference.
+  ///
+  /// The arguments must satisfy at least one of them. Empty when a permission allows the command without constraints.
+  pub args: Vec<ArgConstraints>,
+  /// The limits of the command, merged from every permission allowing it through this reference.
+  /// `None` when a permission does not limit it.
+  pub limits: Option<CommandLimits>,
+}
+
+impl ResolvedCommandReference {
+  /// Whether the capability of this reference applies to the window.
+  pub fn matches_window(&self, window: &str) -> bool {
+    self.windows.iter().any(|w| w.matches(window))
+  }
+}
+
+impl fmt::Debug for ResolvedCommandReference {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("ResolvedCommandReference")
+      .field("capability", &self.capability)
+      .field("permission", &self.permission)
+      .field(
+        "windows",
+        &self.windows.iter().map(|w| w.as_str()).collect::<Vec<_>>(),
+      )
+      .field("args", &self.args)
+      .field("limits", &self.limits)
+      .finish()
+  }
 }
 
 /// A resolved command permission.
-#[derive(Default, Clone, PartialEq, Eq)]
+#[derive(Default, Clone, PartialEq)]
 pub struct ResolvedCommand {
   /// The list of capability/permission that referenced this command.
-  #[cfg(debug_assertions)]
   pub referenced_by: Vec<ResolvedCommandReference>,
   /// The list of window label patterns that was resolved for this command.
   pub windows: Vec<glob::Pattern>,
@@ -45,11 +77,66 @@ pub struct ResolvedCommand {
   pub scope: Option<ScopeKey>,
 }
 
+impl ResolvedCommand {
+  /// Checks the IPC arguments of a call from the window against the [argument constraints](ResolvedCommandReference::args)
+  /// of the capabilities that allow this command on that window.
+  ///
+  /// The constraints of the capabilities targeting other windows do not apply.
+  /// When no constraint set is satisfied, returns the violations of the closest one.
+  pub fn validate_args(
+    &self,
+    window: &str,
+    args: &serde_json::Value,
+  ) -> Result<(), Vec<ArgViolation>> {
+    let references = self
+      .referenced_by
+      .iter()
+      .filter(|r| r.matches_window(window))
+      .collect::<Vec<_>>();
+    // a capability allows the command on this window without constraints
+    if references.iter().any(|r| r.args.is_empty()) {
+      return Ok(());
+    }
+
+    let mut closest: Option<Vec<ArgViolation>> = None;
+    for constraints in references.iter().flat_map(|r| &r.args) {
+      match validate_args(constraints, args) {
+        Ok(()) => return Ok(()),
+        Err(violations) => {
+          if closest
+            .as_ref()
+            .map_or(true, |closest| violations.len() < closest.len())
+          {
+            closest.replace(violations);
+          }
+        }
+      }
+    }
+    closest.map_or(Ok(()), Err)
+  }
+
+  /// The [limits](ResolvedCommandReference::limits) of a call from the window,
+  /// merged from the capabilities that allow this command on that window.
+  ///
+  /// The limits of the capabilities targeting other windows do not apply.
+  /// `None` when a capability allows the command on this window without limits.
+  pub fn limits(&self, window: &str) -> Option<CommandLimits> {
+    self
+      .referenced_by
+      .iter()
+      .filter(|r| r.matches_window(window))
+      .map(|r| r.limits)
+      .collect::<Option<Vec<_>>>()
+      .and_then(|limits| limits.into_iter().reduce(CommandLimits::merge))
+  }
+}
+
 impl fmt::Debug for ResolvedCommand {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     f.debug_struct("ResolvedCommand")
       .field("windows", &self.windows)
       .field("scope", &self.scope)
+      .field("referenced_by", &self.referenced_by)
       .finish()
   }
 }
@@ -115,99 +202,81 @@ impl Resolved {
     let mut global_scope: BTreeMap<String, Vec<Scopes>> = BTreeMap::new();
 
     // resolve commands
-    for capability in capabilities.values() {
-      if !capability.platforms.contains(&target) {
-        continue;
-      }
-
-      for permission_entry in &capability.permissions {
-        let permission_id = permission_entry.identifier();
-        let permission_name = permission_id.get_base();
-
-        if let Some(plugin_name) = permission_id.get_prefix() {
-          let permissions = get_permissions(plugin_name, permission_name, &acl)?;
-
-          let mut resolved_scope = Scopes::default();
-          let mut commands = Commands::default();
-
-          if let PermissionEntry::ExtendedPermission {
-            identifier: _,
-            scope,
-          } = permission_entry
-          {
-            if let Some(allow) = scope.allow.clone() {
-              resolved_scope
-                .allow
-                .get_or_insert_with(Default::default)
-                .extend(allow);
-            }
-            if let Some(deny) = scope.deny.clone() {
-              resolved_scope
-                .deny
-                .get_or_insert_with(Default::default)
-                .extend(deny);
-            }
-          }
-
-          for permission in permissions {
-            if let Some(allow) = permission.scope.allow.clone() {
-              resolved_scope
-                .allow
-                .get_or_insert_with(Default::default)
-                .extend(allow);
-            }
-            if let Some(deny) = permission.scope.deny.clone() {
-              resolved_scope
-                .deny
-                .get_or_insert_with(Default::default)
-                .extend(deny);
-            }
+    for entry in capability_permissions(&acl, &capabilities, target)? {
+      let CapabilityPermission {
+        capability,
+        plugin_name,
+        permission_id,
+        permissions,
+        scope: resolved_scope,
+        commands,
+      } = entry;
+      if commands.allow.is_empty() && commands.deny.is_empty() {
+        // global scope
+        global_scope
+          .entry(plugin_name.unwrap_or(APP_ACL_KEY).to_string())
+          .or_default()
+          .push(resolved_scope);
+      } else {
+        let has_scope = resolved_scope.allow.is_some() || resolved_scope.deny.is_some();
+        if has_scope {
+          current_scope_id += 1;
+          command_scopes.insert(current_scope_id, resolved_scope);
+        }
 
-            commands.allow.extend(permission.commands.allow.clone());
-            commands.deny.extend(permission.commands.deny.clone());
-          }
+        let scope_id = if has_scope {
+          Some(current_scope_id)
+        } else {
+          None
+        };
 
-          if commands.allow.is_empty() && commands.deny.is_empty() {
-            // global scope
-            global_scope
-              .entry(plugin_name.to_string())
-              .or_default()
-              .push(resolved_scope);
+        for allowed_command in &commands.allow {
+          // the command is unconstrained for the windows of this capability
+          // if any of its permissions allows it without argument constraints
+          let args = permissions
+            .iter()
+            .filter(|p| p.commands.allow.contains(allowed_command))
+            .map(|p| &p.args)
+            .collect::<Vec<_>>();
+          let args = if args.iter().any(|args| args.is_empty()) {
+            Vec::new()
           } else {
-            let has_scope = resolved_scope.allow.is_some() || resolved_scope.deny.is_some();
-            if has_scope {
-              current_scope_id += 1;
-              command_scopes.insert(current_scope_id, resolved_scope);
+            for constraint in args.iter().flat_map(|args| args.values()) {
+              constraint.check_patterns()?;
             }
+            args.into_iter().cloned().collect()
+          };
 
-            let scope_id = if has_scope {
-              Some(current_scope_id)
-            } else {
-              None
-            };
-
-            for allowed_command in &commands.allow {
-              resolve_command(
-                &mut allowed_commands,
-                format!("plugin:{plugin_name}|{allowed_command}"),
-                capability,
-                scope_id,
-                #[cfg(debug_assertions)]
-                permission_name.to_string(),
-              );
-            }
+          // likewise, the command is unlimited for the windows of this capability
+          // if any of its permissions allows it without limits
+          let limits = permissions
+            .iter()
+            .filter(|p| p.commands.allow.contains(allowed_command))
+            .map(|p| p.limits.filter(|limits| !limits.is_empty()))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|limits| limits.into_iter().reduce(CommandLimits::merge));
+
+          resolve_command(
+            &mut allowed_commands,
+            command_name(plugin_name, allowed_command),
+            capability,
+            scope_id,
+            args,
+            limits,
+            permission_id.get().to_string(),
+          )?;
+        }
 
-            for denied_command in &commands.deny {
-              resolve_command(
-                &mut denied_commands,
-                format!("plugin:{plugin_name}|{denied_command}"),
-                capability,
-                scope_id,
-                #[cfg(debug_assertions)]
-                permission_name.to_string(),
-              );
-            }
-          }
+        for denied_command in &commands.deny {
+          resolve_command(
+            &mut denied_commands,
+            command_name(plugin_name, denied_command),
+            capability,
+            scope_id,
+            Vec::new(),
+            None,
+            permission_id.get().to_string(),
+          )?;
         }
       }
     }
@@ -269,7 +338,6 @@ impl Resolved {
           Ok((
             key,
             ResolvedCommand {
-              #[cfg(debug_assertions)]
               referenced_by: cmd.referenced_by,
               windows: parse_window_patterns(cmd.windows)?,
               scope: cmd.resolved_scope_key,
@@ -283,7 +351,6 @@ impl Resolved {
           Ok((
             key,
             ResolvedCommand {
-              #[cfg(debug_assertions)]
               referenced_by: cmd.referenced_by,
               windows: parse_window_patterns(cmd.windows)?,
               scope: cmd.resolved_scope_key,
@@ -299,6 +366,124 @@ impl Resolved {
   }
 }
 
+/// A permission entry of a capability, with the permissions it references.
+pub(crate) struct CapabilityPermission<'a> {
+  pub capability: &'a Capability,
+  /// The plugin of the permission, `None` for the application.
+  pub plugin_name: Option<&'a str>,
+  pub permission_id: &'a Identifier,
+  pub permissions: Vec<&'a Permission>,
+  /// The scope of the permissions and of the permission entry.
+  pub scope: Scopes,
+  /// The commands of the permissions. The permissions only define a global scope when empty.
+  pub commands: Commands,
+}
+
+/// Lists the permission entries of the capabilities enabled on the target, resolving the permissions they reference.
+pub(crate) fn capability_permissions<'a>(
+  acl: &'a BTreeMap<String, Manifest>,
+  capabilities: &'a BTreeMap<String, Capability>,
+  target: Target,
+) -> Result<Vec<CapabilityPermission<'a>>, Error> {
+  let mut entries = Vec::new();
+
+  for capability in capabilities.values() {
+    if !capability.platforms.contains(&target) {
+      continue;
+    }
+
+    for permission_entry in &capability.permissions {
+      let permission_id = permission_entry.identifier();
+      let permission_name = permission_id.get_base();
+
+      // permissions without a prefix reference the application commands
+      let plugin_name = permission_id.get_prefix();
+      let permissions = get_permissions(plugin_name, permission_name, acl)?;
+
+      let mut resolved_scope = Scopes::default();
+      let mut commands = Commands::default();
+
+      if let Some(required) = permission_entry.min_version() {
+        check_permission_versions(capability, permission_id.get(), &permissions, required)?;
+      }
+
+      if let PermissionEntry::ExtendedPermission { scope, .. } = permission_entry {
+        if let Some(allow) = scope.allow.clone() {
+          resolved_scope
+            .allow
+            .get_or_insert_with(Default::default)
+            .extend(allow);
+        }
+        if let Some(deny) = scope.deny.clone() {
+          resolved_scope
+            .deny
+            .get_or_insert_with(Default::default)
+            .extend(deny);
+        }
+      }
+
+      for permission in &permissions {
+        if let Some(allow) = permission.scope.allow.clone() {
+          resolved_scope
+            .allow
+            .get_or_insert_with(Default::default)
+            .extend(allow);
+        }
+        if let Some(deny) = permission.scope.deny.clone() {
+          resolved_scope
+            .deny
+            .get_or_insert_with(Default::default)
+            .extend(deny);
+        }
+
+        commands.allow.extend(permission.commands.allow.clone());
+        commands.deny.extend(permission.commands.deny.clone());
+      }
+
+      entries.push(CapabilityPermission {
+        capability,
+        plugin_name,
+        permission_id,
+        permissions,
+        scope: resolved_scope,
+        commands,
+      });
+    }
+  }
+
+  Ok(entries)
+}
+
+/// The IPC command name of a plugin command, or the raw name for application commands.
+fn command_name(plugin_name: Option<&str>, command: &str) -> String {
+  match plugin_name {
+    Some(plugin_name) => format!("plugin:{plugin_name}|{command}"),
+    None => command.to_string(),
+  }
+}
+
+/// Checks that the permissions referenced in the capability are at least at the required version.
+fn check_permission_versions(
+  capability: &Capability,
+  permission: &str,
+  permissions: &[&Permission],
+  required: NonZeroU64,
+) -> Result<(), Error> {
+  for resolved in permissions {
+    let found = resolved.version.unwrap_or(NonZeroU64::MIN);
+    if found < required {
+      return Err(Error::PermissionVersion {
+        capability: capability.identifier.clone(),
+        permission: permission.to_string(),
+        resolved: resolved.identifier.clone(),
+        required,
+        found,
+      });
+    }
+  }
+  Ok(())
+}
+
 fn parse_window_patterns(windows: HashSet<String>) -> Result<Vec<glob::Pattern>, Error> {
   let mut patterns = Vec::new();
   for window in windows {
@@ -307,9 +492,25 @@ fn parse_window_patterns(windows: HashSet<String>) -> Result<Vec<glob::Pattern>,
   Ok(patterns)
 }
 
+/// The execution contexts a capability applies to.
+fn capability_contexts(capability: &Capability) -> Vec<ExecutionContext> {
+  match &capability.context {
+    CapabilityContext::Local => {
+      vec![ExecutionContext::Local]
+    }
+    CapabilityContext::Remote { urls } => urls
+      .iter()
+      .map(|url| ExecutionContext::Remote {
+        url: url
+          .parse()
+          .unwrap_or_else(|e| panic!("invalid remote URL pattern {url}: {e}")),
+      })
+      .collect(),
+  }
+}
+
 #[derive(Debug, Default)]
 struct ResolvedCommandTemp {
-  #[cfg(debug_assertions)]
   pub referenced_by: Vec<ResolvedCommandReference>,
   pub windows: HashSet<String>,
   pub scope: Vec<ScopeKey>,
@@ -321,22 +522,17 @@ fn resolve_command(
   command: String,
   capability: &Capability,
   scope_id: Option<ScopeKey>,
-  #[cfg(debug_assertions)] referenced_by_permission_identifier: String,
-) {
-  let contexts = match &capability.context {
-    CapabilityContext::Local => {
-      vec![ExecutionContext::Local]
-    }
-    CapabilityContext::Remote { domains } => domains
-      .iter()
-      .map(|domain| ExecutionContext::Remote {
-        domain: Pattern::new(domain)
-          .unwrap_or_else(|e| panic!("invalid glob pattern for remote domain {domain}: {e}")),
-      })
-      .collect(),
-  };
-
-  for context in contexts {
+  args: Vec<ArgConstraints>,
+  limits: Option<CommandLimits>,
+  referenced_by_permission_identifier: String,
+) -> Result<(), Error> {
+  let windows = capability
+    .windows
+    .iter()
+    .map(|window| glob::Pattern::new(window))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  for context in capability_contexts(capability) {
     let resolved = commands
       .entry(CommandKey {
         name: command.clone(),
@@ -344,10 +540,12 @@ fn resolve_command(
       })
       .or_default();
 
-    #[cfg(debug_assertions)]
     resolved.referenced_by.push(ResolvedCommandReference {
       capability: capability.identifier.clone(),
       permission: referenced_by_permission_identifier.clone(),
+      windows: windows.clone(),
+      args: args.clone(),
+      limits,
     });
 
     resolved.windows.extend(capability.windows.clone());
@@ -355,6 +553,8 @@ fn resolve_command(
       resolved.scope.push(id);
     }
   }
+
+  Ok(())
 }
 
 // get the permissions from a permission set
@@ -381,33 +581,206 @@ fn get_permission_set_permissions<'a>(
 }
 
 fn get_permissions<'a>(
-  plugin_name: &'a str,
+  plugin_name: Option<&'a str>,
   permission_name: &'a str,
   acl: &'a BTreeMap<String, Manifest>,
 ) -> Result<Vec<&'a Permission>, Error> {
-  let manifest = acl.get(plugin_name).ok_or_else(|| Error::UnknownPlugin {
-    plugin: plugin_name.to_string(),
-    available: acl.keys().cloned().collect::<Vec<_>>().join(", "),
-  })?;
+  let unknown_permission = || match plugin_name {
+    Some(plugin_name) => Error::UnknownPermission {
+      plugin: plugin_name.to_string(),
+      permission: permission_name.to_string(),
+    },
+    None => Error::UnknownAppPermission {
+      permission: permission_name.to_string(),
+    },
+  };
+
+  let manifest = match plugin_name {
+    Some(plugin_name) => acl.get(plugin_name).ok_or_else(|| Error::UnknownPlugin {
+      plugin: plugin_name.to_string(),
+      available: acl
+        .keys()
+        .filter(|key| *key != APP_ACL_KEY)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", "),
+    })?,
+    None => acl.get(APP_ACL_KEY).ok_or_else(unknown_permission)?,
+  };
 
   if permission_name == "default" {
     manifest
       .default_permission
       .as_ref()
-      .ok_or_else(|| Error::UnknownPermission {
-        plugin: plugin_name.to_string(),
-        permission: permission_name.to_string(),
-      })
+      .ok_or_else(unknown_permission)
       .and_then(|default| get_permission_set_permissions(manifest, default))
   } else if let Some(set) = manifest.permission_sets.get(permission_name) {
     get_permission_set_permissions(manifest, set)
   } else if let Some(permission) = manifest.permissions.get(permission_name) {
     Ok(vec![permission])
   } else {
-    Err(Error::UnknownPermission {
-      plugin: plugin_name.to_string(),
-      permission: permission_name.to_string(),
-    })
+    Err(unknown_permission())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::acl::args::ArgConstraint;
+
+  fn permission(identifier: &str, version: Option<u64>) -> Permission {
+    Permission {
+      version: version.and_then(NonZeroU64::new),
+      identifier: identifier.into(),
+      description: None,
+      commands: Commands {
+        allow: vec![identifier.into()],
+        deny: Vec::new(),
+      },
+      scope: Default::default(),
+      args: Default::default(),
+      limits: None,
+    }
+  }
+
+  fn acl() -> BTreeMap<String, Manifest> {
+    let manifest = Manifest {
+      default_permission: None,
+      permissions: [
+        ("allow-read", permission("allow-read", Some(3))),
+        ("allow-write", permission("allow-write", None)),
+        ("allow-read-docs", {
+          let mut permission = permission("allow-read-docs", None);
+          permission.commands.allow = vec!["allow-read".into()];
+          permission.args.insert(
+            "path".into(),
+            ArgConstraint {
+              glob: vec!["/docs/**".into()],
+              ..Default::default()
+            },
+          );
+          permission
+        }),
+        ("allow-read-throttled", {
+          let mut permission = permission("allow-read-throttled", None);
+          permission.commands.allow = vec!["allow-read".into()];
+          permission.limits = Some(CommandLimits {
+            max_concurrent: std::num::NonZeroU32::new(1),
+            rate: None,
+          });
+          permission
+        }),
+      ]
+      .into_iter()
+      .map(|(identifier, permission)| (identifier.to_string(), permission))
+      .collect(),
+      permission_sets: [(
+        "read-write".to_string(),
+        PermissionSet {
+          identifier: "read-write".into(),
+          description: String::new(),
+          permissions: vec!["allow-read".into(), "allow-write".into()],
+        },
+      )]
+      .into(),
+      global_scope_schema: None,
+    };
+    [("fs".to_string(), manifest)].into()
+  }
+
+  fn capability(identifier: &str, window: &str, permission: &str) -> (String, Capability) {
+    let capability = serde_json::from_value(serde_json::json!({
+      "identifier": identifier,
+      "windows": [window],
+      "permissions": [permission],
+    }))
+    .unwrap();
+    (identifier.to_string(), capability)
+  }
+
+  fn resolve(permission: serde_json::Value) -> Result<Resolved, Error> {
+    let capability: Capability = serde_json::from_value(serde_json::json!({
+      "identifier": "main",
+      "windows": ["main"],
+      "permissions": [permission],
+    }))
+    .unwrap();
+    Resolved::resolve(
+      acl(),
+      [("main".to_string(), capability)].into(),
+      Target::Linux,
+    )
+  }
+
+  #[test]
+  fn permission_min_version() {
+    assert!(resolve(serde_json::json!("fs:allow-read")).is_ok());
+    assert!(resolve(serde_json::json!({ "identifier": "fs:allow-read", "minVersion": 3 })).is_ok());
+
+    let error = resolve(serde_json::json!({ "identifier": "fs:allow-read", "minVersion": 4 }))
+      .unwrap_err()
+      .to_string();
+    assert_eq!(
+      error,
+      "capability main requires version 4 of permission fs:allow-read, but 3 is the version of allow-read"
+    );
+
+    // unversioned permissions are at version 1, and every permission of a set is checked
+    assert!(matches!(
+      resolve(serde_json::json!({ "identifier": "fs:read-write", "minVersion": 2 })),
+      Err(Error::PermissionVersion { resolved, found, .. }) if resolved == "allow-write" && found.get() == 1
+    ));
+  }
+
+  #[test]
+  fn args_per_window() {
+    let resolved = Resolved::resolve(
+      acl(),
+      [
+        capability("docs", "docs", "fs:allow-read-docs"),
+        capability("main", "main", "fs:allow-read"),
+      ]
+      .into(),
+      Target::Linux,
+    )
+    .unwrap();
+    let command = &resolved.allowed_commands[&CommandKey {
+      name: "plugin:fs|allow-read".into(),
+      context: ExecutionContext::Local,
+    }];
+
+    // the unconstrained grant of the main window does not lift the constraints of the docs window
+    let outside = serde_json::json!({ "path": "/etc/passwd" });
+    assert!(command.validate_args("main", &outside).is_ok());
+    assert!(command.validate_args("docs", &outside).is_err());
+    assert!(command
+      .validate_args("docs", &serde_json::json!({ "path": "/docs/guide.md" }))
+      .is_ok());
+  }
+
+  #[test]
+  fn limits_per_window() {
+    let resolved = Resolved::resolve(
+      acl(),
+      [
+        capability("a", "a", "fs:allow-read-throttled"),
+        capability("b", "b", "fs:allow-read"),
+      ]
+      .into(),
+      Target::Linux,
+    )
+    .unwrap();
+    let command = &resolved.allowed_commands[&CommandKey {
+      name: "plugin:fs|allow-read".into(),
+      context: ExecutionContext::Local,
+    }];
+
+    // the unlimited grant of window b does not lift the cap of window a
+    assert_eq!(
+      command.limits("a").and_then(|limits| limits.max_concurrent),
+      std::num::NonZeroU32::new(1)
+    );
+    assert_eq!(command.limits("b"), None);
   }
 }
 
@@ -433,51 +806,59 @@ mod build {
     }
   }
 
-  #[cfg(debug_assertions)]
   impl ToTokens for ResolvedCommandReference {
     fn to_tokens(&self, tokens: &mut TokenStream) {
       let capability = str_lit(&self.capability);
       let permission = str_lit(&self.permission);
+      let windows = window_patterns_lit(&self.windows);
+      let args = args_lit(&self.args);
+      let limits = opt_lit(self.limits.as_ref());
       literal_struct!(
         tokens,
         ::tauri::utils::acl::resolved::ResolvedCommandReference,
         capability,
-        permission
+        permission,
+        windows,
+        args,
+        limits
       )
     }
   }
 
   impl ToTokens for ResolvedCommand {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-      #[cfg(debug_assertions)]
       let referenced_by = vec_lit(&self.referenced_by, identity);
-
-      let windows = vec_lit(&self.windows, |window| {
-        let w = window.as_str();
-        quote!(#w.parse().unwrap())
-      });
+      let windows = window_patterns_lit(&self.windows);
       let scope = opt_lit(self.scope.as_ref());
 
-      #[cfg(debug_assertions)]
-      {
-        literal_struct!(
-          tokens,
-          ::tauri::utils::acl::resolved::ResolvedCommand,
-          referenced_by,
-          windows,
-          scope
-        )
-      }
-      #[cfg(not(debug_assertions))]
       literal_struct!(
         tokens,
         ::tauri::utils::acl::resolved::ResolvedCommand,
+        referenced_by,
         windows,
         scope
       )
     }
   }
 
+  fn args_lit(args: &[ArgConstraints]) -> TokenStream {
+    vec_lit(args, |args| {
+      map_lit(
+        quote! { ::std::collections::BTreeMap },
+        args,
+        str_lit,
+        identity,
+      )
+    })
+  }
+
+  fn window_patterns_lit(windows: &[glob::Pattern]) -> TokenStream {
+    vec_lit(windows, |window| {
+      let w = window.as_str();
+      quote!(#w.parse().unwrap())
+    })
+  }
+
   impl ToTokens for ResolvedScope {
     fn to_tokens(&self, tokens: &mut TokenStream) {
       let allow = vec_lit(&self.allow, identity);
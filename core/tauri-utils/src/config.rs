@@ -1214,6 +1214,11 @@ pub struct WindowConfig {
   ///
   /// - **macOS**: Requires the `macos-proxy` feature flag and only compiles for macOS 14+.
   pub proxy_url: Option<Url>,
+  /// Whether the IPC calls of this window go through the isolation frame.
+  ///
+  /// Defaults to `true` when [`pattern`](SecurityConfig#structfield.pattern) is set to `isolation`,
+  /// including windows that load remote content. Enabling it requires the isolation pattern to be configured.
+  pub isolation: Option<bool>,
 }
 
 impl Default for WindowConfig {
@@ -1259,6 +1264,7 @@ impl Default for WindowConfig {
       incognito: false,
       parent: None,
       proxy_url: None,
+      isolation: None,
     }
   }
 }
@@ -1558,6 +1564,32 @@ pub struct SecurityConfig {
   /// The pattern to use.
   #[serde(default)]
   pub pattern: PatternKind,
+  /// Enables the access control list for the application commands.
+  ///
+  /// When enabled, the commands registered with `tauri::Builder::invoke_handler` must be allowed by a capability,
+  /// like plugin commands. Application permissions are referenced without a prefix, e.g. `allow-my-command`,
+  /// and can be generated with `tauri_build::AppManifest::commands`.
+  #[serde(default, alias = "app-acl")]
+  pub app_acl: bool,
+  /// Reports Content Security Policy violations to the application.
+  ///
+  /// When enabled, Tauri adds the `report-uri` and `report-to` directives to the CSP of the assets it serves,
+  /// pointing to an internal custom protocol that emits each violation as a `tauri://csp-violation` event
+  /// to the webview that triggered it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Unsupported, the policy is injected with a `<meta>` tag which cannot declare report endpoints.
+  #[serde(default, alias = "csp-report")]
+  pub csp_report: bool,
+  /// Binds IPC access to the page load that received it.
+  ///
+  /// When enabled, each page load gets a new token, set in the page once it is loaded,
+  /// that must be sent with every IPC call. The token is invalidated when the webview navigates,
+  /// so calls replayed from a previous page load are rejected.
+  /// IPC calls made before the page is loaded wait for its token.
+  #[serde(default, alias = "ipc-token")]
+  pub ipc_token: bool,
 }
 
 /// The application pattern.
@@ -2181,6 +2213,7 @@ mod build {
       let window_effects = opt_lit(self.window_effects.as_ref());
       let incognito = self.incognito;
       let parent = opt_str_lit(self.parent.as_ref());
+      let isolation = opt_lit(self.isolation.as_ref());
 
       literal_struct!(
         tokens,
@@ -2224,7 +2257,8 @@ mod build {
         shadow,
         window_effects,
         incognito,
-        parent
+        parent,
+        isolation
       );
     }
   }
@@ -2458,6 +2492,9 @@ mod build {
       let dangerous_disable_asset_csp_modification = &self.dangerous_disable_asset_csp_modification;
       let asset_protocol = &self.asset_protocol;
       let pattern = &self.pattern;
+      let app_acl = self.app_acl;
+      let csp_report = self.csp_report;
+      let ipc_token = self.ipc_token;
 
       literal_struct!(
         tokens,
@@ -2467,7 +2504,10 @@ mod build {
         freeze_prototype,
         dangerous_disable_asset_csp_modification,
         asset_protocol,
-        pattern
+        pattern,
+        app_acl,
+        csp_report,
+        ipc_token
       );
     }
   }
@@ -2606,6 +2646,9 @@ mod test {
         dangerous_disable_asset_csp_modification: DisabledCspModificationKind::Flag(false),
         asset_protocol: AssetProtocolConfig::default(),
         pattern: Default::default(),
+        app_acl: false,
+        csp_report: false,
+        ipc_token: false,
       },
       tray_icon: None,
       macos_private_api: false,
@@ -95,6 +95,21 @@ impl Keys {
     &self.aes_gcm
   }
 
+  /// Encrypts a message using the generated keys, as the isolation frame does.
+  pub fn encrypt(&self, payload: &[u8]) -> Result<RawIsolationPayload<'static>, Error> {
+    let mut nonce = [0u8; 12];
+    getrandom(&mut nonce)?;
+    let payload = self
+      .aes_gcm
+      .key
+      .encrypt(Nonce::from_slice(&nonce), payload)
+      .map_err(|_| self::Error::Aes)?;
+    Ok(RawIsolationPayload {
+      nonce: Cow::Owned(nonce.to_vec()),
+      payload: Cow::Owned(payload),
+    })
+  }
+
   /// Decrypts a message using the generated keys.
   pub fn decrypt(&self, raw: RawIsolationPayload<'_>) -> Result<Vec<u8>, Error> {
     let RawIsolationPayload { nonce, payload } = raw;
@@ -108,7 +123,7 @@ impl Keys {
 }
 
 /// Raw representation of
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct RawIsolationPayload<'a> {
   nonce: Cow<'a, [u8]>,
   payload: Cow<'a, [u8]>,
@@ -151,4 +166,16 @@ mod test {
     let _ = super::Keys::new()?;
     Ok(())
   }
+
+  #[test]
+  fn encrypt_decrypt() -> Result<(), Box<dyn std::error::Error>> {
+    let keys = super::Keys::new()?;
+    let encrypted = keys.encrypt(b"{\"cmd\":\"ping\"}")?;
+    assert_eq!(keys.decrypt(encrypted)?, b"{\"cmd\":\"ping\"}");
+
+    // a payload encrypted with the keys of another webview is rejected
+    let other = super::Keys::new()?;
+    assert!(other.decrypt(keys.encrypt(b"{}")?).is_err());
+    Ok(())
+  }
 }
@@ -180,6 +180,10 @@ const PLUGINS: &[(&str, &[(&str, bool)])] = &[
       ("set_show_menu_on_left_click", false),
     ],
   ),
+  (
+    "webdriver",
+    &[("windows", true), ("emit", true), ("listen", true)],
+  ),
 ];
 
 // checks if the given Cargo feature is enabled.
@@ -8,6 +8,7 @@
   }
 
   const osName = __TEMPLATE_os_name__
+  const cancelInvokeCommand = __TEMPLATE_cancel_invoke_command__
 
   Object.defineProperty(window.__TAURI_INTERNALS__, 'convertFileSrc', {
     value: function (filePath, protocol = 'asset') {
@@ -24,12 +25,12 @@
       var prop = `_${identifier}`
 
       Object.defineProperty(window, prop, {
-        value: (result) => {
+        value: (result, response) => {
           if (once) {
             Reflect.deleteProperty(window, prop)
           }
 
-          return callback && callback(result)
+          return callback && callback(result, response)
         },
         writable: false,
         configurable: true
@@ -54,11 +55,33 @@
 
   Object.defineProperty(window.__TAURI_INTERNALS__, 'invoke', {
     value: function (cmd, payload = {}, options) {
+      const signal = options?.signal
+      const timeout = options?.timeout
+      const withResponse = options?.withResponse
+      if (signal || timeout !== undefined || withResponse) {
+        // only the headers are sent to the backend, with the timeout as the deadline header
+        // `options.headers` can also be a `Headers` instance or a list of entries
+        const headers = Object.fromEntries(new Headers(options.headers))
+        if (timeout !== undefined) {
+          headers['Tauri-Deadline'] = Math.max(0, Math.ceil(timeout)).toString()
+        }
+        options = Object.keys(headers).length > 0 ? { headers } : undefined
+      }
+
       return new Promise(function (resolve, reject) {
         const callback = window.__TAURI_INTERNALS__.transformCallback(function (
-          r
+          r,
+          response
         ) {
-          resolve(r)
+          resolve(
+            withResponse
+              ? {
+                  body: r,
+                  status: response?.status ?? 200,
+                  headers: response?.headers ?? {}
+                }
+              : r
+          )
           delete window[`_${error}`]
         },
         true)
@@ -78,6 +101,13 @@
             payload,
             options
           })
+
+          // the command rejects the promise if it is still running
+          signal?.addEventListener(
+            'abort',
+            () => window.__TAURI_INTERNALS__.cancelInvoke(callback),
+            { once: true }
+          )
         }
         if ('ipc' in window.__TAURI_INTERNALS__) {
           action()
@@ -91,4 +121,12 @@
       })
     }
   })
+
+  Object.defineProperty(window.__TAURI_INTERNALS__, 'cancelInvoke', {
+    value: function (callback) {
+      return window.__TAURI_INTERNALS__.invoke(cancelInvokeCommand, {
+        callback
+      })
+    }
+  })
 })()
@@ -8,6 +8,50 @@
   const fetchChannelDataCommand = __TEMPLATE_fetch_channel_data_command__
   const useCustomProtocol = __TEMPLATE_use_custom_protocol__
 
+  function sendPostMessage(message) {
+    const { data } = processIpcMessage(message)
+    window.ipc.postMessage(data)
+  }
+
+  function isBinaryPayload(payload) {
+    return (
+      payload instanceof ArrayBuffer ||
+      ArrayBuffer.isView(payload) ||
+      (typeof FormData !== 'undefined' && payload instanceof FormData)
+    )
+  }
+
+  function toBase64(bytes) {
+    let binary = ''
+    // chunked to stay below the maximum number of function arguments
+    for (let i = 0; i < bytes.length; i += 0x8000) {
+      binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000))
+    }
+    return btoa(binary)
+  }
+
+  async function encodeBinaryPayload(payload, options) {
+    const headers = new Headers(options?.headers)
+    let bytes
+    if (payload instanceof ArrayBuffer || ArrayBuffer.isView(payload)) {
+      bytes = ArrayBuffer.isView(payload)
+        ? new Uint8Array(payload.buffer, payload.byteOffset, payload.byteLength)
+        : new Uint8Array(payload)
+      if (!headers.has('Content-Type')) {
+        headers.set('Content-Type', 'application/octet-stream')
+      }
+    } else {
+      // the Response encodes the multipart body and generates its boundary
+      const response = new Response(payload)
+      headers.set('Content-Type', response.headers.get('Content-Type'))
+      bytes = new Uint8Array(await response.arrayBuffer())
+    }
+    return {
+      headers: Object.fromEntries(headers),
+      rawPayload: toBase64(bytes)
+    }
+  }
+
   Object.defineProperty(window.__TAURI_INTERNALS__, 'postMessage', {
     value: (message) => {
       const { cmd, callback, error, payload, options } = message
@@ -32,45 +76,80 @@
           method: 'POST',
           body: data,
           headers: {
-            'Content-Type': contentType,
+            ...(contentType && { 'Content-Type': contentType }),
             'Tauri-Callback': callback,
             'Tauri-Error': error,
             ...options?.headers
           }
         })
           .then((response) => {
-            const cb = response.ok ? callback : error
+            // successful responses can have a custom status
+            const ok =
+              response.ok || response.headers.get('Tauri-Response') === 'ok'
+            const cb = ok ? callback : error
+            // the status and headers set by the command, without the ones of the IPC protocol
+            const parts = ok
+              ? {
+                  status: response.status,
+                  headers: Object.fromEntries(
+                    [...response.headers].filter(
+                      ([name]) =>
+                        !name.startsWith('access-control-') &&
+                        name !== 'tauri-response'
+                    )
+                  )
+                }
+              : undefined
             // we need to split here because on Android the content-type gets duplicated
             switch (
               (response.headers.get('content-type') || '').split(',')[0]
             ) {
               case 'application/json':
-                return response.json().then((r) => [cb, r])
+                // statuses like 204 No Content have an empty body
+                return response
+                  .text()
+                  .then((r) => [cb, r ? JSON.parse(r) : null, parts])
               case 'text/plain':
-                return response.text().then((r) => [cb, r])
+                return response.text().then((r) => [cb, r, parts])
               default:
-                return response.arrayBuffer().then((r) => [cb, r])
+                return response.arrayBuffer().then((r) => [cb, r, parts])
             }
           })
-          .then(([cb, data]) => {
+          // a failed request (e.g. a rejected CORS preflight) must still settle the promise
+          .catch((e) => [error, e.toString()])
+          .then(([cb, data, parts]) => {
             if (window[`_${cb}`]) {
-              window[`_${cb}`](data)
+              window[`_${cb}`](data, parts)
             } else {
               console.warn(
                 `[TAURI] Couldn't find callback id {cb} in window. This might happen when the app is reloaded while Rust is running an asynchronous operation.`
               )
             }
           })
+      } else if (isBinaryPayload(payload)) {
+        // binary payloads cannot be serialized to JSON,
+        // so they are sent as base64 with their content type
+        encodeBinaryPayload(payload, options)
+          .then(({ headers, rawPayload }) =>
+            sendPostMessage({
+              cmd,
+              callback,
+              error,
+              options: { ...options, headers },
+              payload: null,
+              rawPayload
+            })
+          )
+          .catch((e) => window[`_${error}`]?.(e.toString()))
       } else {
         // otherwise use the postMessage interface
-        const { data } = processIpcMessage({
+        sendPostMessage({
           cmd,
           callback,
           error,
           options,
           payload
         })
-        window.ipc.postMessage(data)
       }
     }
   })
@@ -92,43 +92,90 @@
     )
   }
 
-  Object.defineProperty(window.__TAURI_INTERNALS__, 'ipc', {
-    // todo: JSDoc this function
-    value: Object.freeze((message) => {
-      switch (pattern) {
-        case 'brownfield':
-          window.__TAURI_INTERNALS__.postMessage(message)
-          break
-
-        case 'isolation':
-          if (!isIsolationPayload(message)) {
-            console.error(
-              'Tauri "Isolation" Pattern found an invalid isolation message payload',
-              message
-            )
-            break
-          }
+  /**
+   * @type {boolean}
+   */
+  const ipcTokenEnabled = __TEMPLATE_ipc_token__
 
-          if (isolation.ready) {
-            sendIsolationMessage(message)
-          } else {
-            isolation.queue.push(message)
-          }
+  /**
+   * @type {(token: string) => void}
+   */
+  let resolveIpcToken
 
-          break
+  /**
+   * The token of this page load, set by the core once the page is loaded.
+   *
+   * @type {Promise<string>}
+   */
+  const ipcToken = new Promise((resolve) => {
+    resolveIpcToken = resolve
+  })
 
-        case 'error':
+  /**
+   * Sends a message through the IPC of the current pattern.
+   *
+   * @param {object} message - the IPC message
+   */
+  function sendMessage(message) {
+    switch (pattern) {
+      case 'brownfield':
+        window.__TAURI_INTERNALS__.postMessage(message)
+        break
+
+      case 'isolation':
+        if (!isIsolationPayload(message)) {
           console.error(
-            'Tauri IPC found a Tauri Pattern, but it was an error. Check for other log messages to find the cause.'
+            'Tauri "Isolation" Pattern found an invalid isolation message payload',
+            message
           )
           break
+        }
 
-        default:
-          console.error(
-            'Tauri IPC did not find a Tauri Pattern that it understood.'
-          )
-          break
+        if (isolation.ready) {
+          sendIsolationMessage(message)
+        } else {
+          isolation.queue.push(message)
+        }
+
+        break
+
+      case 'error':
+        console.error(
+          'Tauri IPC found a Tauri Pattern, but it was an error. Check for other log messages to find the cause.'
+        )
+        break
+
+      default:
+        console.error(
+          'Tauri IPC did not find a Tauri Pattern that it understood.'
+        )
+        break
+    }
+  }
+
+  // a promise only resolves once, so the token of the page load cannot be replaced
+  Object.defineProperty(window.__TAURI_INTERNALS__, 'setIpcToken', {
+    value: Object.freeze((token) => resolveIpcToken(token))
+  })
+
+  Object.defineProperty(window.__TAURI_INTERNALS__, 'ipc', {
+    // todo: JSDoc this function
+    value: Object.freeze((message) => {
+      if (!ipcTokenEnabled) {
+        sendMessage(message)
+        return
       }
+
+      ipcToken.then((token) => {
+        const options = message.options || {}
+        sendMessage({
+          ...message,
+          options: {
+            ...options,
+            headers: { ...options.headers, 'Tauri-Ipc-Token': token }
+          }
+        })
+      })
     })
   })
 
@@ -14,6 +14,11 @@
       contentType: 'application/octet-stream',
       data: message
     }
+  } else if (typeof FormData !== 'undefined' && message instanceof FormData) {
+    // the content type is set by fetch since it includes the multipart boundary
+    return {
+      data: message
+    }
   } else {
     const data = JSON.stringify(message, (_k, val) => {
       if (val instanceof Map) {
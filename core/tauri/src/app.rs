@@ -4,8 +4,8 @@
 
 use crate::{
   ipc::{
-    channel::ChannelDataIpcQueue, CallbackFn, CommandArg, CommandItem, Invoke, InvokeError,
-    InvokeHandler, InvokeResponder, InvokeResponse,
+    audit::IpcAudit, channel::ChannelDataIpcQueue, CallbackFn, CommandArg, CommandItem, Invoke,
+    InvokeError, InvokeHandler, InvokeResponder, InvokeResponse,
   },
   manager::{
     webview::{UriSchemeProtocol, WebviewLabelDef},
@@ -47,6 +47,7 @@ use std::{
   collections::HashMap,
   fmt,
   sync::{mpsc::Sender, Arc},
+  time::Duration,
 };
 
 use crate::{event::EventId, runtime::RuntimeHandle, Event, EventTarget};
@@ -1017,6 +1018,12 @@ pub struct Builder<R: Runtime> {
   /// The script that initializes the `window.__TAURI_INTERNALS__.postMessage` function.
   invoke_initialization_script: String,
 
+  /// The IPC audit log.
+  ipc_audit: Option<IpcAudit<R>>,
+
+  /// The default timeout of the async commands.
+  command_timeout: Option<Duration>,
+
   /// The setup hook.
   setup: SetupHook<R>,
 
@@ -1093,6 +1100,8 @@ impl<R: Runtime> Builder<R> {
       .render_default(&Default::default())
       .unwrap()
       .into_string(),
+      ipc_audit: None,
+      command_timeout: None,
       on_page_load: None,
       plugins: PluginStore::default(),
       uri_scheme_protocols: Default::default(),
@@ -1159,6 +1168,51 @@ impl<R: Runtime> Builder<R> {
     self
   }
 
+  /// Records every IPC call checked against the Access Control List.
+  ///
+  /// Each record contains the command, the webview, window and origin that sent it,
+  /// the capability that matched and whether the call was allowed, denied or did not match any capability.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use tauri::ipc::audit::{AuditDecision, IpcAudit};
+  ///
+  /// tauri::Builder::default()
+  ///   .ipc_audit(IpcAudit::new(|_app, record| {
+  ///     if record.decision != AuditDecision::Allowed {
+  ///       println!("{} was {:?} on {}", record.command, record.decision, record.window);
+  ///     }
+  ///   }));
+  /// ```
+  ///
+  /// See [`IpcAudit::json_lines`] to write the records to the app log directory.
+  #[must_use]
+  pub fn ipc_audit(mut self, audit: IpcAudit<R>) -> Self {
+    self.ipc_audit.replace(audit);
+    self
+  }
+
+  /// Sets the default timeout of the async commands.
+  ///
+  /// A command that takes longer is dropped and its call rejected with a [`TimedOutError`](crate::ipc::timeout::TimedOutError).
+  /// Commands can override it with `#[tauri::command(timeout = <milliseconds>)]`,
+  /// see the [`timeout`](crate::ipc::timeout) module.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::time::Duration;
+  ///
+  /// tauri::Builder::default()
+  ///   .command_timeout(Duration::from_secs(30));
+  /// ```
+  #[must_use]
+  pub fn command_timeout(mut self, timeout: Duration) -> Self {
+    self.command_timeout.replace(timeout);
+    self
+  }
+
   /// Defines the setup hook.
   ///
   /// # Examples
@@ -1547,6 +1601,8 @@ tauri::Builder::default()
       #[cfg(desktop)]
       HashMap::new(),
       (self.invoke_responder, self.invoke_initialization_script),
+      self.ipc_audit,
+      self.command_timeout,
     ));
 
     let runtime_args = RuntimeInitArgs {
@@ -1643,6 +1699,7 @@ tauri::Builder::default()
     });
 
     app.manage(ChannelDataIpcQueue::default());
+    app.manage(crate::ipc::stream::StreamAcks::default());
     app.handle.plugin(crate::ipc::channel::plugin())?;
 
     #[cfg(windows)]
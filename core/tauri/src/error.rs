@@ -46,6 +46,13 @@ pub enum Error {
   /// Failed to serialize/deserialize.
   #[error("JSON error: {0}")]
   Json(#[from] serde_json::Error),
+  /// Failed to serialize to MessagePack.
+  #[cfg(feature = "ipc-msgpack")]
+  #[error("MessagePack error: {0}")]
+  MessagePack(#[from] rmp_serde::encode::Error),
+  /// Invalid HTTP header or status.
+  #[error("HTTP error: {0}")]
+  Http(#[from] http::Error),
   /// IO error.
   #[error("{0}")]
   Io(#[from] std::io::Error),
@@ -72,6 +79,11 @@ pub enum Error {
   #[cfg(feature = "isolation")]
   #[error("isolation pattern error: {0}")]
   IsolationPattern(#[from] tauri_utils::pattern::isolation::Error),
+  /// The webview requested the isolation pattern, but the application does not use it.
+  #[error(
+    "webview `{0}` enables isolation, but the application does not use the isolation pattern"
+  )]
+  IsolationPatternNotConfigured(String),
   /// An invalid window URL was provided. Includes details about the error.
   #[error("invalid window url: {0}")]
   InvalidWebviewUrl(&'static str),
@@ -0,0 +1,424 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Audit log of the IPC calls checked against the Access Control List.
+//!
+//! See [`crate::Builder::ipc_audit`].
+
+use std::{
+  fs::{self, File, OpenOptions},
+  io::Write,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+  },
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{AppHandle, Manager, Runtime};
+
+/// A closure that receives every sampled [`AuditRecord`].
+pub type IpcAuditHook<R> = dyn Fn(&AppHandle<R>, &AuditRecord) + Send + Sync + 'static;
+
+/// The Access Control List decision for an IPC call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditDecision {
+  /// A capability allowed the command.
+  Allowed,
+  /// A capability denied the command.
+  Denied,
+  /// No capability allowed nor denied the command on this window and origin.
+  ///
+  /// Application commands are still executed in this case unless [`app_acl`](crate::utils::config::SecurityConfig#structfield.app_acl) is enabled.
+  NoMatch,
+  /// A capability allowed the command but the arguments break the constraints of its permissions.
+  ArgsDenied,
+  /// A capability allowed the command but the webview exceeded the limits of its permissions.
+  RateLimited,
+}
+
+/// A single IPC call checked against the Access Control List.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+  /// Milliseconds since the Unix epoch when the call was received.
+  pub timestamp: u64,
+  /// The command name, without the plugin prefix.
+  pub command: String,
+  /// The plugin that owns the command, `None` for application commands.
+  pub plugin: Option<String>,
+  /// The label of the webview that sent the message.
+  pub webview: String,
+  /// The label of the window hosting the webview.
+  pub window: String,
+  /// The origin of the call, either `local` or `remote: <URL>`.
+  pub origin: String,
+  /// Identifier of the capability that allowed or denied the command.
+  pub capability: Option<String>,
+  /// The Access Control List decision.
+  pub decision: AuditDecision,
+}
+
+impl AuditRecord {
+  pub(crate) fn now() -> u64 {
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_millis() as u64)
+      .unwrap_or_default()
+  }
+}
+
+/// Configuration of the IPC audit log.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tauri::ipc::audit::{IpcAudit, JsonLinesSink};
+///
+/// tauri::Builder::default()
+///   // record every denied call but only 10% of the allowed ones
+///   .ipc_audit(IpcAudit::json_lines(JsonLinesSink::new()).sample_rate(0.1))
+///   .run(tauri::generate_context!("test/fixture/src-tauri/tauri.conf.json"))
+///   .expect("error while running tauri application");
+/// ```
+pub struct IpcAudit<R: Runtime> {
+  hook: Box<IpcAuditHook<R>>,
+  sample_rate: f64,
+  allowed_count: AtomicU64,
+}
+
+impl<R: Runtime> IpcAudit<R> {
+  /// Creates an audit log that sends each record to the given closure.
+  pub fn new<F: Fn(&AppHandle<R>, &AuditRecord) + Send + Sync + 'static>(hook: F) -> Self {
+    Self {
+      hook: Box::new(hook),
+      sample_rate: 1.,
+      allowed_count: AtomicU64::new(0),
+    }
+  }
+
+  /// Creates an audit log that writes each record to the given [`JsonLinesSink`].
+  pub fn json_lines(sink: JsonLinesSink) -> Self {
+    Self::new(move |app, record| {
+      if let Err(e) = sink.write(app, record) {
+        log::error!("failed to write IPC audit record: {e}");
+      }
+    })
+  }
+
+  /// Sets the fraction of allowed calls that are recorded, between `0.0` and `1.0`. Defaults to `1.0`.
+  ///
+  /// Denied and unmatched calls are always recorded.
+  #[must_use]
+  pub fn sample_rate(mut self, rate: f64) -> Self {
+    self.sample_rate = rate.clamp(0., 1.);
+    self
+  }
+
+  pub(crate) fn record(&self, app: &AppHandle<R>, record: AuditRecord) {
+    if record.decision == AuditDecision::Allowed && !self.sample() {
+      return;
+    }
+    (self.hook)(app, &record);
+  }
+
+  /// Evenly spreads the recorded calls, e.g. a rate of `0.25` keeps every fourth allowed call.
+  fn sample(&self) -> bool {
+    if self.sample_rate >= 1. {
+      return true;
+    }
+    let count = self.allowed_count.fetch_add(1, Ordering::Relaxed) as f64;
+    ((count + 1.) * self.sample_rate).floor() > (count * self.sample_rate).floor()
+  }
+}
+
+/// A sink that writes the audit records as JSON lines to the [`app_log_dir`](crate::path::PathResolver::app_log_dir),
+/// rotating the file when it gets too large.
+///
+/// The current file is named `<file_name>.jsonl` and rotated files are named `<file_name>.<n>.jsonl`,
+/// where `1` is the most recent one.
+pub struct JsonLinesSink {
+  file_name: String,
+  max_file_size: u64,
+  max_files: usize,
+  state: Mutex<Option<SinkState>>,
+}
+
+struct SinkState {
+  dir: PathBuf,
+  file: File,
+  size: u64,
+}
+
+impl Default for JsonLinesSink {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl JsonLinesSink {
+  /// Creates a sink writing to `ipc-audit.jsonl`, keeping up to 5 rotated files of 10 MiB.
+  pub fn new() -> Self {
+    Self {
+      file_name: "ipc-audit".into(),
+      max_file_size: 10 * 1024 * 1024,
+      max_files: 5,
+      state: Mutex::new(None),
+    }
+  }
+
+  /// Sets the name of the log file, without the extension.
+  #[must_use]
+  pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+    self.file_name = file_name.into();
+    self
+  }
+
+  /// Sets the size in bytes after which the log file is rotated.
+  #[must_use]
+  pub fn max_file_size(mut self, max_file_size: u64) -> Self {
+    self.max_file_size = max_file_size;
+    self
+  }
+
+  /// Sets how many rotated files are kept. Older files are deleted.
+  #[must_use]
+  pub fn max_files(mut self, max_files: usize) -> Self {
+    self.max_files = max_files;
+    self
+  }
+
+  /// Appends the record to the log file.
+  pub fn write<R: Runtime>(&self, app: &AppHandle<R>, record: &AuditRecord) -> crate::Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+
+    let mut state = self.state.lock().unwrap();
+    let state = match &mut *state {
+      Some(state) => state,
+      None => {
+        let dir = app.path().app_log_dir()?;
+        fs::create_dir_all(&dir)?;
+        let file = self.open(&dir)?;
+        let size = file.metadata()?.len();
+        state.insert(SinkState { dir, file, size })
+      }
+    };
+
+    if state.size > 0 && state.size + line.len() as u64 > self.max_file_size {
+      self.rotate(&state.dir)?;
+      state.file = self.open(&state.dir)?;
+      state.size = 0;
+    }
+
+    state.file.write_all(&line)?;
+    state.size += line.len() as u64;
+
+    Ok(())
+  }
+
+  fn path(&self, dir: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+      dir.join(format!("{}.jsonl", self.file_name))
+    } else {
+      dir.join(format!("{}.{index}.jsonl", self.file_name))
+    }
+  }
+
+  fn open(&self, dir: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(self.path(dir, 0))
+  }
+
+  fn rotate(&self, dir: &Path) -> std::io::Result<()> {
+    // when no rotated files are kept this removes the current file
+    let oldest = self.path(dir, self.max_files);
+    if oldest.exists() {
+      fs::remove_file(oldest)?;
+    }
+    for index in (0..self.max_files).rev() {
+      let path = self.path(dir, index);
+      if path.exists() {
+        fs::rename(path, self.path(dir, index + 1))?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn record(decision: AuditDecision) -> AuditRecord {
+    AuditRecord {
+      timestamp: 0,
+      command: "ping".into(),
+      plugin: None,
+      webview: "main".into(),
+      window: "main".into(),
+      origin: "local".into(),
+      capability: None,
+      decision,
+    }
+  }
+
+  #[test]
+  fn sample_rate() {
+    use std::sync::Arc;
+
+    let app = crate::test::mock_app();
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let records_ = records.clone();
+    let audit = IpcAudit::new(move |_app, record| {
+      records_.lock().unwrap().push(record.decision);
+    })
+    .sample_rate(0.25);
+
+    for _ in 0..8 {
+      audit.record(app.handle(), record(AuditDecision::Allowed));
+    }
+    audit.record(app.handle(), record(AuditDecision::Denied));
+    audit.record(app.handle(), record(AuditDecision::NoMatch));
+
+    assert_eq!(
+      *records.lock().unwrap(),
+      vec![
+        AuditDecision::Allowed,
+        AuditDecision::Allowed,
+        AuditDecision::Denied,
+        AuditDecision::NoMatch
+      ]
+    );
+  }
+
+  #[test]
+  fn serialize_record() {
+    let mut record = record(AuditDecision::NoMatch);
+    record.plugin.replace("fs".into());
+    assert_eq!(
+      serde_json::to_string(&record).unwrap(),
+      r#"{"timestamp":0,"command":"ping","plugin":"fs","webview":"main","window":"main","origin":"local","capability":null,"decision":"no-match"}"#
+    );
+  }
+
+  #[test]
+  fn records_final_decision() {
+    use std::{
+      num::{NonZeroU32, NonZeroU64},
+      sync::Arc,
+    };
+
+    use tauri_utils::acl::{
+      args::ArgConstraint,
+      limits::{CommandLimits, RateLimit},
+      resolved::{CommandKey, ResolvedCommand, ResolvedCommandReference},
+      ExecutionContext,
+    };
+
+    use crate::{
+      ipc::CallbackFn,
+      test::{get_ipc_response, mock_builder, mock_context, noop_assets},
+      webview::InvokeRequest,
+      WebviewUrl, WebviewWindowBuilder,
+    };
+
+    #[crate::command(root = "crate")]
+    fn ping(value: u32) -> u32 {
+      value
+    }
+
+    let mut context = mock_context(noop_assets());
+    context.resolved_acl().allowed_commands.insert(
+      CommandKey {
+        name: "ping".into(),
+        context: ExecutionContext::Local,
+      },
+      ResolvedCommand {
+        windows: vec![glob::Pattern::new("main").unwrap()],
+        referenced_by: vec![ResolvedCommandReference {
+          capability: "main".into(),
+          permission: "allow-ping".into(),
+          windows: vec![glob::Pattern::new("main").unwrap()],
+          args: vec![[(
+            "value".to_string(),
+            ArgConstraint {
+              one_of: vec![1.into()],
+              ..Default::default()
+            },
+          )]
+          .into()],
+          limits: Some(CommandLimits {
+            max_concurrent: None,
+            rate: Some(RateLimit {
+              calls: NonZeroU32::new(1).unwrap(),
+              period_ms: NonZeroU64::new(60_000).unwrap(),
+            }),
+          }),
+        }],
+        ..Default::default()
+      },
+    );
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let records_ = records.clone();
+    let app = mock_builder()
+      .invoke_handler(crate::generate_handler![ping])
+      .ipc_audit(IpcAudit::new(move |_app, record| {
+        records_.lock().unwrap().push(record.decision);
+      }))
+      .build(context)
+      .unwrap();
+    let webview = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+
+    for value in [2, 1, 1] {
+      let _ = get_ipc_response(
+        &webview,
+        InvokeRequest {
+          cmd: "ping".into(),
+          callback: CallbackFn(0),
+          error: CallbackFn(1),
+          body: serde_json::json!({ "value": value }).into(),
+          headers: Default::default(),
+        },
+      );
+    }
+
+    assert_eq!(
+      *records.lock().unwrap(),
+      vec![
+        AuditDecision::ArgsDenied,
+        AuditDecision::Allowed,
+        AuditDecision::RateLimited
+      ]
+    );
+  }
+
+  #[test]
+  fn rotate() {
+    let dir = std::env::temp_dir().join(format!("tauri-ipc-audit-{}", AuditRecord::now()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let sink = JsonLinesSink::new().max_files(2);
+    for index in 0..4 {
+      fs::write(sink.path(&dir, 0), index.to_string()).unwrap();
+      sink.rotate(&dir).unwrap();
+    }
+
+    assert!(!sink.path(&dir, 0).exists());
+    assert_eq!(fs::read_to_string(sink.path(&dir, 1)).unwrap(), "3");
+    assert_eq!(fs::read_to_string(sink.path(&dir, 2)).unwrap(), "2");
+    assert!(!sink.path(&dir, 3).exists());
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
@@ -7,17 +7,18 @@ use std::{collections::BTreeMap, ops::Deref};
 
 use serde::de::DeserializeOwned;
 use state::TypeMap;
+use url::Url;
 
 use tauri_utils::acl::Value;
 use tauri_utils::acl::{
   resolved::{CommandKey, Resolved, ResolvedCommand, ResolvedScope, ScopeKey},
-  ExecutionContext,
+  ExecutionContext, APP_ACL_KEY,
 };
 
 use crate::{ipc::InvokeError, sealed::ManagerBase, Runtime};
 use crate::{AppHandle, Manager};
 
-use super::{CommandArg, CommandItem};
+use super::{audit::AuditDecision, limits::Limiter, CommandArg, CommandItem};
 
 /// The runtime authority used to authorize IPC execution based on the Access Control List.
 pub struct RuntimeAuthority {
@@ -26,6 +27,7 @@ pub struct RuntimeAuthority {
   allowed_commands: BTreeMap<CommandKey, ResolvedCommand>,
   denied_commands: BTreeMap<CommandKey, ResolvedCommand>,
   pub(crate) scope_manager: ScopeManager,
+  pub(crate) limiter: Limiter,
 }
 
 /// The origin trying to access the IPC.
@@ -34,8 +36,8 @@ pub enum Origin {
   Local,
   /// Remote origin.
   Remote {
-    /// Remote origin domain.
-    domain: String,
+    /// Remote origin URL.
+    url: Url,
   },
 }
 
@@ -43,7 +45,7 @@ impl Display for Origin {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
       Self::Local => write!(f, "local"),
-      Self::Remote { domain } => write!(f, "remote: {domain}"),
+      Self::Remote { url } => write!(f, "remote: {url}"),
     }
   }
 }
@@ -52,12 +54,9 @@ impl Origin {
   fn matches(&self, context: &ExecutionContext) -> bool {
     match (self, context) {
       (Self::Local, ExecutionContext::Local) => true,
-      (
-        Self::Remote { domain },
-        ExecutionContext::Remote {
-          domain: domain_pattern,
-        },
-      ) => domain_pattern.matches(domain),
+      (Self::Remote { url }, ExecutionContext::Remote { url: url_pattern }) => {
+        url_pattern.matches(url)
+      }
       _ => false,
     }
   }
@@ -81,13 +80,14 @@ impl RuntimeAuthority {
         command_cache,
         global_scope_cache: Default::default(),
       },
+      limiter: Limiter::default(),
     }
   }
 
   #[cfg(debug_assertions)]
   pub(crate) fn resolve_access_message(
     &self,
-    plugin: &str,
+    plugin: Option<&str>,
     command_name: &str,
     window: &str,
     origin: &Origin,
@@ -126,14 +126,31 @@ impl RuntimeAuthority {
       false
     }
 
-    let command = format!("plugin:{plugin}|{command_name}");
+    // application commands are not prefixed
+    let (command, command_display, manifest_key) = match plugin {
+      Some(plugin) => (
+        format!("plugin:{plugin}|{command_name}"),
+        format!("{plugin}.{command_name}"),
+        plugin,
+      ),
+      None => (
+        command_name.to_string(),
+        command_name.to_string(),
+        APP_ACL_KEY,
+      ),
+    };
+    let permission_identifier = |permission: &str| match plugin {
+      Some(plugin) => format!("{plugin}:{permission}"),
+      None => permission.to_string(),
+    };
+
     if let Some((_cmd, resolved)) = self
       .denied_commands
       .iter()
       .find(|(cmd, _)| cmd.name == command && origin.matches(&cmd.context))
     {
       format!(
-        "{plugin}.{command_name} denied on origin {origin}, referenced by: {}",
+        "{command_display} denied on origin {origin}, referenced by: {}",
         print_references(resolved)
       )
     } else {
@@ -150,10 +167,10 @@ impl RuntimeAuthority {
         if resolved.windows.iter().any(|w| w.matches(window)) {
           "allowed".to_string()
         } else {
-          format!("{plugin}.{command_name} not allowed on window {window}, expected one of {}, referenced by {}", resolved.windows.iter().map(|w| w.as_str()).collect::<Vec<_>>().join(", "), print_references(resolved))
+          format!("{command_display} not allowed on window {window}, expected one of {}, referenced by {}", resolved.windows.iter().map(|w| w.as_str()).collect::<Vec<_>>().join(", "), print_references(resolved))
         }
       } else {
-        let permission_error_detail = if let Some(manifest) = self.acl.get(plugin) {
+        let permission_error_detail = if let Some(manifest) = self.acl.get(manifest_key) {
           let mut permissions_referencing_command = Vec::new();
 
           if let Some(default) = &manifest.default_permission {
@@ -178,26 +195,28 @@ impl RuntimeAuthority {
             "Permissions associated with this command: {}",
             permissions_referencing_command
               .iter()
-              .map(|p| format!("{plugin}:{p}"))
+              .map(|p| permission_identifier(p))
               .collect::<Vec<_>>()
               .join(", ")
           )
-        } else {
+        } else if plugin.is_some() {
           "Plugin did not define its manifest".to_string()
+        } else {
+          "The app did not define its manifest, see `tauri_build::AppManifest`".to_string()
         };
 
         if command_matches.is_empty() {
-          format!("{plugin}.{command_name} not allowed. {permission_error_detail}")
+          format!("{command_display} not allowed. {permission_error_detail}")
         } else {
           format!(
-            "{plugin}.{command_name} not allowed on origin [{}]. Please create a capability that has this origin on the context field.\n\nFound matches for: {}\n\n{permission_error_detail}",
+            "{command_display} not allowed on origin [{}]. Please create a capability that has this origin on the context field.\n\nFound matches for: {}\n\n{permission_error_detail}",
             origin,
             command_matches
               .iter()
               .map(|(cmd, resolved)| {
                 let context = match &cmd.context {
                   ExecutionContext::Local => "[local]".to_string(),
-                  ExecutionContext::Remote { domain } => format!("[remote: {}]", domain.as_str()),
+                  ExecutionContext::Remote { url } => format!("[remote: {url}]"),
                 };
                 format!(
                   "- context: {context}, referenced by: {}",
@@ -219,23 +238,73 @@ impl RuntimeAuthority {
     window: &str,
     origin: &Origin,
   ) -> Option<&ResolvedCommand> {
-    if self
+    self
+      .resolve_access_decision(command, window, origin)
+      .command
+  }
+
+  /// Checks if the given IPC execution is allowed, keeping track of the decision and the capability that made it.
+  pub(crate) fn resolve_access_decision(
+    &self,
+    command: &str,
+    window: &str,
+    origin: &Origin,
+  ) -> AccessResolution<'_> {
+    if let Some((_cmd, resolved)) = self
       .denied_commands
-      .keys()
-      .any(|cmd| cmd.name == command && origin.matches(&cmd.context))
+      .iter()
+      .find(|(cmd, _)| cmd.name == command && origin.matches(&cmd.context))
     {
-      None
-    } else {
-      self
-        .allowed_commands
+      // the denied command applies to all windows, but prefer the capability that targets this window
+      let capability = resolved
+        .referenced_by
         .iter()
-        .find(|(cmd, _)| cmd.name == command && origin.matches(&cmd.context))
-        .map(|(_cmd, resolved)| resolved)
-        .filter(|resolved| resolved.windows.iter().any(|w| w.matches(window)))
+        .find(|r| r.windows.iter().any(|w| w.matches(window)))
+        .or_else(|| resolved.referenced_by.first())
+        .map(|r| r.capability.as_str());
+      return AccessResolution {
+        decision: AuditDecision::Denied,
+        capability,
+        command: None,
+      };
+    }
+
+    let allowed = self
+      .allowed_commands
+      .iter()
+      .find(|(cmd, _)| cmd.name == command && origin.matches(&cmd.context))
+      .map(|(_cmd, resolved)| resolved)
+      .filter(|resolved| resolved.windows.iter().any(|w| w.matches(window)));
+
+    match allowed {
+      Some(resolved) => AccessResolution {
+        decision: AuditDecision::Allowed,
+        capability: resolved
+          .referenced_by
+          .iter()
+          .find(|r| r.windows.iter().any(|w| w.matches(window)))
+          .map(|r| r.capability.as_str()),
+        command: Some(resolved),
+      },
+      None => AccessResolution {
+        decision: AuditDecision::NoMatch,
+        capability: None,
+        command: None,
+      },
     }
   }
 }
 
+/// The result of [`RuntimeAuthority::resolve_access_decision`].
+pub(crate) struct AccessResolution<'a> {
+  /// Whether the command was allowed, denied or did not match any capability.
+  pub decision: AuditDecision,
+  /// Identifier of the capability that allowed or denied the command.
+  pub capability: Option<&'a str>,
+  /// The resolved command when it is allowed.
+  pub command: Option<&'a ResolvedCommand>,
+}
+
 /// List of allowed and denied objects that match either the command-specific or plugin global scope criterias.
 #[derive(Debug)]
 pub struct ScopeValue<T: ScopeObject> {
@@ -328,23 +397,16 @@ impl<'a, T: ScopeObject> GlobalScope<'a, T> {
 impl<'a, R: Runtime, T: ScopeObject> CommandArg<'a, R> for GlobalScope<'a, T> {
   /// Grabs the [`ResolvedScope`] from the [`CommandItem`] and returns the associated [`GlobalScope`].
   fn from_command(command: CommandItem<'a, R>) -> Result<Self, InvokeError> {
+    // application commands use the global scope of the application permissions
+    let plugin = command.plugin.unwrap_or(APP_ACL_KEY);
     command
-      .plugin
-      .ok_or_else(|| {
-        InvokeError::from_anyhow(anyhow::anyhow!(
-          "global scope not available for app commands"
-        ))
-      })
-      .and_then(|plugin| {
-        command
-          .message
-          .webview
-          .manager()
-          .runtime_authority
-          .scope_manager
-          .get_global_scope_typed(command.message.webview.app_handle(), plugin)
-          .map_err(InvokeError::from_error)
-      })
+      .message
+      .webview
+      .manager()
+      .runtime_authority
+      .scope_manager
+      .get_global_scope_typed(command.message.webview.app_handle(), plugin)
+      .map_err(InvokeError::from_error)
       .map(GlobalScope)
   }
 }
@@ -452,11 +514,11 @@ impl ScopeManager {
 mod tests {
   use glob::Pattern;
   use tauri_utils::acl::{
-    resolved::{CommandKey, Resolved, ResolvedCommand},
+    resolved::{CommandKey, Resolved, ResolvedCommand, ResolvedCommandReference},
     ExecutionContext,
   };
 
-  use crate::ipc::Origin;
+  use crate::ipc::{audit::AuditDecision, Origin};
 
   use super::RuntimeAuthority;
 
@@ -491,16 +553,16 @@ mod tests {
     );
   }
 
-  #[test]
-  fn remote_domain_matches() {
-    let domain = "tauri.app";
+  fn remote_authority(
+    pattern: &str,
+    window: &str,
+  ) -> (CommandKey, ResolvedCommand, RuntimeAuthority) {
     let command = CommandKey {
       name: "my-command".into(),
       context: ExecutionContext::Remote {
-        domain: Pattern::new(domain).unwrap(),
+        url: pattern.parse().unwrap(),
       },
     };
-    let window = "main";
 
     let resolved_cmd = ResolvedCommand {
       windows: vec![Pattern::new(window).unwrap()],
@@ -516,55 +578,107 @@ mod tests {
       ..Default::default()
     });
 
+    (command, resolved_cmd, authority)
+  }
+
+  fn remote_origin(url: &str) -> Origin {
+    Origin::Remote {
+      url: url.parse().unwrap(),
+    }
+  }
+
+  #[test]
+  fn remote_domain_matches() {
+    let window = "main";
+    let (command, resolved_cmd, authority) = remote_authority("tauri.app", window);
+
+    assert_eq!(
+      authority.resolve_access(&command.name, window, &remote_origin("https://tauri.app")),
+      Some(&resolved_cmd)
+    );
+  }
+
+  #[test]
+  fn remote_domain_glob_pattern_matches() {
+    let window = "main";
+    let (command, resolved_cmd, authority) = remote_authority("tauri.*", window);
+
     assert_eq!(
       authority.resolve_access(
         &command.name,
         window,
-        &Origin::Remote {
-          domain: domain.into()
-        }
+        &remote_origin("https://tauri.studio")
       ),
       Some(&resolved_cmd)
     );
   }
 
   #[test]
-  fn remote_domain_glob_pattern_matches() {
-    let domain = "tauri.*";
-    let command = CommandKey {
-      name: "my-command".into(),
-      context: ExecutionContext::Remote {
-        domain: Pattern::new(domain).unwrap(),
-      },
-    };
+  fn remote_url_pattern_matches() {
     let window = "main";
-
-    let resolved_cmd = ResolvedCommand {
-      windows: vec![Pattern::new(window).unwrap()],
-      scope: None,
-      ..Default::default()
-    };
-    let allowed_commands = [(command.clone(), resolved_cmd.clone())]
-      .into_iter()
-      .collect();
-
-    let authority = RuntimeAuthority::new(Resolved {
-      allowed_commands,
-      ..Default::default()
-    });
+    let (command, resolved_cmd, authority) =
+      remote_authority("https://*.tauri.app:8443/api", window);
 
     assert_eq!(
       authority.resolve_access(
         &command.name,
         window,
-        &Origin::Remote {
-          domain: domain.replace('*', "studio")
-        }
+        &remote_origin("https://v2.tauri.app:8443/api/users")
       ),
       Some(&resolved_cmd)
     );
   }
 
+  #[test]
+  fn remote_scheme_mismatch_denied() {
+    let window = "main";
+    let (command, _, authority) = remote_authority("https://tauri.app", window);
+
+    assert!(authority
+      .resolve_access(&command.name, window, &remote_origin("http://tauri.app"))
+      .is_none());
+  }
+
+  #[test]
+  fn remote_port_mismatch_denied() {
+    let window = "main";
+    let (command, _, authority) = remote_authority("https://tauri.app:8443", window);
+
+    assert!(authority
+      .resolve_access(
+        &command.name,
+        window,
+        &remote_origin("https://tauri.app:9443")
+      )
+      .is_none());
+    assert!(authority
+      .resolve_access(&command.name, window, &remote_origin("https://tauri.app"))
+      .is_none());
+
+    let (command, _, authority) = remote_authority("tauri.app", window);
+    assert!(authority
+      .resolve_access(
+        &command.name,
+        window,
+        &remote_origin("https://tauri.app:8443")
+      )
+      .is_none());
+  }
+
+  #[test]
+  fn remote_path_mismatch_denied() {
+    let window = "main";
+    let (command, _, authority) = remote_authority("tauri.app/api", window);
+
+    assert!(authority
+      .resolve_access(
+        &command.name,
+        window,
+        &remote_origin("https://tauri.app/apis")
+      )
+      .is_none());
+  }
+
   #[test]
   fn remote_context_denied() {
     let command = CommandKey {
@@ -592,7 +706,7 @@ mod tests {
         &command.name,
         window,
         &Origin::Remote {
-          domain: "tauri.app".into()
+          url: "https://tauri.app".parse().unwrap()
         }
       )
       .is_none());
@@ -635,4 +749,90 @@ mod tests {
       .resolve_access(&command.name, window, &Origin::Local)
       .is_none());
   }
+
+  fn reference(capability: &str, window: &str) -> ResolvedCommandReference {
+    ResolvedCommandReference {
+      capability: capability.into(),
+      permission: "allow-my-command".into(),
+      windows: vec![Pattern::new(window).unwrap()],
+      args: Vec::new(),
+      limits: None,
+    }
+  }
+
+  #[test]
+  fn decision_reports_matched_capability() {
+    let command = CommandKey {
+      name: "my-command".into(),
+      context: ExecutionContext::Local,
+    };
+    let allowed_commands = [(
+      command.clone(),
+      ResolvedCommand {
+        referenced_by: vec![reference("main", "main"), reference("settings", "settings")],
+        windows: vec![
+          Pattern::new("main").unwrap(),
+          Pattern::new("settings").unwrap(),
+        ],
+        ..Default::default()
+      },
+    )]
+    .into_iter()
+    .collect();
+
+    let authority = RuntimeAuthority::new(Resolved {
+      allowed_commands,
+      ..Default::default()
+    });
+
+    let resolution = authority.resolve_access_decision(&command.name, "settings", &Origin::Local);
+    assert_eq!(resolution.decision, AuditDecision::Allowed);
+    assert_eq!(resolution.capability, Some("settings"));
+    assert!(resolution.command.is_some());
+
+    let resolution = authority.resolve_access_decision(&command.name, "other", &Origin::Local);
+    assert_eq!(resolution.decision, AuditDecision::NoMatch);
+    assert_eq!(resolution.capability, None);
+    assert!(resolution.command.is_none());
+  }
+
+  #[test]
+  fn decision_reports_denying_capability() {
+    let command = CommandKey {
+      name: "my-command".into(),
+      context: ExecutionContext::Local,
+    };
+    let window = "main";
+    let allowed_commands = [(
+      command.clone(),
+      ResolvedCommand {
+        referenced_by: vec![reference("allow", window)],
+        windows: vec![Pattern::new(window).unwrap()],
+        ..Default::default()
+      },
+    )]
+    .into_iter()
+    .collect();
+    let denied_commands = [(
+      command.clone(),
+      ResolvedCommand {
+        referenced_by: vec![reference("deny", window)],
+        windows: vec![Pattern::new(window).unwrap()],
+        ..Default::default()
+      },
+    )]
+    .into_iter()
+    .collect();
+
+    let authority = RuntimeAuthority::new(Resolved {
+      allowed_commands,
+      denied_commands,
+      ..Default::default()
+    });
+
+    let resolution = authority.resolve_access_decision(&command.name, window, &Origin::Local);
+    assert_eq!(resolution.decision, AuditDecision::Denied);
+    assert_eq!(resolution.capability, Some("deny"));
+    assert!(resolution.command.is_none());
+  }
 }
@@ -0,0 +1,605 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Batches of IPC calls answered in a single response.
+
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{Arc, Mutex},
+};
+
+use http::{header::HeaderName, HeaderMap, HeaderValue};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+
+use super::{
+  codes, CallbackFn, CancellationToken, CancelledError, CommandError, InvokeBody, InvokeResponse,
+  OwnedInvokeResponder,
+};
+use crate::{sealed::ManagerBase, webview::InvokeRequest, Runtime, Webview};
+
+/// The command that runs a batch of IPC calls of the webview.
+///
+/// The payload is the list of calls and how to run them,
+/// e.g. `{ "mode": "parallel", "entries": [{ "cmd": "greet", "body": { "name": "tauri" }, "headers": {} }] }`.
+/// Each call goes through [`Webview::on_message`] like any other IPC call, Access Control List included,
+/// and the batch resolves to the list of their results, in the order of the entries.
+///
+/// The batch is an in-flight call on its own: cancelling it cancels all of its calls.
+pub(crate) const BATCH_INVOKE_COMMAND: &str = "__TAURI_BATCH_INVOKE__";
+/// The maximum number of calls in a batch.
+pub(crate) const MAX_BATCH_ENTRIES: usize = 256;
+
+/// How the calls of a batch are run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum BatchMode {
+  /// Each call starts once the previous one is resolved.
+  #[default]
+  Sequential,
+  /// All calls start right away.
+  Parallel,
+}
+
+/// A call of the batch.
+#[derive(Deserialize)]
+struct BatchEntry {
+  cmd: String,
+  #[serde(default)]
+  body: JsonValue,
+  #[serde(default)]
+  headers: HashMap<String, String>,
+}
+
+/// The payload of the [`BATCH_INVOKE_COMMAND`].
+#[derive(Deserialize)]
+struct BatchPayload {
+  #[serde(default)]
+  mode: BatchMode,
+  entries: Vec<BatchEntry>,
+}
+
+fn invalid_batch(message: impl Into<String>) -> CommandError {
+  CommandError::new(codes::INVALID_BATCH, message)
+}
+
+impl BatchPayload {
+  fn from_body(body: InvokeBody) -> Result<Self, CommandError> {
+    let payload = match body {
+      InvokeBody::Json(payload) => {
+        Self::deserialize(payload).map_err(|e| invalid_batch(e.to_string()))?
+      }
+      InvokeBody::Raw(_) => return Err(invalid_batch("unexpected raw payload")),
+    };
+    if payload.entries.len() > MAX_BATCH_ENTRIES {
+      return Err(
+        invalid_batch(format!(
+          "a batch cannot have more than {MAX_BATCH_ENTRIES} entries"
+        ))
+        .with_data(json!({ "entries": payload.entries.len(), "max": MAX_BATCH_ENTRIES })),
+      );
+    }
+    Ok(payload)
+  }
+}
+
+impl BatchEntry {
+  /// The request of the call.
+  ///
+  /// Its callback id is derived from the one of the batch. The calls of a batch are not registered as
+  /// in-flight calls of the webview, so the id does not need to be unique.
+  fn into_request(
+    self,
+    batch_callback: CallbackFn,
+    index: usize,
+  ) -> Result<InvokeRequest, CommandError> {
+    if self.cmd == BATCH_INVOKE_COMMAND {
+      return Err(invalid_batch("batches cannot be nested").with_data(json!({ "index": index })));
+    }
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in self.headers {
+      match (
+        HeaderName::from_bytes(name.as_bytes()),
+        HeaderValue::from_str(&value),
+      ) {
+        (Ok(name), Ok(value)) => {
+          headers.insert(name, value);
+        }
+        _ => {
+          return Err(
+            invalid_batch(format!("invalid header `{name}` `{value}`"))
+              .with_data(json!({ "index": index, "header": name })),
+          )
+        }
+      }
+    }
+
+    let callback = CallbackFn(batch_callback.0.wrapping_add(index as u32 + 1));
+    Ok(InvokeRequest {
+      cmd: self.cmd,
+      callback,
+      error: callback,
+      body: InvokeBody::Json(self.body),
+      headers,
+    })
+  }
+}
+
+/// The result of a call of the batch, as given to the frontend.
+fn entry_result(response: InvokeResponse) -> JsonValue {
+  match response {
+    InvokeResponse::Ok(response) => {
+      let parts = response.parts();
+      json!({
+        "ok": true,
+        "body": response.into_payload().into_json(),
+        "status": parts.status,
+        "headers": parts.headers,
+      })
+    }
+    InvokeResponse::Err(error) => json!({ "ok": false, "error": error.0 }),
+  }
+}
+
+fn error_result(error: CommandError) -> JsonValue {
+  entry_result(InvokeResponse::Err(error.into()))
+}
+
+type BatchDone<R> = Box<dyn FnOnce(Webview<R>, Vec<JsonValue>) + Send>;
+type BatchCall = Result<InvokeRequest, CommandError>;
+
+/// Handles the [`BATCH_INVOKE_COMMAND`].
+pub(crate) fn run<R: Runtime>(
+  webview: Webview<R>,
+  request: InvokeRequest,
+  responder: Box<OwnedInvokeResponder<R>>,
+) {
+  let InvokeRequest {
+    cmd,
+    callback,
+    error,
+    body,
+    ..
+  } = request;
+
+  let respond = move |webview: Webview<R>, response: InvokeResponse| {
+    if let Some(custom_responder) = &webview.manager().webview.invoke_responder {
+      custom_responder(&webview, &cmd, &response, callback, error);
+    }
+    responder(webview, cmd, response, callback, error);
+  };
+
+  let payload = match BatchPayload::from_body(body) {
+    Ok(payload) => payload,
+    Err(e) => {
+      respond(webview, InvokeResponse::Err(e.into()));
+      return;
+    }
+  };
+
+  #[cfg(feature = "tracing")]
+  let _span = tracing::trace_span!(
+    "ipc::request::batch",
+    mode = ?payload.mode,
+    entries = payload.entries.len()
+  )
+  .entered();
+
+  let cancellation = webview
+    .manager()
+    .webview
+    .cancellations
+    .register(webview.label(), callback);
+
+  let calls = payload
+    .entries
+    .into_iter()
+    .enumerate()
+    .map(|(index, entry)| entry.into_request(callback, index));
+  let done: BatchDone<R> = Box::new(move |webview, results| {
+    webview
+      .manager()
+      .webview
+      .cancellations
+      .unregister(webview.label(), callback);
+    respond(
+      webview,
+      InvokeResponse::Ok(InvokeBody::Json(results.into()).into()),
+    )
+  });
+
+  match payload.mode {
+    BatchMode::Sequential => run_sequential(
+      webview,
+      Arc::new(Mutex::new(SequentialBatch {
+        calls: calls.collect(),
+        results: Vec::new(),
+        done: Some(done),
+        cancellation,
+      })),
+    ),
+    BatchMode::Parallel => run_parallel(webview, calls.collect(), cancellation, done),
+  }
+}
+
+/// The state of a batch running sequentially.
+struct SequentialBatch<R: Runtime> {
+  calls: VecDeque<BatchCall>,
+  results: Vec<JsonValue>,
+  done: Option<BatchDone<R>>,
+  cancellation: CancellationToken,
+}
+
+/// The progress of a call of a sequential batch.
+enum Step {
+  /// The call is running.
+  Pending,
+  /// The call resolved before [`Webview::on_batch_message`] returned.
+  Resolved(InvokeResponse),
+  /// The call is still running after [`Webview::on_batch_message`] returned,
+  /// so its responder runs the rest of the batch.
+  Detached,
+}
+
+/// Runs the calls of the batch one after the other.
+///
+/// Calls resolved right away are handled in this loop instead of their responder,
+/// so batches of synchronous commands do not grow the stack.
+fn run_sequential<R: Runtime>(webview: Webview<R>, batch: Arc<Mutex<SequentialBatch<R>>>) {
+  loop {
+    let mut state = batch.lock().unwrap();
+    let request = match state.calls.pop_front() {
+      None => {
+        let done = state.done.take();
+        let results = std::mem::take(&mut state.results);
+        drop(state);
+        if let Some(done) = done {
+          done(webview, results);
+        }
+        return;
+      }
+      Some(Err(error)) => {
+        state.results.push(error_result(error));
+        continue;
+      }
+      Some(Ok(request)) => {
+        if let Some(reason) = state.cancellation.reason() {
+          let error = CancelledError {
+            command: request.cmd,
+            reason,
+          };
+          state.results.push(error_result(error.into()));
+          continue;
+        }
+        request
+      }
+    };
+    let cancellation = state.cancellation.clone();
+    drop(state);
+
+    let step = Arc::new(Mutex::new(Step::Pending));
+    let step_ = step.clone();
+    let batch_ = batch.clone();
+    webview.clone().on_batch_message(
+      request,
+      Box::new(move |webview, _cmd, response, _callback, _error| {
+        let mut step = step_.lock().unwrap();
+        if let Step::Pending = *step {
+          *step = Step::Resolved(response);
+          return;
+        }
+        drop(step);
+        batch_.lock().unwrap().results.push(entry_result(response));
+        run_sequential(webview, batch_);
+      }),
+      &cancellation,
+    );
+
+    let step = std::mem::replace(&mut *step.lock().unwrap(), Step::Detached);
+    match step {
+      Step::Resolved(response) => batch.lock().unwrap().results.push(entry_result(response)),
+      // the responder runs the rest of the batch once the call is resolved
+      _ => return,
+    }
+  }
+}
+
+/// The results of a batch running in parallel.
+struct ParallelBatch<R: Runtime> {
+  results: Vec<Option<JsonValue>>,
+  remaining: usize,
+  done: Option<BatchDone<R>>,
+}
+
+impl<R: Runtime> ParallelBatch<R> {
+  /// Records the result of a call, returning the callback and results of the batch once all calls are resolved.
+  fn resolve(&mut self, index: usize, result: JsonValue) -> Option<(BatchDone<R>, Vec<JsonValue>)> {
+    self.results[index].replace(result);
+    self.remaining -= 1;
+    if self.remaining > 0 {
+      return None;
+    }
+    let results = self.results.drain(..).map(Option::unwrap_or_default);
+    Some((self.done.take()?, results.collect()))
+  }
+}
+
+/// Starts all calls of the batch, resolving it once all of them are resolved.
+fn run_parallel<R: Runtime>(
+  webview: Webview<R>,
+  calls: Vec<BatchCall>,
+  cancellation: CancellationToken,
+  done: BatchDone<R>,
+) {
+  if calls.is_empty() {
+    done(webview, Vec::new());
+    return;
+  }
+
+  let batch = Arc::new(Mutex::new(ParallelBatch {
+    results: vec![None; calls.len()],
+    remaining: calls.len(),
+    done: Some(done),
+  }));
+
+  for (index, call) in calls.into_iter().enumerate() {
+    match call {
+      Ok(request) => {
+        let batch = batch.clone();
+        webview.clone().on_batch_message(
+          request,
+          Box::new(move |webview, _cmd, response, _callback, _error| {
+            // the lock is released before resolving the batch
+            let resolved = batch.lock().unwrap().resolve(index, entry_result(response));
+            if let Some((done, results)) = resolved {
+              done(webview, results);
+            }
+          }),
+          &cancellation,
+        );
+      }
+      Err(error) => {
+        let resolved = batch.lock().unwrap().resolve(index, error_result(error));
+        if let Some((done, results)) = resolved {
+          done(webview.clone(), results);
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::mpsc::{channel, Receiver};
+
+  use super::*;
+  use crate::{
+    command,
+    ipc::{cancellation::CANCEL_INVOKE_COMMAND, CommandError, InvokeError, Response},
+    test::{mock_builder, mock_context, noop_assets, MockRuntime},
+    App, Manager, WebviewUrl, WebviewWindowBuilder,
+  };
+
+  #[command(root = "crate")]
+  fn greet(name: String) -> String {
+    format!("Hello, {name}!")
+  }
+
+  #[command(root = "crate")]
+  fn fail() -> Result<(), CommandError> {
+    Err(CommandError::new("FAILED", "failed"))
+  }
+
+  #[command(root = "crate")]
+  async fn delayed(ms: u64) -> u64 {
+    tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+    ms
+  }
+
+  #[command(root = "crate")]
+  async fn wait() {
+    std::future::pending::<()>().await;
+  }
+
+  #[command(root = "crate")]
+  fn page(request: crate::ipc::Request<'_>) -> crate::Result<Response> {
+    let cursor = request.headers()["x-cursor"].to_str().unwrap().to_string();
+    Response::new(json!([])).with_header("X-Next-Cursor", cursor + "1")
+  }
+
+  fn app() -> App<MockRuntime> {
+    let app = mock_builder()
+      .invoke_handler(crate::generate_handler![greet, fail, delayed, wait, page])
+      .build(mock_context(noop_assets()))
+      .unwrap();
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+    app
+  }
+
+  fn invoke(app: &App<MockRuntime>, body: JsonValue) -> Receiver<InvokeResponse> {
+    call(app, BATCH_INVOKE_COMMAND, 1, body)
+  }
+
+  fn call(
+    app: &App<MockRuntime>,
+    cmd: &str,
+    callback: u32,
+    body: JsonValue,
+  ) -> Receiver<InvokeResponse> {
+    let (tx, rx) = channel();
+    app.get_webview_window("main").unwrap().on_message(
+      InvokeRequest {
+        cmd: cmd.into(),
+        callback: CallbackFn(callback),
+        error: CallbackFn(callback + 1),
+        body: body.into(),
+        headers: Default::default(),
+      },
+      Box::new(move |_webview, _cmd, response, _callback, _error| {
+        tx.send(response).unwrap();
+      }),
+    );
+    rx
+  }
+
+  fn results(response: InvokeResponse) -> JsonValue {
+    match response {
+      InvokeResponse::Ok(response) => response.into_payload().into_json(),
+      InvokeResponse::Err(InvokeError(error)) => panic!("unexpected error {error}"),
+    }
+  }
+
+  #[test]
+  fn sequential_batch() {
+    let app = app();
+    let response = invoke(
+      &app,
+      json!({
+        "entries": [
+          { "cmd": "greet", "body": { "name": "tauri" } },
+          { "cmd": "fail" },
+          { "cmd": "unknown" },
+          { "cmd": "page", "headers": { "X-Cursor": "a" } },
+          { "cmd": BATCH_INVOKE_COMMAND },
+          { "cmd": "delayed", "body": { "ms": 1 } },
+        ]
+      }),
+    );
+    let results = results(response.recv().unwrap());
+
+    assert_eq!(
+      results[0],
+      json!({ "ok": true, "body": "Hello, tauri!", "status": 200, "headers": {} })
+    );
+    assert_eq!(
+      results[1],
+      json!({ "ok": false, "error": { "code": "FAILED", "message": "failed" } })
+    );
+    assert_eq!(results[2]["ok"], false);
+    assert_eq!(
+      results[2]["error"]["code"],
+      crate::ipc::codes::COMMAND_NOT_FOUND
+    );
+    assert_eq!(results[3]["headers"], json!({ "x-next-cursor": "a1" }));
+    assert_eq!(
+      results[4],
+      json!({
+        "ok": false,
+        "error": {
+          "code": codes::INVALID_BATCH,
+          "message": "batches cannot be nested",
+          "data": { "index": 4 }
+        }
+      })
+    );
+    assert_eq!(results[5]["body"], 1);
+  }
+
+  #[test]
+  fn parallel_batch() {
+    let app = app();
+    let response = invoke(
+      &app,
+      json!({
+        "mode": "parallel",
+        "entries": [
+          { "cmd": "delayed", "body": { "ms": 50 } },
+          { "cmd": "delayed", "body": { "ms": 1 } },
+          { "cmd": "greet", "body": {} },
+        ]
+      }),
+    );
+    let results = results(response.recv().unwrap());
+
+    // the results are in the order of the entries, not in the order the calls were resolved
+    assert_eq!(results[0]["body"], 50);
+    assert_eq!(results[1]["body"], 1);
+    assert_eq!(results[2]["ok"], false);
+    assert_eq!(results[2]["error"]["code"], crate::ipc::codes::INVALID_ARGS);
+  }
+
+  #[test]
+  fn empty_and_invalid_batches() {
+    let app = app();
+    for mode in ["sequential", "parallel"] {
+      let response = invoke(&app, json!({ "mode": mode, "entries": [] }));
+      assert_eq!(results(response.recv().unwrap()), json!([]));
+    }
+
+    let response = invoke(&app, json!({ "mode": "unknown", "entries": [] }));
+    assert!(matches!(
+      response.recv().unwrap(),
+      InvokeResponse::Err(InvokeError(error)) if error["code"] == codes::INVALID_BATCH
+    ));
+
+    let entries = vec![json!({ "cmd": "greet" }); MAX_BATCH_ENTRIES + 1];
+    let response = invoke(&app, json!({ "entries": entries }));
+    assert!(matches!(
+      response.recv().unwrap(),
+      InvokeResponse::Err(InvokeError(error)) if error["code"] == codes::INVALID_BATCH
+    ));
+  }
+
+  #[test]
+  fn long_sequential_batch() {
+    let app = app();
+    let entries = vec![json!({ "cmd": "greet", "body": { "name": "tauri" } }); MAX_BATCH_ENTRIES];
+    let results = results(invoke(&app, json!({ "entries": entries })).recv().unwrap());
+    let results = results.as_array().unwrap();
+    assert_eq!(results.len(), MAX_BATCH_ENTRIES);
+    assert!(results
+      .iter()
+      .all(|result| result["body"] == "Hello, tauri!"));
+  }
+
+  #[test]
+  fn cancel_batch() {
+    let app = app();
+    for mode in ["sequential", "parallel"] {
+      let response = invoke(
+        &app,
+        json!({ "mode": mode, "entries": [{ "cmd": "wait" }, { "cmd": "wait" }] }),
+      );
+
+      let cancel = call(&app, CANCEL_INVOKE_COMMAND, 10, json!({ "callback": 1 }));
+      assert!(matches!(
+        cancel.recv().unwrap(),
+        InvokeResponse::Ok(response)
+          if matches!(response.payload(), InvokeBody::Json(JsonValue::Bool(true)))
+      ));
+
+      // the running call and the calls that did not start yet are cancelled
+      let results = results(response.recv().unwrap());
+      for result in results.as_array().unwrap() {
+        assert_eq!(result["error"]["code"], codes::CANCELLED);
+        assert_eq!(result["error"]["data"]["reason"], "aborted");
+      }
+    }
+  }
+
+  #[test]
+  fn entries_are_not_in_flight_calls() {
+    let app = app();
+    // the first entry of the batch derives the same callback id
+    let other = call(&app, "wait", 2, json!({}));
+
+    let response = invoke(
+      &app,
+      json!({ "entries": [{ "cmd": "greet", "body": { "name": "a" } }] }),
+    );
+    assert_eq!(results(response.recv().unwrap())[0]["body"], "Hello, a!");
+
+    // the other call is still in flight and can be cancelled
+    let cancel = call(&app, CANCEL_INVOKE_COMMAND, 10, json!({ "callback": 2 }));
+    assert!(matches!(
+      cancel.recv().unwrap(),
+      InvokeResponse::Ok(response)
+        if matches!(response.payload(), InvokeBody::Json(JsonValue::Bool(true)))
+    ));
+    assert!(matches!(other.recv().unwrap(), InvokeResponse::Err(_)));
+  }
+}
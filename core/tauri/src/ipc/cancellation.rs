@@ -0,0 +1,422 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Cancellation of in-flight IPC calls.
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tokio::sync::Notify;
+
+use super::{
+  codes, CallbackFn, CommandArg, CommandItem, InvokeBody, InvokeError, InvokeResponse, IpcError,
+};
+use crate::Runtime;
+
+/// The command that cancels an in-flight IPC call of the webview.
+///
+/// The payload is the callback id of the call, e.g. `{ "callback": 1234 }`.
+pub(crate) const CANCEL_INVOKE_COMMAND: &str = "__TAURI_CANCEL_INVOKE__";
+
+/// Why an IPC call was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CancellationReason {
+  /// The frontend aborted the call.
+  Aborted,
+  /// The webview navigated away from the page that made the call.
+  Navigation,
+  /// The webview was destroyed.
+  Destroyed,
+  /// The command took longer than its timeout, see [`timeout`](crate::ipc::timeout).
+  TimedOut,
+}
+
+/// The rejection sent to the frontend when an async command is cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("command {command} cancelled: {reason:?}")]
+pub struct CancelledError {
+  /// The command name.
+  pub command: String,
+  /// Why the call was cancelled.
+  pub reason: CancellationReason,
+}
+
+impl IpcError for CancelledError {
+  fn code(&self) -> &str {
+    codes::CANCELLED
+  }
+
+  fn data(&self) -> Option<JsonValue> {
+    serde_json::to_value(self).ok()
+  }
+}
+
+#[derive(Default)]
+struct Inner {
+  reason: Mutex<Option<CancellationReason>>,
+  notify: Notify,
+  /// The tokens cancelled along with this one, e.g. the calls of a batch.
+  children: Mutex<Vec<CancellationToken>>,
+}
+
+/// A signal that the IPC call was cancelled, available as a command argument.
+///
+/// When an async command is cancelled, its future is dropped and the call is rejected with a [`CancelledError`].
+/// Synchronous commands and commands that spawn their own tasks can check the token to stop early.
+///
+/// # Examples
+///
+/// ```rust
+/// use tauri::ipc::CancellationToken;
+///
+/// #[tauri::command]
+/// async fn index(files: Vec<String>, cancellation: CancellationToken) -> usize {
+///   // dropping the command future does not stop the blocking task, so it checks the token
+///   tauri::async_runtime::spawn_blocking(move || {
+///     let mut indexed = 0;
+///     for _file in files {
+///       if cancellation.is_cancelled() {
+///         break;
+///       }
+///       indexed += 1;
+///     }
+///     indexed
+///   })
+///   .await
+///   .unwrap_or_default()
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+  inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for CancellationToken {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("CancellationToken")
+      .field("reason", &self.reason())
+      .finish()
+  }
+}
+
+impl CancellationToken {
+  /// Whether the call was cancelled.
+  pub fn is_cancelled(&self) -> bool {
+    self.reason().is_some()
+  }
+
+  /// Why the call was cancelled, `None` if it was not.
+  pub fn reason(&self) -> Option<CancellationReason> {
+    *self.inner.reason.lock().unwrap()
+  }
+
+  /// Waits until the call is cancelled.
+  pub async fn cancelled(&self) -> CancellationReason {
+    loop {
+      // registered before checking the reason so a concurrent cancellation is not missed
+      let notified = self.inner.notify.notified();
+      if let Some(reason) = self.reason() {
+        return reason;
+      }
+      notified.await;
+    }
+  }
+
+  /// Cancels the call, keeping the first reason if it was already cancelled.
+  pub(crate) fn cancel(&self, reason: CancellationReason) {
+    {
+      let mut current = self.inner.reason.lock().unwrap();
+      if current.is_some() {
+        return;
+      }
+      current.replace(reason);
+      self.inner.notify.notify_waiters();
+    }
+    let children = std::mem::take(&mut *self.inner.children.lock().unwrap());
+    for child in children {
+      child.cancel(reason);
+    }
+  }
+
+  /// Creates a token that is cancelled along with this one, but can be cancelled on its own.
+  pub(crate) fn child(&self) -> Self {
+    let child = Self::default();
+    // the reason is checked with the children locked so a concurrent cancellation is not missed
+    let mut children = self.inner.children.lock().unwrap();
+    match self.reason() {
+      Some(reason) => child.cancel(reason),
+      None => children.push(child.clone()),
+    }
+    child
+  }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for CancellationToken {
+  /// Grabs the [`CancellationToken`] of the call from the [`CommandItem`]. This will never fail.
+  fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+    Ok(command.message.cancellation.clone())
+  }
+}
+
+/// The payload of the [`CANCEL_INVOKE_COMMAND`].
+#[derive(Deserialize)]
+struct CancelInvokePayload {
+  callback: CallbackFn,
+}
+
+/// The cancellation tokens of the in-flight calls of each webview, keyed by their callback id.
+#[derive(Default)]
+pub(crate) struct Cancellations {
+  webviews: Mutex<HashMap<String, HashMap<u32, CancellationToken>>>,
+}
+
+impl Cancellations {
+  /// Creates the token of an in-flight call.
+  pub(crate) fn register(&self, label: &str, callback: CallbackFn) -> CancellationToken {
+    let token = CancellationToken::default();
+    self
+      .webviews
+      .lock()
+      .unwrap()
+      .entry(label.into())
+      .or_default()
+      .insert(callback.0, token.clone());
+    token
+  }
+
+  /// Removes the token of a resolved call.
+  pub(crate) fn unregister(&self, label: &str, callback: CallbackFn) {
+    let mut webviews = self.webviews.lock().unwrap();
+    if let Some(calls) = webviews.get_mut(label) {
+      calls.remove(&callback.0);
+      if calls.is_empty() {
+        webviews.remove(label);
+      }
+    }
+  }
+
+  /// Cancels an in-flight call, returning `false` if it was not found.
+  pub(crate) fn cancel(
+    &self,
+    label: &str,
+    callback: CallbackFn,
+    reason: CancellationReason,
+  ) -> bool {
+    let token = self
+      .webviews
+      .lock()
+      .unwrap()
+      .get_mut(label)
+      .and_then(|calls| calls.remove(&callback.0));
+    if let Some(token) = &token {
+      token.cancel(reason);
+    }
+    token.is_some()
+  }
+
+  /// Handles the [`CANCEL_INVOKE_COMMAND`], resolving to whether the call was found.
+  pub(crate) fn cancel_invoke(&self, label: &str, body: &InvokeBody) -> InvokeResponse {
+    let payload = match body {
+      InvokeBody::Json(payload) => CancelInvokePayload::deserialize(payload),
+      InvokeBody::Raw(_) => return InvokeResponse::Err("unexpected raw payload".into()),
+    };
+    match payload {
+      Ok(payload) => {
+        let cancelled = self.cancel(label, payload.callback, CancellationReason::Aborted);
+        InvokeResponse::Ok(InvokeBody::Json(cancelled.into()).into())
+      }
+      Err(e) => InvokeResponse::Err(e.to_string().into()),
+    }
+  }
+
+  /// Cancels all in-flight calls of the webview.
+  pub(crate) fn cancel_all(&self, label: &str, reason: CancellationReason) {
+    let calls = self.webviews.lock().unwrap().remove(label);
+    for token in calls.into_iter().flat_map(HashMap::into_values) {
+      token.cancel(reason);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cancel_call() {
+    let cancellations = Cancellations::default();
+    let token = cancellations.register("main", CallbackFn(1));
+    let other = cancellations.register("main", CallbackFn(2));
+
+    assert!(cancellations.cancel("main", CallbackFn(1), CancellationReason::Aborted));
+    assert_eq!(token.reason(), Some(CancellationReason::Aborted));
+    assert!(!other.is_cancelled());
+
+    // already cancelled or resolved calls are not found
+    assert!(!cancellations.cancel("main", CallbackFn(1), CancellationReason::Aborted));
+    cancellations.unregister("main", CallbackFn(2));
+    assert!(!cancellations.cancel("main", CallbackFn(2), CancellationReason::Aborted));
+    assert!(!other.is_cancelled());
+  }
+
+  #[test]
+  fn cancel_all_calls_of_webview() {
+    let cancellations = Cancellations::default();
+    let first = cancellations.register("main", CallbackFn(1));
+    let second = cancellations.register("main", CallbackFn(2));
+    let other = cancellations.register("other", CallbackFn(1));
+
+    cancellations.cancel_all("main", CancellationReason::Navigation);
+    assert_eq!(first.reason(), Some(CancellationReason::Navigation));
+    assert_eq!(second.reason(), Some(CancellationReason::Navigation));
+    assert!(!other.is_cancelled());
+
+    // the first reason is kept
+    first.cancel(CancellationReason::Destroyed);
+    assert_eq!(first.reason(), Some(CancellationReason::Navigation));
+  }
+
+  #[test]
+  fn cancel_children() {
+    let parent = CancellationToken::default();
+    let first = parent.child();
+    let second = parent.child();
+
+    first.cancel(CancellationReason::TimedOut);
+    assert!(!parent.is_cancelled());
+    assert!(!second.is_cancelled());
+
+    parent.cancel(CancellationReason::Aborted);
+    assert_eq!(first.reason(), Some(CancellationReason::TimedOut));
+    assert_eq!(second.reason(), Some(CancellationReason::Aborted));
+    assert_eq!(parent.child().reason(), Some(CancellationReason::Aborted));
+  }
+
+  #[test]
+  fn wait_for_cancellation() {
+    let token = CancellationToken::default();
+    let token_ = token.clone();
+    let task = crate::async_runtime::spawn(async move { token_.cancelled().await });
+    token.cancel(CancellationReason::Destroyed);
+    assert_eq!(
+      crate::async_runtime::block_on(task).unwrap(),
+      CancellationReason::Destroyed
+    );
+  }
+
+  mod command {
+    use std::sync::mpsc::{channel, Receiver};
+
+    use serde_json::json;
+
+    use super::super::*;
+    use crate::{
+      command,
+      sealed::ManagerBase,
+      test::{mock_builder, mock_context, noop_assets, MockRuntime},
+      webview::InvokeRequest,
+      App, Manager, WebviewUrl, WebviewWindowBuilder,
+    };
+
+    #[command(root = "crate")]
+    async fn wait(cancellation: CancellationToken) -> Option<CancellationReason> {
+      std::future::pending::<()>().await;
+      cancellation.reason()
+    }
+
+    fn app() -> App<MockRuntime> {
+      let app = mock_builder()
+        .invoke_handler(crate::generate_handler![wait])
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      app
+    }
+
+    fn invoke(
+      app: &App<MockRuntime>,
+      cmd: &str,
+      callback: u32,
+      body: serde_json::Value,
+    ) -> Receiver<InvokeResponse> {
+      let (tx, rx) = channel();
+      app.get_webview_window("main").unwrap().on_message(
+        InvokeRequest {
+          cmd: cmd.into(),
+          callback: CallbackFn(callback),
+          error: CallbackFn(callback + 1),
+          body: body.into(),
+          headers: Default::default(),
+        },
+        Box::new(move |_webview, _cmd, response, _callback, _error| {
+          tx.send(response).unwrap();
+        }),
+      );
+      rx
+    }
+
+    fn assert_cancelled(response: InvokeResponse, reason: &str) {
+      let error = match response {
+        InvokeResponse::Err(InvokeError(error)) => error,
+        InvokeResponse::Ok(_) => panic!("expected the call to be rejected"),
+      };
+      assert_eq!(error["code"], codes::CANCELLED);
+      assert_eq!(
+        error["data"],
+        json!({ "command": "wait", "reason": reason })
+      );
+    }
+
+    #[test]
+    fn abort_from_frontend() {
+      let app = app();
+      let call = invoke(&app, "wait", 1, json!({}));
+
+      let cancel = invoke(&app, CANCEL_INVOKE_COMMAND, 10, json!({ "callback": 1 }));
+      assert!(matches!(
+        cancel.recv().unwrap(),
+        InvokeResponse::Ok(response)
+          if matches!(response.payload(), InvokeBody::Json(serde_json::Value::Bool(true)))
+      ));
+      assert_cancelled(call.recv().unwrap(), "aborted");
+
+      // the call is no longer in flight
+      let cancel = invoke(&app, CANCEL_INVOKE_COMMAND, 10, json!({ "callback": 1 }));
+      assert!(matches!(
+        cancel.recv().unwrap(),
+        InvokeResponse::Ok(response)
+          if matches!(response.payload(), InvokeBody::Json(serde_json::Value::Bool(false)))
+      ));
+    }
+
+    #[test]
+    fn cancel_on_navigation() {
+      let app = app();
+      let call = invoke(&app, "wait", 1, json!({}));
+
+      app
+        .get_webview_window("main")
+        .unwrap()
+        .navigate("https://tauri.app".parse().unwrap());
+      assert_cancelled(call.recv().unwrap(), "navigation");
+    }
+
+    #[test]
+    fn cancel_on_destroy() {
+      let app = app();
+      let call = invoke(&app, "wait", 1, json!({}));
+
+      app.manager().on_window_close("main");
+      assert_cancelled(call.recv().unwrap(), "destroyed");
+    }
+  }
+}
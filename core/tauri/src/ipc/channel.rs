@@ -20,14 +20,22 @@ use crate::{
   Manager, Runtime, State, Webview,
 };
 
-use super::{CallbackFn, InvokeBody, InvokeError, IpcResponse, Request, Response};
+use super::{
+  stream::StreamAcks, CallbackFn, InvokeBody, InvokeError, IpcResponse, Request, Response,
+};
 
 pub const IPC_PAYLOAD_PREFIX: &str = "__CHANNEL__:";
 pub const CHANNEL_PLUGIN_NAME: &str = "__TAURI_CHANNEL__";
 // TODO: ideally this const references CHANNEL_PLUGIN_NAME
 pub const FETCH_CHANNEL_DATA_COMMAND: &str = "plugin:__TAURI_CHANNEL__|fetch";
+pub const ACK_STREAM_COMMAND: &str = "plugin:__TAURI_CHANNEL__|ack";
 pub(crate) const CHANNEL_ID_HEADER_NAME: &str = "Tauri-Channel-Id";
 
+/// Whether the command is an internal command of the channel plugin, which is always allowed.
+pub(crate) fn is_channel_command(cmd: &str) -> bool {
+  cmd == FETCH_CHANNEL_DATA_COMMAND || cmd == ACK_STREAM_COMMAND
+}
+
 static CHANNEL_COUNTER: AtomicU32 = AtomicU32::new(0);
 static CHANNEL_DATA_COUNTER: AtomicU32 = AtomicU32::new(0);
 
@@ -198,8 +206,13 @@ fn fetch(
   }
 }
 
+#[command(root = "crate")]
+fn ack(channel: u32, index: u64, acks: State<'_, StreamAcks>) -> bool {
+  acks.ack(channel, index)
+}
+
 pub fn plugin<R: Runtime>() -> TauriPlugin<R> {
   PluginBuilder::new(CHANNEL_PLUGIN_NAME)
-    .invoke_handler(crate::generate_handler![fetch])
+    .invoke_handler(crate::generate_handler![fetch, ack])
     .build()
 }
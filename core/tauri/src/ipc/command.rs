@@ -8,7 +8,7 @@
 //! attribute macro along the way and used by [`crate::generate_handler`] macro.
 
 use crate::{
-  ipc::{InvokeBody, InvokeError, InvokeMessage},
+  ipc::{encoding::ArgValue, InvokeBody, InvokeError, InvokeMessage},
   Runtime,
 };
 use serde::{
@@ -69,7 +69,22 @@ impl<'de, D: Deserialize<'de>, R: Runtime> CommandArg<'de, R> for D {
   }
 }
 
-/// Pass the result of [`serde_json::Value::get`] into [`serde_json::Value`]'s deserializer.
+impl<'a, R: Runtime> CommandItem<'a, R> {
+  /// The value of the argument in the IPC payload, `None` if the key does not exist.
+  fn value(&self) -> Result<Option<ArgValue<'a>>, serde_json::Error> {
+    let message = self.message;
+    match (&message.decoded_payload, &message.payload) {
+      (Some(decoded), _) => Ok(decoded.get(self.key)),
+      (None, InvokeBody::Json(v)) => Ok(v.get(self.key).map(ArgValue::Json)),
+      (None, InvokeBody::Raw(_body)) => Err(serde_json::Error::custom(format!(
+        "command {} expected a value for key {} but the IPC call used a bytes payload",
+        self.name, self.key
+      ))),
+    }
+  }
+}
+
+/// Pass the value of the [`CommandItem`]'s key into the deserializer of its encoding.
 ///
 /// Returns an error if the [`CommandItem`]'s key does not exist in the value.
 macro_rules! pass {
@@ -82,24 +97,14 @@ macro_rules! pass {
           )))
       }
 
-      match &self.message.payload {
-        InvokeBody::Raw(_body) => {
+      match self.value()? {
+        Some(value) => value.$fn($($arg),*),
+        None => {
           Err(serde_json::Error::custom(format!(
-            "command {} expected a value for key {} but the IPC call used a bytes payload",
+            "command {} missing required key {}",
             self.name, self.key
           )))
         }
-        InvokeBody::Json(v) => {
-          match v.get(self.key) {
-            Some(value) => value.$fn($($arg),*),
-            None => {
-              Err(serde_json::Error::custom(format!(
-                "command {} missing required key {}",
-                self.name, self.key
-              )))
-            }
-          }
-        }
       }
     }
   }
@@ -108,8 +113,8 @@ macro_rules! pass {
 /// A [`Deserializer`] wrapper around [`CommandItem`].
 ///
 /// If the key doesn't exist, an error will be returned if the deserialized type is not expecting
-/// an optional item. If the key does exist, the value will be called with
-/// [`Value`](serde_json::Value)'s [`Deserializer`] implementation.
+/// an optional item. If the key does exist, the value will be called with the [`Deserializer`]
+/// implementation of the payload encoding, e.g. [`Value`](serde_json::Value)'s for JSON.
 impl<'de, R: Runtime> Deserializer<'de> for CommandItem<'de, R> {
   type Error = serde_json::Error;
 
@@ -132,15 +137,9 @@ impl<'de, R: Runtime> Deserializer<'de> for CommandItem<'de, R> {
   pass!(deserialize_byte_buf, visitor: V);
 
   fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-    match &self.message.payload {
-      InvokeBody::Raw(_body) => Err(serde_json::Error::custom(format!(
-        "command {} expected a value for key {} but the IPC call used a bytes payload",
-        self.name, self.key
-      ))),
-      InvokeBody::Json(v) => match v.get(self.key) {
-        Some(value) => value.deserialize_option(visitor),
-        None => visitor.visit_none(),
-      },
+    match self.value()? {
+      Some(value) => value.deserialize_option(visitor),
+      None => visitor.visit_none(),
     }
   }
 
@@ -183,14 +182,26 @@ impl<'de, R: Runtime> Deserializer<'de> for CommandItem<'de, R> {
 #[doc(hidden)]
 pub mod private {
   use crate::{
-    ipc::{InvokeBody, InvokeError, InvokeResolver, IpcResponse},
+    ipc::{stream, InvokeBody, InvokeError, InvokeMessage, InvokeResolver, IpcResponse, Response},
     Runtime,
   };
-  use futures_util::{FutureExt, TryFutureExt};
+  use futures_util::{FutureExt, Stream, StreamExt, TryFutureExt};
+  #[cfg(feature = "validate-regex")]
+  pub use regex;
+  pub use serde_json;
   use std::future::Future;
   #[cfg(feature = "tracing")]
   pub use tracing;
 
+  /// Warns that a command marked with `#[tauri::command(deprecated)]` was called.
+  pub fn warn_deprecated_command(command: &str, since: Option<&str>, note: Option<&str>) {
+    let since = since
+      .map(|since| format!(" since {since}"))
+      .unwrap_or_default();
+    let note = note.map(|note| format!(": {note}")).unwrap_or_default();
+    log::warn!("command `{command}` is deprecated{since}{note}");
+  }
+
   // ===== impl IpcResponse =====
 
   pub struct ResponseTag;
@@ -220,11 +231,16 @@ pub mod private {
     }
 
     #[inline(always)]
-    pub fn future<T>(self, value: T) -> impl Future<Output = Result<InvokeBody, InvokeError>>
+    pub fn future<R, T>(
+      self,
+      value: T,
+      _message: &InvokeMessage<R>,
+    ) -> impl Future<Output = Result<Response, InvokeError>>
     where
+      R: Runtime,
       T: IpcResponse,
     {
-      std::future::ready(value.body().map_err(InvokeError::from_error))
+      std::future::ready(value.into_response().map_err(InvokeError::from_error))
     }
   }
 
@@ -258,18 +274,20 @@ pub mod private {
     }
 
     #[inline(always)]
-    pub fn future<T, E>(
+    pub fn future<R, T, E>(
       self,
       value: Result<T, E>,
-    ) -> impl Future<Output = Result<InvokeBody, InvokeError>>
+      _message: &InvokeMessage<R>,
+    ) -> impl Future<Output = Result<Response, InvokeError>>
     where
+      R: Runtime,
       T: IpcResponse,
       E: Into<InvokeError>,
     {
       std::future::ready(
         value
           .map_err(Into::into)
-          .and_then(|value| value.body().map_err(InvokeError::from_error)),
+          .and_then(|value| value.into_response().map_err(InvokeError::from_error)),
       )
     }
   }
@@ -288,12 +306,17 @@ pub mod private {
 
   impl FutureTag {
     #[inline(always)]
-    pub fn future<T, F>(self, value: F) -> impl Future<Output = Result<InvokeBody, InvokeError>>
+    pub fn future<R, T, F>(
+      self,
+      value: F,
+      _message: &InvokeMessage<R>,
+    ) -> impl Future<Output = Result<Response, InvokeError>>
     where
+      R: Runtime,
       T: IpcResponse,
       F: Future<Output = T> + Send + 'static,
     {
-      value.map(|value| value.body().map_err(InvokeError::from_error))
+      value.map(|value| value.into_response().map_err(InvokeError::from_error))
     }
   }
 
@@ -315,15 +338,278 @@ pub mod private {
 
   impl ResultFutureTag {
     #[inline(always)]
-    pub fn future<T, E, F>(self, value: F) -> impl Future<Output = Result<InvokeBody, InvokeError>>
+    pub fn future<R, T, E, F>(
+      self,
+      value: F,
+      _message: &InvokeMessage<R>,
+    ) -> impl Future<Output = Result<Response, InvokeError>>
     where
+      R: Runtime,
       T: IpcResponse,
       E: Into<InvokeError>,
       F: Future<Output = Result<T, E>> + Send,
     {
-      value
-        .err_into()
-        .map(|result| result.and_then(|value| value.body().map_err(InvokeError::from_error)))
+      value.err_into().map(|result| {
+        result.and_then(|value| value.into_response().map_err(InvokeError::from_error))
+      })
+    }
+  }
+
+  // ===== Future<Output = impl Stream<Item = impl IpcResponse>> =====
+
+  pub struct StreamFutureTag;
+
+  pub trait StreamFutureKind {
+    #[inline(always)]
+    fn async_kind(&self) -> StreamFutureTag {
+      StreamFutureTag
+    }
+  }
+  impl<T: IpcResponse, S: Stream<Item = T>, F: Future<Output = S>> StreamFutureKind for &F {}
+
+  impl StreamFutureTag {
+    #[inline(always)]
+    pub fn future<R, T, S, F>(
+      self,
+      value: F,
+      message: &InvokeMessage<R>,
+    ) -> impl Future<Output = Result<InvokeBody, InvokeError>>
+    where
+      R: Runtime,
+      T: IpcResponse,
+      S: Stream<Item = T> + Send,
+      F: Future<Output = S> + Send,
+    {
+      let target = stream::StreamTarget::from_message(message);
+      async move {
+        let target = target?;
+        let stream = value.await.map(Ok::<T, InvokeError>);
+        target.pump(stream).await
+      }
+    }
+  }
+
+  // ===== Future<Output = Result<impl Stream<Item = Result<impl IpcResponse, _>>, impl Into<InvokeError>>> =====
+
+  pub struct ResultStreamFutureTag;
+
+  pub trait ResultStreamFutureKind {
+    #[inline(always)]
+    fn async_kind(&self) -> ResultStreamFutureTag {
+      ResultStreamFutureTag
+    }
+  }
+
+  impl<
+      T: IpcResponse,
+      E: Into<InvokeError>,
+      SE: Into<InvokeError>,
+      S: Stream<Item = Result<T, SE>>,
+      F: Future<Output = Result<S, E>>,
+    > ResultStreamFutureKind for F
+  {
+  }
+
+  impl ResultStreamFutureTag {
+    #[inline(always)]
+    pub fn future<R, T, E, SE, S, F>(
+      self,
+      value: F,
+      message: &InvokeMessage<R>,
+    ) -> impl Future<Output = Result<InvokeBody, InvokeError>>
+    where
+      R: Runtime,
+      T: IpcResponse,
+      E: Into<InvokeError>,
+      SE: Into<InvokeError>,
+      S: Stream<Item = Result<T, SE>> + Send,
+      F: Future<Output = Result<S, E>> + Send,
+    {
+      let target = stream::StreamTarget::from_message(message);
+      async move {
+        let target = target?;
+        let stream = value.await.map_err(Into::into)?;
+        target.pump(stream).await
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  mod command {
+    use serde_json::json;
+
+    use crate::{
+      ipc::{codes, CallbackFn, InvokeBody},
+      test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime},
+      webview::InvokeRequest,
+      App, Manager, WebviewUrl, WebviewWindowBuilder,
+    };
+
+    struct Counter(std::sync::Mutex<u32>);
+
+    #[crate::commands(root = "crate")]
+    impl Counter {
+      fn get(&self) -> u32 {
+        *self.0.lock().unwrap()
+      }
+
+      async fn add(&self, amount: u32) -> Result<u32, String> {
+        let mut count = self.0.lock().unwrap();
+        *count += amount;
+        Ok(*count)
+      }
+    }
+
+    struct Unmanaged;
+
+    #[crate::commands(root = "crate")]
+    impl Unmanaged {
+      fn unmanaged(&self) {}
+    }
+
+    mod tally {
+      pub struct Tally;
+
+      // the group macro is exported with the visibility of the methods
+      #[crate::commands(root = "crate")]
+      impl Tally {
+        pub fn tally(&self) -> &'static str {
+          "public method"
+        }
+      }
+    }
+
+    #[crate::command(root = "crate")]
+    fn get() -> &'static str {
+      "free function"
+    }
+
+    #[crate::command(
+      root = "crate",
+      deprecated(since = "1.2.0", note = "use `get` instead")
+    )]
+    fn old_get() -> &'static str {
+      "deprecated"
+    }
+
+    #[crate::command(root = "crate", deprecated)]
+    fn older_get() -> &'static str {
+      "deprecated"
+    }
+
+    fn app() -> App<MockRuntime> {
+      let app = mock_builder()
+        .invoke_handler(crate::generate_handler![
+          get,
+          old_get,
+          older_get,
+          impl Counter as "counter_",
+          impl Unmanaged,
+          impl tally::Tally
+        ])
+        .manage(Counter(Default::default()))
+        .manage(tally::Tally)
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      app
+    }
+
+    fn invoke(
+      app: &App<MockRuntime>,
+      cmd: &str,
+      body: serde_json::Value,
+    ) -> Result<serde_json::Value, serde_json::Value> {
+      get_ipc_response(
+        &app.get_webview_window("main").unwrap(),
+        InvokeRequest {
+          cmd: cmd.into(),
+          callback: CallbackFn(0),
+          error: CallbackFn(1),
+          body: body.into(),
+          headers: Default::default(),
+        },
+      )
+      .map(InvokeBody::into_json)
+    }
+
+    #[test]
+    fn state_methods() {
+      let app = app();
+      assert_eq!(invoke(&app, "counter_get", json!({})), Ok(json!(0)));
+      assert_eq!(
+        invoke(&app, "counter_add", json!({ "amount": 2 })),
+        Ok(json!(2))
+      );
+      assert_eq!(invoke(&app, "counter_get", json!({})), Ok(json!(2)));
+      assert_eq!(invoke(&app, "get", json!({})), Ok(json!("free function")));
+      assert_eq!(invoke(&app, "tally", json!({})), Ok(json!("public method")));
+      // the prefix is required
+      assert!(invoke(&app, "add", json!({ "amount": 2 })).is_err());
+
+      let error = invoke(&app, "unmanaged", json!({})).unwrap_err();
+      assert_eq!(error["code"], codes::STATE_NOT_MANAGED);
+    }
+
+    /// The deprecated commands of this test module, used to ignore the records of other tests.
+    const DEPRECATED_COMMANDS: [&str; 2] = ["old_get", "older_get"];
+
+    /// Records the deprecation warnings logged by the commands of this module.
+    struct DeprecationLogger(std::sync::Mutex<Vec<String>>);
+
+    impl log::Log for DeprecationLogger {
+      fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() == log::Level::Warn
+      }
+
+      fn log(&self, record: &log::Record<'_>) {
+        let message = record.args().to_string();
+        if self.enabled(record.metadata())
+          && DEPRECATED_COMMANDS
+            .iter()
+            .any(|command| message.starts_with(&format!("command `{command}` is deprecated")))
+        {
+          self.0.lock().unwrap().push(message);
+        }
+      }
+
+      fn flush(&self) {}
+    }
+
+    static LOGGER: DeprecationLogger = DeprecationLogger(std::sync::Mutex::new(Vec::new()));
+    static INIT_LOGGER: std::sync::Once = std::sync::Once::new();
+
+    #[test]
+    fn deprecated_commands() {
+      // the logger is global to the test binary, so it is installed once and filters the records
+      INIT_LOGGER.call_once(|| {
+        log::set_logger(&LOGGER).expect("failed to install the deprecation logger");
+        log::set_max_level(log::LevelFilter::Warn);
+      });
+
+      let app = app();
+      for _ in 0..2 {
+        assert_eq!(invoke(&app, "old_get", json!({})), Ok(json!("deprecated")));
+        assert_eq!(
+          invoke(&app, "older_get", json!({})),
+          Ok(json!("deprecated"))
+        );
+      }
+
+      // each command warns only the first time it is called
+      let mut warnings = LOGGER.0.lock().unwrap().clone();
+      warnings.sort();
+      assert_eq!(
+        warnings,
+        [
+          "command `old_get` is deprecated since 1.2.0: use `get` instead",
+          "command `older_get` is deprecated",
+        ]
+      );
     }
   }
 }
@@ -0,0 +1,749 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Structured binary IPC payloads: MessagePack and `multipart/form-data`.
+//!
+//! MessagePack requires the `ipc-msgpack` feature and `multipart/form-data` requires the `ipc-form-data` feature.
+
+use http::{header::CONTENT_TYPE, HeaderMap};
+#[cfg(any(feature = "ipc-msgpack", feature = "ipc-form-data"))]
+use serde::de::Error;
+#[cfg(feature = "ipc-form-data")]
+use serde::de::{value::BorrowedStrDeserializer, value::SeqDeserializer};
+#[cfg(feature = "ipc-form-data")]
+use serde::forward_to_deserialize_any;
+#[cfg(feature = "ipc-msgpack")]
+use serde::Serialize;
+use serde::{de::Visitor, Deserializer};
+use serde_json::Value as JsonValue;
+
+use super::{codes, CommandError, InvokeBody};
+#[cfg(feature = "ipc-msgpack")]
+use super::{IpcResponse, Response};
+
+/// The MessagePack content type.
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+/// The legacy MessagePack content type.
+const X_MSGPACK_CONTENT_TYPE: &str = "application/x-msgpack";
+
+/// Whether the IPC request body with this content type is decoded by [`DecodedPayload::from_request`].
+pub(crate) fn is_structured_content_type(content_type: &mime::Mime) -> bool {
+  let essence = content_type.essence_str();
+  essence == MSGPACK_CONTENT_TYPE
+    || essence == X_MSGPACK_CONTENT_TYPE
+    || essence == mime::MULTIPART_FORM_DATA.essence_str()
+}
+
+fn invalid_payload(message: impl Into<String>) -> CommandError {
+  CommandError::new(codes::INVALID_PAYLOAD, message)
+}
+
+/// A response serialized with MessagePack instead of JSON.
+///
+/// The frontend receives an `ArrayBuffer` with the encoded value.
+/// Fields serialized as bytes, e.g. with [`serde_bytes`](https://docs.rs/serde_bytes), are encoded as MessagePack binaries
+/// instead of number arrays.
+///
+/// # Examples
+///
+/// ```rust
+/// use tauri::ipc::MessagePack;
+///
+/// #[derive(serde::Serialize)]
+/// struct Thumbnail {
+///   name: String,
+///   width: u32,
+///   height: u32,
+/// }
+///
+/// #[tauri::command]
+/// fn thumbnail(name: String) -> MessagePack<Thumbnail> {
+///   MessagePack(Thumbnail { name, width: 64, height: 64 })
+/// }
+/// ```
+#[cfg(feature = "ipc-msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ipc-msgpack")))]
+#[derive(Debug, Clone)]
+pub struct MessagePack<T>(pub T);
+
+#[cfg(feature = "ipc-msgpack")]
+impl<T: Serialize> IpcResponse for MessagePack<T> {
+  fn body(self) -> crate::Result<InvokeBody> {
+    // structs are encoded as maps so the frontend gets objects
+    rmp_serde::to_vec_named(&self.0)
+      .map(InvokeBody::Raw)
+      .map_err(Into::into)
+  }
+
+  fn into_response(self) -> crate::Result<Response> {
+    Response::new(self.body()?).with_header(CONTENT_TYPE, MSGPACK_CONTENT_TYPE)
+  }
+}
+
+/// A `multipart/form-data` field value.
+#[cfg(feature = "ipc-form-data")]
+#[derive(Debug, Clone)]
+pub(crate) enum FormValue {
+  /// A plain text field.
+  Text(String),
+  /// A field with the `application/json` content type.
+  Json(JsonValue),
+  /// A file or a field with any other content type.
+  Bytes(Vec<u8>),
+}
+
+/// A `multipart/form-data` field.
+#[cfg(feature = "ipc-form-data")]
+#[derive(Debug, Clone)]
+pub(crate) struct FormField {
+  name: String,
+  value: FormValue,
+}
+
+/// An IPC request body decoded from a structured binary encoding.
+#[derive(Debug, Clone)]
+pub(crate) enum DecodedPayload {
+  /// A MessagePack value, the command arguments are the entries of a map.
+  #[cfg(feature = "ipc-msgpack")]
+  MessagePack(rmpv::Value),
+  /// The fields of a `multipart/form-data` body, the command arguments are the fields with the same name.
+  #[cfg(feature = "ipc-form-data")]
+  FormData(Vec<FormField>),
+}
+
+/// The value of a command argument, deserialized with the deserializer of its encoding.
+pub(crate) enum ArgValue<'a> {
+  Json(&'a JsonValue),
+  #[cfg(feature = "ipc-msgpack")]
+  MessagePack(&'a rmpv::Value),
+  #[cfg(feature = "ipc-form-data")]
+  Text(&'a str),
+  #[cfg(feature = "ipc-form-data")]
+  Bytes(&'a [u8]),
+}
+
+impl DecodedPayload {
+  /// Decodes a raw IPC request body based on its `Content-Type` header.
+  ///
+  /// Returns `None` if the body does not use a structured encoding.
+  #[cfg_attr(
+    not(any(feature = "ipc-msgpack", feature = "ipc-form-data")),
+    allow(unused_variables)
+  )]
+  pub(crate) fn from_request(
+    headers: &HeaderMap,
+    body: &InvokeBody,
+  ) -> Result<Option<Self>, CommandError> {
+    let InvokeBody::Raw(body) = body else {
+      return Ok(None);
+    };
+    let Some(content_type) = headers
+      .get(CONTENT_TYPE)
+      .and_then(|h| h.to_str().ok())
+      .and_then(|h| h.parse::<mime::Mime>().ok())
+    else {
+      return Ok(None);
+    };
+
+    match content_type.essence_str() {
+      MSGPACK_CONTENT_TYPE | X_MSGPACK_CONTENT_TYPE => {
+        #[cfg(feature = "ipc-msgpack")]
+        {
+          rmpv::decode::read_value(&mut body.as_slice())
+            .map(|value| Some(Self::MessagePack(value)))
+            .map_err(|e| invalid_payload(format!("invalid MessagePack body: {e}")))
+        }
+        #[cfg(not(feature = "ipc-msgpack"))]
+        Err(invalid_payload(
+          "MessagePack request bodies require the `ipc-msgpack` feature of tauri",
+        ))
+      }
+      essence if essence == mime::MULTIPART_FORM_DATA.essence_str() => {
+        #[cfg(feature = "ipc-form-data")]
+        {
+          let boundary = content_type
+            .get_param(mime::BOUNDARY)
+            .ok_or_else(|| invalid_payload("missing multipart boundary"))?;
+          parse_form_data(boundary.as_str(), body)
+            .map(|fields| Some(Self::FormData(fields)))
+            .map_err(invalid_payload)
+        }
+        #[cfg(not(feature = "ipc-form-data"))]
+        Err(invalid_payload(
+          "multipart/form-data request bodies require the `ipc-form-data` feature of tauri",
+        ))
+      }
+      _ => Ok(None),
+    }
+  }
+
+  /// The value of the command argument with the given name.
+  #[cfg_attr(
+    not(any(feature = "ipc-msgpack", feature = "ipc-form-data")),
+    allow(unused_variables)
+  )]
+  pub(crate) fn get(&self, key: &str) -> Option<ArgValue<'_>> {
+    match *self {
+      #[cfg(feature = "ipc-msgpack")]
+      Self::MessagePack(rmpv::Value::Map(ref entries)) => entries
+        .iter()
+        .find(|(k, _)| k.as_str() == Some(key))
+        .map(|(_, value)| ArgValue::MessagePack(value)),
+      #[cfg(feature = "ipc-msgpack")]
+      Self::MessagePack(_) => None,
+      #[cfg(feature = "ipc-form-data")]
+      Self::FormData(ref fields) => {
+        fields
+          .iter()
+          .find(|field| field.name == key)
+          .map(|field| match &field.value {
+            FormValue::Text(text) => ArgValue::Text(text),
+            FormValue::Json(value) => ArgValue::Json(value),
+            FormValue::Bytes(bytes) => ArgValue::Bytes(bytes),
+          })
+      }
+    }
+  }
+
+  /// The JSON representation of the arguments, used to check the permission argument constraints.
+  ///
+  /// Binary values are represented as `null`, so they never satisfy a constraint.
+  pub(crate) fn to_json(&self) -> JsonValue {
+    match *self {
+      #[cfg(feature = "ipc-msgpack")]
+      Self::MessagePack(ref value) => msgpack_to_json(value),
+      // reversed so the first field with a name is kept, like in `get`
+      #[cfg(feature = "ipc-form-data")]
+      Self::FormData(ref fields) => JsonValue::Object(
+        fields
+          .iter()
+          .rev()
+          .map(|field| {
+            let value = match &field.value {
+              FormValue::Text(text) => text.clone().into(),
+              FormValue::Json(value) => value.clone(),
+              FormValue::Bytes(_) => JsonValue::Null,
+            };
+            (field.name.clone(), value)
+          })
+          .collect(),
+      ),
+    }
+  }
+}
+
+#[cfg(feature = "ipc-msgpack")]
+fn msgpack_to_json(value: &rmpv::Value) -> JsonValue {
+  match value {
+    rmpv::Value::Boolean(b) => (*b).into(),
+    rmpv::Value::Integer(i) => i
+      .as_i64()
+      .map(Into::into)
+      .or_else(|| i.as_u64().map(Into::into))
+      .unwrap_or_default(),
+    rmpv::Value::F32(f) => (*f).into(),
+    rmpv::Value::F64(f) => (*f).into(),
+    rmpv::Value::String(s) => s.as_str().map(Into::into).unwrap_or_default(),
+    rmpv::Value::Array(values) => values.iter().map(msgpack_to_json).collect(),
+    // reversed so the first entry with a key is kept, like in `DecodedPayload::get`
+    rmpv::Value::Map(entries) => JsonValue::Object(
+      entries
+        .iter()
+        .rev()
+        .filter_map(|(k, v)| Some((k.as_str()?.to_string(), msgpack_to_json(v))))
+        .collect(),
+    ),
+    rmpv::Value::Nil | rmpv::Value::Binary(_) | rmpv::Value::Ext(..) => JsonValue::Null,
+  }
+}
+
+/// Parses a `multipart/form-data` body as defined in [RFC 7578](https://www.rfc-editor.org/rfc/rfc7578).
+#[cfg(feature = "ipc-form-data")]
+fn parse_form_data(boundary: &str, body: &[u8]) -> Result<Vec<FormField>, String> {
+  use futures_util::FutureExt;
+
+  // the whole body is in memory, so the parser futures complete without waiting
+  const INCOMPLETE: &str = "incomplete multipart body";
+
+  let body = bytes::Bytes::copy_from_slice(body);
+  let mut multipart = multer::Multipart::new(
+    futures_util::stream::iter([Ok::<_, std::convert::Infallible>(body)]),
+    boundary,
+  );
+  let mut fields = Vec::new();
+  loop {
+    let field = multipart
+      .next_field()
+      .now_or_never()
+      .ok_or(INCOMPLETE)?
+      .map_err(|e| e.to_string())?;
+    let Some(field) = field else {
+      return Ok(fields);
+    };
+
+    let name = field
+      .name()
+      .ok_or("multipart field without a name")?
+      .to_string();
+    let is_file = field.file_name().is_some();
+    let content_type = field.content_type().cloned();
+    let data = field
+      .bytes()
+      .now_or_never()
+      .ok_or(INCOMPLETE)?
+      .map_err(|e| e.to_string())?;
+
+    let value = match (content_type.as_ref().map(|m| m.essence_str()), is_file) {
+      (Some("application/json"), _) => FormValue::Json(
+        serde_json::from_slice(&data).map_err(|e| format!("invalid JSON field {name}: {e}"))?,
+      ),
+      (None | Some("text/plain"), false) => FormValue::Text(
+        String::from_utf8(data.to_vec()).map_err(|_| format!("invalid text field {name}"))?,
+      ),
+      _ => FormValue::Bytes(data.to_vec()),
+    };
+    fields.push(FormField { name, value });
+  }
+}
+
+/// Forwards a [`Deserializer`] method to the deserializer of the argument encoding.
+macro_rules! forward {
+  ($($fn:ident($($arg:ident: $argt:ty),*)),* $(,)?) => {
+    $(
+      fn $fn<V: Visitor<'de>>(self, $($arg: $argt,)* visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+          Self::Json(value) => value.$fn($($arg,)* visitor),
+          #[cfg(feature = "ipc-msgpack")]
+          Self::MessagePack(value) => value
+            .clone()
+            .$fn($($arg,)* visitor)
+            .map_err(serde_json::Error::custom),
+          #[cfg(feature = "ipc-form-data")]
+          Self::Text(text) => TextDeserializer(text).$fn($($arg,)* visitor),
+          #[cfg(feature = "ipc-form-data")]
+          Self::Bytes(bytes) => BytesDeserializer(bytes).$fn($($arg,)* visitor),
+        }
+      }
+    )*
+  };
+}
+
+impl<'de> Deserializer<'de> for ArgValue<'de> {
+  type Error = serde_json::Error;
+
+  forward! {
+    deserialize_any(),
+    deserialize_bool(),
+    deserialize_i8(),
+    deserialize_i16(),
+    deserialize_i32(),
+    deserialize_i64(),
+    deserialize_u8(),
+    deserialize_u16(),
+    deserialize_u32(),
+    deserialize_u64(),
+    deserialize_f32(),
+    deserialize_f64(),
+    deserialize_char(),
+    deserialize_str(),
+    deserialize_string(),
+    deserialize_bytes(),
+    deserialize_byte_buf(),
+    deserialize_option(),
+    deserialize_unit(),
+    deserialize_unit_struct(name: &'static str),
+    deserialize_newtype_struct(name: &'static str),
+    deserialize_seq(),
+    deserialize_tuple(len: usize),
+    deserialize_tuple_struct(name: &'static str, len: usize),
+    deserialize_map(),
+    deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+    deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+    deserialize_identifier(),
+    deserialize_ignored_any(),
+  }
+}
+
+/// Deserializes a text field, parsing it for booleans, numbers and chars.
+#[cfg(feature = "ipc-form-data")]
+pub(crate) struct TextDeserializer<'a>(pub(crate) &'a str);
+
+#[cfg(feature = "ipc-form-data")]
+macro_rules! parse_text {
+  ($($fn:ident => $visit:ident),* $(,)?) => {
+    $(
+      fn $fn<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.$visit(self.0.trim().parse().map_err(serde_json::Error::custom)?)
+      }
+    )*
+  };
+}
+
+#[cfg(feature = "ipc-form-data")]
+impl<'de> Deserializer<'de> for TextDeserializer<'de> {
+  type Error = serde_json::Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    visitor.visit_borrowed_str(self.0)
+  }
+
+  parse_text! {
+    deserialize_bool => visit_bool,
+    deserialize_i8 => visit_i8,
+    deserialize_i16 => visit_i16,
+    deserialize_i32 => visit_i32,
+    deserialize_i64 => visit_i64,
+    deserialize_i128 => visit_i128,
+    deserialize_u8 => visit_u8,
+    deserialize_u16 => visit_u16,
+    deserialize_u32 => visit_u32,
+    deserialize_u64 => visit_u64,
+    deserialize_u128 => visit_u128,
+    deserialize_f32 => visit_f32,
+    deserialize_f64 => visit_f64,
+  }
+
+  fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    visitor.visit_char(self.0.parse().map_err(serde_json::Error::custom)?)
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    name: &'static str,
+    variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Self::Error> {
+    BorrowedStrDeserializer::new(self.0).deserialize_enum(name, variants, visitor)
+  }
+
+  forward_to_deserialize_any! {
+    str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+    identifier ignored_any
+  }
+}
+
+/// Deserializes a binary value as a byte sequence, or as bytes for types that support them.
+#[cfg(feature = "ipc-form-data")]
+pub(crate) struct BytesDeserializer<'a>(pub(crate) &'a [u8]);
+
+#[cfg(feature = "ipc-form-data")]
+impl<'de, 'a> Deserializer<'de> for BytesDeserializer<'a> {
+  type Error = serde_json::Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    SeqDeserializer::new(self.0.iter().copied()).deserialize_any(visitor)
+  }
+
+  fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    visitor.visit_bytes(self.0)
+  }
+
+  fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    visitor.visit_bytes(self.0)
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Self::Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+    unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(feature = "ipc-form-data")]
+  fn form_data_headers(boundary: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      CONTENT_TYPE,
+      format!("multipart/form-data; boundary={boundary}")
+        .parse()
+        .unwrap(),
+    );
+    headers
+  }
+
+  #[cfg(feature = "ipc-form-data")]
+  #[test]
+  fn parse_form_data_fields() {
+    let body = b"--XyZ\r\n\
+      Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+      hello world\r\n--XyZ\r\n\
+      Content-Disposition: form-data; name=\"meta\"; filename=\"a;b.json\"\r\n\
+      Content-Type: application/json\r\n\r\n\
+      {\"tags\":[\"a\"]}\r\n--XyZ\r\n\
+      Content-Disposition: form-data; name=\"file\"; filename=\"a.bin\"\r\n\
+      Content-Type: application/octet-stream\r\n\r\n\
+      \x00\x01\r\n--XyZ--\r\n";
+    let payload = DecodedPayload::from_request(&form_data_headers("XyZ"), &body.to_vec().into())
+      .unwrap()
+      .unwrap();
+
+    assert!(matches!(
+      payload.get("title"),
+      Some(ArgValue::Text("hello world"))
+    ));
+    assert!(
+      matches!(payload.get("meta"), Some(ArgValue::Json(value)) if *value == serde_json::json!({ "tags": ["a"] }))
+    );
+    assert!(matches!(payload.get("file"), Some(ArgValue::Bytes([0, 1]))));
+    assert!(payload.get("missing").is_none());
+    assert_eq!(
+      payload.to_json(),
+      serde_json::json!({ "title": "hello world", "meta": { "tags": ["a"] }, "file": null })
+    );
+  }
+
+  #[cfg(feature = "ipc-form-data")]
+  #[test]
+  fn invalid_form_data() {
+    let headers = form_data_headers("XyZ");
+    let unterminated = b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1".to_vec();
+    assert!(DecodedPayload::from_request(&headers, &unterminated.into()).is_err());
+    let unnamed = b"--XyZ\r\nContent-Disposition: form-data\r\n\r\n1\r\n--XyZ--".to_vec();
+    assert!(DecodedPayload::from_request(&headers, &unnamed.into()).is_err());
+  }
+
+  #[cfg(feature = "ipc-msgpack")]
+  #[test]
+  fn decode_msgpack() {
+    let value = rmpv::Value::Map(vec![
+      ("name".into(), "tauri".into()),
+      ("data".into(), rmpv::Value::Binary(vec![1, 2])),
+    ]);
+    let mut body = Vec::new();
+    rmpv::encode::write_value(&mut body, &value).unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, MSGPACK_CONTENT_TYPE.parse().unwrap());
+    let payload = DecodedPayload::from_request(&headers, &body.into())
+      .unwrap()
+      .unwrap();
+
+    assert!(
+      matches!(payload.get("data"), Some(ArgValue::MessagePack(rmpv::Value::Binary(b))) if b == &[1, 2])
+    );
+    assert_eq!(
+      payload.to_json(),
+      serde_json::json!({ "name": "tauri", "data": null })
+    );
+
+    // other bodies are left as is
+    assert!(
+      DecodedPayload::from_request(&HeaderMap::new(), &vec![1].into())
+        .unwrap()
+        .is_none()
+    );
+  }
+
+  #[cfg(feature = "ipc-form-data")]
+  #[test]
+  fn deserialize_bytes() {
+    use serde::Deserialize;
+
+    let bytes: Vec<u8> = Deserialize::deserialize(BytesDeserializer(&[1, 2])).unwrap();
+    assert_eq!(bytes, vec![1, 2]);
+    let bytes: Option<Vec<u8>> = Deserialize::deserialize(BytesDeserializer(&[3])).unwrap();
+    assert_eq!(bytes, Some(vec![3]));
+    assert!(String::deserialize(BytesDeserializer(&[1])).is_err());
+  }
+
+  #[cfg(feature = "ipc-form-data")]
+  #[test]
+  fn deserialize_text() {
+    use serde::Deserialize;
+
+    assert_eq!(u32::deserialize(TextDeserializer("42")).unwrap(), 42);
+    assert!(bool::deserialize(TextDeserializer("true")).unwrap());
+    assert_eq!(
+      Option::<f64>::deserialize(TextDeserializer("1.5")).unwrap(),
+      Some(1.5)
+    );
+    assert_eq!(String::deserialize(TextDeserializer("42")).unwrap(), "42");
+    assert!(u32::deserialize(TextDeserializer("-1")).is_err());
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+      Fast,
+    }
+    assert_eq!(
+      Mode::deserialize(TextDeserializer("fast")).unwrap(),
+      Mode::Fast
+    );
+  }
+
+  #[cfg(feature = "ipc-msgpack")]
+  #[test]
+  fn serialize_msgpack_response() {
+    #[derive(Serialize)]
+    struct Response {
+      name: &'static str,
+    }
+    let InvokeBody::Raw(bytes) = MessagePack(Response { name: "tauri" }).body().unwrap() else {
+      panic!("expected a raw body");
+    };
+    assert_eq!(
+      rmpv::decode::read_value(&mut bytes.as_slice()).unwrap(),
+      rmpv::Value::Map(vec![("name".into(), "tauri".into())])
+    );
+  }
+
+  #[cfg(not(feature = "ipc-msgpack"))]
+  #[test]
+  fn msgpack_requires_feature() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, MSGPACK_CONTENT_TYPE.parse().unwrap());
+    let error = DecodedPayload::from_request(&headers, &vec![0x80].into()).unwrap_err();
+    assert_eq!(error.code, codes::INVALID_PAYLOAD);
+  }
+
+  #[cfg(all(feature = "ipc-msgpack", feature = "ipc-form-data"))]
+  mod command {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+      command,
+      ipc::CallbackFn,
+      test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime},
+      webview::InvokeRequest,
+      App, Manager, WebviewUrl, WebviewWindowBuilder,
+    };
+
+    #[derive(Debug, serde::Deserialize, Serialize, PartialEq)]
+    struct Meta {
+      tags: Vec<String>,
+    }
+
+    #[command(root = "crate")]
+    fn upload(
+      name: String,
+      size: u32,
+      meta: Meta,
+      image: Vec<u8>,
+      thumbnail: Option<Vec<u8>>,
+    ) -> JsonValue {
+      json!({
+        "name": name,
+        "size": size,
+        "tags": meta.tags,
+        "image": image.len(),
+        "thumbnail": thumbnail.map(|t| t.len()),
+      })
+    }
+
+    #[command(root = "crate")]
+    fn echo(meta: Meta, count: u32) -> MessagePack<(Meta, u32)> {
+      MessagePack((meta, count))
+    }
+
+    fn app() -> App<MockRuntime> {
+      let app = mock_builder()
+        .invoke_handler(crate::generate_handler![upload, echo])
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      app
+    }
+
+    fn request(cmd: &str, content_type: &str, body: Vec<u8>) -> InvokeRequest {
+      let mut headers = HeaderMap::new();
+      headers.insert(CONTENT_TYPE, content_type.parse().unwrap());
+      InvokeRequest {
+        cmd: cmd.into(),
+        callback: CallbackFn(0),
+        error: CallbackFn(1),
+        body: body.into(),
+        headers,
+      }
+    }
+
+    #[test]
+    fn form_data_arguments() {
+      let app = app();
+      let webview = app.get_webview_window("main").unwrap();
+      let mut body = b"--b\r\n\
+        Content-Disposition: form-data; name=\"name\"\r\n\r\n\
+        avatar.png\r\n--b\r\n\
+        Content-Disposition: form-data; name=\"size\"\r\n\r\n\
+        2048\r\n--b\r\n\
+        Content-Disposition: form-data; name=\"meta\"; filename=\"blob\"\r\n\
+        Content-Type: application/json\r\n\r\n\
+        {\"tags\":[\"profile\"]}\r\n--b\r\n\
+        Content-Disposition: form-data; name=\"image\"; filename=\"blob\"\r\n\
+        Content-Type: application/octet-stream\r\n\r\n"
+        .to_vec();
+      body.extend(vec![7; 2048]);
+      body.extend(b"\r\n--b--\r\n");
+
+      let response = get_ipc_response(
+        &webview,
+        request("upload", "multipart/form-data; boundary=b", body),
+      )
+      .unwrap()
+      .deserialize::<JsonValue>()
+      .unwrap();
+      assert_eq!(
+        response,
+        json!({ "name": "avatar.png", "size": 2048, "tags": ["profile"], "image": 2048, "thumbnail": null })
+      );
+
+      let error = get_ipc_response(
+        &webview,
+        request(
+          "upload",
+          "multipart/form-data; boundary=b",
+          b"--b\r\n".to_vec(),
+        ),
+      )
+      .unwrap_err();
+      assert_eq!(error["code"], codes::INVALID_PAYLOAD);
+    }
+
+    #[test]
+    fn msgpack_arguments_and_response() {
+      let app = app();
+      let webview = app.get_webview_window("main").unwrap();
+      let body =
+        rmp_serde::to_vec_named(&json!({ "meta": { "tags": ["a", "b"] }, "count": 2 })).unwrap();
+
+      let response =
+        get_ipc_response(&webview, request("echo", MSGPACK_CONTENT_TYPE, body)).unwrap();
+      let InvokeBody::Raw(bytes) = response else {
+        panic!("expected a MessagePack response");
+      };
+      let (meta, count): (Meta, u32) = rmp_serde::from_slice(&bytes).unwrap();
+      assert_eq!(meta.tags, vec!["a", "b"]);
+      assert_eq!(count, 2);
+    }
+  }
+}
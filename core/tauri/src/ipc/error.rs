@@ -0,0 +1,206 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Structured command errors with stable codes.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// The prefix of the error codes reserved for errors produced by Tauri.
+pub const RESERVED_CODE_PREFIX: &str = "TAURI_";
+
+/// The codes of the errors produced by Tauri.
+pub mod codes {
+  /// The command is not allowed by the Access Control List.
+  pub const ACL_DENIED: &str = "TAURI_ACL_DENIED";
+  /// The command arguments do not satisfy the constraints of the Access Control List.
+  pub const ARGS_DENIED: &str = "TAURI_ARGS_DENIED";
+  /// The webview exceeded the limits of the command, see [`RateLimitedError`](crate::ipc::limits::RateLimitedError).
+  pub const RATE_LIMITED: &str = "TAURI_RATE_LIMITED";
+  /// The command or its plugin does not exist.
+  pub const COMMAND_NOT_FOUND: &str = "TAURI_COMMAND_NOT_FOUND";
+  /// A command argument could not be deserialized.
+  pub const INVALID_ARGS: &str = "TAURI_INVALID_ARGS";
+  /// The request body could not be decoded with its content type, e.g. MessagePack or `multipart/form-data`.
+  pub const INVALID_PAYLOAD: &str = "TAURI_INVALID_PAYLOAD";
+  /// A command argument breaks its validation rules, see [`validation`](crate::ipc::validation).
+  pub const VALIDATION_FAILED: &str = "TAURI_VALIDATION_FAILED";
+  /// An async command took longer than its timeout, see [`TimedOutError`](crate::ipc::timeout::TimedOutError).
+  pub const TIMED_OUT: &str = "TAURI_TIMED_OUT";
+  /// A command returning a stream was called without a stream channel, see `invokeStream`.
+  pub const STREAM_CHANNEL_MISSING: &str = "TAURI_STREAM_CHANNEL_MISSING";
+  /// The batch of calls is malformed, see `invokeBatch`.
+  pub const INVALID_BATCH: &str = "TAURI_INVALID_BATCH";
+  /// The call was cancelled, see [`CancelledError`](crate::ipc::CancelledError).
+  pub const CANCELLED: &str = "TAURI_CANCELLED";
+  /// The request does not include the IPC token of the page.
+  pub const IPC_TOKEN_MISSING: &str = "TAURI_IPC_TOKEN_MISSING";
+  /// The IPC token does not match the current page load of the webview.
+  pub const IPC_TOKEN_INVALID: &str = "TAURI_IPC_TOKEN_INVALID";
+  /// A command uses a [`State`](crate::State) that was not managed.
+  pub const STATE_NOT_MANAGED: &str = "TAURI_STATE_NOT_MANAGED";
+  /// Any other [`Error`](crate::Error) returned by a command.
+  pub const INTERNAL: &str = "TAURI_INTERNAL";
+}
+
+/// The error envelope sent to the frontend, with a stable code the frontend can match on.
+///
+/// Any [`IpcError`] can be converted to this envelope,
+/// and the errors produced by Tauri use the codes in [`codes`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tauri::ipc::CommandError;
+///
+/// #[tauri::command]
+/// fn rename(name: String) -> Result<(), CommandError> {
+///   if name.is_empty() {
+///     return Err(
+///       CommandError::new("EMPTY_NAME", "the name cannot be empty")
+///         .with_data(serde_json::json!({ "field": "name" })),
+///     );
+///   }
+///   Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("{message}")]
+pub struct CommandError {
+  /// The stable error code.
+  pub code: String,
+  /// The human readable error message.
+  pub message: String,
+  /// Additional data about the error.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub data: Option<JsonValue>,
+}
+
+impl CommandError {
+  /// Creates an error with the given code and message.
+  pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+    Self {
+      code: code.into(),
+      message: message.into(),
+      data: None,
+    }
+  }
+
+  /// Sets the additional data of the error, ignoring it if it cannot be serialized.
+  pub fn with_data<T: Serialize>(mut self, data: T) -> Self {
+    self.data = serde_json::to_value(data).ok();
+    self
+  }
+}
+
+impl<E: IpcError> From<E> for CommandError {
+  fn from(error: E) -> Self {
+    Self {
+      code: error.code().into(),
+      message: error.to_string(),
+      data: error.data(),
+    }
+  }
+}
+
+/// An error with a stable code, sent to the frontend as a [`CommandError`].
+///
+/// This is usually implemented with the [`IpcError`](macro@crate::ipc::IpcError) derive macro,
+/// which also implements the conversion to [`InvokeError`](crate::ipc::InvokeError) so the error can be returned by commands.
+pub trait IpcError: std::fmt::Display {
+  /// The stable code of the error.
+  fn code(&self) -> &str;
+
+  /// Additional data about the error.
+  fn data(&self) -> Option<JsonValue> {
+    None
+  }
+}
+
+impl IpcError for crate::Error {
+  fn code(&self) -> &str {
+    match self {
+      Self::InvalidArgs(..) => codes::INVALID_ARGS,
+      _ => codes::INTERNAL,
+    }
+  }
+
+  fn data(&self) -> Option<JsonValue> {
+    match self {
+      Self::InvalidArgs(command, argument, _) => {
+        Some(serde_json::json!({ "command": command, "argument": argument }))
+      }
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+  use crate::ipc::{InvokeError, IpcError};
+
+  #[derive(Debug, IpcError, thiserror::Error)]
+  #[ipc_error(root = "crate")]
+  enum FileError {
+    #[error("file {0} not found")]
+    NotFound(String),
+    #[error("file {path} is too large")]
+    #[ipc_error(code = "FILE_TOO_LARGE")]
+    TooLarge {
+      path: String,
+      #[ipc_error(data)]
+      limit: u64,
+    },
+    #[error("permission denied")]
+    PermissionDenied,
+  }
+
+  #[test]
+  fn derive_codes() {
+    let error: CommandError = FileError::NotFound("a.txt".into()).into();
+    assert_eq!(
+      error,
+      CommandError::new("NOT_FOUND", "file a.txt not found")
+    );
+
+    let error: CommandError = FileError::TooLarge {
+      path: "a.txt".into(),
+      limit: 10,
+    }
+    .into();
+    assert_eq!(error.code, "FILE_TOO_LARGE");
+    assert_eq!(error.data, Some(json!(10)));
+
+    let error: InvokeError = FileError::PermissionDenied.into();
+    assert_eq!(
+      error.0,
+      json!({ "code": "PERMISSION_DENIED", "message": "permission denied" })
+    );
+  }
+
+  #[test]
+  fn framework_errors() {
+    let error = crate::Error::InvalidArgs(
+      "greet",
+      "name",
+      serde_json::from_str::<String>("1").unwrap_err(),
+    );
+    let error: InvokeError = error.into();
+    assert_eq!(error.0["code"], codes::INVALID_ARGS);
+    assert_eq!(
+      error.0["data"],
+      json!({ "command": "greet", "argument": "name" })
+    );
+
+    let error: InvokeError = crate::Error::NoParent.into();
+    assert_eq!(
+      error.0,
+      json!({ "code": codes::INTERNAL, "message": "path does not have a parent" })
+    );
+  }
+}
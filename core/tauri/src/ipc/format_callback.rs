@@ -6,7 +6,7 @@ use serde::Serialize;
 use serde_json::value::RawValue;
 use serialize_to_javascript::Serialized;
 
-use super::CallbackFn;
+use super::{CallbackFn, ResponseParts};
 
 /// The information about this is quite limited. On Chrome/Edge and Firefox, [the maximum string size is approximately 1 GB](https://stackoverflow.com/a/34958490).
 ///
@@ -84,11 +84,34 @@ fn serialize_js_with<T: Serialize, F: FnOnce(&str) -> String>(
 /// than 10 KiB with `JSON.parse('...')`.
 /// See [json-parse-benchmark](https://github.com/GoogleChromeLabs/json-parse-benchmark).
 pub fn format<T: Serialize>(function_name: CallbackFn, arg: &T) -> crate::Result<String> {
+  format_call(function_name, arg, None)
+}
+
+/// Formats a function name and response body to be evaluated as callback,
+/// passing the status and headers of the response as the second argument.
+pub(crate) fn format_response<T: Serialize>(
+  function_name: CallbackFn,
+  body: &T,
+  parts: &ResponseParts,
+) -> crate::Result<String> {
+  let parts = serde_json::to_string(parts)?;
+  format_call(function_name, body, Some(&parts))
+}
+
+fn format_call<T: Serialize>(
+  function_name: CallbackFn,
+  arg: &T,
+  extra_arg: Option<&str>,
+) -> crate::Result<String> {
   serialize_js_with(arg, Default::default(), |arg| {
+    let args = match extra_arg {
+      Some(extra_arg) => format!("{arg}, {extra_arg}"),
+      None => arg.into(),
+    };
     format!(
       r#"
     if (window["_{fn}"]) {{
-      window["_{fn}"]({arg})
+      window["_{fn}"]({args})
     }} else {{
       console.warn("[TAURI] Couldn't find callback id {fn} in window. This happens when the app is reloaded while Rust is running an asynchronous operation.")
     }}"#,
@@ -213,4 +236,14 @@ mod test {
       serde_json::Value::String(value),
     ))
   }
+
+  #[test]
+  fn format_response_parts() {
+    let parts = ResponseParts {
+      status: 206,
+      headers: [("x-next-cursor".to_string(), "c".to_string())].into(),
+    };
+    let js = format_response(CallbackFn(1), &"body", &parts).unwrap();
+    assert!(js.contains(r#"window["_1"]("body", {"status":206,"headers":{"x-next-cursor":"c"}})"#));
+  }
 }
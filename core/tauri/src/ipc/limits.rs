@@ -0,0 +1,314 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Enforcement of the command limits declared in the permissions.
+
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tauri_utils::acl::limits::CommandLimits;
+
+/// The limit that rejected an IPC call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RateLimitKind {
+  /// The webview has too many calls of this command in flight.
+  Concurrency,
+  /// The webview called this command too many times in the configured period.
+  Rate,
+}
+
+/// An IPC call rejected because the webview exceeded the limits of the command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("command {command} rate limited: {kind:?} limit exceeded")]
+pub struct RateLimitedError {
+  /// The command name.
+  pub command: String,
+  /// The limit that was exceeded.
+  pub kind: RateLimitKind,
+  /// How long to wait before the call is allowed again, in milliseconds.
+  ///
+  /// `None` for the concurrency limit, which depends on the calls in flight.
+  pub retry_after_ms: Option<u64>,
+}
+
+/// Counters of the limits of a command called by a webview.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IpcLimitCounters {
+  /// Number of calls in flight.
+  pub in_flight: u32,
+  /// Number of calls made in the current rate limit period.
+  pub calls_in_period: usize,
+  /// Total number of rejected calls.
+  pub rejected: u64,
+}
+
+#[derive(Default)]
+struct Counter {
+  in_flight: u32,
+  calls: VecDeque<Instant>,
+  period: Option<Duration>,
+  rejected: u64,
+}
+
+impl Counter {
+  fn prune(&mut self, now: Instant) {
+    if let Some(period) = self.period {
+      while self
+        .calls
+        .front()
+        .is_some_and(|call| now.duration_since(*call) >= period)
+      {
+        self.calls.pop_front();
+      }
+    }
+  }
+
+  /// Whether the counter has no call in flight nor in the current rate limit period.
+  fn is_idle(&self) -> bool {
+    self.in_flight == 0 && self.calls.is_empty()
+  }
+}
+
+/// (webview label, command name)
+type CounterKey = (String, String);
+
+/// Tracks the calls of each webview to the limited commands.
+#[derive(Default)]
+pub(crate) struct Limiter {
+  counters: Arc<Mutex<HashMap<CounterKey, Counter>>>,
+}
+
+/// Marks a call as in flight until dropped.
+pub(crate) struct InFlightGuard {
+  counters: Arc<Mutex<HashMap<CounterKey, Counter>>>,
+  key: CounterKey,
+}
+
+impl Drop for InFlightGuard {
+  fn drop(&mut self) {
+    if let Some(counter) = self.counters.lock().unwrap().get_mut(&self.key) {
+      counter.in_flight = counter.in_flight.saturating_sub(1);
+    }
+  }
+}
+
+impl Limiter {
+  /// Registers a call of `command` by `webview`, returning a guard that must be held until the call is resolved.
+  pub(crate) fn acquire(
+    &self,
+    webview: &str,
+    command: &str,
+    limits: &CommandLimits,
+  ) -> Result<InFlightGuard, RateLimitedError> {
+    let key = (webview.to_string(), command.to_string());
+    let now = Instant::now();
+
+    let mut counters = self.counters.lock().unwrap();
+    // drop the counters whose calls are all resolved and out of their rate limit period
+    counters.retain(|_, counter| {
+      counter.prune(now);
+      !counter.is_idle()
+    });
+    let counter = counters.entry(key.clone()).or_default();
+
+    if let Some(rate) = limits.rate {
+      let period = Duration::from_millis(rate.period_ms.get());
+      counter.period.replace(period);
+      counter.prune(now);
+
+      if counter.calls.len() >= rate.calls.get() as usize {
+        counter.rejected += 1;
+        let retry_after = counter
+          .calls
+          .front()
+          .map(|oldest| period.saturating_sub(now.duration_since(*oldest)))
+          .unwrap_or_default();
+        return Err(RateLimitedError {
+          command: command.into(),
+          kind: RateLimitKind::Rate,
+          retry_after_ms: Some(retry_after.as_millis() as u64),
+        });
+      }
+    }
+
+    if let Some(max_concurrent) = limits.max_concurrent {
+      if counter.in_flight >= max_concurrent.get() {
+        counter.rejected += 1;
+        return Err(RateLimitedError {
+          command: command.into(),
+          kind: RateLimitKind::Concurrency,
+          retry_after_ms: None,
+        });
+      }
+    }
+
+    if limits.rate.is_some() {
+      counter.calls.push_back(now);
+    }
+    counter.in_flight += 1;
+
+    Ok(InFlightGuard {
+      counters: self.counters.clone(),
+      key,
+    })
+  }
+
+  /// Drops the counters of a destroyed webview.
+  pub(crate) fn remove_webview(&self, webview: &str) {
+    self
+      .counters
+      .lock()
+      .unwrap()
+      .retain(|(label, _), _| label != webview);
+  }
+
+  /// The counters of `command` called by `webview`, `None` if it was never called
+  /// or if its calls are all resolved and out of the rate limit period.
+  pub(crate) fn counters(&self, webview: &str, command: &str) -> Option<IpcLimitCounters> {
+    let mut counters = self.counters.lock().unwrap();
+    let counter = counters.get_mut(&(webview.to_string(), command.to_string()))?;
+    counter.prune(Instant::now());
+    Some(IpcLimitCounters {
+      in_flight: counter.in_flight,
+      calls_in_period: counter.calls.len(),
+      rejected: counter.rejected,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::num::{NonZeroU32, NonZeroU64};
+
+  use tauri_utils::acl::limits::RateLimit;
+
+  use super::*;
+
+  #[test]
+  fn concurrency() {
+    let limiter = Limiter::default();
+    let limits = CommandLimits {
+      max_concurrent: NonZeroU32::new(2),
+      rate: None,
+    };
+
+    let first = limiter.acquire("main", "search", &limits).unwrap();
+    let _second = limiter.acquire("main", "search", &limits).unwrap();
+    let error = limiter
+      .acquire("main", "search", &limits)
+      .err()
+      .expect("expected the concurrency limit to be exceeded");
+    assert_eq!(error.kind, RateLimitKind::Concurrency);
+    assert_eq!(error.retry_after_ms, None);
+
+    // limits are tracked per webview
+    assert!(limiter.acquire("other", "search", &limits).is_ok());
+
+    drop(first);
+    assert!(limiter.acquire("main", "search", &limits).is_ok());
+
+    assert_eq!(
+      limiter.counters("main", "search"),
+      Some(IpcLimitCounters {
+        in_flight: 1,
+        calls_in_period: 0,
+        rejected: 1,
+      })
+    );
+  }
+
+  #[test]
+  fn rate() {
+    let limiter = Limiter::default();
+    let limits = CommandLimits {
+      max_concurrent: None,
+      rate: Some(RateLimit {
+        calls: NonZeroU32::new(2).unwrap(),
+        period_ms: NonZeroU64::new(60_000).unwrap(),
+      }),
+    };
+
+    // guards are dropped right away, only the rate applies
+    limiter.acquire("main", "search", &limits).unwrap();
+    limiter.acquire("main", "search", &limits).unwrap();
+    let error = limiter
+      .acquire("main", "search", &limits)
+      .err()
+      .expect("expected the rate limit to be exceeded");
+    assert_eq!(error.kind, RateLimitKind::Rate);
+    assert!(error.retry_after_ms.is_some_and(|ms| ms <= 60_000));
+
+    assert_eq!(
+      limiter.counters("main", "search"),
+      Some(IpcLimitCounters {
+        in_flight: 0,
+        calls_in_period: 2,
+        rejected: 1,
+      })
+    );
+    assert_eq!(limiter.counters("main", "other"), None);
+  }
+
+  #[test]
+  fn rate_period_expires() {
+    let limiter = Limiter::default();
+    let limits = CommandLimits {
+      max_concurrent: None,
+      rate: Some(RateLimit {
+        calls: NonZeroU32::new(1).unwrap(),
+        period_ms: NonZeroU64::new(10).unwrap(),
+      }),
+    };
+
+    limiter.acquire("main", "search", &limits).unwrap();
+    assert!(limiter.acquire("main", "search", &limits).is_err());
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(limiter.acquire("main", "search", &limits).is_ok());
+  }
+
+  #[test]
+  fn evicts_counters() {
+    let limiter = Limiter::default();
+    let concurrency = CommandLimits {
+      max_concurrent: NonZeroU32::new(1),
+      rate: None,
+    };
+    let rate = CommandLimits {
+      max_concurrent: None,
+      rate: Some(RateLimit {
+        calls: NonZeroU32::new(1).unwrap(),
+        period_ms: NonZeroU64::new(10).unwrap(),
+      }),
+    };
+
+    let guard = limiter.acquire("main", "search", &concurrency).unwrap();
+    limiter.acquire("main", "fetch", &rate).unwrap();
+    limiter.acquire("other", "search", &concurrency).unwrap();
+    assert_eq!(limiter.counters.lock().unwrap().len(), 3);
+
+    // the resolved call of `other` is evicted on the next call
+    limiter.acquire("main", "read", &rate).unwrap();
+    assert_eq!(limiter.counters.lock().unwrap().len(), 3);
+    assert_eq!(limiter.counters("other", "search"), None);
+
+    // the rate limited calls are evicted once their period expires
+    std::thread::sleep(Duration::from_millis(20));
+    limiter.acquire("other", "search", &concurrency).unwrap();
+    assert_eq!(limiter.counters.lock().unwrap().len(), 2);
+    assert_eq!(limiter.counters("main", "fetch"), None);
+    assert!(limiter.counters("main", "search").is_some());
+
+    // destroying the webview drops its counters, even with calls in flight
+    limiter.remove_webview("main");
+    assert_eq!(limiter.counters("main", "search"), None);
+    drop(guard);
+    assert_eq!(limiter.counters.lock().unwrap().len(), 1);
+  }
+}
@@ -6,10 +6,14 @@
 //!
 //! This module includes utilities to send messages to the JS layer of the webview.
 
-use std::sync::{Arc, Mutex};
-
-use futures_util::Future;
-use http::HeaderMap;
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use futures_util::{future::Either, Future};
+use http::{header::HeaderName, HeaderMap, HeaderValue, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 pub use serialize_to_javascript::Options as SerializeOptions;
@@ -18,16 +22,32 @@ use tauri_utils::acl::resolved::ResolvedCommand;
 
 use crate::{webview::Webview, Runtime, StateManager};
 
+pub mod audit;
 mod authority;
+pub(crate) mod batch;
+pub(crate) mod cancellation;
 pub(crate) mod channel;
 mod command;
+pub(crate) mod encoding;
+mod error;
 #[cfg(any(target_os = "macos", target_os = "ios", not(ipc_custom_protocol)))]
 pub(crate) mod format_callback;
+pub mod limits;
 pub(crate) mod protocol;
+pub(crate) mod stream;
+pub mod timeout;
+pub mod token;
+pub mod validation;
 
 pub use authority::{CommandScope, GlobalScope, Origin, RuntimeAuthority, ScopeObject, ScopeValue};
+pub use cancellation::{CancellationReason, CancellationToken, CancelledError};
 pub use channel::{Channel, JavaScriptChannelId};
 pub use command::{private, CommandArg, CommandItem};
+#[cfg(feature = "ipc-msgpack")]
+pub use encoding::MessagePack;
+pub use error::{codes, CommandError, IpcError, RESERVED_CODE_PREFIX};
+pub use tauri_macros::{IpcError, Validate};
+pub use validation::{Validate, ValidationError, ValidationErrors};
 
 /// A closure that is run every time Tauri receives a message it doesn't explicitly handle.
 pub type InvokeHandler<R> = dyn Fn(Invoke<R>) -> bool + Send + Sync + 'static;
@@ -125,6 +145,14 @@ impl<'a, R: Runtime> CommandArg<'a, R> for Request<'a> {
 pub trait IpcResponse {
   /// Resolve the IPC response body.
   fn body(self) -> crate::Result<InvokeBody>;
+
+  /// Resolve the IPC response, with the status and headers sent to the frontend along with the body.
+  fn into_response(self) -> crate::Result<Response>
+  where
+    Self: Sized,
+  {
+    self.body().map(Response::new)
+  }
 }
 
 impl<T: Serialize> IpcResponse for T {
@@ -135,22 +163,148 @@ impl<T: Serialize> IpcResponse for T {
   }
 }
 
-/// The IPC request.
+/// The IPC response.
+///
+/// Besides the body, a response can have a status and headers, e.g. a pagination cursor or the content type of a raw body,
+/// which are available to the frontend with `invokeWithResponse` from `@tauri-apps/api/core`.
+///
+/// # Examples
+///
+/// ```rust
+/// use tauri::ipc::Response;
+///
+/// #[tauri::command]
+/// fn read_page(cursor: Option<String>) -> tauri::Result<Response> {
+///   let (items, next_cursor) = (vec!["a", "b"], "c");
+///   Response::new(serde_json::to_value(items)?)
+///     .with_header("X-Next-Cursor", next_cursor)?
+///     .with_header("Cache-Control", "max-age=60")
+/// }
+/// ```
+#[derive(Debug, Clone)]
 pub struct Response {
   body: InvokeBody,
+  status: StatusCode,
+  headers: HeaderMap,
 }
 
 impl IpcResponse for Response {
   fn body(self) -> crate::Result<InvokeBody> {
     Ok(self.body)
   }
+
+  fn into_response(self) -> crate::Result<Response> {
+    Ok(self)
+  }
+}
+
+impl From<InvokeBody> for Response {
+  fn from(body: InvokeBody) -> Self {
+    Self::new(body)
+  }
 }
 
 impl Response {
   /// Defines a response with the given body.
   pub fn new(body: impl Into<InvokeBody>) -> Self {
-    Self { body: body.into() }
+    Self {
+      body: body.into(),
+      status: StatusCode::OK,
+      headers: HeaderMap::new(),
+    }
+  }
+
+  /// Sets the status of the response, `200 OK` by default.
+  ///
+  /// The promise is still resolved with the body, whatever the status.
+  /// Statuses that cannot have a body, e.g. `204 No Content`, replace the body with `null`.
+  #[must_use]
+  pub fn with_status(mut self, status: StatusCode) -> Self {
+    // the webview drops the body of these statuses, so the postMessage IPC must not send it either
+    if matches!(status.as_u16(), 101 | 103 | 204 | 205 | 304) {
+      self.body = InvokeBody::default();
+    }
+    self.status = status;
+    self
+  }
+
+  /// Appends a header to the response.
+  ///
+  /// The `Content-Type` header replaces the default content type of [raw](InvokeBody::Raw) bodies,
+  /// JSON bodies are always sent as `application/json`.
+  pub fn with_header<K, V>(mut self, name: K, value: V) -> crate::Result<Self>
+  where
+    HeaderName: TryFrom<K>,
+    <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+    HeaderValue: TryFrom<V>,
+    <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+  {
+    let name = HeaderName::try_from(name).map_err(Into::into)?;
+    let value = HeaderValue::try_from(value).map_err(Into::into)?;
+    self.headers.append(name, value);
+    Ok(self)
+  }
+
+  /// The response body.
+  pub fn payload(&self) -> &InvokeBody {
+    &self.body
+  }
+
+  /// The response status.
+  pub fn status(&self) -> StatusCode {
+    self.status
+  }
+
+  /// The response headers.
+  pub fn headers(&self) -> &HeaderMap {
+    &self.headers
+  }
+
+  /// Consumes the response, returning its body.
+  pub fn into_payload(self) -> InvokeBody {
+    self.body
+  }
+
+  /// Consumes the response, returning its status, headers and body.
+  pub(crate) fn into_parts(self) -> (StatusCode, HeaderMap, InvokeBody) {
+    (self.status, self.headers, self.body)
   }
+
+  /// Whether the response has a status or headers to send along with the body.
+  #[allow(dead_code)]
+  pub(crate) fn has_parts(&self) -> bool {
+    self.status != StatusCode::OK || !self.headers.is_empty()
+  }
+
+  /// The status and headers of the response, as given to the frontend.
+  pub(crate) fn parts(&self) -> ResponseParts {
+    ResponseParts {
+      status: self.status.as_u16(),
+      headers: self.headers.iter().fold(
+        HashMap::<String, String>::new(),
+        |mut headers, (name, value)| {
+          if let Ok(value) = value.to_str() {
+            // repeated headers are joined like the fetch `Headers`
+            headers
+              .entry(name.as_str().into())
+              .and_modify(|values| {
+                values.push_str(", ");
+                values.push_str(value);
+              })
+              .or_insert_with(|| value.into());
+          }
+          headers
+        },
+      ),
+    }
+  }
+}
+
+/// The status and headers of a [`Response`] given to the frontend.
+#[derive(Debug, Serialize)]
+pub(crate) struct ResponseParts {
+  pub status: u16,
+  pub headers: HashMap<String, String>,
 }
 
 /// The message and resolver given to a custom command.
@@ -196,7 +350,7 @@ impl<T: Serialize> From<T> for InvokeError {
 impl From<crate::Error> for InvokeError {
   #[inline(always)]
   fn from(error: crate::Error) -> Self {
-    Self(JsonValue::String(error.to_string()))
+    CommandError::from(error).into()
   }
 }
 
@@ -204,7 +358,7 @@ impl From<crate::Error> for InvokeError {
 #[derive(Debug)]
 pub enum InvokeResponse {
   /// Resolve the promise.
-  Ok(InvokeBody),
+  Ok(Response),
   /// Reject the promise.
   Err(InvokeError),
 }
@@ -215,8 +369,10 @@ impl Serialize for InvokeResponse {
     S: serde::Serializer,
   {
     match self {
-      Self::Ok(InvokeBody::Json(j)) => j.serialize(serializer),
-      Self::Ok(InvokeBody::Raw(b)) => b.serialize(serializer),
+      Self::Ok(response) => match response.payload() {
+        InvokeBody::Json(j) => j.serialize(serializer),
+        InvokeBody::Raw(b) => b.serialize(serializer),
+      },
       Self::Err(e) => e.0.serialize(serializer),
     }
   }
@@ -226,7 +382,7 @@ impl<T: IpcResponse, E: Into<InvokeError>> From<Result<T, E>> for InvokeResponse
   #[inline]
   fn from(result: Result<T, E>) -> Self {
     match result {
-      Ok(ok) => match ok.body() {
+      Ok(ok) => match ok.into_response() {
         Ok(value) => Self::Ok(value),
         Err(err) => Self::Err(InvokeError::from_error(err)),
       },
@@ -241,6 +397,52 @@ impl From<InvokeError> for InvokeResponse {
   }
 }
 
+/// Runs the task of a call until it resolves, the call is cancelled or it times out.
+async fn cancellable<T, F>(
+  cancellation: CancellationToken,
+  cmd: String,
+  timeout: Option<Duration>,
+  task: F,
+) -> Result<T, InvokeError>
+where
+  F: Future<Output = Result<T, InvokeError>>,
+{
+  let task = std::pin::pin!(task);
+  let cancelled = std::pin::pin!(cancellation.cancelled());
+  let run = futures_util::future::select(task, cancelled);
+  let result = match timeout {
+    Some(timeout) => match tokio::time::timeout(timeout, run).await {
+      Ok(result) => result,
+      Err(_) => {
+        let timeout_ms = timeout.as_millis() as u64;
+        #[cfg(feature = "tracing")]
+        let _span =
+          tracing::warn_span!("ipc::request::timeout", cmd = cmd, timeout_ms = timeout_ms)
+            .entered();
+        cancellation.cancel(CancellationReason::TimedOut);
+        return Err(
+          CommandError::from(timeout::TimedOutError {
+            command: cmd,
+            timeout_ms,
+          })
+          .into(),
+        );
+      }
+    },
+    None => run.await,
+  };
+  match result {
+    Either::Left((result, _)) => result,
+    Either::Right((reason, _)) => Err(
+      CommandError::from(CancelledError {
+        command: cmd,
+        reason,
+      })
+      .into(),
+    ),
+  }
+}
+
 /// Resolver of a invoke message.
 #[default_runtime(crate::Wry, wry)]
 pub struct InvokeResolver<R: Runtime> {
@@ -249,6 +451,9 @@ pub struct InvokeResolver<R: Runtime> {
   cmd: String,
   pub(crate) callback: CallbackFn,
   pub(crate) error: CallbackFn,
+  cancellation: CancellationToken,
+  timeout: Option<Duration>,
+  deadline: Option<Instant>,
 }
 
 impl<R: Runtime> Clone for InvokeResolver<R> {
@@ -259,17 +464,24 @@ impl<R: Runtime> Clone for InvokeResolver<R> {
       cmd: self.cmd.clone(),
       callback: self.callback,
       error: self.error,
+      cancellation: self.cancellation.clone(),
+      timeout: self.timeout,
+      deadline: self.deadline,
     }
   }
 }
 
 impl<R: Runtime> InvokeResolver<R> {
+  #[allow(clippy::too_many_arguments)]
   pub(crate) fn new(
     webview: Webview<R>,
     responder: Arc<Mutex<Option<Box<OwnedInvokeResponder<R>>>>>,
     cmd: String,
     callback: CallbackFn,
     error: CallbackFn,
+    cancellation: CancellationToken,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
   ) -> Self {
     Self {
       webview,
@@ -277,16 +489,33 @@ impl<R: Runtime> InvokeResolver<R> {
       cmd,
       callback,
       error,
+      cancellation,
+      timeout,
+      deadline,
     }
   }
 
+  /// Sets the timeout of the async task of the call, replacing the [default command timeout](crate::Builder::command_timeout).
+  ///
+  /// The [deadline](timeout::DEADLINE_HEADER) requested by the frontend still applies if it is shorter.
+  #[must_use]
+  pub fn with_timeout(mut self, timeout: Duration) -> Self {
+    self.timeout.replace(timeout);
+    self
+  }
+
   /// Reply to the invoke promise with an async task.
+  ///
+  /// The task is dropped and the promise rejected with a [`CancelledError`] if the call is cancelled,
+  /// or with a [`TimedOutError`](timeout::TimedOutError) if it times out.
   pub fn respond_async<T, F>(self, task: F)
   where
-    T: IpcResponse,
+    T: IpcResponse + 'static,
     F: Future<Output = Result<T, InvokeError>> + Send + 'static,
   {
     crate::async_runtime::spawn(async move {
+      let timeout = timeout::remaining(self.timeout, self.deadline);
+      let task = cancellable(self.cancellation, self.cmd.clone(), timeout, task);
       Self::return_task(
         self.webview,
         self.responder,
@@ -300,13 +529,19 @@ impl<R: Runtime> InvokeResolver<R> {
   }
 
   /// Reply to the invoke promise with an async task which is already serialized.
-  pub fn respond_async_serialized<F>(self, task: F)
+  ///
+  /// The task is dropped and the promise rejected with a [`CancelledError`] if the call is cancelled,
+  /// or with a [`TimedOutError`](timeout::TimedOutError) if it times out.
+  pub fn respond_async_serialized<B, F>(self, task: F)
   where
-    F: Future<Output = Result<InvokeBody, InvokeError>> + Send + 'static,
+    B: Into<Response>,
+    F: Future<Output = Result<B, InvokeError>> + Send + 'static,
   {
     crate::async_runtime::spawn(async move {
+      let timeout = timeout::remaining(self.timeout, self.deadline);
+      let task = cancellable(self.cancellation, self.cmd.clone(), timeout, task);
       let response = match task.await {
-        Ok(ok) => InvokeResponse::Ok(ok),
+        Ok(ok) => InvokeResponse::Ok(ok.into()),
         Err(err) => InvokeResponse::Err(err),
       };
       Self::return_result(
@@ -436,8 +671,12 @@ pub struct InvokeMessage<R: Runtime> {
   pub(crate) command: String,
   /// The JSON argument passed on the invoke message.
   pub(crate) payload: InvokeBody,
+  /// The payload decoded from MessagePack or `multipart/form-data`.
+  pub(crate) decoded_payload: Option<encoding::DecodedPayload>,
   /// The request headers.
   pub(crate) headers: HeaderMap,
+  /// The cancellation signal of the call.
+  pub(crate) cancellation: CancellationToken,
 }
 
 impl<R: Runtime> Clone for InvokeMessage<R> {
@@ -447,7 +686,9 @@ impl<R: Runtime> Clone for InvokeMessage<R> {
       state: self.state.clone(),
       command: self.command.clone(),
       payload: self.payload.clone(),
+      decoded_payload: self.decoded_payload.clone(),
       headers: self.headers.clone(),
+      cancellation: self.cancellation.clone(),
     }
   }
 }
@@ -459,14 +700,18 @@ impl<R: Runtime> InvokeMessage<R> {
     state: Arc<StateManager>,
     command: String,
     payload: InvokeBody,
+    decoded_payload: Option<encoding::DecodedPayload>,
     headers: HeaderMap,
+    cancellation: CancellationToken,
   ) -> Self {
     Self {
       webview,
       state,
       command,
       payload,
+      decoded_payload,
       headers,
+      cancellation,
     }
   }
 
@@ -511,8 +756,134 @@ impl<R: Runtime> InvokeMessage<R> {
   pub fn headers(&self) -> &HeaderMap {
     &self.headers
   }
+
+  /// The cancellation signal of the call.
+  #[inline(always)]
+  pub fn cancellation(&self) -> &CancellationToken {
+    &self.cancellation
+  }
 }
 
 /// The `Callback` type is the return value of the `transformCallback` JavaScript function.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct CallbackFn(pub u32);
+
+#[cfg(test)]
+mod tests {
+  use std::sync::mpsc::channel;
+
+  use serde_json::json;
+
+  use super::*;
+  use crate::{
+    command,
+    test::{mock_builder, mock_context, noop_assets, MockRuntime},
+    webview::InvokeRequest,
+    App, Manager, WebviewUrl, WebviewWindowBuilder,
+  };
+
+  #[test]
+  fn response_parts() {
+    let response = Response::new(json!([1, 2]));
+    assert!(!response.has_parts());
+
+    let response = response
+      .with_status(StatusCode::PARTIAL_CONTENT)
+      .with_header("X-Next-Cursor", "abc")
+      .unwrap()
+      .with_header("Cache-Control", "no-cache")
+      .unwrap()
+      .with_header("Cache-Control", "no-store")
+      .unwrap();
+    assert!(response.has_parts());
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()["x-next-cursor"], "abc");
+
+    let parts = serde_json::to_value(response.parts()).unwrap();
+    assert_eq!(
+      parts,
+      json!({
+        "status": 206,
+        "headers": { "x-next-cursor": "abc", "cache-control": "no-cache, no-store" }
+      })
+    );
+
+    assert!(matches!(
+      Response::new(json!(null)).with_header("invalid header", "value"),
+      Err(crate::Error::Http(_))
+    ));
+    let response = Response::new(json!({ "id": 1 })).with_status(StatusCode::NO_CONTENT);
+    assert!(matches!(
+      response.payload(),
+      InvokeBody::Json(JsonValue::Null)
+    ));
+  }
+
+  #[command(root = "crate")]
+  fn page() -> crate::Result<Response> {
+    Response::new(json!(["a", "b"]))
+      .with_status(StatusCode::PARTIAL_CONTENT)
+      .with_header("X-Next-Cursor", "c")
+  }
+
+  #[cfg(feature = "ipc-msgpack")]
+  #[command(root = "crate")]
+  async fn thumbnail() -> MessagePack<Vec<u8>> {
+    MessagePack(vec![1, 2, 3])
+  }
+
+  fn invoke(app: &App<MockRuntime>, cmd: &str) -> Response {
+    let (tx, rx) = channel();
+    app.get_webview_window("main").unwrap().on_message(
+      InvokeRequest {
+        cmd: cmd.into(),
+        callback: CallbackFn(1),
+        error: CallbackFn(2),
+        body: json!({}).into(),
+        headers: Default::default(),
+      },
+      Box::new(move |_webview, _cmd, response, _callback, _error| {
+        tx.send(response).unwrap();
+      }),
+    );
+    match rx.recv().unwrap() {
+      InvokeResponse::Ok(response) => response,
+      InvokeResponse::Err(e) => panic!("unexpected error {:?}", e.0),
+    }
+  }
+
+  fn app(handler: fn(Invoke<MockRuntime>) -> bool) -> App<MockRuntime> {
+    let app = mock_builder()
+      .invoke_handler(handler)
+      .build(mock_context(noop_assets()))
+      .unwrap();
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+    app
+  }
+
+  #[test]
+  fn command_response_parts() {
+    let app = app(crate::generate_handler![page]);
+
+    let response = invoke(&app, "page");
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()["x-next-cursor"], "c");
+    assert!(matches!(response.payload(), InvokeBody::Json(v) if v == &json!(["a", "b"])));
+  }
+
+  #[cfg(feature = "ipc-msgpack")]
+  #[test]
+  fn msgpack_response_parts() {
+    let app = app(crate::generate_handler![thumbnail]);
+
+    let response = invoke(&app, "thumbnail");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers()[http::header::CONTENT_TYPE],
+      "application/msgpack"
+    );
+    assert!(matches!(response.payload(), InvokeBody::Raw(_)));
+  }
+}
@@ -7,17 +7,24 @@ use std::{borrow::Cow, sync::Arc};
 use crate::{
   manager::AppManager,
   webview::{InvokeRequest, UriSchemeProtocolHandler},
-  Runtime,
+  Runtime, Webview,
 };
 use http::{
-  header::{ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE},
+  header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+    CONTENT_TYPE,
+  },
   HeaderValue, Method, StatusCode,
 };
 
-use super::{CallbackFn, InvokeBody, InvokeResponse};
+use super::{CallbackFn, CommandError, InvokeBody, InvokeResponse, OwnedInvokeResponder};
 
 const TAURI_CALLBACK_HEADER_NAME: &str = "Tauri-Callback";
 const TAURI_ERROR_HEADER_NAME: &str = "Tauri-Error";
+/// Marks the responses resolving the promise, whatever their status.
+const TAURI_RESPONSE_HEADER_NAME: &str = "Tauri-Response";
+/// The request headers the frontend is allowed to send, answered to the CORS preflight request.
+const ALLOWED_HEADERS: &str = "Content-Type, Tauri-Callback, Tauri-Error, Tauri-Channel-Id, Tauri-Ipc-Token, Tauri-Stream-Channel, Tauri-Deadline";
 
 #[cfg(any(target_os = "macos", target_os = "ios", not(ipc_custom_protocol)))]
 pub fn message_handler<R: Runtime>(
@@ -43,13 +50,17 @@ pub fn get<R: Runtime>(manager: Arc<AppManager<R>>, label: String) -> UriSchemeP
       response
         .headers_mut()
         .insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+      // the response headers set by commands are readable from the webview origin
+      response
+        .headers_mut()
+        .insert(ACCESS_CONTROL_EXPOSE_HEADERS, HeaderValue::from_static("*"));
       responder.respond(response);
     };
 
     match *request.method() {
       Method::POST => {
         if let Some(webview) = manager.get_webview(&label) {
-          match parse_invoke_request(&manager, request) {
+          match parse_invoke_request(&manager, &label, request) {
             Ok(request) => {
               #[cfg(feature = "tracing")]
               span.record(
@@ -62,7 +73,8 @@ pub fn get<R: Runtime>(manager: Arc<AppManager<R>>, label: String) -> UriSchemeP
               #[cfg(feature = "tracing")]
               let request_span = tracing::trace_span!("ipc::request::handle", cmd = request.cmd);
 
-              webview.on_message(
+              handle_invoke_request(
+                webview,
                 request,
                 Box::new(move |_webview, _cmd, response, _callback, _error| {
                   #[cfg(feature = "tracing")]
@@ -81,14 +93,24 @@ pub fn get<R: Runtime>(manager: Arc<AppManager<R>>, label: String) -> UriSchemeP
                   .entered();
 
                   let (mut response, mime_type) = match response {
-                    InvokeResponse::Ok(InvokeBody::Json(v)) => (
-                      http::Response::new(serde_json::to_vec(&v).unwrap().into()),
-                      mime::APPLICATION_JSON,
-                    ),
-                    InvokeResponse::Ok(InvokeBody::Raw(v)) => (
-                      http::Response::new(v.into()),
-                      mime::APPLICATION_OCTET_STREAM,
-                    ),
+                    InvokeResponse::Ok(response) => {
+                      let (status, mut headers, body) = response.into_parts();
+                      let (body, mime_type) = match body {
+                        InvokeBody::Json(v) => {
+                          // the frontend parses JSON bodies based on their content type
+                          headers.remove(CONTENT_TYPE);
+                          (serde_json::to_vec(&v).unwrap(), mime::APPLICATION_JSON)
+                        }
+                        InvokeBody::Raw(v) => (v, mime::APPLICATION_OCTET_STREAM),
+                      };
+                      let mut response = http::Response::new(body.into());
+                      *response.status_mut() = status;
+                      *response.headers_mut() = headers;
+                      response
+                        .headers_mut()
+                        .insert(TAURI_RESPONSE_HEADER_NAME, HeaderValue::from_static("ok"));
+                      (response, mime_type)
+                    }
                     InvokeResponse::Err(e) => {
                       let mut response =
                         http::Response::new(serde_json::to_vec(&e.0).unwrap().into());
@@ -97,12 +119,20 @@ pub fn get<R: Runtime>(manager: Arc<AppManager<R>>, label: String) -> UriSchemeP
                     }
                   };
 
-                  #[cfg(feature = "tracing")]
-                  response_span.record("mime_type", mime_type.essence_str());
+                  // raw bodies can have a custom content type
+                  if !response.headers().contains_key(CONTENT_TYPE) {
+                    response.headers_mut().insert(
+                      CONTENT_TYPE,
+                      HeaderValue::from_str(mime_type.essence_str()).unwrap(),
+                    );
+                  }
 
-                  response.headers_mut().insert(
-                    CONTENT_TYPE,
-                    HeaderValue::from_str(mime_type.essence_str()).unwrap(),
+                  #[cfg(feature = "tracing")]
+                  response_span.record(
+                    "mime_type",
+                    response.headers()[CONTENT_TYPE]
+                      .to_str()
+                      .unwrap_or_default(),
                   );
 
                   respond(response);
@@ -139,7 +169,7 @@ pub fn get<R: Runtime>(manager: Arc<AppManager<R>>, label: String) -> UriSchemeP
         let mut r = http::Response::new(Vec::new().into());
         r.headers_mut().insert(
           ACCESS_CONTROL_ALLOW_HEADERS,
-          HeaderValue::from_static("Content-Type, Tauri-Callback, Tauri-Error, Tauri-Channel-Id"),
+          HeaderValue::from_static(ALLOWED_HEADERS),
         );
         respond(r);
       }
@@ -201,12 +231,29 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
       headers: HeaderMap,
     }
 
+    fn deserialize_base64<'de, D: Deserializer<'de>>(
+      deserializer: D,
+    ) -> std::result::Result<Option<Vec<u8>>, D::Error> {
+      use base64::Engine;
+      Option::<String>::deserialize(deserializer)?
+        .map(|raw| base64::engine::general_purpose::STANDARD.decode(raw))
+        .transpose()
+        .map_err(serde::de::Error::custom)
+    }
+
     #[derive(Deserialize)]
     struct Message {
       cmd: String,
       callback: CallbackFn,
       error: CallbackFn,
       payload: serde_json::Value,
+      /// A binary payload encoded as base64, e.g. a `FormData` or MessagePack body.
+      #[serde(
+        rename = "rawPayload",
+        default,
+        deserialize_with = "deserialize_base64"
+      )]
+      raw_payload: Option<Vec<u8>>,
       options: Option<RequestOptions>,
     }
 
@@ -224,7 +271,7 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
         options: Option<RequestOptions>,
       }
 
-      if let crate::Pattern::Isolation { crypto_keys, .. } = &*manager.pattern {
+      if let Some(crypto_keys) = manager.webview.isolation_keys(label) {
         #[cfg(feature = "tracing")]
         let _span = tracing::trace_span!("ipc::request::decrypt_isolation_payload").entered();
 
@@ -237,6 +284,7 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
                 callback: message.callback,
                 error: message.error,
                 payload: serde_json::from_slice(&crypto_keys.decrypt(message.payload)?)?,
+                raw_payload: None,
                 options: message.options,
               })
             }),
@@ -254,19 +302,24 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
           cmd: message.cmd,
           callback: message.callback,
           error: message.error,
-          body: message.payload.into(),
+          body: match message.raw_payload {
+            Some(raw_payload) => raw_payload.into(),
+            None => message.payload.into(),
+          },
           headers: message.options.map(|o| o.headers.0).unwrap_or_default(),
         };
 
         #[cfg(feature = "tracing")]
         let request_span = tracing::trace_span!("ipc::request::handle", cmd = request.cmd);
 
-        webview.on_message(
+        handle_invoke_request(
+          webview,
           request,
           Box::new(move |webview, cmd, response, callback, error| {
             use crate::ipc::{
               format_callback::{
-                format as format_callback, format_result as format_callback_result,
+                format as format_callback, format_response as format_callback_response,
+                format_result as format_callback_result,
               },
               Channel,
             };
@@ -303,8 +356,10 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
                 "ipc::request::response",
                 response = serde_json::to_string(&response).unwrap(),
                 mime_type = match &response {
-                  InvokeResponse::Ok(InvokeBody::Json(_)) => mime::APPLICATION_JSON,
-                  InvokeResponse::Ok(InvokeBody::Raw(_)) => mime::APPLICATION_OCTET_STREAM,
+                  InvokeResponse::Ok(ok) => match ok.payload() {
+                    InvokeBody::Json(_) => mime::APPLICATION_JSON,
+                    InvokeBody::Raw(_) => mime::APPLICATION_OCTET_STREAM,
+                  },
                   InvokeResponse::Err(_) => mime::TEXT_PLAIN,
                 }
                 .essence_str()
@@ -312,31 +367,39 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
               .entered();
 
               match &response {
-                InvokeResponse::Ok(InvokeBody::Json(v)) => {
-                  if !(cfg!(target_os = "macos") || cfg!(target_os = "ios"))
-                    && matches!(v, JsonValue::Object(_) | JsonValue::Array(_))
-                  {
-                    let _ = Channel::from_callback_fn(webview, callback).send(v);
-                  } else {
-                    responder_eval(
-                      &webview,
-                      format_callback_result(Result::<_, ()>::Ok(v), callback, error),
-                      error,
-                    )
+                // the status and headers are given to the callback along with the body
+                InvokeResponse::Ok(ok) if ok.has_parts() => responder_eval(
+                  &webview,
+                  format_callback_response(callback, &response, &ok.parts()),
+                  error,
+                ),
+                InvokeResponse::Ok(ok) => match ok.payload() {
+                  InvokeBody::Json(v) => {
+                    if !(cfg!(target_os = "macos") || cfg!(target_os = "ios"))
+                      && matches!(v, JsonValue::Object(_) | JsonValue::Array(_))
+                    {
+                      let _ = Channel::from_callback_fn(webview, callback).send(v);
+                    } else {
+                      responder_eval(
+                        &webview,
+                        format_callback_result(Result::<_, ()>::Ok(v), callback, error),
+                        error,
+                      )
+                    }
                   }
-                }
-                InvokeResponse::Ok(InvokeBody::Raw(v)) => {
-                  if cfg!(target_os = "macos") || cfg!(target_os = "ios") {
-                    responder_eval(
-                      &webview,
-                      format_callback_result(Result::<_, ()>::Ok(v), callback, error),
-                      error,
-                    );
-                  } else {
-                    let _ =
-                      Channel::from_callback_fn(webview, callback).send(InvokeBody::Raw(v.clone()));
+                  InvokeBody::Raw(v) => {
+                    if cfg!(target_os = "macos") || cfg!(target_os = "ios") {
+                      responder_eval(
+                        &webview,
+                        format_callback_result(Result::<_, ()>::Ok(v), callback, error),
+                        error,
+                      );
+                    } else {
+                      let _ = Channel::from_callback_fn(webview, callback)
+                        .send(InvokeBody::Raw(v.clone()));
+                    }
                   }
-                }
+                },
                 InvokeResponse::Err(e) => responder_eval(
                   &webview,
                   format_callback_result(Result::<(), _>::Err(&e.0), callback, error),
@@ -360,8 +423,55 @@ fn handle_ipc_message<R: Runtime>(message: String, manager: &AppManager<R>, labe
   }
 }
 
+/// Checks the IPC token of the request before handing it to the webview.
+///
+/// Requests rejected by the token check are answered right away.
+fn handle_invoke_request<R: Runtime>(
+  webview: Webview<R>,
+  request: InvokeRequest,
+  responder: Box<OwnedInvokeResponder<R>>,
+) {
+  use crate::sealed::ManagerBase;
+
+  let manager = webview.manager_owned();
+  if !manager.config.app.security.ipc_token {
+    webview.on_message(request, responder);
+    return;
+  }
+
+  let authorization = manager
+    .webview
+    .ipc_tokens
+    .authorize(webview.label(), &request.headers);
+  let response = match authorization {
+    Ok(()) => {
+      webview.on_message(request, responder);
+      return;
+    }
+    Err(error) => InvokeResponse::Err(CommandError::from(error).into()),
+  };
+
+  if let Some(custom_responder) = &manager.webview.invoke_responder {
+    custom_responder(
+      &webview,
+      &request.cmd,
+      &response,
+      request.callback,
+      request.error,
+    );
+  }
+  responder(
+    webview,
+    request.cmd,
+    response,
+    request.callback,
+    request.error,
+  );
+}
+
 fn parse_invoke_request<R: Runtime>(
   #[allow(unused_variables)] manager: &AppManager<R>,
+  #[allow(unused_variables)] label: &str,
   request: http::Request<Vec<u8>>,
 ) -> std::result::Result<InvokeRequest, String> {
   #[allow(unused_mut)]
@@ -374,7 +484,7 @@ fn parse_invoke_request<R: Runtime>(
 
   // the body is not set if ipc_custom_protocol is not enabled so we'll just ignore it
   #[cfg(all(feature = "isolation", ipc_custom_protocol))]
-  if let crate::Pattern::Isolation { crypto_keys, .. } = &*manager.pattern {
+  if let Some(crypto_keys) = manager.webview.isolation_keys(label) {
     #[cfg(feature = "tracing")]
     let _span = tracing::trace_span!("ipc::request::decrypt_isolation_payload").entered();
 
@@ -415,7 +525,10 @@ fn parse_invoke_request<R: Runtime>(
   #[cfg(feature = "tracing")]
   let span = tracing::trace_span!("ipc::request::deserialize").entered();
 
-  let body = if content_type == mime::APPLICATION_OCTET_STREAM {
+  let body = if content_type == mime::APPLICATION_OCTET_STREAM
+    // MessagePack and multipart bodies are decoded along with the request headers by the webview
+    || crate::ipc::encoding::is_structured_content_type(&content_type)
+  {
     body.into()
   } else if content_type == mime::APPLICATION_JSON {
     if cfg!(ipc_custom_protocol) {
@@ -443,3 +556,58 @@ fn parse_invoke_request<R: Runtime>(
 
   Ok(payload)
 }
+
+#[cfg(test)]
+mod tests {
+  use std::sync::mpsc::channel;
+
+  use super::*;
+  use crate::{
+    app::UriSchemeResponder,
+    ipc::{stream::STREAM_CHANNEL_HEADER_NAME, timeout::DEADLINE_HEADER},
+    test::{mock_app, MockRuntime},
+  };
+
+  fn preflight(app: &crate::App<MockRuntime>) -> http::Response<Cow<'static, [u8]>> {
+    let handler = get(app.handle().manager.clone(), "main".into());
+    let request = http::Request::builder()
+      .method(Method::OPTIONS)
+      .uri("ipc://localhost/cmd")
+      .body(Vec::new())
+      .unwrap();
+    let (tx, rx) = channel();
+    handler(
+      request,
+      UriSchemeResponder(Box::new(move |response| tx.send(response).unwrap())),
+    );
+    rx.recv().unwrap()
+  }
+
+  #[test]
+  fn preflight_allows_ipc_headers() {
+    let app = mock_app();
+    let response = preflight(&app);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+    let allowed = response.headers()[ACCESS_CONTROL_ALLOW_HEADERS]
+      .to_str()
+      .unwrap()
+      .split(", ")
+      .collect::<Vec<_>>();
+    for header in [
+      CONTENT_TYPE.as_str(),
+      TAURI_CALLBACK_HEADER_NAME,
+      TAURI_ERROR_HEADER_NAME,
+      STREAM_CHANNEL_HEADER_NAME,
+      DEADLINE_HEADER,
+    ] {
+      assert!(
+        allowed
+          .iter()
+          .any(|allowed| allowed.eq_ignore_ascii_case(header)),
+        "{header} is not allowed"
+      );
+    }
+  }
+}
@@ -0,0 +1,415 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Streaming of async command results through a [`Channel`].
+
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tokio::sync::Notify;
+
+use super::{
+  codes, CallbackFn, Channel, CommandError, InvokeBody, InvokeError, InvokeMessage, IpcResponse,
+};
+use crate::{Manager, Runtime, Webview};
+
+/// The request header with the id of the channel that receives the items of a stream command.
+pub(crate) const STREAM_CHANNEL_HEADER_NAME: &str = "Tauri-Stream-Channel";
+/// How many items can be sent before the frontend acknowledges them.
+pub(crate) const STREAM_WINDOW: u64 = 16;
+
+/// An event of a streamed command result.
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+enum StreamEvent {
+  /// The next item of the stream.
+  Item(JsonValue),
+  /// The stream ended, this is the last message.
+  End,
+  /// The stream yielded an error, this is the last message.
+  Error(JsonValue),
+}
+
+/// A message sent through the stream channel.
+///
+/// Channel messages might be received out of order, so the frontend uses the index to reorder them.
+#[derive(Debug, Serialize)]
+struct StreamMessage {
+  index: u64,
+  #[serde(flatten)]
+  event: StreamEvent,
+}
+
+#[derive(Default)]
+struct Acknowledgement {
+  /// How many items the frontend has received.
+  received: Mutex<u64>,
+  notify: Notify,
+}
+
+/// The acknowledged items of each active stream, keyed by channel id.
+#[derive(Default, Clone)]
+pub(crate) struct StreamAcks(Arc<Mutex<HashMap<u32, Arc<Acknowledgement>>>>);
+
+impl StreamAcks {
+  fn register(&self, channel: u32) -> AcknowledgementGuard {
+    let ack = Arc::new(Acknowledgement::default());
+    self.0.lock().unwrap().insert(channel, ack.clone());
+    AcknowledgementGuard {
+      acks: self.clone(),
+      channel,
+      ack,
+    }
+  }
+
+  /// Marks all items of the stream up to the given index as received.
+  ///
+  /// Returns `false` if the stream is not active.
+  pub(crate) fn ack(&self, channel: u32, index: u64) -> bool {
+    let ack = self.0.lock().unwrap().get(&channel).cloned();
+    if let Some(ack) = &ack {
+      let mut received = ack.received.lock().unwrap();
+      *received = (*received).max(index + 1);
+      ack.notify.notify_waiters();
+    }
+    ack.is_some()
+  }
+}
+
+/// Removes the stream from [`StreamAcks`] when it finishes or its command is cancelled.
+struct AcknowledgementGuard {
+  acks: StreamAcks,
+  channel: u32,
+  ack: Arc<Acknowledgement>,
+}
+
+impl AcknowledgementGuard {
+  /// Waits until the item at the given index fits in the [`STREAM_WINDOW`].
+  async fn reserve(&self, index: u64) {
+    loop {
+      // registered before checking the counter so a concurrent ack is not missed
+      let notified = self.ack.notify.notified();
+      if index < *self.ack.received.lock().unwrap() + STREAM_WINDOW {
+        return;
+      }
+      notified.await;
+    }
+  }
+}
+
+impl Drop for AcknowledgementGuard {
+  fn drop(&mut self) {
+    self.acks.0.lock().unwrap().remove(&self.channel);
+  }
+}
+
+/// The channel that receives the items of a stream command.
+pub(crate) struct StreamTarget<R: Runtime> {
+  webview: Webview<R>,
+  channel: CallbackFn,
+}
+
+impl<R: Runtime> StreamTarget<R> {
+  /// Reads the channel from the [`STREAM_CHANNEL_HEADER_NAME`] header of the invoke.
+  pub(crate) fn from_message(message: &InvokeMessage<R>) -> Result<Self, InvokeError> {
+    message
+      .headers()
+      .get(STREAM_CHANNEL_HEADER_NAME)
+      .and_then(|v| v.to_str().ok())
+      .and_then(|id| id.parse().ok())
+      .map(|id| Self {
+        webview: message.webview(),
+        channel: CallbackFn(id),
+      })
+      .ok_or_else(|| {
+        CommandError::new(
+          codes::STREAM_CHANNEL_MISSING,
+          format!(
+            "command {} returns a stream and must be called with the `{STREAM_CHANNEL_HEADER_NAME}` header, use `invokeStream` instead of `invoke`",
+            message.command()
+          ),
+        )
+        .with_data(serde_json::json!({ "command": message.command() }))
+        .into()
+      })
+  }
+
+  /// Sends the items of the stream through the channel, waiting for the frontend to acknowledge them.
+  ///
+  /// Resolves once the terminal message is sent, or with the error the stream yielded.
+  pub(crate) async fn pump<T, E, S>(self, stream: S) -> Result<InvokeBody, InvokeError>
+  where
+    T: IpcResponse,
+    E: Into<InvokeError>,
+    S: Stream<Item = Result<T, E>>,
+  {
+    let guard = self
+      .webview
+      .state::<StreamAcks>()
+      .inner()
+      .register(self.channel.0);
+    let channel = Channel::from_callback_fn(self.webview, self.channel);
+    let send = |index, event| {
+      channel
+        .send(StreamMessage { index, event })
+        .map_err(InvokeError::from_error)
+    };
+
+    let mut stream = std::pin::pin!(stream);
+    let mut index = 0;
+    let result = loop {
+      guard.reserve(index).await;
+      let item = match stream.next().await {
+        Some(item) => item
+          .map_err(Into::into)
+          .and_then(|item| item.body().map_err(InvokeError::from_error)),
+        None => break Ok(()),
+      };
+      match item {
+        Ok(body) => send(index, StreamEvent::Item(body_value(body)))?,
+        Err(e) => break Err(e),
+      }
+      index += 1;
+    };
+
+    match result {
+      Ok(()) => {
+        send(index, StreamEvent::End)?;
+        Ok(InvokeBody::default())
+      }
+      Err(e) => {
+        send(index, StreamEvent::Error(e.0.clone()))?;
+        Err(e)
+      }
+    }
+  }
+}
+
+fn body_value(body: InvokeBody) -> JsonValue {
+  match body {
+    InvokeBody::Json(value) => value,
+    InvokeBody::Raw(bytes) => bytes.into(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn serialize_messages() {
+    let message = StreamMessage {
+      index: 3,
+      event: StreamEvent::Item(1.into()),
+    };
+    assert_eq!(
+      serde_json::to_value(message).unwrap(),
+      serde_json::json!({ "index": 3, "event": "item", "data": 1 })
+    );
+    let message = StreamMessage {
+      index: 4,
+      event: StreamEvent::End,
+    };
+    assert_eq!(
+      serde_json::to_value(message).unwrap(),
+      serde_json::json!({ "index": 4, "event": "end" })
+    );
+  }
+
+  #[test]
+  fn window_waits_for_acks() {
+    let acks = StreamAcks::default();
+    let guard = acks.register(1);
+    let reserved = crate::async_runtime::spawn(async move {
+      guard.reserve(STREAM_WINDOW).await;
+      guard
+    });
+
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    assert!(!reserved.inner().is_finished());
+
+    assert!(acks.ack(1, 0));
+    let guard = crate::async_runtime::block_on(reserved).unwrap();
+    drop(guard);
+    assert!(!acks.ack(1, 1));
+  }
+
+  mod command {
+    use std::{
+      sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::channel,
+      },
+      time::Duration,
+    };
+
+    use futures_util::stream;
+    use serde_json::json;
+
+    use super::super::*;
+    use crate::{
+      command,
+      ipc::channel::ChannelDataIpcQueue,
+      test::{
+        get_ipc_response, get_ipc_stream, mock_builder, mock_context, noop_assets, MockRuntime,
+      },
+      webview::InvokeRequest,
+      App, State, WebviewUrl, WebviewWindowBuilder,
+    };
+
+    #[derive(Default)]
+    struct Produced(Arc<AtomicU64>);
+
+    #[command(root = "crate")]
+    async fn count(to: u32) -> impl Stream<Item = u32> {
+      stream::iter(0..to)
+    }
+
+    #[command(root = "crate")]
+    async fn fallible(
+      fail_at: Option<u32>,
+      reject: bool,
+    ) -> Result<impl Stream<Item = Result<u32, String>>, String> {
+      if reject {
+        return Err("rejected".into());
+      }
+      Ok(stream::iter(0..3).map(move |i| {
+        if Some(i) == fail_at {
+          Err(format!("failed at {i}"))
+        } else {
+          Ok(i)
+        }
+      }))
+    }
+
+    #[command(root = "crate")]
+    async fn endless(
+      produced: State<'_, Produced>,
+    ) -> Result<impl Stream<Item = Result<u64, String>>, String> {
+      let produced = produced.0.clone();
+      Ok(stream::iter(0..).map(move |i| {
+        produced.fetch_add(1, Ordering::SeqCst);
+        Ok(i)
+      }))
+    }
+
+    fn app() -> App<MockRuntime> {
+      let app = mock_builder()
+        .invoke_handler(crate::generate_handler![count, fallible, endless])
+        .manage(Produced::default())
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      app
+    }
+
+    fn request(cmd: &str, body: serde_json::Value) -> InvokeRequest {
+      InvokeRequest {
+        cmd: cmd.into(),
+        callback: CallbackFn(1),
+        error: CallbackFn(2),
+        body: body.into(),
+        headers: Default::default(),
+      }
+    }
+
+    #[test]
+    fn stream_items() {
+      let app = app();
+      let webview = app.get_webview_window("main").unwrap();
+      assert_eq!(
+        get_ipc_stream(&webview, request("count", json!({ "to": 40 }))),
+        Ok((0..40).map(Into::into).collect())
+      );
+      assert_eq!(
+        get_ipc_stream(&webview, request("count", json!({ "to": 0 }))),
+        Ok(Vec::new())
+      );
+    }
+
+    #[test]
+    fn stream_errors() {
+      let app = app();
+      let webview = app.get_webview_window("main").unwrap();
+      assert_eq!(
+        get_ipc_stream(
+          &webview,
+          request("fallible", json!({ "failAt": null, "reject": false }))
+        ),
+        Ok(vec![0.into(), 1.into(), 2.into()])
+      );
+      assert_eq!(
+        get_ipc_stream(
+          &webview,
+          request("fallible", json!({ "failAt": 1, "reject": false }))
+        ),
+        Err("failed at 1".into())
+      );
+      assert_eq!(
+        get_ipc_stream(
+          &webview,
+          request("fallible", json!({ "failAt": null, "reject": true }))
+        ),
+        Err("rejected".into())
+      );
+    }
+
+    #[test]
+    fn requires_stream_channel() {
+      let app = app();
+      let webview = app.get_webview_window("main").unwrap();
+      let error = get_ipc_response(&webview, request("count", json!({ "to": 1 }))).unwrap_err();
+      assert_eq!(error["code"], codes::STREAM_CHANNEL_MISSING);
+      assert_eq!(error["data"], json!({ "command": "count" }));
+      assert!(error["message"]
+        .as_str()
+        .unwrap()
+        .contains(STREAM_CHANNEL_HEADER_NAME));
+    }
+
+    #[test]
+    fn backpressure() {
+      let app = app();
+      let webview = app.get_webview_window("main").unwrap();
+      let produced = app.state::<Produced>().0.clone();
+      let wait_for = |count| {
+        for _ in 0..100 {
+          if produced.load(Ordering::SeqCst) >= count {
+            break;
+          }
+          std::thread::sleep(Duration::from_millis(10));
+        }
+        // give the producer a chance to go past the window
+        std::thread::sleep(Duration::from_millis(50));
+        produced.load(Ordering::SeqCst)
+      };
+
+      let mut request = request("endless", json!({}));
+      request.headers.insert(STREAM_CHANNEL_HEADER_NAME, 5.into());
+      let (tx, _rx) = channel();
+      webview.on_message(
+        request,
+        Box::new(move |_webview, _cmd, response, _callback, _error| {
+          let _ = tx.send(response);
+        }),
+      );
+
+      assert_eq!(wait_for(STREAM_WINDOW), STREAM_WINDOW);
+      assert_eq!(
+        app.state::<ChannelDataIpcQueue>().0.lock().unwrap().len() as u64,
+        STREAM_WINDOW
+      );
+
+      assert!(app.state::<StreamAcks>().ack(5, 7));
+      assert_eq!(wait_for(STREAM_WINDOW + 8), STREAM_WINDOW + 8);
+    }
+  }
+}
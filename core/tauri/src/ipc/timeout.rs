@@ -0,0 +1,214 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Timeouts of asynchronous commands.
+//!
+//! An asynchronous command can be given a timeout with `#[tauri::command(timeout = <milliseconds>)]`,
+//! or with [`Builder::command_timeout`](crate::Builder::command_timeout) for all commands.
+//! The frontend can shorten the timeout of a single call with the [`DEADLINE_HEADER`].
+//!
+//! When the timeout elapses, the command future is dropped, its [`CancellationToken`](super::CancellationToken)
+//! is cancelled with [`CancellationReason::TimedOut`](super::CancellationReason::TimedOut)
+//! and the call is rejected with a [`TimedOutError`].
+
+use std::time::{Duration, Instant};
+
+use http::HeaderMap;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::{codes, IpcError};
+
+/// The request header with the number of milliseconds the call may take, counted from when it is received.
+///
+/// The call is rejected with a [`TimedOutError`] if it takes longer,
+/// even if the command timeout is longer or the command has no timeout.
+pub const DEADLINE_HEADER: &str = "Tauri-Deadline";
+
+/// The rejection sent to the frontend when an async command takes longer than its timeout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("command {command} timed out after {timeout_ms}ms")]
+pub struct TimedOutError {
+  /// The command name.
+  pub command: String,
+  /// The timeout that elapsed, in milliseconds.
+  pub timeout_ms: u64,
+}
+
+impl IpcError for TimedOutError {
+  fn code(&self) -> &str {
+    codes::TIMED_OUT
+  }
+
+  fn data(&self) -> Option<JsonValue> {
+    serde_json::to_value(self).ok()
+  }
+}
+
+/// The deadline requested by the frontend with the [`DEADLINE_HEADER`].
+pub(crate) fn deadline(headers: &HeaderMap) -> Option<Instant> {
+  let millis = headers
+    .get(DEADLINE_HEADER)?
+    .to_str()
+    .ok()?
+    .trim()
+    .parse()
+    .ok()?;
+  Some(Instant::now() + Duration::from_millis(millis))
+}
+
+/// The time a call may still take, the shortest of its timeout and its remaining time before the deadline.
+pub(crate) fn remaining(timeout: Option<Duration>, deadline: Option<Instant>) -> Option<Duration> {
+  let until_deadline = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+  match (timeout, until_deadline) {
+    (Some(timeout), Some(until_deadline)) => Some(timeout.min(until_deadline)),
+    (timeout, until_deadline) => timeout.or(until_deadline),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn deadline_header() {
+    let mut headers = HeaderMap::new();
+    assert!(deadline(&headers).is_none());
+
+    headers.insert(DEADLINE_HEADER, "invalid".parse().unwrap());
+    assert!(deadline(&headers).is_none());
+
+    headers.insert(DEADLINE_HEADER, "5000".parse().unwrap());
+    let remaining = deadline(&headers).unwrap().duration_since(Instant::now());
+    assert!(remaining <= Duration::from_secs(5) && remaining > Duration::from_secs(4));
+  }
+
+  #[test]
+  fn shortest_remaining_time() {
+    let second = Duration::from_secs(1);
+    assert_eq!(remaining(None, None), None);
+    assert_eq!(remaining(Some(second), None), Some(second));
+
+    let later = Instant::now() + 10 * second;
+    assert_eq!(remaining(Some(second), Some(later)), Some(second));
+    assert!(remaining(None, Some(later)).unwrap() > second);
+    assert!(remaining(Some(20 * second), Some(later)).unwrap() <= 10 * second);
+
+    let elapsed = Instant::now();
+    assert_eq!(remaining(Some(second), Some(elapsed)), Some(Duration::ZERO));
+  }
+
+  mod command {
+    use std::sync::mpsc::{channel, Receiver};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+      command,
+      ipc::{CallbackFn, InvokeError, InvokeResponse},
+      test::{mock_builder, mock_context, noop_assets, MockRuntime},
+      webview::InvokeRequest,
+      App, Builder, Manager, WebviewUrl, WebviewWindowBuilder,
+    };
+
+    #[command(root = "crate", timeout = 10)]
+    async fn slow() {
+      std::future::pending::<()>().await;
+    }
+
+    #[command(root = "crate", timeout = 60000)]
+    async fn slow_with_long_timeout() {
+      std::future::pending::<()>().await;
+    }
+
+    #[command(root = "crate")]
+    async fn pending() {
+      std::future::pending::<()>().await;
+    }
+
+    #[command(root = "crate", timeout = 60000)]
+    async fn fast() -> u8 {
+      1
+    }
+
+    fn app(builder: Builder<MockRuntime>) -> App<MockRuntime> {
+      let app = builder
+        .invoke_handler(crate::generate_handler![
+          slow,
+          slow_with_long_timeout,
+          pending,
+          fast
+        ])
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      app
+    }
+
+    fn invoke(app: &App<MockRuntime>, cmd: &str, headers: HeaderMap) -> Receiver<InvokeResponse> {
+      let (tx, rx) = channel();
+      app.get_webview_window("main").unwrap().on_message(
+        InvokeRequest {
+          cmd: cmd.into(),
+          callback: CallbackFn(1),
+          error: CallbackFn(2),
+          body: json!({}).into(),
+          headers,
+        },
+        Box::new(move |_webview, _cmd, response, _callback, _error| {
+          tx.send(response).unwrap();
+        }),
+      );
+      rx
+    }
+
+    fn rejection(response: InvokeResponse) -> serde_json::Value {
+      match response {
+        InvokeResponse::Err(InvokeError(error)) => error,
+        InvokeResponse::Ok(_) => panic!("expected the call to be rejected"),
+      }
+    }
+
+    #[test]
+    fn command_timeout() {
+      let app = app(mock_builder());
+      let error = rejection(invoke(&app, "slow", Default::default()).recv().unwrap());
+      assert_eq!(error["code"], codes::TIMED_OUT);
+      assert_eq!(error["data"], json!({ "command": "slow", "timeoutMs": 10 }));
+
+      assert!(matches!(
+        invoke(&app, "fast", Default::default()).recv().unwrap(),
+        InvokeResponse::Ok(_)
+      ));
+    }
+
+    #[test]
+    fn default_timeout() {
+      let app = app(mock_builder().command_timeout(Duration::from_millis(10)));
+      let error = rejection(invoke(&app, "pending", Default::default()).recv().unwrap());
+      assert_eq!(
+        error["data"],
+        json!({ "command": "pending", "timeoutMs": 10 })
+      );
+    }
+
+    #[test]
+    fn deadline_shortens_timeout() {
+      let app = app(mock_builder());
+      let mut headers = HeaderMap::new();
+      headers.insert(DEADLINE_HEADER, "10".parse().unwrap());
+      let error = rejection(
+        invoke(&app, "slow_with_long_timeout", headers)
+          .recv()
+          .unwrap(),
+      );
+      assert_eq!(error["code"], codes::TIMED_OUT);
+      assert!(error["data"]["timeoutMs"].as_u64().unwrap() <= 10);
+    }
+  }
+}
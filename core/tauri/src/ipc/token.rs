@@ -0,0 +1,260 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Binding of the IPC access to a page load, see [`SecurityConfig::ipc_token`](crate::utils::config::SecurityConfig::ipc_token).
+//!
+//! Every page load of a webview gets a new token, evaluated in the page once it is loaded.
+//! The token must be sent with all of its IPC calls, and navigating invalidates it
+//! before the next page load gets its own token.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use http::HeaderMap;
+use serde::Serialize;
+
+use super::{codes, IpcError};
+
+/// The header that carries the page load token on IPC requests.
+pub(crate) const IPC_TOKEN_HEADER_NAME: &str = "Tauri-Ipc-Token";
+
+/// An IPC call rejected because it was not bound to the current page load of the webview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+pub enum IpcTokenError {
+  /// The request does not include the IPC token.
+  #[error("missing IPC token")]
+  MissingToken,
+  /// The IPC token does not match the current page load, e.g. it was issued before a navigation.
+  #[error("invalid IPC token")]
+  InvalidToken,
+}
+
+impl IpcError for IpcTokenError {
+  fn code(&self) -> &str {
+    match self {
+      Self::MissingToken => codes::IPC_TOKEN_MISSING,
+      Self::InvalidToken => codes::IPC_TOKEN_INVALID,
+    }
+  }
+}
+
+/// The token of the current page load of each webview.
+#[derive(Default)]
+pub(crate) struct IpcTokens {
+  webviews: Mutex<HashMap<String, String>>,
+}
+
+fn random_hex() -> String {
+  let mut raw = [0u8; 32];
+  getrandom::getrandom(&mut raw).expect("failed to get random bytes");
+  raw.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl IpcTokens {
+  /// Generates the token of a new page load of the webview, replacing the previous one.
+  pub(crate) fn issue(&self, label: &str) -> String {
+    let token = random_hex();
+    self
+      .webviews
+      .lock()
+      .unwrap()
+      .insert(label.into(), token.clone());
+    token
+  }
+
+  /// Invalidates the token of the webview, called when it navigates or is closed.
+  pub(crate) fn invalidate(&self, label: &str) {
+    self.webviews.lock().unwrap().remove(label);
+  }
+
+  /// Checks that an IPC request of the webview carries the token of its current page load.
+  pub(crate) fn authorize(&self, label: &str, headers: &HeaderMap) -> Result<(), IpcTokenError> {
+    let token = headers
+      .get(IPC_TOKEN_HEADER_NAME)
+      .and_then(|value| value.to_str().ok())
+      .ok_or(IpcTokenError::MissingToken)?;
+    match self.webviews.lock().unwrap().get(label) {
+      Some(expected) if expected == token => Ok(()),
+      _ => Err(IpcTokenError::InvalidToken),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use http::HeaderValue;
+
+  use super::*;
+
+  fn headers(token: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(IPC_TOKEN_HEADER_NAME, HeaderValue::from_str(token).unwrap());
+    headers
+  }
+
+  #[test]
+  fn authorize() {
+    let tokens = IpcTokens::default();
+    let token = tokens.issue("main");
+
+    assert_eq!(tokens.authorize("main", &headers(&token)), Ok(()));
+    assert_eq!(
+      tokens.authorize("main", &HeaderMap::new()),
+      Err(IpcTokenError::MissingToken)
+    );
+    assert_eq!(
+      tokens.authorize("main", &headers("forged")),
+      Err(IpcTokenError::InvalidToken)
+    );
+    // tokens are bound to the webview
+    assert_eq!(
+      tokens.authorize("other", &headers(&token)),
+      Err(IpcTokenError::InvalidToken)
+    );
+  }
+
+  #[test]
+  fn replay_after_navigation() {
+    let tokens = IpcTokens::default();
+    let token = tokens.issue("main");
+
+    // no token is valid while the next page loads
+    tokens.invalidate("main");
+    assert_eq!(
+      tokens.authorize("main", &headers(&token)),
+      Err(IpcTokenError::InvalidToken)
+    );
+
+    // the new page load gets a new token, the old one stays invalid
+    let new_token = tokens.issue("main");
+    assert_ne!(new_token, token);
+    assert_eq!(
+      tokens.authorize("main", &headers(&token)),
+      Err(IpcTokenError::InvalidToken)
+    );
+    assert_eq!(tokens.authorize("main", &headers(&new_token)), Ok(()));
+  }
+
+  #[cfg(ipc_custom_protocol)]
+  mod protocol {
+    use std::borrow::Cow;
+
+    use http::StatusCode;
+
+    use super::super::*;
+    use crate::{
+      sealed::ManagerBase,
+      test::{mock_builder, mock_context, noop_assets, MockRuntime},
+      App, WebviewUrl, WebviewWindowBuilder,
+    };
+
+    fn token_app() -> App<MockRuntime> {
+      let mut context = mock_context(noop_assets());
+      context.config_mut().app.security.ipc_token = true;
+      let app = mock_builder()
+        .invoke_handler(|invoke| {
+          invoke.resolver.resolve("pong");
+          true
+        })
+        .build(context)
+        .unwrap();
+      WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      app
+    }
+
+    /// Loads a page in the webview, returning the token evaluated in it.
+    fn load(app: &App<MockRuntime>, url: &str) -> String {
+      let mut webview = app.manager().get_webview("main").unwrap();
+      webview.navigate(url.parse().unwrap());
+      let script = webview.webview.dispatcher.last_evaluated_script().unwrap();
+      let token = app.manager().webview.ipc_tokens.webviews.lock().unwrap()["main"].clone();
+      assert!(script.contains(&token));
+      token
+    }
+
+    fn invoke(app: &App<MockRuntime>, token: Option<&str>) -> http::Response<Cow<'static, [u8]>> {
+      let protocol = crate::ipc::protocol::get(app.manager_owned(), "main".into());
+      let mut request = http::Request::builder()
+        .method("POST")
+        .uri("ipc://localhost/ping")
+        .header("Tauri-Callback", "0")
+        .header("Tauri-Error", "1")
+        .header("Content-Type", "application/json");
+      if let Some(token) = token {
+        request = request.header(IPC_TOKEN_HEADER_NAME, token);
+      }
+      let (tx, rx) = std::sync::mpsc::channel();
+      protocol(
+        request.body(b"{}".to_vec()).unwrap(),
+        crate::app::UriSchemeResponder(Box::new(move |response| tx.send(response).unwrap())),
+      );
+      rx.recv().unwrap()
+    }
+
+    fn rejection(response: &http::Response<Cow<'static, [u8]>>) -> serde_json::Value {
+      assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+      serde_json::from_slice(response.body()).unwrap()
+    }
+
+    #[test]
+    fn invoke_requires_token() {
+      let app = token_app();
+      let token = load(&app, "tauri://localhost");
+
+      let response = invoke(&app, None);
+      assert_eq!(rejection(&response)["code"], codes::IPC_TOKEN_MISSING);
+
+      let response = invoke(&app, Some("forged"));
+      assert_eq!(rejection(&response)["code"], codes::IPC_TOKEN_INVALID);
+
+      let response = invoke(&app, Some(&token));
+      assert_eq!(response.status(), StatusCode::OK);
+      assert_eq!(response.body().as_ref(), b"\"pong\"");
+    }
+
+    #[test]
+    fn replay_after_navigating_away() {
+      let app = token_app();
+      let token = load(&app, "tauri://localhost");
+
+      let new_token = load(&app, "https://tauri.app");
+      assert_ne!(new_token, token);
+
+      // the token of the previous page load cannot be replayed, and there is no handshake to get the new one
+      let response = invoke(&app, Some(&token));
+      assert_eq!(rejection(&response)["code"], codes::IPC_TOKEN_INVALID);
+
+      let response = invoke(&app, Some(&new_token));
+      assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+      let app = mock_builder()
+        .invoke_handler(|invoke| {
+          invoke.resolver.resolve("pong");
+          true
+        })
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      let mut webview = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      webview.navigate("tauri://localhost".parse().unwrap());
+      assert!(app
+        .manager()
+        .webview
+        .ipc_tokens
+        .webviews
+        .lock()
+        .unwrap()
+        .is_empty());
+
+      let response = invoke(&app, None);
+      assert_eq!(response.status(), StatusCode::OK);
+    }
+  }
+}
@@ -0,0 +1,551 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Declarative validation of command arguments.
+//!
+//! The `#[validate(...)]` attribute is accepted on the arguments of a [`command`](macro@crate::command)
+//! and on the fields of a struct deriving [`Validate`](macro@crate::ipc::Validate):
+//!
+//! - `range(min = 1, max = 10)` checks a number, either bound is optional.
+//! - `length(min = 1, max = 64)` checks the number of characters of a string or items of a collection.
+//! - `regex = "^[a-z]+$"` checks that a string matches the pattern. Requires the `validate-regex` feature.
+//! - `custom = path::to::function` calls a `fn(&T) -> Result<(), String>`, the rule is named after the function.
+//! - `nested` validates a value whose type implements [`Validate`].
+//!
+//! `None` values are skipped by the `range`, `length` and `regex` rules.
+//! The arguments are checked before the command runs,
+//! and all the broken rules are rejected as a single [`CommandError`] with the [`codes::VALIDATION_FAILED`] code.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tauri::ipc::Validate;
+//!
+//! #[derive(serde::Deserialize, Validate)]
+//! struct Address {
+//!   #[validate(length(min = 1))]
+//!   street: String,
+//! }
+//!
+//! #[derive(serde::Deserialize, Validate)]
+//! struct Signup {
+//!   #[validate(length(min = 3, max = 16))]
+//!   username: String,
+//!   #[validate(nested)]
+//!   address: Option<Address>,
+//! }
+//!
+//! #[tauri::command]
+//! fn signup(#[validate(nested)] form: Signup, #[validate(range(min = 13))] age: u8) {}
+//! ```
+
+use std::{
+  collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+  fmt::Display,
+};
+
+use serde::Serialize;
+
+use super::{codes, CommandError};
+
+/// A rule broken by a command argument.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationError {
+  /// The path of the value, e.g. `form.addresses[0].street`.
+  pub path: String,
+  /// The name of the rule, e.g. `length`.
+  pub rule: String,
+  /// Why the value breaks the rule.
+  pub message: String,
+}
+
+/// The rules broken by the arguments of a command.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+  /// Records the error of a rule check, if any.
+  pub fn check(&mut self, path: &str, rule: &str, result: Result<(), String>) {
+    if let Err(message) = result {
+      self.0.push(ValidationError {
+        path: path.into(),
+        rule: rule.into(),
+        message,
+      });
+    }
+  }
+
+  /// Whether no rule was broken.
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// The broken rules.
+  pub fn errors(&self) -> &[ValidationError] {
+    &self.0
+  }
+
+  /// Converts the broken rules to the error rejected by the command.
+  pub fn into_command_error(self, command: &str) -> CommandError {
+    let message = format!(
+      "invalid arguments for command {command}: {}",
+      self
+        .0
+        .iter()
+        .map(|e| format!("`{}` breaks the {} rule ({})", e.path, e.rule, e.message))
+        .collect::<Vec<_>>()
+        .join(", ")
+    );
+    CommandError::new(codes::VALIDATION_FAILED, message).with_data(serde_json::json!({
+      "command": command,
+      "errors": self.0,
+    }))
+  }
+}
+
+/// A value with validation rules.
+///
+/// This is usually implemented with the [`Validate`](macro@crate::ipc::Validate) derive macro.
+pub trait Validate {
+  /// Checks the rules of the value, recording the broken ones with their path under `path`.
+  fn validate(&self, path: &str, errors: &mut ValidationErrors);
+}
+
+impl<T: Validate> Validate for Option<T> {
+  fn validate(&self, path: &str, errors: &mut ValidationErrors) {
+    if let Some(value) = self {
+      value.validate(path, errors);
+    }
+  }
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+  fn validate(&self, path: &str, errors: &mut ValidationErrors) {
+    (**self).validate(path, errors);
+  }
+}
+
+impl<T: Validate> Validate for [T] {
+  fn validate(&self, path: &str, errors: &mut ValidationErrors) {
+    for (index, value) in self.iter().enumerate() {
+      value.validate(&format!("{path}[{index}]"), errors);
+    }
+  }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+  fn validate(&self, path: &str, errors: &mut ValidationErrors) {
+    self.as_slice().validate(path, errors);
+  }
+}
+
+/// The path of a field of the value at `path`.
+pub fn field_path(path: &str, field: &str) -> String {
+  if path.is_empty() {
+    field.into()
+  } else {
+    format!("{path}.{field}")
+  }
+}
+
+/// The rules of the `#[validate(...)]` attribute.
+pub mod rules {
+  use super::*;
+
+  /// A number checked by the `range` rule.
+  pub trait Bounded {
+    /// The type of the bounds.
+    type Bound: PartialOrd + Display;
+
+    /// The number to check, `None` to skip the rule.
+    fn bounded(&self) -> Option<&Self::Bound>;
+  }
+
+  macro_rules! impl_bounded {
+    ($($ty:ty),*) => {
+      $(
+        impl Bounded for $ty {
+          type Bound = Self;
+
+          fn bounded(&self) -> Option<&Self> {
+            Some(self)
+          }
+        }
+      )*
+    };
+  }
+
+  impl_bounded!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+  impl<T: Bounded> Bounded for Option<T> {
+    type Bound = T::Bound;
+
+    fn bounded(&self) -> Option<&Self::Bound> {
+      self.as_ref().and_then(Bounded::bounded)
+    }
+  }
+
+  /// A string or collection checked by the `length` rule.
+  pub trait Length {
+    /// The number of characters or items, `None` to skip the rule.
+    fn length(&self) -> Option<usize>;
+  }
+
+  impl Length for str {
+    fn length(&self) -> Option<usize> {
+      Some(self.chars().count())
+    }
+  }
+
+  impl Length for String {
+    fn length(&self) -> Option<usize> {
+      self.as_str().length()
+    }
+  }
+
+  impl<T> Length for [T] {
+    fn length(&self) -> Option<usize> {
+      Some(self.len())
+    }
+  }
+
+  impl<T> Length for Vec<T> {
+    fn length(&self) -> Option<usize> {
+      Some(self.len())
+    }
+  }
+
+  impl<K, V, S> Length for HashMap<K, V, S> {
+    fn length(&self) -> Option<usize> {
+      Some(self.len())
+    }
+  }
+
+  impl<T, S> Length for HashSet<T, S> {
+    fn length(&self) -> Option<usize> {
+      Some(self.len())
+    }
+  }
+
+  impl<K, V> Length for BTreeMap<K, V> {
+    fn length(&self) -> Option<usize> {
+      Some(self.len())
+    }
+  }
+
+  impl<T> Length for BTreeSet<T> {
+    fn length(&self) -> Option<usize> {
+      Some(self.len())
+    }
+  }
+
+  impl<T: Length> Length for Option<T> {
+    fn length(&self) -> Option<usize> {
+      self.as_ref().and_then(Length::length)
+    }
+  }
+
+  /// A string checked by the `regex` rule.
+  pub trait Text {
+    /// The string to check, `None` to skip the rule.
+    fn text(&self) -> Option<&str>;
+  }
+
+  impl Text for str {
+    fn text(&self) -> Option<&str> {
+      Some(self)
+    }
+  }
+
+  impl Text for String {
+    fn text(&self) -> Option<&str> {
+      Some(self)
+    }
+  }
+
+  impl<T: Text> Text for Option<T> {
+    fn text(&self) -> Option<&str> {
+      self.as_ref().and_then(Text::text)
+    }
+  }
+
+  /// Checks that the number is within the bounds.
+  pub fn range<V: Bounded + ?Sized>(
+    value: &V,
+    min: Option<V::Bound>,
+    max: Option<V::Bound>,
+  ) -> Result<(), String> {
+    match value.bounded() {
+      Some(value) if min.as_ref().is_some_and(|min| value < min) => {
+        Err(format!("must be at least {}", min.unwrap()))
+      }
+      Some(value) if max.as_ref().is_some_and(|max| value > max) => {
+        Err(format!("must be at most {}", max.unwrap()))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Checks that the number of characters or items is within the bounds.
+  pub fn length<V: Length + ?Sized>(
+    value: &V,
+    min: Option<usize>,
+    max: Option<usize>,
+  ) -> Result<(), String> {
+    match value.length() {
+      Some(length) if min.is_some_and(|min| length < min) => {
+        Err(format!("length must be at least {}", min.unwrap()))
+      }
+      Some(length) if max.is_some_and(|max| length > max) => {
+        Err(format!("length must be at most {}", max.unwrap()))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Checks that the string matches the pattern.
+  #[cfg(feature = "validate-regex")]
+  pub fn regex<V: Text + ?Sized>(value: &V, pattern: &regex::Regex) -> Result<(), String> {
+    match value.text() {
+      Some(text) if !pattern.is_match(text) => {
+        Err(format!("must match the `{}` pattern", pattern.as_str()))
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+  use crate::ipc::Validate;
+
+  fn is_even(value: &u32) -> Result<(), String> {
+    if value & 1 == 0 {
+      Ok(())
+    } else {
+      Err("must be even".into())
+    }
+  }
+
+  #[derive(serde::Deserialize, Validate)]
+  #[validate(root = "crate")]
+  struct Address {
+    #[validate(length(min = 1))]
+    street: String,
+    #[validate(custom = is_even)]
+    number: u32,
+  }
+
+  #[derive(serde::Deserialize, Validate)]
+  #[validate(root = "crate")]
+  #[allow(dead_code)]
+  struct Form {
+    #[cfg_attr(feature = "validate-regex", validate(regex = "^[a-z]+$"))]
+    #[validate(length(max = 4))]
+    username: String,
+    #[validate(range(min = 1, max = 10))]
+    rating: Option<u8>,
+    #[validate(nested)]
+    addresses: Vec<Address>,
+    #[serde(rename = "zip")]
+    #[validate(length(min = 5, max = 5))]
+    zip_code: Option<String>,
+    unchecked: String,
+  }
+
+  fn validate(form: &Form) -> Vec<(String, String)> {
+    let mut errors = ValidationErrors::default();
+    form.validate("form", &mut errors);
+    errors
+      .errors()
+      .iter()
+      .map(|e| (e.path.clone(), e.rule.clone()))
+      .collect()
+  }
+
+  #[test]
+  fn rules() {
+    assert_eq!(rules::range(&5, Some(1), Some(10)), Ok(()));
+    assert!(rules::range(&0, Some(1), None).is_err());
+    assert!(rules::range(&1.5, None, Some(1.0)).is_err());
+    assert_eq!(rules::range(&None::<u8>, Some(1), None), Ok(()));
+    assert_eq!(rules::length("héllo", Some(5), Some(5)), Ok(()));
+    assert!(rules::length(&vec![1, 2], None, Some(1)).is_err());
+  }
+
+  #[cfg(feature = "validate-regex")]
+  #[test]
+  fn regex() {
+    let pattern = regex::Regex::new("^[a-z]+$").unwrap();
+    assert_eq!(rules::regex("abc", &pattern), Ok(()));
+    assert!(rules::regex(&Some("ABC".to_string()), &pattern).is_err());
+    assert_eq!(rules::regex(&None::<String>, &pattern), Ok(()));
+
+    let form = Form {
+      username: "AB".into(),
+      rating: None,
+      addresses: Vec::new(),
+      zip_code: None,
+      unchecked: "".into(),
+    };
+    assert_eq!(
+      validate(&form),
+      [("form.username".to_string(), "regex".to_string())]
+    );
+  }
+
+  #[test]
+  fn derive() {
+    let mut form = Form {
+      username: "tauri".into(),
+      rating: Some(0),
+      addresses: vec![
+        Address {
+          street: "main".into(),
+          number: 2,
+        },
+        Address {
+          street: "".into(),
+          number: 3,
+        },
+      ],
+      zip_code: Some("123".into()),
+      unchecked: "".into(),
+    };
+    assert_eq!(
+      validate(&form),
+      [
+        ("form.username", "length"),
+        ("form.rating", "range"),
+        ("form.addresses[1].street", "length"),
+        ("form.addresses[1].number", "is_even"),
+        ("form.zip", "length"),
+      ]
+      .map(|(path, rule)| (path.to_string(), rule.to_string()))
+    );
+
+    form.username = "ab".into();
+    form.rating = None;
+    form.addresses.clear();
+    form.zip_code = None;
+    assert!(validate(&form).is_empty());
+  }
+
+  #[test]
+  fn command_error() {
+    let mut errors = ValidationErrors::default();
+    errors.check("name", "length", Err("length must be at most 4".into()));
+    let error = errors.into_command_error("greet");
+    assert_eq!(error.code, codes::VALIDATION_FAILED);
+    assert_eq!(
+      error.message,
+      "invalid arguments for command greet: `name` breaks the length rule (length must be at most 4)"
+    );
+    assert_eq!(
+      error.data,
+      Some(json!({
+        "command": "greet",
+        "errors": [{ "path": "name", "rule": "length", "message": "length must be at most 4" }]
+      }))
+    );
+  }
+
+  mod command {
+    use serde_json::json;
+
+    use super::Address;
+    use crate::{
+      command,
+      ipc::{codes, CallbackFn, InvokeBody},
+      test::{get_ipc_response, mock_builder, mock_context, noop_assets},
+      webview::InvokeRequest,
+      WebviewUrl, WebviewWindowBuilder,
+    };
+
+    #[command(root = "crate")]
+    fn register(
+      #[validate(length(min = 1, max = 8))] display_name: String,
+      #[validate(range(min = 1))] count: u32,
+      #[validate(nested)] address: Option<Address>,
+    ) -> String {
+      let number = address.map_or(0, |address| address.number);
+      format!("{display_name} {count} {number}")
+    }
+
+    #[command(root = "crate")]
+    async fn register_async(
+      #[validate(length(min = 1, max = 8))] display_name: String,
+    ) -> Result<String, String> {
+      Ok(display_name)
+    }
+
+    fn invoke(cmd: &str, body: serde_json::Value) -> Result<serde_json::Value, serde_json::Value> {
+      let app = mock_builder()
+        .invoke_handler(crate::generate_handler![register, register_async])
+        .build(mock_context(noop_assets()))
+        .unwrap();
+      let webview = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+        .build()
+        .unwrap();
+      get_ipc_response(
+        &webview,
+        InvokeRequest {
+          cmd: cmd.into(),
+          callback: CallbackFn(0),
+          error: CallbackFn(1),
+          body: body.into(),
+          headers: Default::default(),
+        },
+      )
+      .map(InvokeBody::into_json)
+    }
+
+    #[test]
+    fn validate_arguments() {
+      assert_eq!(
+        invoke(
+          "register",
+          json!({ "displayName": "tauri", "count": 1, "address": null })
+        ),
+        Ok(json!("tauri 1 0"))
+      );
+
+      let error = invoke(
+        "register",
+        json!({
+          "displayName": "",
+          "count": 0,
+          "address": { "street": "", "number": 2 }
+        }),
+      )
+      .unwrap_err();
+      assert_eq!(error["code"], codes::VALIDATION_FAILED);
+      let paths = error["data"]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| (e["path"].as_str().unwrap(), e["rule"].as_str().unwrap()))
+        .collect::<Vec<_>>();
+      assert_eq!(
+        paths,
+        [
+          ("displayName", "length"),
+          ("count", "range"),
+          ("address.street", "length")
+        ]
+      );
+
+      assert_eq!(
+        invoke("register_async", json!({ "displayName": "tauri" })),
+        Ok(json!("tauri"))
+      );
+      let error = invoke("register_async", json!({ "displayName": "" })).unwrap_err();
+      assert_eq!(error["data"]["errors"][0]["path"], "displayName");
+    }
+  }
+}
@@ -37,6 +37,10 @@
 //! - **icon-ico**: Adds support to set `.ico` window icons. Enables [`Icon::File`] and [`Icon::Raw`] variants.
 //! - **icon-png**: Adds support to set `.png` window icons. Enables [`Icon::File`] and [`Icon::Raw`] variants.
 //! - **macos-proxy**: Adds support for [`WebviewBuilder::proxy_url`] on macOS. Requires macOS 14+.
+//! - **webdriver**: Enables the [`webdriver`] test plugin used by `tauri-driver` to invoke commands, emit and listen to events and switch windows by label. Do not enable it on production builds.
+//! - **ipc-msgpack**: Enables MessagePack IPC request bodies and the [`ipc::MessagePack`] response type.
+//! - **ipc-form-data**: Enables `multipart/form-data` IPC request bodies, e.g. a `FormData` with files.
+//! - **validate-regex**: Enables the `regex` rule of the [`ipc::validation`] attribute.
 //!
 //! ## Cargo allowlist features
 //!
@@ -75,7 +79,7 @@ pub use resources::{Resource, ResourceId, ResourceTable};
 pub use swift_rs;
 #[cfg(mobile)]
 pub use tauri_macros::mobile_entry_point;
-pub use tauri_macros::{command, generate_handler};
+pub use tauri_macros::{command, commands, generate_handler};
 
 pub(crate) mod app;
 pub mod async_runtime;
@@ -105,6 +109,9 @@ mod state;
 #[cfg(all(desktop, feature = "tray-icon"))]
 #[cfg_attr(docsrs, doc(cfg(all(desktop, feature = "tray-icon"))))]
 pub mod tray;
+#[cfg(feature = "webdriver")]
+#[cfg_attr(docsrs, doc(cfg(feature = "webdriver")))]
+pub mod webdriver;
 pub use tauri_utils as utils;
 
 pub use http;
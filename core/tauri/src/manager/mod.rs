@@ -7,6 +7,7 @@ use std::{
   collections::HashMap,
   fmt,
   sync::{Arc, Mutex, MutexGuard},
+  time::Duration,
 };
 
 use serde::Serialize;
@@ -23,7 +24,9 @@ use tauri_utils::{
 use crate::{
   app::{AppHandle, GlobalWindowEventListener, OnPageLoad},
   event::{assert_event_name_is_valid, Event, EventId, EventTarget, Listeners},
-  ipc::{Invoke, InvokeHandler, InvokeResponder, RuntimeAuthority},
+  ipc::{
+    audit::IpcAudit, CancellationReason, Invoke, InvokeHandler, InvokeResponder, RuntimeAuthority,
+  },
   plugin::PluginStore,
   utils::{assets::Assets, config::Config, PackageInfo},
   Context, Pattern, Runtime, StateManager, Window,
@@ -107,6 +110,17 @@ fn set_csp<R: Runtime>(
     default_src.push(crate::pattern::format_real_schema(schema));
   }
 
+  if manager.config().app.security.csp_report {
+    csp.insert(
+      "report-uri".into(),
+      CspDirectiveSources::Inline(crate::protocol::csp::report_url()),
+    );
+    csp.insert(
+      "report-to".into(),
+      CspDirectiveSources::Inline(crate::protocol::csp::REPORT_TO_GROUP.into()),
+    );
+  }
+
   Csp::DirectiveMap(csp).to_string()
 }
 
@@ -127,6 +141,15 @@ fn replace_with_callback<F: FnMut() -> String>(
   result
 }
 
+/// Generates a 128-bit nonce for a page load.
+fn generate_nonce() -> String {
+  let mut raw = [0u8; 16];
+  getrandom::getrandom(&mut raw).expect("failed to get random bytes");
+  format!("{:032x}", u128::from_ne_bytes(raw))
+}
+
+/// Replaces the nonce `token` in the asset with a nonce generated for this page load,
+/// allowing it and the asset hashes on the `directive`.
 fn replace_csp_nonce(
   asset: &mut String,
   token: &str,
@@ -134,31 +157,20 @@ fn replace_csp_nonce(
   directive: &str,
   hashes: Vec<String>,
 ) {
-  let mut nonces = Vec::new();
+  let mut nonce = None;
   *asset = replace_with_callback(asset, token, || {
-    #[cfg(target_pointer_width = "64")]
-    let mut raw = [0u8; 8];
-    #[cfg(target_pointer_width = "32")]
-    let mut raw = [0u8; 4];
-    #[cfg(target_pointer_width = "16")]
-    let mut raw = [0u8; 2];
-    getrandom::getrandom(&mut raw).expect("failed to get random bytes");
-    let nonce = usize::from_ne_bytes(raw);
-    nonces.push(nonce);
-    nonce.to_string()
+    nonce.get_or_insert_with(generate_nonce).clone()
   });
 
-  if !(nonces.is_empty() && hashes.is_empty()) {
-    let nonce_sources = nonces
-      .into_iter()
-      .map(|n| format!("'nonce-{n}'"))
-      .collect::<Vec<String>>();
+  if !(nonce.is_none() && hashes.is_empty()) {
     let sources = csp.entry(directive.into()).or_default();
     let self_source = "'self'".to_string();
     if !sources.contains(&self_source) {
       sources.push(self_source);
     }
-    sources.extend(nonce_sources);
+    if let Some(nonce) = nonce {
+      sources.push(format!("'nonce-{nonce}'"));
+    }
     sources.extend(hashes);
   }
 }
@@ -236,6 +248,8 @@ impl<R: Runtime> AppManager<R> {
       crate::app::GlobalMenuEventListener<Window<R>>,
     >,
     (invoke_responder, invoke_initialization_script): (Option<Arc<InvokeResponder<R>>>, String),
+    ipc_audit: Option<IpcAudit<R>>,
+    command_timeout: Option<Duration>,
   ) -> Self {
     // generate a random isolation key at runtime
     #[cfg(feature = "isolation")]
@@ -257,6 +271,12 @@ impl<R: Runtime> AppManager<R> {
         uri_scheme_protocols: Mutex::new(uri_scheme_protocols),
         invoke_responder,
         invoke_initialization_script,
+        ipc_audit,
+        command_timeout,
+        #[cfg(feature = "isolation")]
+        isolation_keys: Default::default(),
+        ipc_tokens: Default::default(),
+        cancellations: Default::default(),
       },
       #[cfg(all(desktop, feature = "tray-icon"))]
       tray: tray::TrayManager {
@@ -538,13 +558,21 @@ impl<R: Runtime> AppManager<R> {
   pub(crate) fn on_window_close(&self, label: &str) {
     if let Some(window) = self.window.windows_lock().remove(label) {
       for webview in window.webviews() {
-        self.webview.webviews_lock().remove(webview.label());
+        self.on_webview_close(webview.label());
       }
     }
   }
 
   pub(crate) fn on_webview_close(&self, label: &str) {
     self.webview.webviews_lock().remove(label);
+    #[cfg(feature = "isolation")]
+    self.webview.isolation_keys.lock().unwrap().remove(label);
+    self.webview.ipc_tokens.invalidate(label);
+    self
+      .webview
+      .cancellations
+      .cancel_all(label, CancellationReason::Destroyed);
+    self.runtime_authority.limiter.remove_webview(label);
   }
 
   pub fn windows(&self) -> HashMap<String, Window<R>> {
@@ -586,7 +614,9 @@ impl<R: Runtime> AppManager<R> {
 
 #[cfg(test)]
 mod tests {
-  use super::replace_with_callback;
+  use std::collections::HashMap;
+
+  use super::{replace_csp_nonce, replace_with_callback};
 
   #[test]
   fn string_replace_with_callback() {
@@ -604,6 +634,40 @@ mod tests {
       assert_eq!(replace_with_callback(src, pattern, replacement), result);
     }
   }
+
+  #[test]
+  fn csp_nonce_per_page_load() {
+    let html = r#"<script nonce="__TAURI_SCRIPT_NONCE__"></script><script nonce="__TAURI_SCRIPT_NONCE__"></script>"#;
+
+    let load = || {
+      let mut asset = html.to_string();
+      let mut csp = HashMap::new();
+      replace_csp_nonce(
+        &mut asset,
+        "__TAURI_SCRIPT_NONCE__",
+        &mut csp,
+        "script-src",
+        vec!["'sha256-hash'".into()],
+      );
+      let sources = Vec::<String>::from(csp.remove("script-src").unwrap());
+      (asset, sources)
+    };
+
+    let (first_asset, first_sources) = load();
+    assert_eq!(first_sources.len(), 3);
+    assert_eq!(first_sources[0], "'self'");
+    assert_eq!(first_sources[2], "'sha256-hash'");
+    let nonce = first_sources[1]
+      .strip_prefix("'nonce-")
+      .and_then(|n| n.strip_suffix('\''))
+      .unwrap();
+    // every script of the page shares the nonce
+    assert_eq!(first_asset.matches(nonce).count(), 2);
+
+    let (second_asset, second_sources) = load();
+    assert_ne!(first_sources[1], second_sources[1]);
+    assert_ne!(first_asset, second_asset);
+  }
 }
 
 #[cfg(test)]
@@ -648,6 +712,8 @@ mod test {
       Default::default(),
       Default::default(),
       (None, "".into()),
+      None,
+      None,
     );
 
     #[cfg(custom_protocol)]
@@ -8,6 +8,7 @@ use std::{
   fmt,
   fs::create_dir_all,
   sync::{Arc, Mutex, MutexGuard},
+  time::Duration,
 };
 
 use serde::Serialize;
@@ -18,10 +19,16 @@ use url::Url;
 
 use crate::{
   app::{OnPageLoad, UriSchemeResponder},
-  ipc::{InvokeHandler, InvokeResponder},
-  pattern::PatternJavascript,
+  ipc::{
+    audit::IpcAudit,
+    cancellation::{Cancellations, CANCEL_INVOKE_COMMAND},
+    token::IpcTokens,
+    CancellationReason, InvokeHandler, InvokeResponder,
+  },
+  pattern::{PatternJavascript, PatternObject},
+  protocol::csp::CSP_REPORT_PROTOCOL,
   sealed::ManagerBase,
-  webview::PageLoadPayload,
+  webview::{PageLoadEvent, PageLoadPayload},
   AppHandle, EventLoopMessage, Manager, Runtime, Webview, Window,
 };
 
@@ -48,6 +55,7 @@ pub(crate) struct IsolationJavascript<'a> {
 #[default_template("../../scripts/ipc.js")]
 pub(crate) struct IpcJavascript<'a> {
   pub(crate) isolation_origin: &'a str,
+  pub(crate) ipc_token: bool,
 }
 
 /// Uses a custom URI scheme handler to resolve file requests
@@ -78,6 +86,17 @@ pub struct WebviewManager<R: Runtime> {
   pub invoke_responder: Option<Arc<InvokeResponder<R>>>,
   /// The script that initializes the invoke system.
   pub invoke_initialization_script: String,
+  /// The IPC audit log.
+  pub ipc_audit: Option<IpcAudit<R>>,
+  /// The default timeout of the async commands.
+  pub command_timeout: Option<Duration>,
+  /// The isolation keys of each webview whose IPC goes through the isolation frame.
+  #[cfg(feature = "isolation")]
+  pub isolation_keys: Mutex<HashMap<String, tauri_utils::pattern::isolation::Keys>>,
+  /// The IPC secret and page load token of each webview.
+  pub(crate) ipc_tokens: IpcTokens,
+  /// The cancellation tokens of the in-flight IPC calls of each webview.
+  pub(crate) cancellations: Cancellations,
 }
 
 impl<R: Runtime> fmt::Debug for WebviewManager<R> {
@@ -105,11 +124,21 @@ impl<R: Runtime> WebviewManager<R> {
       .insert(uri_scheme, protocol);
   }
 
+  /// The isolation keys of the webview, `None` if its IPC does not go through the isolation frame.
+  #[cfg(feature = "isolation")]
+  pub(crate) fn isolation_keys(
+    &self,
+    label: &str,
+  ) -> Option<tauri_utils::pattern::isolation::Keys> {
+    self.isolation_keys.lock().unwrap().get(label).cloned()
+  }
+
   /// Get a locked handle to the webviews.
   pub(crate) fn webviews_lock(&self) -> MutexGuard<'_, HashMap<String, Webview<R>>> {
     self.webviews.lock().expect("poisoned webview manager")
   }
 
+  #[allow(clippy::too_many_arguments)]
   fn prepare_pending_webview<M: Manager<R>>(
     &self,
     mut pending: PendingWebview<EventLoopMessage, R>,
@@ -118,6 +147,7 @@ impl<R: Runtime> WebviewManager<R> {
     window_labels: &[String],
     webview_labels: &[WebviewLabelDef],
     manager: &M,
+    isolated: bool,
   ) -> crate::Result<PendingWebview<EventLoopMessage, R>> {
     let app_manager = manager.manager();
 
@@ -129,18 +159,28 @@ impl<R: Runtime> WebviewManager<R> {
       .initialization_script();
 
     let pattern_init = PatternJavascript {
-      pattern: (&*app_manager.pattern).into(),
+      pattern: if isolated {
+        (&*app_manager.pattern).into()
+      } else {
+        PatternObject::Brownfield
+      },
     }
     .render_default(&Default::default())?;
 
     let mut webview_attributes = pending.webview_attributes;
 
+    // a recreated webview never reuses the token of the previous one
+    self.ipc_tokens.invalidate(label);
+
     let ipc_init = IpcJavascript {
       isolation_origin: &match &*app_manager.pattern {
         #[cfg(feature = "isolation")]
-        crate::Pattern::Isolation { schema, .. } => crate::pattern::format_real_schema(schema),
+        crate::Pattern::Isolation { schema, .. } if isolated => {
+          crate::pattern::format_real_schema(schema)
+        }
         _ => "".to_string(),
       },
+      ipc_token: app_manager.config.app.security.ipc_token,
     }
     .render_default(&Default::default())?;
 
@@ -189,7 +229,7 @@ impl<R: Runtime> WebviewManager<R> {
       )?);
 
     #[cfg(feature = "isolation")]
-    if let crate::Pattern::Isolation { schema, .. } = &*app_manager.pattern {
+    if let (true, crate::Pattern::Isolation { schema, .. }) = (isolated, &*app_manager.pattern) {
       webview_attributes = webview_attributes.initialization_script(
         &IsolationJavascript {
           isolation_src: &crate::pattern::format_real_schema(schema),
@@ -259,6 +299,16 @@ impl<R: Runtime> WebviewManager<R> {
       registered_scheme_protocols.push("ipc".into());
     }
 
+    if app_manager.config.app.security.csp_report
+      && !registered_scheme_protocols.contains(&CSP_REPORT_PROTOCOL.into())
+    {
+      let protocol = crate::protocol::csp::get(manager.manager_owned(), pending.label.clone());
+      pending.register_uri_scheme_protocol(CSP_REPORT_PROTOCOL, move |request, responder| {
+        protocol(request, UriSchemeResponder(responder))
+      });
+      registered_scheme_protocols.push(CSP_REPORT_PROTOCOL.into());
+    }
+
     let label = pending.label.clone();
     let app_manager_ = manager.manager_owned();
     let on_page_load_handler = pending.on_page_load_handler.take();
@@ -267,7 +317,30 @@ impl<R: Runtime> WebviewManager<R> {
       .replace(Box::new(move |url, event| {
         let payload = PageLoadPayload { url: &url, event };
 
+        if event == PageLoadEvent::Started {
+          // the token of the previous page load must not be replayed after navigating away
+          app_manager_.webview.ipc_tokens.invalidate(&label);
+          // nobody is waiting for the calls of the previous page load anymore
+          app_manager_
+            .webview
+            .cancellations
+            .cancel_all(&label, CancellationReason::Navigation);
+        }
+
         if let Some(w) = app_manager_.get_webview(&label) {
+          // the document of the new page is only reachable once it is loaded,
+          // the previous page never sees its token
+          if event == PageLoadEvent::Finished && app_manager_.config.app.security.ipc_token {
+            let token = app_manager_.webview.ipc_tokens.issue(&label);
+            let script = format!(
+              "window.__TAURI_INTERNALS__.setIpcToken({})",
+              serde_json::Value::String(token)
+            );
+            if let Err(e) = w.eval(&script) {
+              log::error!("failed to set the IPC token of webview {label}: {e}");
+            }
+          }
+
           if let Some(on_page_load) = &app_manager_.webview.on_page_load {
             on_page_load(&w, &payload);
           }
@@ -298,17 +371,20 @@ impl<R: Runtime> WebviewManager<R> {
     }
 
     #[cfg(feature = "isolation")]
-    if let crate::Pattern::Isolation {
-      assets,
-      schema,
-      key: _,
-      crypto_keys,
-    } = &*app_manager.pattern
     {
-      let protocol = crate::protocol::isolation::get(assets.clone(), *crypto_keys.aes_gcm().raw());
-      pending.register_uri_scheme_protocol(schema, move |request, responder| {
-        protocol(request, UriSchemeResponder(responder))
-      });
+      let mut isolation_keys = self.isolation_keys.lock().unwrap();
+      // a recreated webview never reuses the keys of the previous one
+      isolation_keys.remove(&pending.label);
+      if let (true, crate::Pattern::Isolation { assets, schema, .. }) =
+        (isolated, &*app_manager.pattern)
+      {
+        let keys = tauri_utils::pattern::isolation::Keys::new()?;
+        let protocol = crate::protocol::isolation::get(assets.clone(), *keys.aes_gcm().raw());
+        pending.register_uri_scheme_protocol(schema, move |request, responder| {
+          protocol(request, UriSchemeResponder(responder))
+        });
+        isolation_keys.insert(pending.label.clone(), keys);
+      }
     }
 
     Ok(pending)
@@ -345,6 +421,7 @@ impl<R: Runtime> WebviewManager<R> {
     #[default_template("../../scripts/core.js")]
     struct CoreJavascript<'a> {
       os_name: &'a str,
+      cancel_invoke_command: &'a str,
     }
 
     let bundle_script = if with_global_tauri {
@@ -365,6 +442,7 @@ impl<R: Runtime> WebviewManager<R> {
       bundle_script,
       core_script: &CoreJavascript {
         os_name: std::env::consts::OS,
+        cancel_invoke_command: CANCEL_INVOKE_COMMAND,
       }
       .render_default(&Default::default())?
       .into_string(),
@@ -387,12 +465,15 @@ impl<R: Runtime> WebviewManager<R> {
     window_label: &str,
     window_labels: &[String],
     webview_labels: &[WebviewLabelDef],
+    isolation: Option<bool>,
   ) -> crate::Result<PendingWebview<EventLoopMessage, R>> {
     if self.webviews_lock().contains_key(&pending.label) {
       return Err(crate::Error::WebviewLabelAlreadyExists(pending.label));
     }
 
     let app_manager = manager.manager();
+    let isolated =
+      crate::pattern::resolve_isolation(&app_manager.pattern, &pending.label, isolation)?;
 
     #[allow(unused_mut)] // mut url only for the data-url parsing
     let mut url = match &pending.webview_attributes.url {
@@ -486,6 +567,7 @@ impl<R: Runtime> WebviewManager<R> {
       window_labels,
       webview_labels,
       manager,
+      isolated,
     )?;
 
     #[cfg(any(target_os = "macos", target_os = "ios", not(ipc_custom_protocol)))]
@@ -523,7 +605,7 @@ impl<R: Runtime> WebviewManager<R> {
     pending.navigation_handler = Some(Box::new(move |url| {
       // always allow navigation events for the isolation iframe and do not emit them for consumers
       #[cfg(feature = "isolation")]
-      if let crate::Pattern::Isolation { schema, .. } = &*pattern {
+      if let (true, crate::Pattern::Isolation { schema, .. }) = (isolated, &*pattern) {
         if url.scheme() == schema
           && url.domain() == Some(crate::pattern::ISOLATION_IFRAME_SRC_DOMAIN)
         {
@@ -32,9 +32,6 @@ pub enum Pattern<A: Assets = EmbeddedAssets> {
     ///
     /// This should be regenerated at runtime.
     key: String,
-
-    /// Cryptographically secure keys
-    crypto_keys: Box<tauri_utils::pattern::isolation::Keys>,
   },
 }
 
@@ -87,6 +84,27 @@ pub(crate) struct PatternJavascript {
   pub(crate) pattern: PatternObject,
 }
 
+/// Whether the IPC of a webview goes through the isolation frame,
+/// based on its `isolation` option and the application pattern.
+pub(crate) fn resolve_isolation(
+  #[allow(unused_variables)] pattern: &Pattern,
+  label: &str,
+  isolation: Option<bool>,
+) -> crate::Result<bool> {
+  #[cfg(feature = "isolation")]
+  let uses_isolation_pattern = matches!(pattern, Pattern::Isolation { .. });
+  #[cfg(not(feature = "isolation"))]
+  let uses_isolation_pattern = false;
+
+  match isolation {
+    Some(true) if !uses_isolation_pattern => {
+      Err(crate::Error::IsolationPatternNotConfigured(label.into()))
+    }
+    Some(isolation) => Ok(isolation),
+    None => Ok(uses_isolation_pattern),
+  }
+}
+
 #[allow(dead_code)]
 pub(crate) fn format_real_schema(schema: &str) -> String {
   if cfg!(windows) || cfg!(target_os = "android") {
@@ -95,3 +113,157 @@ pub(crate) fn format_real_schema(schema: &str) -> String {
     format!("{schema}://{ISOLATION_IFRAME_SRC_DOMAIN}")
   }
 }
+
+#[cfg(all(test, feature = "isolation"))]
+mod tests {
+  use std::sync::Arc;
+
+  use tauri_utils::assets::{phf, phf::phf_map, EmbeddedAssets};
+
+  use super::Pattern;
+  use crate::{
+    sealed::ManagerBase,
+    test::{mock_app, mock_builder, mock_context, noop_assets, MockRuntime},
+    App, WebviewUrl, WebviewWindowBuilder,
+  };
+
+  fn isolation_app() -> App<MockRuntime> {
+    let mut context = mock_context(noop_assets());
+    context.pattern = Pattern::Isolation {
+      assets: Arc::new(EmbeddedAssets::new(phf_map! {}, &[], phf_map! {})),
+      schema: "isolation".into(),
+      key: Default::default(),
+    };
+    mock_builder()
+      .invoke_handler(|invoke| {
+        invoke.resolver.resolve("pong");
+        true
+      })
+      .build(context)
+      .unwrap()
+  }
+
+  fn raw_key(app: &App<MockRuntime>, label: &str) -> Option<[u8; 32]> {
+    app
+      .manager()
+      .webview
+      .isolation_keys(label)
+      .map(|keys| *keys.aes_gcm().raw())
+  }
+
+  #[cfg(ipc_custom_protocol)]
+  fn invoke(
+    app: &App<MockRuntime>,
+    label: &str,
+    body: Vec<u8>,
+  ) -> http::Response<std::borrow::Cow<'static, [u8]>> {
+    let protocol = crate::ipc::protocol::get(app.manager_owned(), label.into());
+    let (tx, rx) = std::sync::mpsc::channel();
+    protocol(
+      http::Request::builder()
+        .method("POST")
+        .uri("ipc://localhost/ping")
+        .header("Tauri-Callback", "0")
+        .header("Tauri-Error", "1")
+        .header("Content-Type", "application/json")
+        .body(body)
+        .unwrap(),
+      crate::app::UriSchemeResponder(Box::new(move |response| tx.send(response).unwrap())),
+    );
+    rx.recv().unwrap()
+  }
+
+  #[cfg(ipc_custom_protocol)]
+  fn encrypt(app: &App<MockRuntime>, label: &str, payload: &[u8]) -> Vec<u8> {
+    let keys = app.manager().webview.isolation_keys(label).unwrap();
+    serde_json::to_vec(&keys.encrypt(payload).unwrap()).unwrap()
+  }
+
+  #[test]
+  fn key_per_webview() {
+    let app = isolation_app();
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+    WebviewWindowBuilder::new(
+      &app,
+      "remote",
+      WebviewUrl::External("https://tauri.app".parse().unwrap()),
+    )
+    .build()
+    .unwrap();
+
+    let main_key = raw_key(&app, "main").expect("main webview is not isolated");
+    let remote_key = raw_key(&app, "remote").expect("remote webview is not isolated");
+    assert_ne!(main_key, remote_key);
+  }
+
+  #[test]
+  fn key_rotates_on_recreate() {
+    let app = isolation_app();
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+    let first_key = raw_key(&app, "main").unwrap();
+
+    app.manager().on_window_close("main");
+    assert_eq!(raw_key(&app, "main"), None);
+
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+    let second_key = raw_key(&app, "main").unwrap();
+    assert_ne!(first_key, second_key);
+  }
+
+  #[test]
+  fn disabled_per_webview() {
+    let app = isolation_app();
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .isolation(false)
+      .build()
+      .unwrap();
+    assert_eq!(raw_key(&app, "main"), None);
+  }
+
+  #[test]
+  fn requires_isolation_pattern() {
+    let app = mock_app();
+    let result = WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .isolation(true)
+      .build();
+    assert!(matches!(
+      result,
+      Err(crate::Error::IsolationPatternNotConfigured(label)) if label == "main"
+    ));
+  }
+
+  #[cfg(ipc_custom_protocol)]
+  #[test]
+  fn ipc_through_isolation() {
+    let app = isolation_app();
+    WebviewWindowBuilder::new(&app, "main", WebviewUrl::default())
+      .build()
+      .unwrap();
+    WebviewWindowBuilder::new(&app, "other", WebviewUrl::default())
+      .build()
+      .unwrap();
+    WebviewWindowBuilder::new(&app, "brownfield", WebviewUrl::default())
+      .isolation(false)
+      .build()
+      .unwrap();
+
+    let response = invoke(&app, "main", encrypt(&app, "main", b"{}"));
+    assert_eq!(response.status(), http::StatusCode::OK);
+    assert_eq!(response.body().as_ref(), b"\"pong\"");
+
+    // isolated webviews must encrypt their payloads with their own key
+    let response = invoke(&app, "main", b"{}".to_vec());
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    let response = invoke(&app, "main", encrypt(&app, "other", b"{}"));
+    assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+
+    let response = invoke(&app, "brownfield", b"{}".to_vec());
+    assert_eq!(response.status(), http::StatusCode::OK);
+  }
+}
@@ -6,7 +6,7 @@
 
 use crate::{
   app::UriSchemeResponder,
-  ipc::{Invoke, InvokeHandler, ScopeObject, ScopeValue},
+  ipc::{codes, CommandError, Invoke, InvokeHandler, ScopeObject, ScopeValue},
   manager::webview::UriSchemeProtocol,
   utils::config::PluginConfig,
   webview::PageLoadPayload,
@@ -849,7 +849,10 @@ impl<R: Runtime> PluginStore<R> {
         return p.extend_api(invoke);
       }
     }
-    invoke.resolver.reject(format!("plugin {plugin} not found"));
+    invoke.resolver.reject(CommandError::new(
+      codes::COMMAND_NOT_FOUND,
+      format!("plugin {plugin} not found"),
+    ));
     true
   }
 }
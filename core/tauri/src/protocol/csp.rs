@@ -0,0 +1,328 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Content Security Policy violation reporting.
+
+use std::{borrow::Cow, sync::Arc};
+
+use http::{
+  header::{ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE},
+  HeaderValue, Method, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  manager::AppManager, webview::UriSchemeProtocolHandler, EventTarget, Manager, Runtime,
+};
+
+/// The custom protocol that receives the CSP violation reports.
+pub(crate) const CSP_REPORT_PROTOCOL: &str = "csp-report";
+/// The endpoint name used on the `report-to` directive and the `Reporting-Endpoints` header.
+pub(crate) const REPORT_TO_GROUP: &str = "tauri-csp";
+/// The event emitted to the webview when it violates its Content Security Policy.
+///
+/// The payload is a [`CspViolation`].
+pub const CSP_VIOLATION_EVENT: &str = "tauri://csp-violation";
+
+/// The URL of the CSP report endpoint.
+pub(crate) fn report_url() -> String {
+  if cfg!(windows) || cfg!(target_os = "android") {
+    format!("http://{CSP_REPORT_PROTOCOL}.localhost/")
+  } else {
+    format!("{CSP_REPORT_PROTOCOL}://localhost/")
+  }
+}
+
+/// A Content Security Policy violation reported by a webview.
+///
+/// Reports sent through both the `report-uri` and the `report-to` directives are normalized to this type.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CspViolation {
+  /// The URL of the document where the violation happened.
+  pub document_uri: String,
+  /// The referrer of the document.
+  pub referrer: Option<String>,
+  /// The URL of the blocked resource, or `inline` / `eval` for blocked inline code.
+  pub blocked_uri: Option<String>,
+  /// The directive whose enforcement caused the violation.
+  pub effective_directive: String,
+  /// The policy that was violated.
+  pub original_policy: String,
+  /// Either `enforce` or `report`.
+  pub disposition: Option<String>,
+  /// The URL of the script that caused the violation.
+  pub source_file: Option<String>,
+  /// The line in the source file where the violation happened.
+  pub line_number: Option<u32>,
+  /// The column in the source file where the violation happened.
+  pub column_number: Option<u32>,
+  /// The first characters of the blocked inline script or style.
+  pub sample: Option<String>,
+  /// The HTTP status code of the document.
+  pub status_code: Option<u16>,
+}
+
+/// Body sent by the `report-uri` directive.
+#[derive(Deserialize)]
+struct LegacyReport {
+  #[serde(rename = "csp-report")]
+  report: LegacyViolation,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct LegacyViolation {
+  document_uri: String,
+  referrer: Option<String>,
+  blocked_uri: Option<String>,
+  violated_directive: Option<String>,
+  effective_directive: Option<String>,
+  original_policy: String,
+  disposition: Option<String>,
+  source_file: Option<String>,
+  line_number: Option<u32>,
+  column_number: Option<u32>,
+  script_sample: Option<String>,
+  status_code: Option<u16>,
+}
+
+/// A report sent by the `report-to` directive (Reporting API).
+#[derive(Deserialize)]
+struct Report {
+  #[serde(rename = "type")]
+  kind: String,
+  #[serde(default)]
+  body: Option<ReportBody>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ReportBody {
+  #[serde(rename = "documentURL")]
+  document_url: String,
+  referrer: Option<String>,
+  #[serde(rename = "blockedURL")]
+  blocked_url: Option<String>,
+  effective_directive: String,
+  original_policy: String,
+  disposition: Option<String>,
+  source_file: Option<String>,
+  line_number: Option<u32>,
+  column_number: Option<u32>,
+  sample: Option<String>,
+  status_code: Option<u16>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ReportPayload {
+  Legacy(Box<LegacyReport>),
+  Reports(Vec<Report>),
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+  value.filter(|v| !v.is_empty())
+}
+
+impl From<LegacyViolation> for CspViolation {
+  fn from(report: LegacyViolation) -> Self {
+    Self {
+      document_uri: report.document_uri,
+      referrer: non_empty(report.referrer),
+      blocked_uri: non_empty(report.blocked_uri),
+      // older engines only send the violated directive
+      effective_directive: non_empty(report.effective_directive)
+        .or(report.violated_directive)
+        .unwrap_or_default(),
+      original_policy: report.original_policy,
+      disposition: non_empty(report.disposition),
+      source_file: non_empty(report.source_file),
+      line_number: report.line_number,
+      column_number: report.column_number,
+      sample: non_empty(report.script_sample),
+      status_code: report.status_code,
+    }
+  }
+}
+
+impl From<ReportBody> for CspViolation {
+  fn from(body: ReportBody) -> Self {
+    Self {
+      document_uri: body.document_url,
+      referrer: non_empty(body.referrer),
+      blocked_uri: non_empty(body.blocked_url),
+      effective_directive: body.effective_directive,
+      original_policy: body.original_policy,
+      disposition: non_empty(body.disposition),
+      source_file: non_empty(body.source_file),
+      line_number: body.line_number,
+      column_number: body.column_number,
+      sample: non_empty(body.sample),
+      status_code: body.status_code,
+    }
+  }
+}
+
+/// Parses a `report-uri` or `report-to` request body, ignoring reports that are not CSP violations.
+pub(crate) fn parse_reports(body: &[u8]) -> serde_json::Result<Vec<CspViolation>> {
+  let violations = match serde_json::from_slice(body)? {
+    ReportPayload::Legacy(report) => vec![report.report.into()],
+    ReportPayload::Reports(reports) => reports
+      .into_iter()
+      .filter(|report| report.kind == "csp-violation")
+      .filter_map(|report| report.body.map(Into::into))
+      .collect(),
+  };
+  Ok(violations)
+}
+
+pub fn get<R: Runtime>(manager: Arc<AppManager<R>>, label: String) -> UriSchemeProtocolHandler {
+  Box::new(move |request, responder| {
+    let respond = move |mut response: http::Response<Cow<'static, [u8]>>| {
+      response
+        .headers_mut()
+        .insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+      responder.respond(response);
+    };
+    let text_response = |status: StatusCode, message: String| {
+      let mut response = http::Response::new(message.into_bytes().into());
+      *response.status_mut() = status;
+      response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_str(mime::TEXT_PLAIN.essence_str()).unwrap(),
+      );
+      response
+    };
+
+    match *request.method() {
+      Method::POST => match parse_reports(request.body()) {
+        Ok(violations) => {
+          if let Some(webview) = manager.get_webview(&label) {
+            for violation in violations {
+              let _ = webview.emit_to(
+                EventTarget::webview(label.clone()),
+                CSP_VIOLATION_EVENT,
+                violation,
+              );
+            }
+          }
+          let mut response = http::Response::new(Vec::new().into());
+          *response.status_mut() = StatusCode::NO_CONTENT;
+          respond(response);
+        }
+        Err(e) => respond(text_response(StatusCode::BAD_REQUEST, e.to_string())),
+      },
+
+      Method::OPTIONS => {
+        let mut response = http::Response::new(Vec::new().into());
+        response.headers_mut().insert(
+          ACCESS_CONTROL_ALLOW_HEADERS,
+          HeaderValue::from_static("Content-Type"),
+        );
+        respond(response);
+      }
+
+      _ => respond(text_response(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "only POST and OPTIONS are allowed".into(),
+      )),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_report_uri() {
+    let body = br#"{
+      "csp-report": {
+        "document-uri": "tauri://localhost/",
+        "referrer": "",
+        "violated-directive": "script-src-elem",
+        "effective-directive": "script-src-elem",
+        "original-policy": "default-src 'self'",
+        "disposition": "enforce",
+        "blocked-uri": "inline",
+        "line-number": 10,
+        "column-number": 5,
+        "source-file": "tauri://localhost/",
+        "status-code": 200,
+        "script-sample": "alert(1)"
+      }
+    }"#;
+
+    assert_eq!(
+      parse_reports(body).unwrap(),
+      vec![CspViolation {
+        document_uri: "tauri://localhost/".into(),
+        referrer: None,
+        blocked_uri: Some("inline".into()),
+        effective_directive: "script-src-elem".into(),
+        original_policy: "default-src 'self'".into(),
+        disposition: Some("enforce".into()),
+        source_file: Some("tauri://localhost/".into()),
+        line_number: Some(10),
+        column_number: Some(5),
+        sample: Some("alert(1)".into()),
+        status_code: Some(200),
+      }]
+    );
+
+    // older engines do not send the effective directive
+    let body = br#"{
+      "csp-report": {
+        "document-uri": "tauri://localhost/",
+        "violated-directive": "img-src",
+        "original-policy": "img-src 'self'",
+        "blocked-uri": "https://example.com/image.png"
+      }
+    }"#;
+    let violations = parse_reports(body).unwrap();
+    assert_eq!(violations[0].effective_directive, "img-src");
+  }
+
+  #[test]
+  fn parse_report_to() {
+    let body = br#"[
+      {
+        "age": 0,
+        "type": "csp-violation",
+        "url": "http://tauri.localhost/",
+        "user_agent": "Mozilla/5.0",
+        "body": {
+          "documentURL": "http://tauri.localhost/",
+          "blockedURL": "https://example.com/script.js",
+          "effectiveDirective": "script-src-elem",
+          "originalPolicy": "default-src 'self'",
+          "disposition": "report",
+          "statusCode": 200
+        }
+      },
+      {
+        "age": 0,
+        "type": "deprecation",
+        "url": "http://tauri.localhost/",
+        "body": { "id": "deprecated-api" }
+      }
+    ]"#;
+
+    assert_eq!(
+      parse_reports(body).unwrap(),
+      vec![CspViolation {
+        document_uri: "http://tauri.localhost/".into(),
+        blocked_uri: Some("https://example.com/script.js".into()),
+        effective_directive: "script-src-elem".into(),
+        original_policy: "default-src 'self'".into(),
+        disposition: Some("report".into()),
+        status_code: Some(200),
+        ..Default::default()
+      }]
+    );
+
+    assert!(parse_reports(b"{}").is_err());
+  }
+}
@@ -4,6 +4,7 @@
 
 #[cfg(feature = "protocol-asset")]
 pub mod asset;
+pub mod csp;
 #[cfg(feature = "isolation")]
 pub mod isolation;
 pub mod tauri;
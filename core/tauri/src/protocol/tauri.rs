@@ -158,6 +158,18 @@ fn get_response<R: Runtime>(
     builder = builder.header(CONTENT_TYPE, &asset.mime_type);
     if let Some(csp) = &asset.csp_header {
       builder = builder.header("Content-Security-Policy", csp);
+      // the CSP is injected as a <meta> tag on Linux, where the report directives are stripped
+      #[cfg(not(target_os = "linux"))]
+      if manager.config().app.security.csp_report {
+        builder = builder.header(
+          "Reporting-Endpoints",
+          format!(
+            "{}=\"{}\"",
+            crate::protocol::csp::REPORT_TO_GROUP,
+            crate::protocol::csp::report_url()
+          ),
+        );
+      }
     }
     builder.body(asset.bytes.into())?
   };
@@ -168,6 +180,12 @@ fn get_response<R: Runtime>(
   #[cfg(target_os = "linux")]
   if let Some(response_csp) = response.headers().get("Content-Security-Policy") {
     let response_csp = String::from_utf8_lossy(response_csp.as_bytes());
+    // report endpoints cannot be declared in a <meta> tag
+    let response_csp = response_csp
+      .split(';')
+      .filter(|directive| !directive.trim_start().starts_with("report-"))
+      .collect::<Vec<_>>()
+      .join(";");
     let html = String::from_utf8_lossy(response.body());
     let body = html.replacen(tauri_utils::html::CSP_TOKEN, &response_csp, 1);
     *response.body_mut() = body.as_bytes().to_vec().into();
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::{
-  ipc::{CommandArg, CommandItem, InvokeError},
+  ipc::{codes, CommandArg, CommandError, CommandItem, InvokeError},
   Runtime,
 };
 use state::TypeMap;
@@ -45,14 +45,21 @@ impl<'r, T: Send + Sync + std::fmt::Debug> std::fmt::Debug for State<'r, T> {
 }
 
 impl<'r, 'de: 'r, T: Send + Sync + 'static, R: Runtime> CommandArg<'de, R> for State<'r, T> {
-  /// Grabs the [`State`] from the [`CommandItem`]. This will never fail.
+  /// Grabs the [`State`] from the [`CommandItem`].
+  ///
+  /// Fails with the [`codes::STATE_NOT_MANAGED`] error if the state was not managed.
   fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
-    Ok(command.message.state_ref().try_get().unwrap_or_else(|| {
-      panic!(
-        "state not managed for field `{}` on command `{}`. You must call `.manage()` before using this command",
-        command.key, command.name
+    command.message.state_ref().try_get().ok_or_else(|| {
+      CommandError::new(
+        codes::STATE_NOT_MANAGED,
+        format!(
+          "state not managed for field `{}` on command `{}`. You must call `.manage()` before using this command",
+          command.key, command.name
+        ),
       )
-    }))
+      .with_data(serde_json::json!({ "command": command.name, "argument": command.key }))
+      .into()
+    })
   }
 }
 
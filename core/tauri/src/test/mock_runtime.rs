@@ -7,7 +7,7 @@
 
 use tauri_runtime::{
   monitor::Monitor,
-  webview::{DetachedWebview, PendingWebview},
+  webview::{DetachedWebview, PageLoadEvent, PendingWebview},
   window::{
     dpi::{PhysicalPosition, PhysicalSize, Position, Size},
     CursorIcon, DetachedWindow, PendingWindow, RawWindow, WindowEvent, WindowId,
@@ -155,14 +155,18 @@ impl<T: UserEvent> RuntimeHandle<T> for MockRuntimeHandle {
       },
     );
 
-    let webview = webview_id.map(|id| DetachedWebview {
-      label: pending.label.clone(),
-      dispatcher: MockWebviewDispatcher {
-        id,
-        context: self.context.clone(),
-        url: Arc::new(Mutex::new(pending.webview.unwrap().url)),
-        last_evaluated_script: Default::default(),
-      },
+    let webview = webview_id.map(|id| {
+      let pending_webview = pending.webview.unwrap();
+      DetachedWebview {
+        label: pending.label.clone(),
+        dispatcher: MockWebviewDispatcher {
+          id,
+          context: self.context.clone(),
+          url: Arc::new(Mutex::new(pending_webview.url)),
+          last_evaluated_script: Default::default(),
+          on_page_load_handler: Arc::new(Mutex::new(pending_webview.on_page_load_handler)),
+        },
+      }
     });
 
     Ok(DetachedWindow {
@@ -194,6 +198,7 @@ impl<T: UserEvent> RuntimeHandle<T> for MockRuntimeHandle {
         context: self.context.clone(),
         last_evaluated_script: Default::default(),
         url: Arc::new(Mutex::new(pending.url)),
+        on_page_load_handler: Arc::new(Mutex::new(pending.on_page_load_handler)),
       },
     })
   }
@@ -267,12 +272,26 @@ impl<T: UserEvent> RuntimeHandle<T> for MockRuntimeHandle {
   }
 }
 
-#[derive(Debug, Clone)]
+type OnPageLoadHandler = Box<dyn Fn(Url, PageLoadEvent) + Send>;
+
+#[derive(Clone)]
 pub struct MockWebviewDispatcher {
   id: u32,
   context: RuntimeContext,
   url: Arc<Mutex<String>>,
   last_evaluated_script: Arc<Mutex<Option<String>>>,
+  on_page_load_handler: Arc<Mutex<Option<OnPageLoadHandler>>>,
+}
+
+impl fmt::Debug for MockWebviewDispatcher {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("MockWebviewDispatcher")
+      .field("id", &self.id)
+      .field("context", &self.context)
+      .field("url", &self.url)
+      .field("last_evaluated_script", &self.last_evaluated_script)
+      .finish()
+  }
 }
 
 impl MockWebviewDispatcher {
@@ -506,6 +525,10 @@ impl<T: UserEvent> WebviewDispatch<T> for MockWebviewDispatcher {
 
   fn navigate(&self, url: Url) -> Result<()> {
     *self.url.lock().unwrap() = url.to_string();
+    if let Some(handler) = &*self.on_page_load_handler.lock().unwrap() {
+      handler(url.clone(), PageLoadEvent::Started);
+      handler(url, PageLoadEvent::Finished);
+    }
     Ok(())
   }
 
@@ -709,14 +732,18 @@ impl<T: UserEvent> WindowDispatch<T> for MockWindowDispatcher {
       },
     );
 
-    let webview = webview_id.map(|id| DetachedWebview {
-      label: pending.label.clone(),
-      dispatcher: MockWebviewDispatcher {
-        id,
-        context: self.context.clone(),
-        url: Arc::new(Mutex::new(pending.webview.unwrap().url)),
-        last_evaluated_script: Default::default(),
-      },
+    let webview = webview_id.map(|id| {
+      let pending_webview = pending.webview.unwrap();
+      DetachedWebview {
+        label: pending.label.clone(),
+        dispatcher: MockWebviewDispatcher {
+          id,
+          context: self.context.clone(),
+          url: Arc::new(Mutex::new(pending_webview.url)),
+          last_evaluated_script: Default::default(),
+          on_page_load_handler: Arc::new(Mutex::new(pending_webview.on_page_load_handler)),
+        },
+      }
     });
 
     Ok(DetachedWindow {
@@ -747,6 +774,7 @@ impl<T: UserEvent> WindowDispatch<T> for MockWindowDispatcher {
         context: self.context.clone(),
         last_evaluated_script: Default::default(),
         url: Arc::new(Mutex::new(pending.url)),
+        on_page_load_handler: Arc::new(Mutex::new(pending.on_page_load_handler)),
       },
     })
   }
@@ -977,14 +1005,18 @@ impl<T: UserEvent> Runtime<T> for MockRuntime {
       },
     );
 
-    let webview = webview_id.map(|id| DetachedWebview {
-      label: pending.label.clone(),
-      dispatcher: MockWebviewDispatcher {
-        id,
-        context: self.context.clone(),
-        url: Arc::new(Mutex::new(pending.webview.unwrap().url)),
-        last_evaluated_script: Default::default(),
-      },
+    let webview = webview_id.map(|id| {
+      let pending_webview = pending.webview.unwrap();
+      DetachedWebview {
+        label: pending.label.clone(),
+        dispatcher: MockWebviewDispatcher {
+          id,
+          context: self.context.clone(),
+          url: Arc::new(Mutex::new(pending_webview.url)),
+          last_evaluated_script: Default::default(),
+          on_page_load_handler: Arc::new(Mutex::new(pending_webview.on_page_load_handler)),
+        },
+      }
     });
 
     Ok(DetachedWindow {
@@ -1016,6 +1048,7 @@ impl<T: UserEvent> Runtime<T> for MockRuntime {
         context: self.context.clone(),
         last_evaluated_script: Default::default(),
         url: Arc::new(Mutex::new(pending.url)),
+        on_page_load_handler: Arc::new(Mutex::new(pending.on_page_load_handler)),
       },
     })
   }
@@ -50,14 +50,22 @@
 
 mod mock_runtime;
 pub use mock_runtime::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use std::{borrow::Cow, collections::HashMap, fmt::Debug};
+use std::{
+  borrow::Cow,
+  collections::{BTreeMap, HashMap},
+  fmt::Debug,
+  sync::mpsc::RecvTimeoutError,
+  time::Duration,
+};
 
 use crate::{
-  ipc::{InvokeBody, InvokeError, InvokeResponse},
+  ipc::{
+    channel::ChannelDataIpcQueue, stream::StreamAcks, InvokeBody, InvokeError, InvokeResponse,
+  },
   webview::InvokeRequest,
-  App, Builder, Context, Pattern, Webview,
+  App, Builder, Context, Manager, Pattern, Webview,
 };
 use tauri_utils::{
   acl::resolved::Resolved,
@@ -270,11 +278,114 @@ pub fn get_ipc_response<W: AsRef<Webview<MockRuntime>>>(
 
   let res = rx.recv().expect("Failed to receive result from command");
   match res {
-    InvokeResponse::Ok(b) => Ok(b),
+    InvokeResponse::Ok(response) => Ok(response.into_payload()),
     InvokeResponse::Err(InvokeError(v)) => Err(v),
   }
 }
 
+/// Executes the given IPC message of a command that returns a stream and collects its items.
+///
+/// The stream channel header is set to the request callback id, and each item is acknowledged once it is received.
+/// Returns the error of the command or the one yielded by the stream.
+///
+/// # Examples
+///
+/// ```rust
+/// use futures_util::Stream;
+/// use tauri::test::{mock_builder, mock_context, noop_assets};
+///
+/// #[tauri::command]
+/// async fn count(to: u32) -> impl Stream<Item = u32> {
+///     futures_util::stream::iter(0..to)
+/// }
+///
+/// fn main() {
+///     let app = mock_builder()
+///         .invoke_handler(tauri::generate_handler![count])
+///         .build(mock_context(noop_assets()))
+///         .unwrap();
+///     let webview = tauri::WebviewWindowBuilder::new(&app, "main", Default::default()).build().unwrap();
+///
+///     let items = tauri::test::get_ipc_stream(
+///         &webview,
+///         tauri::webview::InvokeRequest {
+///             cmd: "count".into(),
+///             callback: tauri::ipc::CallbackFn(0),
+///             error: tauri::ipc::CallbackFn(1),
+///             body: serde_json::json!({ "to": 3 }).into(),
+///             headers: Default::default(),
+///         },
+///     );
+///     assert_eq!(items, Ok(vec![0.into(), 1.into(), 2.into()]));
+/// }
+///```
+pub fn get_ipc_stream<W: AsRef<Webview<MockRuntime>>>(
+  webview: &W,
+  mut request: InvokeRequest,
+) -> Result<Vec<serde_json::Value>, serde_json::Value> {
+  #[derive(Deserialize)]
+  struct StreamMessage {
+    index: u64,
+    event: String,
+    #[serde(default)]
+    data: serde_json::Value,
+  }
+
+  let webview = webview.as_ref().clone();
+  let channel = request.callback.0;
+  request.headers.insert(
+    crate::ipc::stream::STREAM_CHANNEL_HEADER_NAME,
+    channel.into(),
+  );
+
+  let (tx, rx) = std::sync::mpsc::sync_channel(1);
+  webview.clone().on_message(
+    request,
+    Box::new(move |_window, _cmd, response, _callback, _error| {
+      tx.send(response).unwrap();
+    }),
+  );
+
+  let queue = webview.state::<ChannelDataIpcQueue>();
+  let acks = webview.state::<StreamAcks>();
+  let mut pending = BTreeMap::new();
+  let mut items = Vec::new();
+  loop {
+    queue.0.lock().unwrap().retain(|_id, body| {
+      let message = match body {
+        InvokeBody::Json(value) => StreamMessage::deserialize(&*value).ok(),
+        InvokeBody::Raw(_) => None,
+      };
+      match message {
+        Some(message) => {
+          pending.insert(message.index, message);
+          false
+        }
+        None => true,
+      }
+    });
+
+    while let Some(message) = pending.remove(&(items.len() as u64)) {
+      match message.event.as_str() {
+        "item" => {
+          acks.ack(channel, message.index);
+          items.push(message.data);
+        }
+        "end" => return Ok(items),
+        _ => return Err(message.data),
+      }
+    }
+
+    // the terminal message is sent before the call resolves,
+    // so the loop only ends here if the call is rejected before streaming or does not stream at all
+    match rx.recv_timeout(Duration::from_millis(10)) {
+      Ok(InvokeResponse::Err(InvokeError(e))) => return Err(e),
+      Ok(InvokeResponse::Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+      Err(RecvTimeoutError::Disconnected) => panic!("the command did not return a stream"),
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use std::time::Duration;
@@ -0,0 +1,27 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+Object.defineProperty(window.__TAURI_INTERNALS__.plugins, 'webdriver', {
+  value: {
+    buffers: {},
+    listen: async function (event) {
+      const buffer = []
+      const handler = window.__TAURI_INTERNALS__.transformCallback(
+        function (e) {
+          buffer.push(e)
+        }
+      )
+      const id = await window.__TAURI_INTERNALS__.invoke(
+        'plugin:webdriver|listen',
+        { event: event, handler: handler }
+      )
+      this.buffers[id] = buffer
+      return id
+    },
+    takeEvents: function (id) {
+      const buffer = this.buffers[id]
+      return buffer ? buffer.splice(0) : []
+    }
+  }
+})
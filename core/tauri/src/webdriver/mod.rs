@@ -0,0 +1,90 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Test-only plugin backing the Tauri WebDriver extension commands of `tauri-driver`.
+//!
+//! `tauri-driver` exposes commands to invoke IPC commands, emit and listen to events
+//! and list or switch windows by label. Link this plugin in the builds you run end-to-end tests on
+//! and add the `webdriver:default` permission to the capability of the tested windows:
+//!
+//! ```rust,no_run
+//! tauri::Builder::default()
+//!   .plugin(tauri::webdriver::init());
+//! ```
+//!
+//! This plugin gives the frontend access to every event and window label,
+//! so it must not be shipped in production builds.
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::{
+  command,
+  event::{plugin::EventName, EventTarget},
+  ipc::CallbackFn,
+  plugin::{Builder, TauriPlugin},
+  sealed::ManagerBase,
+  AppHandle, EventId, Manager, Result, Runtime, Webview,
+};
+
+/// A window and the labels of the webviews it hosts.
+#[derive(Serialize)]
+struct WindowInfo {
+  label: String,
+  webviews: Vec<String>,
+}
+
+#[command(root = "crate")]
+fn windows<R: Runtime>(app: AppHandle<R>) -> Vec<WindowInfo> {
+  let webviews = app.manager().webviews();
+  let mut windows = app
+    .manager()
+    .windows()
+    .into_keys()
+    .map(|label| {
+      let mut webview_labels = webviews
+        .values()
+        .filter(|webview| webview.window().label() == label)
+        .map(|webview| webview.label().to_string())
+        .collect::<Vec<_>>();
+      webview_labels.sort();
+      WindowInfo {
+        label,
+        webviews: webview_labels,
+      }
+    })
+    .collect::<Vec<_>>();
+  windows.sort_by(|a, b| a.label.cmp(&b.label));
+  windows
+}
+
+#[command(root = "crate")]
+fn emit<R: Runtime>(
+  app: AppHandle<R>,
+  event: EventName,
+  payload: Option<JsonValue>,
+  target: Option<EventTarget>,
+) -> Result<()> {
+  match target {
+    Some(target) => app.emit_to(target, &event, payload),
+    None => app.emit(&event, payload),
+  }
+}
+
+#[command(root = "crate")]
+fn listen<R: Runtime>(
+  webview: Webview<R>,
+  event: EventName,
+  handler: CallbackFn,
+) -> Result<EventId> {
+  webview.listen_js(&event, EventTarget::Any, handler)
+}
+
+/// Initializes the WebDriver test plugin.
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+  Builder::new("webdriver")
+    .invoke_handler(crate::generate_handler![windows, emit, listen])
+    .js_init_script(include_str!("./init.js").into())
+    .build()
+}
@@ -22,15 +22,24 @@ use tauri_runtime::{
   window::dpi::{PhysicalPosition, PhysicalSize, Position, Size},
   WindowDispatch,
 };
+use tauri_utils::acl::args::ArgsError;
 use tauri_utils::config::{WebviewUrl, WindowConfig};
 pub use url::Url;
 
+pub use crate::protocol::csp::{CspViolation, CSP_VIOLATION_EVENT};
+
 use crate::{
   app::UriSchemeResponder,
   event::{EmitArgs, EventTarget},
   ipc::{
-    CallbackFn, CommandArg, CommandItem, Invoke, InvokeBody, InvokeError, InvokeMessage,
-    InvokeResolver, Origin, OwnedInvokeResponder,
+    audit::{AuditDecision, AuditRecord},
+    batch::{self, BATCH_INVOKE_COMMAND},
+    cancellation::CANCEL_INVOKE_COMMAND,
+    codes,
+    encoding::DecodedPayload,
+    limits::{InFlightGuard, IpcLimitCounters},
+    timeout, CallbackFn, CancellationToken, CommandArg, CommandError, CommandItem, Invoke,
+    InvokeBody, InvokeError, InvokeMessage, InvokeResolver, Origin, OwnedInvokeResponder,
   },
   manager::{webview::WebviewLabelDef, AppManager},
   sealed::{ManagerBase, RuntimeOrDispatch},
@@ -226,6 +235,7 @@ unstable_struct!(
     pub(crate) navigation_handler: Option<Box<NavigationHandler>>,
     pub(crate) on_page_load_handler: Option<Box<OnPageLoad<R>>>,
     pub(crate) download_handler: Option<Arc<DownloadHandler<R>>>,
+    pub(crate) isolation: Option<bool>,
   }
 );
 
@@ -302,6 +312,7 @@ async fn create_window(app: tauri::AppHandle) {
       navigation_handler: None,
       on_page_load_handler: None,
       download_handler: None,
+      isolation: None,
     }
   }
 
@@ -342,6 +353,7 @@ async fn reopen_window(app: tauri::AppHandle) {
       navigation_handler: None,
       on_page_load_handler: None,
       download_handler: None,
+      isolation: config.isolation,
     }
   }
 
@@ -583,6 +595,7 @@ tauri::Builder::default()
       window_label,
       window_labels,
       webview_labels,
+      self.isolation,
     )
   }
 
@@ -758,6 +771,19 @@ fn main() {
     self
   }
 
+  /// Whether the IPC calls of this webview go through the isolation frame.
+  ///
+  /// Defaults to `true` when the application uses the isolation pattern, including webviews that load remote content.
+  /// Each isolated webview encrypts its IPC payloads with its own key, regenerated whenever the webview is created.
+  ///
+  /// Building the webview fails if isolation is enabled and the application does not use the isolation pattern.
+  #[cfg(feature = "isolation")]
+  #[must_use]
+  pub fn isolation(mut self, isolation: bool) -> Self {
+    self.isolation.replace(isolation);
+    self
+  }
+
   /// Set a proxy URL for the WebView for all network requests.
   ///
   /// Must be either a `http://` or a `socks5://` URL.
@@ -1065,19 +1091,86 @@ fn main() {
       })
   }
 
+  /// The counters of the [limits](crate::utils::acl::limits::CommandLimits) of a command called by this webview.
+  ///
+  /// Returns `None` if the command is not limited or was never called by this webview.
+  pub fn ipc_limit_counters(&self, command: &str) -> Option<IpcLimitCounters> {
+    self
+      .manager()
+      .runtime_authority
+      .limiter
+      .counters(&self.webview.label, command)
+  }
+
   /// Handles this window receiving an [`InvokeRequest`].
   pub fn on_message(self, request: InvokeRequest, responder: Box<OwnedInvokeResponder<R>>) {
+    self.handle_message(request, responder, None)
+  }
+
+  /// Handles a call of a batch, cancelled along with the batch instead of being registered as an in-flight call.
+  pub(crate) fn on_batch_message(
+    self,
+    request: InvokeRequest,
+    responder: Box<OwnedInvokeResponder<R>>,
+    batch: &CancellationToken,
+  ) {
+    self.handle_message(request, responder, Some(batch))
+  }
+
+  fn handle_message(
+    self,
+    request: InvokeRequest,
+    responder: Box<OwnedInvokeResponder<R>>,
+    batch: Option<&CancellationToken>,
+  ) {
     let manager = self.manager_owned();
     let current_url = self.url();
     let is_local = self.is_local_url(&current_url);
 
     let custom_responder = self.manager().webview.invoke_responder.clone();
+    // holds the call as in flight for the command limits until it is resolved
+    let in_flight: Arc<Mutex<Option<InFlightGuard>>> = Default::default();
+    let in_flight_ = in_flight.clone();
+
+    let cancellations = &manager.webview.cancellations;
+    if request.cmd == CANCEL_INVOKE_COMMAND {
+      let response = cancellations.cancel_invoke(self.label(), &request.body);
+      if let Some(responder) = &custom_responder {
+        (responder)(
+          &self,
+          &request.cmd,
+          &response,
+          request.callback,
+          request.error,
+        );
+      }
+      responder(self, request.cmd, response, request.callback, request.error);
+      return;
+    }
+    if request.cmd == BATCH_INVOKE_COMMAND {
+      batch::run(self, request, responder);
+      return;
+    }
+    let registered = batch.is_none();
+    let cancellation = match batch {
+      Some(batch) => batch.child(),
+      None => cancellations.register(self.label(), request.callback),
+    };
 
     let resolver = InvokeResolver::new(
       self.clone(),
       Arc::new(Mutex::new(Some(Box::new(
         #[allow(unused_variables)]
         move |webview: Webview<R>, cmd, response, callback, error| {
+          in_flight_.lock().unwrap().take();
+          if registered {
+            webview
+              .manager()
+              .webview
+              .cancellations
+              .unregister(webview.label(), callback);
+          }
+
           if let Some(responder) = &custom_responder {
             (responder)(&webview, &cmd, &response, callback, error);
           }
@@ -1088,8 +1181,19 @@ fn main() {
       request.cmd.clone(),
       request.callback,
       request.error,
+      cancellation.clone(),
+      manager.webview.command_timeout,
+      timeout::deadline(&request.headers),
     );
 
+    let decoded_payload = match DecodedPayload::from_request(&request.headers, &request.body) {
+      Ok(decoded_payload) => decoded_payload,
+      Err(e) => {
+        resolver.reject(e);
+        return;
+      }
+    };
+
     #[cfg(mobile)]
     let app_handle = self.window.app_handle.clone();
 
@@ -1098,23 +1202,60 @@ fn main() {
       manager.state(),
       request.cmd.to_string(),
       request.body,
+      decoded_payload,
       request.headers,
+      cancellation,
     );
 
     let acl_origin = if is_local {
       Origin::Local
     } else {
-      Origin::Remote {
-        domain: current_url
-          .domain()
-          .map(|d| d.to_string())
-          .unwrap_or_default(),
+      Origin::Remote { url: current_url }
+    };
+    let resolution = manager.runtime_authority.resolve_access_decision(
+      &request.cmd,
+      &message.webview.webview.label,
+      &acl_origin,
+    );
+
+    // recorded once the argument constraints and limits are checked, with the final decision
+    let audit_record = manager
+      .webview
+      .ipc_audit
+      .as_ref()
+      .filter(|_| !crate::ipc::channel::is_channel_command(&request.cmd))
+      .map(|_| {
+        let (plugin, command) = match request.cmd.strip_prefix("plugin:") {
+          Some(raw_command) => {
+            let (plugin, command) = raw_command.split_once('|').unwrap_or((raw_command, ""));
+            (Some(plugin.to_string()), command.to_string())
+          }
+          None => (None, request.cmd.clone()),
+        };
+        AuditRecord {
+          timestamp: AuditRecord::now(),
+          command,
+          plugin,
+          webview: message.webview.webview.label.clone(),
+          window: message.webview.window.label().to_string(),
+          origin: acl_origin.to_string(),
+          capability: resolution.capability.map(ToString::to_string),
+          decision: resolution.decision,
+        }
+      });
+    let record_audit = |webview: &Webview<R>, decision: AuditDecision| {
+      if let (Some(audit), Some(record)) = (&manager.webview.ipc_audit, &audit_record) {
+        audit.record(
+          webview.app_handle(),
+          AuditRecord {
+            decision,
+            ..record.clone()
+          },
+        );
       }
     };
-    let resolved_acl = manager
-      .runtime_authority
-      .resolve_access(&request.cmd, &message.webview.webview.label, &acl_origin)
-      .cloned();
+
+    let resolved_acl = resolution.command.cloned();
 
     let mut invoke = Invoke {
       message,
@@ -1122,6 +1263,56 @@ fn main() {
       acl: resolved_acl,
     };
 
+    // enforce the argument constraints of the permissions allowing this command
+    if let Some(acl) = &invoke.acl {
+      let decoded_args = invoke
+        .message
+        .decoded_payload
+        .as_ref()
+        .map(DecodedPayload::to_json);
+      let args = match (&decoded_args, &invoke.message.payload) {
+        (Some(args), _) | (None, InvokeBody::Json(args)) => args,
+        (None, InvokeBody::Raw(_)) => &serde_json::Value::Null,
+      };
+      if let Err(violations) = acl.validate_args(&invoke.message.webview.webview.label, args) {
+        record_audit(&invoke.message.webview, AuditDecision::ArgsDenied);
+        let error = ArgsError {
+          command: request.cmd.clone(),
+          violations,
+        };
+        invoke
+          .resolver
+          .reject(CommandError::new(codes::ARGS_DENIED, error.to_string()).with_data(error));
+        return;
+      }
+    }
+
+    // enforce the limits of the permissions allowing this command on this webview
+    let limits = invoke
+      .acl
+      .as_ref()
+      .and_then(|acl| acl.limits(&invoke.message.webview.webview.label));
+    if let Some(limits) = limits {
+      match manager.runtime_authority.limiter.acquire(
+        &invoke.message.webview.webview.label,
+        &request.cmd,
+        &limits,
+      ) {
+        Ok(guard) => {
+          in_flight.lock().unwrap().replace(guard);
+        }
+        Err(error) => {
+          record_audit(&invoke.message.webview, AuditDecision::RateLimited);
+          invoke
+            .resolver
+            .reject(CommandError::new(codes::RATE_LIMITED, error.to_string()).with_data(error));
+          return;
+        }
+      }
+    }
+
+    record_audit(&invoke.message.webview, resolution.decision);
+
     if let Some((plugin, command_name)) = request.cmd.strip_prefix("plugin:").map(|raw_command| {
       let mut tokens = raw_command.split('|');
       // safe to unwrap: split always has a least one item
@@ -1129,22 +1320,19 @@ fn main() {
       let command = tokens.next().map(|c| c.to_string()).unwrap_or_default();
       (plugin, command)
     }) {
-      if request.cmd != crate::ipc::channel::FETCH_CHANNEL_DATA_COMMAND && invoke.acl.is_none() {
+      if !crate::ipc::channel::is_channel_command(&request.cmd) && invoke.acl.is_none() {
         #[cfg(debug_assertions)]
-        {
-          invoke
-            .resolver
-            .reject(manager.runtime_authority.resolve_access_message(
-              plugin,
-              &command_name,
-              &invoke.message.webview.webview.label,
-              &acl_origin,
-            ));
-        }
+        let message = manager.runtime_authority.resolve_access_message(
+          Some(plugin),
+          &command_name,
+          &invoke.message.webview.webview.label,
+          &acl_origin,
+        );
         #[cfg(not(debug_assertions))]
+        let message = format!("Command {} not allowed by ACL", request.cmd);
         invoke
           .resolver
-          .reject(format!("Command {} not allowed by ACL", request.cmd));
+          .reject(CommandError::new(codes::ACL_DENIED, message));
         return;
       }
 
@@ -1198,13 +1386,35 @@ fn main() {
       }
 
       if !handled {
-        resolver.reject(format!("Command {command} not found"));
+        resolver.reject(CommandError::new(
+          codes::COMMAND_NOT_FOUND,
+          format!("Command {command} not found"),
+        ));
       }
     } else {
+      if manager.config().app.security.app_acl && invoke.acl.is_none() {
+        #[cfg(debug_assertions)]
+        let message = manager.runtime_authority.resolve_access_message(
+          None,
+          &request.cmd,
+          &invoke.message.webview.webview.label,
+          &acl_origin,
+        );
+        #[cfg(not(debug_assertions))]
+        let message = format!("Command {} not allowed by ACL", request.cmd);
+        invoke
+          .resolver
+          .reject(CommandError::new(codes::ACL_DENIED, message));
+        return;
+      }
+
       let command = invoke.message.command.clone();
       let handled = manager.run_invoke_handler(invoke);
       if !handled {
-        resolver.reject(format!("Command {command} not found"));
+        resolver.reject(CommandError::new(
+          codes::COMMAND_NOT_FOUND,
+          format!("Command {command} not found"),
+        ));
       }
     }
   }
@@ -818,6 +818,19 @@ fn main() {
     self
   }
 
+  /// Whether the IPC calls of this webview go through the isolation frame.
+  ///
+  /// Defaults to `true` when the application uses the isolation pattern, including webviews that load remote content.
+  /// Each isolated webview encrypts its IPC payloads with its own key, regenerated whenever the webview is created.
+  ///
+  /// Building the webview fails if isolation is enabled and the application does not use the isolation pattern.
+  #[cfg(feature = "isolation")]
+  #[must_use]
+  pub fn isolation(mut self, isolation: bool) -> Self {
+    self.webview_builder = self.webview_builder.isolation(isolation);
+    self
+  }
+
   /// Sets the webview to automatically grow and shrink its size and position when the parent window resizes.
   #[must_use]
   pub fn auto_resize(mut self) -> Self {
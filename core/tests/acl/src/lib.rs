@@ -12,7 +12,7 @@ mod tests {
   };
 
   use tauri_utils::{
-    acl::{build::parse_capabilities, plugin::Manifest, resolved::Resolved},
+    acl::{build::parse_capabilities, plugin::Manifest, report::Report, resolved::Resolved},
     platform::Target,
   };
 
@@ -71,4 +71,36 @@ mod tests {
       );
     }
   }
+
+  #[test]
+  fn report_acl() {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_snapshot_path("../fixtures/snapshots");
+    let _guard = settings.bind_to_scope();
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let fixtures_path = manifest_dir.join("fixtures").join("capabilities");
+    for fixture_path in read_dir(fixtures_path).expect("failed to read fixtures") {
+      let fixture_entry = fixture_path.expect("failed to read fixture entry");
+      let fixture_plugins_str = read_to_string(fixture_entry.path().join("required-plugins.json"))
+        .expect("failed to read fixture required-plugins.json file");
+      let fixture_plugins: Vec<String> = serde_json::from_str(&fixture_plugins_str)
+        .expect("required-plugins.json is not a valid JSON");
+
+      let manifests = load_plugins(&fixture_plugins);
+      let capabilities = parse_capabilities(&format!("{}/cap*", fixture_entry.path().display()))
+        .expect("failed to parse capabilities");
+
+      let report =
+        Report::new(&manifests, &capabilities, Target::current()).expect("failed to report ACL");
+
+      insta::assert_snapshot!(
+        format!(
+          "report-{}",
+          fixture_entry.path().file_name().unwrap().to_string_lossy()
+        ),
+        serde_json::to_string_pretty(&report).unwrap()
+      );
+    }
+  }
 }
@@ -94,13 +94,35 @@ async function addPluginListener<T>(
  *
  * @since 1.0.0
  */
-type InvokeArgs = Record<string, unknown> | number[] | ArrayBuffer | Uint8Array
+type InvokeArgs =
+  | Record<string, unknown>
+  | number[]
+  | ArrayBuffer
+  | Uint8Array
+  | FormData
 
 /**
  * @since 2.0.0
  */
 interface InvokeOptions {
-  headers: Headers | Record<string, string>
+  headers?: Headers | Record<string, string>
+  /**
+   * Cancels the command when aborted.
+   *
+   * An async command still running is dropped and the promise rejects with a `TAURI_CANCELLED` error.
+   *
+   * @since 2.0.0
+   */
+  signal?: AbortSignal
+  /**
+   * The number of milliseconds the command may take.
+   *
+   * An async command still running after this delay is dropped and the promise rejects with a `TAURI_TIMED_OUT` error,
+   * even if the command has a longer timeout.
+   *
+   * @since 2.0.0
+   */
+  timeout?: number
 }
 
 /**
@@ -109,6 +131,27 @@ interface InvokeOptions {
  * ```typescript
  * import { invoke } from '@tauri-apps/api/core';
  * await invoke('login', { user: 'tauri', password: 'poiwe3h4r5ip3yrhtew9ty' });
+ *
+ * // send binary fields without converting them to number arrays,
+ * // requires the `ipc-form-data` feature of the `tauri` crate
+ * const form = new FormData();
+ * form.append('name', 'avatar.png');
+ * form.append('image', new Blob([bytes]));
+ * await invoke('upload', form);
+ *
+ * // a body encoded with a MessagePack library,
+ * // requires the `ipc-msgpack` feature of the `tauri` crate
+ * await invoke('save', encode({ id: 1, data: bytes }), {
+ *   headers: { 'Content-Type': 'application/msgpack' }
+ * });
+ *
+ * // cancel a long running command
+ * const controller = new AbortController();
+ * invoke('search', { query: 'tauri' }, { signal: controller.signal });
+ * controller.abort();
+ *
+ * // reject the call if the command takes longer than 5 seconds
+ * await invoke('sync', {}, { timeout: 5000 });
  * ```
  *
  * @param cmd The command name.
@@ -126,6 +169,251 @@ async function invoke<T>(
   return window.__TAURI_INTERNALS__.invoke(cmd, args, options)
 }
 
+/**
+ * The response of a command, with the status and headers set by the command.
+ *
+ * @since 2.0.0
+ */
+interface InvokeResponse<T> {
+  /** The response body, the value {@linkcode invoke} resolves to. */
+  body: T
+  /** The response status, `200` unless the command returns a `tauri::ipc::Response` with another status. */
+  status: number
+  /** The response headers, with lowercase names. */
+  headers: Record<string, string>
+}
+
+/**
+ * Sends a message to the backend, resolving to the response body along with its status and headers.
+ * @example
+ * ```typescript
+ * import { invokeWithResponse } from '@tauri-apps/api/core';
+ * const { body, headers } = await invokeWithResponse<string[]>('read_page', { cursor: null });
+ * const nextCursor = headers['x-next-cursor'];
+ * ```
+ *
+ * @param cmd The command name.
+ * @param args The optional arguments to pass to the command.
+ * @param options The request options.
+ * @return A promise resolving to the backend response or rejecting to the backend error.
+ *
+ * @since 2.0.0
+ */
+async function invokeWithResponse<T>(
+  cmd: string,
+  args: InvokeArgs = {},
+  options?: InvokeOptions
+): Promise<InvokeResponse<T>> {
+  return window.__TAURI_INTERNALS__.invoke(cmd, args, {
+    ...options,
+    withResponse: true
+  })
+}
+
+/**
+ * A command call of {@linkcode invokeBatch}.
+ *
+ * @since 2.0.0
+ */
+interface BatchInvokeEntry {
+  /** The command name. */
+  cmd: string
+  /** The command arguments. */
+  args?: Record<string, unknown>
+  /** The request headers. */
+  headers?: Record<string, string>
+}
+
+/**
+ * The result of a command call of {@linkcode invokeBatch}.
+ *
+ * @since 2.0.0
+ */
+type BatchInvokeResult<T = unknown> =
+  | ({ ok: true } & InvokeResponse<T>)
+  | { ok: false; error: unknown }
+
+/**
+ * @since 2.0.0
+ */
+interface BatchInvokeOptions {
+  /**
+   * Whether the commands run one after the other or all at once. Defaults to `sequential`.
+   */
+  mode?: 'sequential' | 'parallel'
+  /**
+   * Cancels all calls of the batch when aborted.
+   */
+  signal?: AbortSignal
+}
+
+/**
+ * Calls multiple commands in a single IPC round-trip.
+ *
+ * Each call is checked against the capabilities like any other call,
+ * and a failing call does not prevent the other calls from running.
+ * A batch can have up to 256 calls.
+ * @example
+ * ```typescript
+ * import { invokeBatch } from '@tauri-apps/api/core';
+ * const [settings, user] = await invokeBatch(
+ *   [{ cmd: 'get_settings' }, { cmd: 'get_user', args: { id: 1 } }],
+ *   { mode: 'parallel' }
+ * );
+ * if (user.ok) {
+ *   console.log(user.body);
+ * } else {
+ *   console.error(user.error);
+ * }
+ * ```
+ *
+ * @param entries The command calls.
+ * @param options The batch options.
+ * @return A promise resolving to the results of the calls, in the order of the entries.
+ *
+ * @since 2.0.0
+ */
+async function invokeBatch(
+  entries: BatchInvokeEntry[],
+  options?: BatchInvokeOptions
+): Promise<BatchInvokeResult[]> {
+  return invoke(
+    '__TAURI_BATCH_INVOKE__',
+    {
+      mode: options?.mode ?? 'sequential',
+      entries: entries.map(({ cmd, args, headers }) => ({
+        cmd,
+        body: args ?? {},
+        headers: headers ?? {}
+      }))
+    },
+    { signal: options?.signal }
+  )
+}
+
+/**
+ * The error envelope of the commands returning a structured error.
+ *
+ * Codes starting with `TAURI_` are reserved for the errors produced by Tauri,
+ * e.g. `TAURI_ACL_DENIED`, `TAURI_COMMAND_NOT_FOUND` or `TAURI_INVALID_ARGS`.
+ *
+ * @since 2.0.0
+ */
+interface CommandError<D = unknown> {
+  /** The stable error code. */
+  code: string
+  /** The human readable error message. */
+  message: string
+  /** Additional data about the error. */
+  data?: D
+}
+
+/**
+ * Checks whether a command rejection is a {@linkcode CommandError}.
+ * @example
+ * ```typescript
+ * import { invoke, isCommandError } from '@tauri-apps/api/core';
+ * try {
+ *   await invoke('read_file', { path: 'a.txt' });
+ * } catch (e) {
+ *   if (isCommandError(e) && e.code === 'NOT_FOUND') {
+ *     // handle the missing file
+ *   }
+ * }
+ * ```
+ *
+ * @since 2.0.0
+ */
+function isCommandError(error: unknown): error is CommandError {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as CommandError).code === 'string' &&
+    typeof (error as CommandError).message === 'string'
+  )
+}
+
+type StreamMessage<T> =
+  | { index: number; event: 'item'; data: T }
+  | { index: number; event: 'end' }
+  | { index: number; event: 'error'; data: unknown }
+
+/**
+ * How many items are received before acknowledging them to the backend.
+ * Must be lower than the window of unacknowledged items the backend sends.
+ */
+const STREAM_ACK_INTERVAL = 8
+
+/**
+ * Calls a command that returns a stream, running `onItem` for each item in order.
+ *
+ * The backend waits for the items to be received before producing more of them.
+ * @example
+ * ```typescript
+ * import { invokeStream } from '@tauri-apps/api/core';
+ * await invokeStream<string>('read_lines', { path: 'log.txt' }, (line) => {
+ *   console.log(line);
+ * });
+ * ```
+ *
+ * @param cmd The command name.
+ * @param args The optional arguments to pass to the command.
+ * @param onItem The function called for each item of the stream.
+ * @param options The request options.
+ * @return A promise resolving when the stream ends, or rejecting with the command or stream error.
+ *
+ * @since 2.0.0
+ */
+async function invokeStream<T>(
+  cmd: string,
+  args: InvokeArgs,
+  onItem: (item: T) => void,
+  options?: InvokeOptions
+): Promise<void> {
+  const channel = new Channel<StreamMessage<T>>()
+  const headers: Record<string, string> = {}
+  new Headers(options?.headers).forEach((value, key) => {
+    headers[key] = value
+  })
+  headers['Tauri-Stream-Channel'] = channel.id.toString()
+
+  return new Promise((resolve, reject) => {
+    // channel messages might arrive out of order
+    const pending = new Map<number, StreamMessage<T>>()
+    let next = 0
+
+    channel.onmessage = (message) => {
+      pending.set(message.index, message)
+      let current = pending.get(next)
+      while (current) {
+        pending.delete(next)
+        switch (current.event) {
+          case 'item':
+            onItem(current.data)
+            if ((next + 1) % STREAM_ACK_INTERVAL === 0) {
+              invoke('plugin:__TAURI_CHANNEL__|ack', {
+                channel: channel.id,
+                index: next
+              }).catch(console.error)
+            }
+            break
+          case 'end':
+            resolve()
+            return
+          case 'error':
+            reject(current.data)
+            return
+        }
+        next += 1
+        current = pending.get(next)
+      }
+    }
+
+    // the promise settles on the terminal message, the call only rejects if it fails before streaming
+    invoke(cmd, args, { ...options, headers }).catch(reject)
+  })
+}
+
 /**
  * Convert a device file path to an URL that can be loaded by the webview.
  * Note that `asset:` and `http://asset.localhost` must be added to [`tauri.security.csp`](https://tauri.app/v1/api/config/#securityconfig.csp) in `tauri.conf.json`.
@@ -204,7 +492,15 @@ export class Resource {
   }
 }
 
-export type { InvokeArgs, InvokeOptions }
+export type {
+  InvokeArgs,
+  InvokeOptions,
+  InvokeResponse,
+  BatchInvokeEntry,
+  BatchInvokeResult,
+  BatchInvokeOptions,
+  CommandError
+}
 
 export {
   transformCallback,
@@ -212,5 +508,9 @@ export {
   PluginListener,
   addPluginListener,
   invoke,
+  invokeWithResponse,
+  invokeBatch,
+  invokeStream,
+  isCommandError,
   convertFileSrc
 }
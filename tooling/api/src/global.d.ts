@@ -4,15 +4,25 @@
 
 /** @ignore */
 
-import type { invoke, transformCallback, convertFileSrc } from './core'
+import type {
+  InvokeArgs,
+  InvokeOptions,
+  transformCallback,
+  convertFileSrc
+} from './core'
 
 /** @ignore */
 declare global {
   interface Window {
     __TAURI_INTERNALS__: {
-      invoke: typeof invoke
+      invoke: <T>(
+        cmd: string,
+        args?: InvokeArgs,
+        options?: InvokeOptions & { withResponse?: boolean }
+      ) => Promise<T>
       transformCallback: typeof transformCallback
       convertFileSrc: typeof convertFileSrc
+      cancelInvoke: (callback: number) => Promise<boolean>
       ipc: (message: {
         cmd: string
         callback: number
@@ -50,6 +50,82 @@ fn get_all_benchmarks() -> Vec<(String, String)> {
   ]
 }
 
+/// The list of the apps that measure their own metrics: benchmark name, binary relative path and number of runs.
+///
+/// Each app writes its samples to the file at `TAURI_BENCH_OUTPUT` as `{ "<metric>": { "<key>": number | number[] } }`.
+fn get_metrics_benchmarks() -> Vec<(String, String, usize)> {
+  vec![
+    (
+      "tauri_ipc".into(),
+      format!("tests/target/{}/release/bench_ipc", utils::get_target()),
+      1,
+    ),
+    (
+      "tauri_event_fanout".into(),
+      format!(
+        "tests/target/{}/release/bench_event_fanout",
+        utils::get_target()
+      ),
+      1,
+    ),
+    (
+      "tauri_startup".into(),
+      format!("tests/target/{}/release/bench_startup", utils::get_target()),
+      10,
+    ),
+  ]
+}
+
+fn run_metrics_benchmarks(new_data: &mut utils::BenchResult) -> Result<()> {
+  for (name, example_exe, runs) in get_metrics_benchmarks() {
+    let mut samples = HashMap::<String, HashMap<String, Vec<f64>>>::new();
+
+    for _ in 0..runs {
+      let file = tempfile::NamedTempFile::new()?;
+
+      let status = Command::new(utils::bench_root_path().join(&example_exe))
+        .env("TAURI_BENCH_OUTPUT", file.path())
+        .stdout(Stdio::inherit())
+        .status()?;
+      if !status.success() {
+        anyhow::bail!("{name} exited with {status}");
+      }
+
+      let output = utils::read_json(file.path().to_str().unwrap())?;
+      for (metric, values) in output.as_object().into_iter().flatten() {
+        let metric_samples = samples.entry(metric.clone()).or_default();
+        for (key, value) in values.as_object().into_iter().flatten() {
+          let key_samples = metric_samples.entry(key.clone()).or_default();
+          match value {
+            serde_json::Value::Array(values) => {
+              key_samples.extend(values.iter().filter_map(|v| v.as_f64()))
+            }
+            value => key_samples.extend(value.as_f64()),
+          }
+        }
+      }
+    }
+
+    for (metric, values) in samples {
+      let results = match metric.as_str() {
+        "invoke_latency" => &mut new_data.ipc_latency,
+        "channel_throughput" => &mut new_data.channel_throughput,
+        "event_fanout" => &mut new_data.event_fanout,
+        "startup_timeline" => &mut new_data.startup_timeline,
+        _ => {
+          eprintln!("{name} reported unknown metric {metric}");
+          continue;
+        }
+      };
+      for (key, values) in values.into_iter().filter(|(_, v)| !v.is_empty()) {
+        results.insert(key, utils::summarize(&values));
+      }
+    }
+  }
+
+  Ok(())
+}
+
 fn run_strace_benchmarks(new_data: &mut utils::BenchResult) -> Result<()> {
   use std::io::Read;
 
@@ -293,6 +369,8 @@ fn main() -> Result<()> {
     ..Default::default()
   };
 
+  run_metrics_benchmarks(&mut new_data)?;
+
   if cfg!(target_os = "linux") {
     run_strace_benchmarks(&mut new_data)?;
     new_data.max_memory = run_max_mem_benchmark()?;
@@ -23,6 +23,18 @@ pub struct BenchResult {
   pub thread_count: HashMap<String, u64>,
   pub syscall_count: HashMap<String, u64>,
   pub cargo_deps: HashMap<String, usize>,
+  /// Invoke round-trip latency in milliseconds, per payload kind and size.
+  #[serde(default)]
+  pub ipc_latency: HashMap<String, HashMap<String, f64>>,
+  /// `Channel` streaming throughput in bytes per second, per chunk size.
+  #[serde(default)]
+  pub channel_throughput: HashMap<String, HashMap<String, f64>>,
+  /// Time in milliseconds for an event to reach all targeted webviews, per webview count.
+  #[serde(default)]
+  pub event_fanout: HashMap<String, HashMap<String, f64>>,
+  /// Time in milliseconds since the start of `main` to reach each startup phase.
+  #[serde(default)]
+  pub startup_timeline: HashMap<String, HashMap<String, f64>>,
 }
 
 #[allow(dead_code)]
@@ -207,6 +219,23 @@ pub fn run(cmd: &[&str]) {
   }
 }
 
+/// Computes the mean, standard deviation, min and max of the samples.
+#[allow(dead_code)]
+pub fn summarize(samples: &[f64]) -> HashMap<String, f64> {
+  let count = samples.len() as f64;
+  let mean = samples.iter().sum::<f64>() / count;
+  let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / count;
+  let min = samples.iter().cloned().fold(f64::INFINITY, f64::min);
+  let max = samples.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+  HashMap::from([
+    ("mean".into(), mean),
+    ("stddev".into(), variance.sqrt()),
+    ("min".into(), min),
+    ("max".into(), max),
+  ])
+}
+
 #[allow(dead_code)]
 pub fn read_json(filename: &str) -> Result<Value> {
   let f = fs::File::open(filename)?;
@@ -0,0 +1,109 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use std::{
+  collections::HashMap,
+  sync::{
+    mpsc::{channel, Receiver, Sender},
+    Mutex,
+  },
+  time::{Duration, Instant},
+};
+use tauri::{command, AppHandle, EventTarget, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+/// Number of listener webviews created.
+const WEBVIEWS: usize = 10;
+/// Number of webviews targeted by each measurement.
+const FAN_OUT: &[usize] = &[1, 5, 10];
+/// Number of events emitted for each measurement.
+const ROUNDS: u32 = 30;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Bench {
+  ready: Mutex<usize>,
+  pong: Mutex<Sender<u32>>,
+  receiver: Mutex<Option<Receiver<u32>>>,
+}
+
+#[command]
+fn listener_ready(app: AppHandle, bench: State<'_, Bench>) {
+  let mut ready = bench.ready.lock().unwrap();
+  *ready += 1;
+  if *ready == WEBVIEWS {
+    let receiver = bench.receiver.lock().unwrap().take().unwrap();
+    std::thread::spawn(move || run(app, receiver));
+  }
+}
+
+#[command]
+fn pong(round: u32, bench: State<'_, Bench>) {
+  let _ = bench.pong.lock().unwrap().send(round);
+}
+
+fn label(index: usize) -> String {
+  format!("listener-{index}")
+}
+
+fn run(app: AppHandle, receiver: Receiver<u32>) {
+  let mut results = HashMap::new();
+  let mut round = 0;
+
+  for &count in FAN_OUT {
+    let targets = (0..count).map(label).collect::<Vec<_>>();
+    let mut samples = Vec::new();
+
+    for _ in 0..ROUNDS {
+      round += 1;
+      let start = Instant::now();
+      app
+        .emit_filter(
+          "ping",
+          round,
+          |target| matches!(target, EventTarget::Webview { label } if targets.contains(label)),
+        )
+        .expect("failed to emit event");
+
+      let mut received = 0;
+      while received < count {
+        match receiver.recv_timeout(TIMEOUT) {
+          Ok(r) if r == round => received += 1,
+          Ok(_) => {}
+          Err(_) => std::process::exit(1),
+        }
+      }
+      samples.push(start.elapsed().as_secs_f64() * 1000.);
+    }
+
+    results.insert(format!("{count}_webviews"), samples);
+  }
+
+  if let Ok(output) = std::env::var("TAURI_BENCH_OUTPUT") {
+    let results = serde_json::json!({ "event_fanout": results });
+    std::fs::write(output, results.to_string()).expect("failed to write results");
+  }
+  std::process::exit(0);
+}
+
+fn main() {
+  let (sender, receiver) = channel();
+
+  tauri::Builder::default()
+    .manage(Bench {
+      ready: Default::default(),
+      pong: Mutex::new(sender),
+      receiver: Mutex::new(Some(receiver)),
+    })
+    .invoke_handler(tauri::generate_handler![listener_ready, pong])
+    .setup(|app| {
+      for index in 0..WEBVIEWS {
+        WebviewWindowBuilder::new(app, label(index), WebviewUrl::App("index.html".into()))
+          .build()?;
+      }
+      Ok(())
+    })
+    .run(tauri::generate_context!())
+    .expect("error while running tauri application");
+}
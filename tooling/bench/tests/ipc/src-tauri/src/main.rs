@@ -0,0 +1,64 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use serde_json::Value;
+use tauri::{
+  command,
+  ipc::{Channel, InvokeBody, Request, Response},
+};
+
+#[command]
+fn app_should_close(exit_code: i32) {
+  std::process::exit(exit_code);
+}
+
+#[command]
+fn echo_json(payload: Value) -> Value {
+  payload
+}
+
+#[command]
+fn echo_raw(request: Request<'_>) -> Response {
+  match request.body() {
+    InvokeBody::Raw(bytes) => Response::new(bytes.clone()),
+    InvokeBody::Json(_) => Response::new(Vec::new()),
+  }
+}
+
+#[command]
+fn stream(channel: Channel, chunk_size: usize, total_size: usize) -> Result<(), String> {
+  let chunk = vec![1u8; chunk_size];
+  let mut sent = 0;
+  while sent < total_size {
+    let len = chunk_size.min(total_size - sent);
+    channel
+      .send(InvokeBody::Raw(chunk[..len].to_vec()))
+      .map_err(|e| e.to_string())?;
+    sent += len;
+  }
+  Ok(())
+}
+
+#[command]
+fn report(results: Value) -> Result<(), String> {
+  if let Ok(output) = std::env::var("TAURI_BENCH_OUTPUT") {
+    std::fs::write(output, results.to_string()).map_err(|e| e.to_string())?;
+  }
+  std::process::exit(0);
+}
+
+fn main() {
+  tauri::Builder::default()
+    .invoke_handler(tauri::generate_handler![
+      app_should_close,
+      echo_json,
+      echo_raw,
+      stream,
+      report
+    ])
+    .run(tauri::generate_context!())
+    .expect("error while running tauri application");
+}
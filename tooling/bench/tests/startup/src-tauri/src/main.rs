@@ -0,0 +1,80 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+#![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+  time::Instant,
+};
+use tauri::{
+  plugin::{Builder as PluginBuilder, TauriPlugin},
+  webview::PageLoadEvent,
+  Runtime,
+};
+
+/// Startup phases in milliseconds since the start of `main`.
+static TIMELINE: Mutex<Vec<(&'static str, f64)>> = Mutex::new(Vec::new());
+static START: OnceLock<Instant> = OnceLock::new();
+
+fn mark(phase: &'static str) {
+  let mut timeline = TIMELINE.lock().unwrap();
+  // only keep the first occurrence of each phase
+  if !timeline.iter().any(|(p, _)| *p == phase) {
+    let elapsed = START.get().unwrap().elapsed().as_secs_f64() * 1000.;
+    timeline.push((phase, elapsed));
+  }
+}
+
+fn report() -> ! {
+  if let Ok(output) = std::env::var("TAURI_BENCH_OUTPUT") {
+    let timeline = TIMELINE
+      .lock()
+      .unwrap()
+      .iter()
+      .cloned()
+      .collect::<HashMap<_, _>>();
+    let results = serde_json::json!({ "startup_timeline": timeline });
+    std::fs::write(output, results.to_string()).expect("failed to write results");
+  }
+  std::process::exit(0);
+}
+
+/// Registered before any other plugin: its setup runs right after the runtime is initialized.
+fn runtime_init_marker<R: Runtime>() -> TauriPlugin<R> {
+  PluginBuilder::new("runtime-init-marker")
+    .setup(|_app, _api| {
+      mark("runtime_init");
+      Ok(())
+    })
+    .build()
+}
+
+/// Registered after every other plugin: its setup runs once all plugins are initialized.
+fn plugin_init_marker<R: Runtime>() -> TauriPlugin<R> {
+  PluginBuilder::new("plugin-init-marker")
+    .setup(|_app, _api| {
+      mark("plugin_init");
+      Ok(())
+    })
+    .on_webview_ready(|_webview| mark("first_webview_created"))
+    .build()
+}
+
+fn main() {
+  START.get_or_init(Instant::now);
+
+  tauri::Builder::default()
+    .plugin(runtime_init_marker())
+    .plugin(plugin_init_marker())
+    .on_page_load(|_webview, payload| {
+      if payload.event() == PageLoadEvent::Finished {
+        mark("first_page_load");
+        report();
+      }
+    })
+    .run(tauri::generate_context!())
+    .expect("error while running tauri application");
+}
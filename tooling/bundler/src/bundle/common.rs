@@ -3,8 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+#[cfg(not(target_os = "windows"))]
+use libflate::gzip;
 use log::debug;
 
+#[cfg(not(target_os = "windows"))]
+use std::io::Write;
 use std::{
   ffi::OsStr,
   fs::{self, File},
@@ -161,6 +165,64 @@ pub fn copy_custom_files(
   Ok(())
 }
 
+/// Reads the `SOURCE_DATE_EPOCH` environment variable.
+///
+/// When it is set, the bundler runs in reproducible mode: the archives it writes get normalized
+/// timestamps, ownership, permissions and entry order so building the same sources twice
+/// produces byte-identical bundles.
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+#[cfg(not(target_os = "windows"))]
+pub fn source_date_epoch() -> Option<u64> {
+  let value = std::env::var("SOURCE_DATE_EPOCH").ok()?;
+  match value.trim().parse() {
+    Ok(epoch) => Some(epoch),
+    Err(_) => {
+      log::warn!("Ignoring invalid `SOURCE_DATE_EPOCH` value {value:?}, expected a UNIX timestamp");
+      None
+    }
+  }
+}
+
+/// Normalizes a tar header for reproducible archives.
+///
+/// The modification time is clamped to `source_date_epoch`, the owner is set to root
+/// and the permissions are reduced to either `0o755` (directories and executables) or `0o644`.
+#[cfg(not(target_os = "windows"))]
+pub fn normalize_tar_header(header: &mut tar::Header, source_date_epoch: u64) {
+  let mtime = header
+    .mtime()
+    .map(|mtime| mtime.min(source_date_epoch))
+    .unwrap_or(source_date_epoch);
+  header.set_mtime(mtime);
+  header.set_uid(0);
+  header.set_gid(0);
+  let entry_type = header.entry_type();
+  let mode = if entry_type.is_symlink() {
+    0o777
+  } else if entry_type.is_dir() || header.mode().unwrap_or(0) & 0o111 != 0 {
+    0o755
+  } else {
+    0o644
+  };
+  header.set_mode(mode);
+}
+
+/// Creates a gzip encoder writing to `writer`.
+///
+/// The gzip header stores the current time by default,
+/// so `source_date_epoch` is used instead when it is set.
+#[cfg(not(target_os = "windows"))]
+pub fn gzip_encoder<W: Write>(
+  writer: W,
+  source_date_epoch: Option<u64>,
+) -> io::Result<gzip::Encoder<W>> {
+  let mut header = gzip::HeaderBuilder::new();
+  if let Some(epoch) = source_date_epoch {
+    header.modification_time(epoch.min(u32::MAX as u64) as u32);
+  }
+  gzip::Encoder::with_options(writer, gzip::EncodeOptions::new().header(header.finish()))
+}
+
 pub trait CommandExt {
   // The `pipe` function sets the stdout and stderr to properly
   // show the command output in the Node.js wrapper.
@@ -110,10 +110,14 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     .expect("Failed to chmod script");
 
   // execute the shell script to build the appimage.
-  Command::new(&sh_file)
-    .current_dir(output_path)
-    .output_ok()
-    .context("error running build_appimage.sh")?;
+  let mut cmd = Command::new(&sh_file);
+  cmd.current_dir(output_path);
+  // only forward a valid timestamp to the AppDir normalization and mksquashfs
+  match common::source_date_epoch() {
+    Some(source_date_epoch) => cmd.env("SOURCE_DATE_EPOCH", source_date_epoch.to_string()),
+    None => cmd.env_remove("SOURCE_DATE_EPOCH"),
+  };
+  cmd.output_ok().context("error running build_appimage.sh")?;
 
   remove_dir_all(&package_dir)?;
   Ok(vec![appimage_path])
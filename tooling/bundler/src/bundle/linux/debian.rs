@@ -27,7 +27,6 @@ use super::{super::common, freedesktop};
 use crate::Settings;
 use anyhow::Context;
 use heck::AsKebabCase;
-use libflate::gzip;
 use log::info;
 use walkdir::WalkDir;
 
@@ -66,6 +65,8 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
 
   info!(action = "Bundling"; "{} ({})", package_name, package_path.display());
 
+  let source_date_epoch = common::source_date_epoch();
+
   let (data_dir, _) = generate_data(settings, &package_dir)
     .with_context(|| "Failed to build data folders and files")?;
   common::copy_custom_files(&settings.deb().files, &data_dir)
@@ -84,13 +85,14 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     .with_context(|| "Failed to create debian-binary file")?;
 
   // Apply tar/gzip/ar to create the final package file.
-  let control_tar_gz_path =
-    tar_and_gzip_dir(control_dir).with_context(|| "Failed to tar/gzip control directory")?;
-  let data_tar_gz_path =
-    tar_and_gzip_dir(data_dir).with_context(|| "Failed to tar/gzip data directory")?;
+  let control_tar_gz_path = tar_and_gzip_dir(control_dir, source_date_epoch)
+    .with_context(|| "Failed to tar/gzip control directory")?;
+  let data_tar_gz_path = tar_and_gzip_dir(data_dir, source_date_epoch)
+    .with_context(|| "Failed to tar/gzip data directory")?;
   create_archive(
     vec![debian_binary_path, control_tar_gz_path, data_tar_gz_path],
     &package_path,
+    source_date_epoch,
   )
   .with_context(|| "Failed to create package archive")?;
   Ok(vec![package_path])
@@ -177,7 +179,7 @@ fn generate_control_file(
 fn generate_md5sums(control_dir: &Path, data_dir: &Path) -> crate::Result<()> {
   let md5sums_path = control_dir.join("md5sums");
   let mut md5sums_file = common::create_file(&md5sums_path)?;
-  for entry in WalkDir::new(data_dir) {
+  for entry in WalkDir::new(data_dir).sort_by_file_name() {
     let entry = entry?;
     let path = entry.path();
     if path.is_dir() {
@@ -226,10 +228,17 @@ fn total_dir_size(dir: &Path) -> crate::Result<u64> {
 }
 
 /// Writes a tar file to the given writer containing the given directory.
-fn create_tar_from_dir<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> crate::Result<W> {
+///
+/// Entries are written sorted by file name. If `source_date_epoch` is set,
+/// the entry headers are normalized with [`common::normalize_tar_header`].
+fn create_tar_from_dir<P: AsRef<Path>, W: Write>(
+  src_dir: P,
+  dest_file: W,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<W> {
   let src_dir = src_dir.as_ref();
   let mut tar_builder = tar::Builder::new(dest_file);
-  for entry in WalkDir::new(src_dir) {
+  for entry in WalkDir::new(src_dir).sort_by_file_name() {
     let entry = entry?;
     let src_path = entry.path();
     if src_path == src_dir {
@@ -242,6 +251,9 @@ fn create_tar_from_dir<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> cr
       header.set_metadata(&stat);
       header.set_uid(0);
       header.set_gid(0);
+      if let Some(epoch) = source_date_epoch {
+        common::normalize_tar_header(&mut header, epoch);
+      }
       tar_builder.append_data(&mut header, dest_path, &mut io::empty())?;
     } else {
       let mut src_file = fs::File::open(src_path)?;
@@ -250,6 +262,9 @@ fn create_tar_from_dir<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> cr
       header.set_metadata(&stat);
       header.set_uid(0);
       header.set_gid(0);
+      if let Some(epoch) = source_date_epoch {
+        common::normalize_tar_header(&mut header, epoch);
+      }
       tar_builder.append_data(&mut header, dest_path, &mut src_file)?;
     }
   }
@@ -260,12 +275,15 @@ fn create_tar_from_dir<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> cr
 /// Creates a `.tar.gz` file from the given directory (placing the new file
 /// within the given directory's parent directory), then deletes the original
 /// directory and returns the path to the new file.
-fn tar_and_gzip_dir<P: AsRef<Path>>(src_dir: P) -> crate::Result<PathBuf> {
+fn tar_and_gzip_dir<P: AsRef<Path>>(
+  src_dir: P,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<PathBuf> {
   let src_dir = src_dir.as_ref();
   let dest_path = src_dir.with_extension("tar.gz");
   let dest_file = common::create_file(&dest_path)?;
-  let gzip_encoder = gzip::Encoder::new(dest_file)?;
-  let gzip_encoder = create_tar_from_dir(src_dir, gzip_encoder)?;
+  let gzip_encoder = common::gzip_encoder(dest_file, source_date_epoch)?;
+  let gzip_encoder = create_tar_from_dir(src_dir, gzip_encoder, source_date_epoch)?;
   let mut dest_file = gzip_encoder.finish().into_result()?;
   dest_file.flush()?;
   Ok(dest_path)
@@ -273,11 +291,105 @@ fn tar_and_gzip_dir<P: AsRef<Path>>(src_dir: P) -> crate::Result<PathBuf> {
 
 /// Creates an `ar` archive from the given source files and writes it to the
 /// given destination path.
-fn create_archive(srcs: Vec<PathBuf>, dest: &Path) -> crate::Result<()> {
+///
+/// If `source_date_epoch` is set, the member headers use it as modification time
+/// and are owned by root instead of copying the source file metadata.
+fn create_archive(
+  srcs: Vec<PathBuf>,
+  dest: &Path,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<()> {
   let mut builder = ar::Builder::new(common::create_file(dest)?);
   for path in &srcs {
-    builder.append_path(path)?;
+    match source_date_epoch {
+      Some(epoch) => {
+        let mut file = File::open(path)?;
+        let identifier = path
+          .file_name()
+          .expect("Path has no file_name")
+          .to_string_lossy()
+          .into_owned()
+          .into_bytes();
+        let mut header = ar::Header::new(identifier, file.metadata()?.len());
+        header.set_mtime(epoch);
+        header.set_mode(0o100644);
+        builder.append(&header, &mut file)?;
+      }
+      None => builder.append_path(path)?,
+    }
   }
   builder.into_inner()?.flush()?;
   Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{create_archive, create_file_with_data, generate_md5sums, tar_and_gzip_dir};
+  use sha2::{Digest, Sha256};
+  use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+  };
+
+  const SOURCE_DATE_EPOCH: u64 = 1_700_000_000;
+
+  fn write_file(path: &Path, contents: &str, mode: u32, mtime: i64) {
+    create_file_with_data(path, contents).expect("failed to write file");
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).expect("failed to set mode");
+    filetime::set_file_mtime(path, filetime::FileTime::from_unix_time(mtime, 0))
+      .expect("failed to set mtime");
+  }
+
+  /// Builds a package from the same files, written in the given order and with the given
+  /// modification time and group permission bits, and returns the package checksum.
+  fn bundle(root: &Path, reverse: bool, mtime: i64, group_bits: u32) -> String {
+    let package_dir = root.join("app_0.1.0_amd64");
+    let data_dir = package_dir.join("data");
+    let control_dir = package_dir.join("control");
+
+    let mut files = vec![
+      ("usr/bin/app", "binary", 0o755 | group_bits),
+      ("usr/lib/app/a.txt", "a", 0o644 | group_bits),
+      ("usr/lib/app/b.txt", "b", 0o644 | group_bits),
+      ("usr/share/applications/app.desktop", "desktop", 0o644),
+    ];
+    if reverse {
+      files.reverse();
+    }
+    for (path, contents, mode) in files {
+      write_file(&data_dir.join(path), contents, mode, mtime);
+    }
+    write_file(&control_dir.join("control"), "Package: app\n", 0o644, mtime);
+    generate_md5sums(&control_dir, &data_dir).expect("failed to generate md5sums");
+
+    let debian_binary_path = package_dir.join("debian-binary");
+    write_file(&debian_binary_path, "2.0\n", 0o644, mtime);
+
+    let control_tar_gz_path =
+      tar_and_gzip_dir(control_dir, Some(SOURCE_DATE_EPOCH)).expect("failed to tar control");
+    let data_tar_gz_path =
+      tar_and_gzip_dir(data_dir, Some(SOURCE_DATE_EPOCH)).expect("failed to tar data");
+    let package_path: PathBuf = root.join("app_0.1.0_amd64.deb");
+    create_archive(
+      vec![debian_binary_path, control_tar_gz_path, data_tar_gz_path],
+      &package_path,
+      Some(SOURCE_DATE_EPOCH),
+    )
+    .expect("failed to create package archive");
+
+    let package = fs::read(package_path).expect("failed to read package");
+    hex::encode(Sha256::digest(package))
+  }
+
+  #[test]
+  fn reproducible_with_source_date_epoch() {
+    let first = tempfile::tempdir().expect("unable to create tempdir");
+    let second = tempfile::tempdir().expect("unable to create tempdir");
+
+    let first_hash = bundle(first.path(), false, 1_700_000_100, 0);
+    let second_hash = bundle(second.path(), true, 1_800_000_000, 0o020);
+
+    assert_eq!(first_hash, second_hash);
+  }
+}
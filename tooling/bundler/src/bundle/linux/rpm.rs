@@ -11,10 +11,11 @@ use rpm::{self, signature::pgp, Dependency, FileMode, FileOptions};
 use std::{
   env,
   fs::{self, File},
+  os::unix::fs::PermissionsExt,
   path::{Path, PathBuf},
 };
 
-use super::freedesktop;
+use super::{super::common, freedesktop};
 
 /// Bundles the project.
 /// Returns a vector of PathBuf that shows where the RPM was created.
@@ -53,6 +54,11 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     .epoch(epoch)
     .release(release);
 
+  let source_date_epoch = common::source_date_epoch();
+  if let Some(source_date_epoch) = source_date_epoch {
+    builder = builder.source_date(source_date_epoch.min(u32::MAX as u64) as u32);
+  }
+
   if let Some(description) = settings.long_description() {
     builder = builder.description(description.trim())
   }
@@ -66,7 +72,7 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
   for bin in settings.binaries() {
     let src = settings.binary_path(bin);
     let dest = Path::new("/usr/bin").join(bin.name());
-    builder = builder.with_file(src, FileOptions::new(dest.to_string_lossy()))?;
+    builder = with_file(builder, &src, &dest, source_date_epoch)?;
   }
 
   // Add external binaries
@@ -79,7 +85,7 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
         .to_string_lossy()
         .replace(&format!("-{}", settings.target()), ""),
     );
-    builder = builder.with_file(&src, FileOptions::new(dest.to_string_lossy()))?;
+    builder = with_file(builder, &src, &dest, source_date_epoch)?;
   }
 
   // Add resources
@@ -98,34 +104,35 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
     for src in settings.resource_files() {
       let src = src?;
       let dest = resource_dir.join(tauri_utils::resources::resource_relpath(&src));
-      builder = builder.with_file(&src, FileOptions::new(dest.to_string_lossy()))?;
+      builder = with_file(builder, &src, &dest, source_date_epoch)?;
     }
   }
 
   // Add Desktop entry file
   let (desktop_src_path, desktop_dest_path) =
     freedesktop::generate_desktop_file(settings, &settings.rpm().desktop_template, &package_dir)?;
-  builder = builder.with_file(
-    desktop_src_path,
-    FileOptions::new(desktop_dest_path.to_string_lossy()),
+  builder = with_file(
+    builder,
+    &desktop_src_path,
+    &desktop_dest_path,
+    source_date_epoch,
   )?;
 
   // Add icons
   for (icon, src) in &freedesktop::list_icon_files(settings, &PathBuf::from("/"))? {
-    builder = builder.with_file(src, FileOptions::new(icon.path.to_string_lossy()))?;
+    builder = with_file(builder, src, &icon.path, source_date_epoch)?;
   }
 
   // Add custom files
   for (rpm_path, src_path) in settings.rpm().files.iter() {
     if src_path.is_file() {
-      builder = builder.with_file(src_path, FileOptions::new(rpm_path.to_string_lossy()))?;
+      builder = with_file(builder, src_path, rpm_path, source_date_epoch)?;
     } else {
       for entry in walkdir::WalkDir::new(src_path) {
         let entry_path = entry?.into_path();
         if entry_path.is_file() {
           let dest_path = rpm_path.join(entry_path.strip_prefix(src_path).unwrap());
-          builder =
-            builder.with_file(&entry_path, FileOptions::new(dest_path.to_string_lossy()))?;
+          builder = with_file(builder, &entry_path, &dest_path, source_date_epoch)?;
         }
       }
     }
@@ -146,3 +153,86 @@ pub fn bundle_project(settings: &Settings) -> crate::Result<Vec<PathBuf>> {
 
   Ok(vec![package_path])
 }
+
+/// Adds the `src` file to the package at `dest`.
+///
+/// The file mode is inherited from `src` unless `source_date_epoch` is set,
+/// in which case it is reduced to either `0o755` (executables) or `0o644` like the `.deb` entries.
+fn with_file(
+  builder: rpm::PackageBuilder,
+  src: &Path,
+  dest: &Path,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<rpm::PackageBuilder> {
+  let mut options = FileOptions::new(dest.to_string_lossy());
+  if source_date_epoch.is_some() {
+    let permissions = if fs::metadata(src)?.permissions().mode() & 0o111 != 0 {
+      0o755
+    } else {
+      0o644
+    };
+    options = options.mode(FileMode::Regular { permissions });
+  }
+  builder.with_file(src, options).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::with_file;
+  use sha2::{Digest, Sha256};
+  use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+  const SOURCE_DATE_EPOCH: u64 = 1_700_000_000;
+
+  /// Builds a package from the same files, added in the given order and written with the given
+  /// modification time and group permission bits, and returns the package checksum.
+  fn bundle(root: &Path, reverse: bool, mtime: i64, group_bits: u32) -> String {
+    let mut files = vec![
+      ("usr/bin/app", "binary", 0o755 | group_bits),
+      ("usr/lib/app/a.txt", "a", 0o644 | group_bits),
+      ("usr/lib/app/b.txt", "b", 0o644 | group_bits),
+      ("usr/share/applications/app.desktop", "desktop", 0o644),
+    ];
+    if reverse {
+      files.reverse();
+    }
+
+    let mut builder = rpm::PackageBuilder::new("app", "0.1.0", "MIT", "x86_64", "app")
+      .release("1")
+      .source_date(SOURCE_DATE_EPOCH as u32);
+    for (path, contents, mode) in files {
+      let src = root.join(path);
+      fs::create_dir_all(src.parent().unwrap()).expect("failed to create directory");
+      fs::write(&src, contents).expect("failed to write file");
+      fs::set_permissions(&src, fs::Permissions::from_mode(mode)).expect("failed to set mode");
+      filetime::set_file_mtime(&src, filetime::FileTime::from_unix_time(mtime, 0))
+        .expect("failed to set mtime");
+      builder = with_file(
+        builder,
+        &src,
+        &Path::new("/").join(path),
+        Some(SOURCE_DATE_EPOCH),
+      )
+      .expect("failed to add file");
+    }
+
+    let mut package = Vec::new();
+    builder
+      .build()
+      .expect("failed to build package")
+      .write(&mut package)
+      .expect("failed to write package");
+    hex::encode(Sha256::digest(package))
+  }
+
+  #[test]
+  fn reproducible_with_source_date_epoch() {
+    let first = tempfile::tempdir().expect("unable to create tempdir");
+    let second = tempfile::tempdir().expect("unable to create tempdir");
+
+    let first_hash = bundle(first.path(), false, 1_700_000_100, 0);
+    let second_hash = bundle(second.path(), true, 1_800_000_000, 0o020);
+
+    assert_eq!(first_hash, second_hash);
+  }
+}
@@ -75,7 +75,7 @@ fn bundle_update_macos(bundles: &[Bundle]) -> crate::Result<Vec<PathBuf>> {
 
     // Create our gzip file (need to send parent)
     // as we walk the source directory (source isnt added)
-    create_tar(source_path, &osx_archived_path)
+    create_tar(source_path, &osx_archived_path, common::source_date_epoch())
       .with_context(|| "Failed to tar.gz update directory")?;
 
     info!(action = "Bundling"; "{} ({})", osx_archived, display_path(&osx_archived_path));
@@ -110,8 +110,12 @@ fn bundle_update_linux(bundles: &[Bundle]) -> crate::Result<Vec<PathBuf>> {
     let appimage_archived_path = PathBuf::from(&appimage_archived);
 
     // Create our gzip file
-    create_tar(source_path, &appimage_archived_path)
-      .with_context(|| "Failed to tar.gz update directory")?;
+    create_tar(
+      source_path,
+      &appimage_archived_path,
+      common::source_date_epoch(),
+    )
+    .with_context(|| "Failed to tar.gz update directory")?;
 
     info!(action = "Bundling"; "{} ({})", appimage_archived, display_path(&appimage_archived_path));
 
@@ -233,28 +237,27 @@ pub fn create_zip(src_file: &Path, dst_file: &Path) -> crate::Result<PathBuf> {
 }
 
 #[cfg(not(target_os = "windows"))]
-fn create_tar(src_dir: &Path, dest_path: &Path) -> crate::Result<PathBuf> {
+fn create_tar(
+  src_dir: &Path,
+  dest_path: &Path,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<PathBuf> {
   let dest_file = common::create_file(dest_path)?;
-  let gzip_encoder = libflate::gzip::Encoder::new(dest_file)?;
+  let gzip_encoder = common::gzip_encoder(dest_file, source_date_epoch)?;
 
-  let gzip_encoder = create_tar_from_src(src_dir, gzip_encoder)?;
+  let gzip_encoder = create_tar_from_src(src_dir, gzip_encoder, source_date_epoch)?;
 
   let mut dest_file = gzip_encoder.finish().into_result()?;
   dest_file.flush()?;
   Ok(dest_path.to_owned())
 }
 
-#[cfg(target_os = "macos")]
-fn create_tar_from_src<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> crate::Result<W> {
-  let src_dir = src_dir.as_ref();
-  let mut builder = tar::Builder::new(dest_file);
-  builder.follow_symlinks(false);
-  builder.append_dir_all(src_dir.file_name().expect("Path has no file_name"), src_dir)?;
-  builder.into_inner().map_err(Into::into)
-}
-
-#[cfg(target_os = "linux")]
-fn create_tar_from_src<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> crate::Result<W> {
+#[cfg(not(target_os = "windows"))]
+fn create_tar_from_src<P: AsRef<Path>, W: Write>(
+  src_dir: P,
+  dest_file: W,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<W> {
   let src_dir = src_dir.as_ref();
   let mut tar_builder = tar::Builder::new(dest_file);
 
@@ -262,33 +265,116 @@ fn create_tar_from_src<P: AsRef<Path>, W: Write>(src_dir: P, dest_file: W) -> cr
   let file_type = fs::metadata(src_dir).expect("Can't read source directory");
   // if it's a file don't need to walkdir
   if file_type.is_file() {
-    let mut src_file = fs::File::open(src_dir)?;
     let file_name = src_dir
       .file_name()
       .expect("Can't extract file name from path");
 
-    tar_builder.append_file(file_name, &mut src_file)?;
+    append_tar_entry(
+      &mut tar_builder,
+      src_dir,
+      Path::new(file_name),
+      source_date_epoch,
+    )?;
   } else {
-    for entry in walkdir::WalkDir::new(src_dir) {
+    // sorted so the entry order does not depend on the filesystem
+    for entry in walkdir::WalkDir::new(src_dir).sort_by_file_name() {
       let entry = entry?;
       let src_path = entry.path();
-      if src_path == src_dir {
-        continue;
-      }
 
       // We add the .parent() because example if we send a path
       // /dev/src-tauri/target/debug/bundle/osx/app.app
       // We need a tar with app.app/<...> (source root folder should be included)
       // safe to unwrap: the path has a parent
       let dest_path = src_path.strip_prefix(src_dir.parent().unwrap())?;
-      if entry.file_type().is_dir() {
-        tar_builder.append_dir(dest_path, src_path)?;
-      } else {
-        let mut src_file = fs::File::open(src_path)?;
-        tar_builder.append_file(dest_path, &mut src_file)?;
-      }
+      append_tar_entry(&mut tar_builder, src_path, dest_path, source_date_epoch)?;
     }
   }
   let dest_file = tar_builder.into_inner()?;
   Ok(dest_file)
 }
+
+/// Appends a file, directory or symlink (without following it) to the tar archive.
+#[cfg(not(target_os = "windows"))]
+fn append_tar_entry<W: Write>(
+  tar_builder: &mut tar::Builder<W>,
+  src_path: &Path,
+  dest_path: &Path,
+  source_date_epoch: Option<u64>,
+) -> crate::Result<()> {
+  let stat = fs::symlink_metadata(src_path)?;
+  let mut header = tar::Header::new_gnu();
+  header.set_metadata(&stat);
+  if let Some(epoch) = source_date_epoch {
+    common::normalize_tar_header(&mut header, epoch);
+  }
+
+  if stat.file_type().is_symlink() {
+    let target = fs::read_link(src_path)?;
+    tar_builder.append_link(&mut header, dest_path, target)?;
+  } else if stat.is_dir() {
+    tar_builder.append_data(&mut header, dest_path, &mut std::io::empty())?;
+  } else {
+    let mut src_file = File::open(src_path)?;
+    tar_builder.append_data(&mut header, dest_path, &mut src_file)?;
+  }
+  Ok(())
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+  use super::create_tar;
+  use sha2::{Digest, Sha256};
+  use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+  const SOURCE_DATE_EPOCH: u64 = 1_700_000_000;
+
+  /// Archives the same files, written in the given order and with the given
+  /// modification time and group permission bits, and returns the archive checksum.
+  fn archive(root: &Path, reverse: bool, mtime: i64, group_bits: u32) -> String {
+    let app_dir = root.join("app.app");
+    let mut files = vec![
+      ("Contents/MacOS/app", "binary", 0o755 | group_bits),
+      ("Contents/Resources/a.txt", "a", 0o644 | group_bits),
+      ("Contents/Resources/b.txt", "b", 0o644 | group_bits),
+      ("Contents/Info.plist", "plist", 0o644),
+    ];
+    if reverse {
+      files.reverse();
+    }
+    for (path, contents, mode) in files {
+      let path = app_dir.join(path);
+      fs::create_dir_all(path.parent().unwrap()).expect("failed to create directory");
+      fs::write(&path, contents).expect("failed to write file");
+      fs::set_permissions(&path, fs::Permissions::from_mode(mode)).expect("failed to set mode");
+    }
+    for entry in walkdir::WalkDir::new(&app_dir) {
+      let entry = entry.expect("failed to read directory entry");
+      filetime::set_symlink_file_times(
+        entry.path(),
+        filetime::FileTime::from_unix_time(mtime, 0),
+        filetime::FileTime::from_unix_time(mtime, 0),
+      )
+      .expect("failed to set mtime");
+    }
+
+    let archive_path = create_tar(
+      &app_dir,
+      &root.join("app.app.tar.gz"),
+      Some(SOURCE_DATE_EPOCH),
+    )
+    .expect("failed to create archive");
+    let archive = fs::read(archive_path).expect("failed to read archive");
+    hex::encode(Sha256::digest(archive))
+  }
+
+  #[test]
+  fn reproducible_with_source_date_epoch() {
+    let first = tempfile::tempdir().expect("unable to create tempdir");
+    let second = tempfile::tempdir().expect("unable to create tempdir");
+
+    let first_hash = archive(first.path(), false, 1_700_000_100, 0);
+    let second_hash = archive(second.path(), true, 1_800_000_000, 0o020);
+
+    assert_eq!(first_hash, second_hash);
+  }
+}
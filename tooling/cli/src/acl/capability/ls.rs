@@ -0,0 +1,155 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeSet;
+
+use clap::Parser;
+use colored::Colorize;
+use tauri_utils::acl::{plugin::Manifest, APP_ACL_KEY};
+
+use crate::{
+  acl::{capability_files, plugin_manifests},
+  Result,
+};
+
+#[derive(Debug, Parser)]
+#[clap(about = "List the capabilities of your application")]
+pub struct Options {
+  /// Only list capabilities affecting the given window label and the commands they allow or deny.
+  #[clap(short, long)]
+  window: Option<String>,
+}
+
+#[derive(Default)]
+struct Commands {
+  allow: BTreeSet<String>,
+  deny: BTreeSet<String>,
+}
+
+pub fn command(options: Options) -> Result<()> {
+  let files = capability_files()?;
+  let manifests = if options.window.is_some() {
+    Some(plugin_manifests()?)
+  } else {
+    None
+  };
+
+  let mut found = false;
+  for file in &files {
+    for capability in &file.capabilities {
+      if let Some(window) = &options.window {
+        let matches = capability.windows.iter().any(|w| {
+          glob::Pattern::new(w)
+            .map(|p| p.matches(window))
+            .unwrap_or(false)
+        });
+        if !matches {
+          continue;
+        }
+      }
+      found = true;
+
+      println!(
+        "{} ({})",
+        capability.identifier.green().bold(),
+        file.path.display()
+      );
+      if !capability.description.is_empty() {
+        println!("  {}", capability.description);
+      }
+      println!("  {} {}", "windows:".cyan(), capability.windows.join(", "));
+
+      let Some(manifests) = &manifests else {
+        println!(
+          "  {} {}",
+          "permissions:".cyan(),
+          capability.permissions.len()
+        );
+        continue;
+      };
+
+      println!("  {}", "permissions:".cyan());
+      let mut commands = Commands::default();
+      for permission in &capability.permissions {
+        let identifier = permission.identifier();
+        println!("    {}", identifier.get());
+
+        let plugin = identifier.get_prefix().unwrap_or(APP_ACL_KEY);
+        match manifests.get(plugin) {
+          Some(manifest) => {
+            resolve_commands(plugin, manifest, identifier.get_base(), &mut commands)
+          }
+          None => log::warn!("Plugin of permission `{}` not found", identifier.get()),
+        }
+      }
+
+      if !commands.allow.is_empty() {
+        println!("  {}", "allowed commands:".cyan());
+        for command in &commands.allow {
+          println!("    {command}");
+        }
+      }
+      if !commands.deny.is_empty() {
+        println!("  {}", "denied commands:".cyan());
+        for command in &commands.deny {
+          println!("    {command}");
+        }
+      }
+    }
+  }
+
+  if !found {
+    match &options.window {
+      Some(window) => log::warn!("No capabilities found for window `{window}`"),
+      None => log::warn!("No capabilities found, create one with `tauri capability new`"),
+    }
+  }
+
+  Ok(())
+}
+
+/// Collects the commands allowed and denied by the given permission, expanding permission sets.
+fn resolve_commands(plugin: &str, manifest: &Manifest, permission: &str, commands: &mut Commands) {
+  fn resolve(
+    plugin: &str,
+    manifest: &Manifest,
+    permission: &str,
+    commands: &mut Commands,
+    visited: &mut BTreeSet<String>,
+  ) {
+    if !visited.insert(permission.to_string()) {
+      return;
+    }
+
+    let set = if permission == "default" {
+      manifest.default_permission.as_ref()
+    } else {
+      manifest.permission_sets.get(permission)
+    };
+
+    if let Some(set) = set {
+      for permission in &set.permissions {
+        resolve(plugin, manifest, permission, commands, visited);
+      }
+    } else if let Some(permission) = manifest.permissions.get(permission) {
+      let command_name = |command: &String| {
+        if plugin == APP_ACL_KEY {
+          command.clone()
+        } else {
+          format!("plugin:{plugin}|{command}")
+        }
+      };
+      commands
+        .allow
+        .extend(permission.commands.allow.iter().map(command_name));
+      commands
+        .deny
+        .extend(permission.commands.deny.iter().map(command_name));
+    } else {
+      log::warn!("Permission `{permission}` not found on `{plugin}`");
+    }
+  }
+
+  resolve(plugin, manifest, permission, commands, &mut BTreeSet::new());
+}
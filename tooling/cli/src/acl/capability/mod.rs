@@ -0,0 +1,37 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use clap::{Parser, Subcommand};
+
+use crate::Result;
+
+mod ls;
+mod new;
+
+#[derive(Parser)]
+#[clap(
+  author,
+  version,
+  about = "Manage or create capabilities for your app",
+  subcommand_required(true),
+  arg_required_else_help(true)
+)]
+pub struct Cli {
+  #[clap(subcommand)]
+  command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+  New(new::Options),
+  #[clap(alias = "list")]
+  Ls(ls::Options),
+}
+
+pub fn command(cli: Cli) -> Result<()> {
+  match cli.command {
+    Commands::New(options) => new::command(options),
+    Commands::Ls(options) => ls::command(options),
+  }
+}
@@ -0,0 +1,133 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Input};
+use serde::Serialize;
+
+use crate::{
+  acl::{capabilities_dir, permission_identifiers, plugin_manifests, FileFormat},
+  Result,
+};
+
+/// Path of the capabilities JSON schema relative to the capabilities folder.
+const CAPABILITIES_SCHEMA_PATH: &str = "../gen/schemas/desktop-schema.json";
+
+#[derive(Debug, Parser)]
+#[clap(about = "Create a new capability file")]
+pub struct Options {
+  /// Capability identifier.
+  identifier: Option<String>,
+  /// Capability description
+  #[clap(long)]
+  description: Option<String>,
+  /// Capability windows
+  #[clap(long, use_value_delimiter = true)]
+  windows: Option<Vec<String>>,
+  /// Capability permissions
+  #[clap(long, use_value_delimiter = true)]
+  permission: Option<Vec<String>>,
+  /// Output file format.
+  #[clap(long, default_value_t = FileFormat::Json)]
+  format: FileFormat,
+  /// The output file.
+  #[clap(short, long)]
+  out: Option<PathBuf>,
+  /// Skip prompting for values
+  #[clap(long)]
+  ci: bool,
+}
+
+#[derive(Serialize)]
+struct Capability {
+  #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+  schema: Option<&'static str>,
+  identifier: String,
+  #[serde(skip_serializing_if = "String::is_empty")]
+  description: String,
+  windows: Vec<String>,
+  permissions: Vec<String>,
+}
+
+pub fn command(mut options: Options) -> Result<()> {
+  options.ci = options.ci || std::env::var("CI").is_ok();
+  let theme = ColorfulTheme::default();
+
+  let identifier = match options.identifier {
+    Some(identifier) => identifier,
+    None if options.ci => anyhow::bail!("the capability identifier is required"),
+    None => Input::<String>::with_theme(&theme)
+      .with_prompt("What's the capability identifier?")
+      .interact_text()?,
+  };
+
+  let description = match options.description {
+    Some(description) => description,
+    None if options.ci => String::new(),
+    None => Input::<String>::with_theme(&theme)
+      .with_prompt("What's the capability description?")
+      .allow_empty(true)
+      .interact_text()?,
+  };
+
+  let windows = match options.windows {
+    Some(windows) => windows,
+    None if options.ci => vec!["main".into()],
+    None => Input::<String>::with_theme(&theme)
+      .with_prompt("Which windows should be affected by this? (comma separated)")
+      .default("main".into())
+      .interact_text()?
+      .split(',')
+      .map(|w| w.trim().to_string())
+      .filter(|w| !w.is_empty())
+      .collect(),
+  };
+
+  let permissions = options.permission.unwrap_or_default();
+  if !permissions.is_empty() {
+    match plugin_manifests() {
+      Ok(manifests) => {
+        let available = permission_identifiers(&manifests);
+        for permission in &permissions {
+          if !available.contains(permission) {
+            anyhow::bail!(
+              "Permission {permission} not found, expected one of {}",
+              available.join(", ")
+            );
+          }
+        }
+      }
+      Err(e) => log::warn!("Skipping permission validation: {e:#}"),
+    }
+  }
+
+  let path = match options.out {
+    Some(out) => out,
+    None => capabilities_dir().join(format!("{identifier}.{}", options.format.extension())),
+  };
+  if path.exists() {
+    anyhow::bail!("capability file {} already exists", path.display());
+  }
+
+  let capability = Capability {
+    schema: matches!(options.format, FileFormat::Json).then_some(CAPABILITIES_SCHEMA_PATH),
+    identifier,
+    description,
+    windows,
+    permissions,
+  };
+  let contents = options.format.serialize(&capability)?;
+
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+
+  log::info!(action = "Created"; "capability at {}", path.display());
+
+  Ok(())
+}
@@ -0,0 +1,455 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  collections::BTreeMap,
+  fmt::Display,
+  fs::{read_to_string, write},
+  path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use tauri_utils::acl::{
+  capability::{Capability, CapabilityFile},
+  plugin::Manifest,
+  APP_ACL_KEY,
+};
+
+use crate::{helpers::app_paths::tauri_dir, Result};
+
+pub mod capability;
+pub mod permission;
+pub mod report;
+
+/// Folder containing the capability files of the application.
+const CAPABILITIES_FOLDER_NAME: &str = "capabilities";
+/// Folder containing the ACL files generated by `tauri-build`.
+const GEN_SCHEMAS_FOLDER_PATH: &str = "gen/schemas";
+const PLUGIN_MANIFESTS_FILE_NAME: &str = "plugin-manifests.json";
+
+#[derive(Parser)]
+#[clap(
+  author,
+  version,
+  about = "Inspect the access control list of your application",
+  subcommand_required(true),
+  arg_required_else_help(true)
+)]
+pub struct Cli {
+  #[clap(subcommand)]
+  command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+  Report(report::Options),
+}
+
+pub fn command(cli: Cli) -> Result<()> {
+  match cli.command {
+    Commands::Report(options) => report::command(options),
+  }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FileFormat {
+  Json,
+  Toml,
+}
+
+impl Display for FileFormat {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.extension())
+  }
+}
+
+impl FileFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Self::Json => "json",
+      Self::Toml => "toml",
+    }
+  }
+
+  pub fn serialize<S: Serialize>(&self, s: &S) -> Result<String> {
+    let contents = match self {
+      Self::Json => serde_json::to_string_pretty(s)?,
+      Self::Toml => toml::to_string(s)?,
+    };
+    Ok(format!("{}\n", contents.trim_end()))
+  }
+}
+
+/// A capability file found on the capabilities folder.
+pub struct CapabilityFileEntry {
+  pub path: PathBuf,
+  pub capabilities: Vec<Capability>,
+}
+
+pub fn capabilities_dir() -> PathBuf {
+  tauri_dir().join(CAPABILITIES_FOLDER_NAME)
+}
+
+/// Reads all capability files from the application capabilities folder.
+pub fn capability_files() -> Result<Vec<CapabilityFileEntry>> {
+  let dir = capabilities_dir();
+  let mut files = Vec::new();
+
+  for path in glob::glob(&format!("{}/**/*", dir.display()))?.flatten() {
+    if is_schema_file(&path) {
+      continue;
+    }
+    let Some(format) = file_format(&path) else {
+      continue;
+    };
+
+    let contents =
+      read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    files.push(CapabilityFileEntry {
+      capabilities: parse_capability_file(format, &contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?,
+      path,
+    });
+  }
+
+  files.sort_by(|a, b| a.path.cmp(&b.path));
+
+  Ok(files)
+}
+
+/// Parses the capabilities defined in a capability file.
+pub fn parse_capability_file(format: FileFormat, contents: &str) -> Result<Vec<Capability>> {
+  let file: CapabilityFile = match format {
+    FileFormat::Json => serde_json::from_str(contents)?,
+    FileFormat::Toml => toml::from_str(contents)?,
+  };
+  Ok(match file {
+    CapabilityFile::Capability(capability) => vec![capability],
+    CapabilityFile::List { capabilities } => capabilities,
+  })
+}
+
+/// Whether the path is a JSON schema generated inside the capabilities folder.
+pub fn is_schema_file(path: &Path) -> bool {
+  path
+    .parent()
+    .and_then(|p| p.file_name())
+    .is_some_and(|name| name == "schemas")
+}
+
+pub fn plugin_manifests_path() -> PathBuf {
+  tauri_dir()
+    .join(GEN_SCHEMAS_FOLDER_PATH)
+    .join(PLUGIN_MANIFESTS_FILE_NAME)
+}
+
+/// Reads the plugin manifests generated by `tauri-build` on the last application build.
+pub fn plugin_manifests() -> Result<BTreeMap<String, Manifest>> {
+  let path = plugin_manifests_path();
+  let contents = read_to_string(&path).with_context(|| {
+    format!(
+      "failed to read {}, run `tauri dev` or `tauri build` to generate it",
+      path.display()
+    )
+  })?;
+  serde_json::from_str(&contents).context("failed to parse the plugin manifests")
+}
+
+/// Lists all permission identifiers defined in the given plugin manifests.
+pub fn permission_identifiers(manifests: &BTreeMap<String, Manifest>) -> Vec<String> {
+  let mut identifiers = Vec::new();
+  for (plugin, manifest) in manifests {
+    let identifier = |id: &str| {
+      if plugin == APP_ACL_KEY {
+        id.to_string()
+      } else {
+        format!("{plugin}:{id}")
+      }
+    };
+
+    if manifest.default_permission.is_some() {
+      identifiers.push(identifier("default"));
+    }
+    identifiers.extend(manifest.permission_sets.keys().map(|id| identifier(id)));
+    identifiers.extend(manifest.permissions.keys().map(|id| identifier(id)));
+  }
+  identifiers
+}
+
+/// Adds or removes a permission identifier from a capability in the given file.
+///
+/// TOML files keep their formatting, JSON files are rewritten with their keys sorted.
+pub fn update_capability_file(
+  path: &Path,
+  capability: &str,
+  update: impl Fn(&mut CapabilityPermissions<'_>) -> bool,
+) -> Result<bool> {
+  let format = file_format(path).expect("unexpected capability file format");
+  let contents = read_to_string(path)?;
+
+  let (updated, contents) = match format {
+    FileFormat::Json => {
+      let mut json: serde_json::Value = serde_json::from_str(&contents)?;
+      let capability =
+        if let Some(capabilities) = json.get_mut("capabilities").and_then(|c| c.as_array_mut()) {
+          capabilities
+            .iter_mut()
+            .find(|c| c.get("identifier").and_then(|i| i.as_str()) == Some(capability))
+        } else {
+          Some(&mut json)
+        };
+      let updated = match capability
+        .and_then(|c| c.get_mut("permissions"))
+        .and_then(|p| p.as_array_mut())
+      {
+        Some(permissions) => update(&mut CapabilityPermissions::Json(permissions)),
+        None => false,
+      };
+      (updated, serde_json::to_string_pretty(&json)? + "\n")
+    }
+    FileFormat::Toml => {
+      let mut toml: toml_edit::Document = contents.parse()?;
+      let capability = if let Some(capabilities) = toml
+        .get_mut("capabilities")
+        .and_then(|c| c.as_array_of_tables_mut())
+      {
+        capabilities
+          .iter_mut()
+          .find(|c| c.get("identifier").and_then(|i| i.as_str()) == Some(capability))
+      } else {
+        Some(toml.as_table_mut())
+      };
+      let updated = match capability
+        .and_then(|c| c.get_mut("permissions"))
+        .and_then(|p| p.as_array_mut())
+      {
+        Some(permissions) => update(&mut CapabilityPermissions::Toml(permissions)),
+        None => false,
+      };
+      (updated, toml.to_string())
+    }
+  };
+
+  if updated {
+    write(path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+  }
+
+  Ok(updated)
+}
+
+/// The permission list of a capability being edited.
+pub enum CapabilityPermissions<'a> {
+  Json(&'a mut Vec<serde_json::Value>),
+  Toml(&'a mut toml_edit::Array),
+}
+
+impl CapabilityPermissions<'_> {
+  fn identifiers(&self) -> Vec<Option<String>> {
+    match self {
+      Self::Json(permissions) => permissions
+        .iter()
+        .map(|p| {
+          p.as_str()
+            .or_else(|| p.get("identifier").and_then(|i| i.as_str()))
+            .map(ToString::to_string)
+        })
+        .collect(),
+      Self::Toml(permissions) => permissions
+        .iter()
+        .map(|p| {
+          p.as_str()
+            .or_else(|| {
+              p.as_inline_table()
+                .and_then(|t| t.get("identifier"))
+                .and_then(|i| i.as_str())
+            })
+            .map(ToString::to_string)
+        })
+        .collect(),
+    }
+  }
+
+  /// Whether the capability references the given permission.
+  pub fn contains(&self, identifier: &str) -> bool {
+    self
+      .identifiers()
+      .iter()
+      .any(|i| i.as_deref() == Some(identifier))
+  }
+
+  /// Adds the permission to the capability, returns `false` if it is already referenced.
+  pub fn add(&mut self, identifier: &str) -> bool {
+    if self.contains(identifier) {
+      return false;
+    }
+    match self {
+      Self::Json(permissions) => permissions.push(identifier.into()),
+      Self::Toml(permissions) => permissions.push(identifier),
+    }
+    true
+  }
+
+  /// Removes all references of the permission from the capability, returns `false` if it was not referenced.
+  pub fn remove(&mut self, identifier: &str) -> bool {
+    let indexes = self
+      .identifiers()
+      .into_iter()
+      .enumerate()
+      .filter(|(_, i)| i.as_deref() == Some(identifier))
+      .map(|(index, _)| index)
+      .collect::<Vec<_>>();
+
+    for index in indexes.iter().rev() {
+      match self {
+        Self::Json(permissions) => {
+          permissions.remove(*index);
+        }
+        Self::Toml(permissions) => {
+          permissions.remove(*index);
+        }
+      }
+    }
+
+    !indexes.is_empty()
+  }
+}
+
+pub fn file_format(path: &Path) -> Option<FileFormat> {
+  match path.extension().and_then(|e| e.to_str()) {
+    Some("json") => Some(FileFormat::Json),
+    Some("toml") => Some(FileFormat::Toml),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tauri-cli-acl-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    write(&path, contents).unwrap();
+    path
+  }
+
+  #[test]
+  fn capability_file_formats() {
+    let capabilities = parse_capability_file(
+      FileFormat::Json,
+      r#"{ "identifier": "main", "windows": ["main"], "permissions": ["fs:default"] }"#,
+    )
+    .unwrap();
+    assert_eq!(capabilities.len(), 1);
+    assert_eq!(capabilities[0].identifier, "main");
+
+    let capabilities = parse_capability_file(
+      FileFormat::Toml,
+      r#"
+[[capabilities]]
+identifier = "main"
+windows = ["main"]
+permissions = []
+
+[[capabilities]]
+identifier = "settings"
+windows = ["settings"]
+permissions = []
+"#,
+    )
+    .unwrap();
+    assert_eq!(capabilities.len(), 2);
+    assert_eq!(capabilities[1].identifier, "settings");
+  }
+
+  #[test]
+  fn update_json_capability() {
+    let path = temp_file(
+      "capability.json",
+      r#"{ "identifier": "main", "windows": ["main"], "permissions": ["fs:default", { "identifier": "fs:allow-read", "allow": ["$APPDATA"] }] }"#,
+    );
+
+    assert!(!update_capability_file(&path, "main", |p| p.add("fs:default")).unwrap());
+    assert!(update_capability_file(&path, "main", |p| p.add("shell:default")).unwrap());
+    assert!(update_capability_file(&path, "main", |p| p.remove("fs:allow-read")).unwrap());
+    assert!(!update_capability_file(&path, "other", |p| p.add("shell:default")).unwrap());
+
+    assert_eq!(
+      read_to_string(&path).unwrap(),
+      r#"{
+  "identifier": "main",
+  "permissions": [
+    "fs:default",
+    "shell:default"
+  ],
+  "windows": [
+    "main"
+  ]
+}
+"#
+    );
+  }
+
+  #[test]
+  fn update_toml_capability() {
+    let path = temp_file(
+      "capabilities.toml",
+      r#"# app capabilities
+[[capabilities]]
+identifier = "main"
+windows = ["main"]
+permissions = ["fs:default"]
+
+[[capabilities]]
+identifier = "settings"
+windows = ["settings"]
+permissions = ["fs:default", { identifier = "fs:allow-read", allow = ["$APPDATA"] }]
+"#,
+    );
+
+    assert!(update_capability_file(&path, "settings", |p| p.remove("fs:allow-read")).unwrap());
+    assert!(update_capability_file(&path, "main", |p| p.add("shell:default")).unwrap());
+
+    assert_eq!(
+      read_to_string(&path).unwrap(),
+      r#"# app capabilities
+[[capabilities]]
+identifier = "main"
+windows = ["main"]
+permissions = ["fs:default", "shell:default"]
+
+[[capabilities]]
+identifier = "settings"
+windows = ["settings"]
+permissions = ["fs:default"]
+"#
+    );
+  }
+
+  #[test]
+  fn permission_identifiers_of_manifests() {
+    let manifests: BTreeMap<String, Manifest> = serde_json::from_value(serde_json::json!({
+      "fs": {
+        "default_permission": { "identifier": "default", "description": "", "permissions": [] },
+        "permissions": { "allow-read": { "identifier": "allow-read" } },
+        "permission_sets": {},
+        "global_scope_schema": null
+      },
+      APP_ACL_KEY: {
+        "default_permission": null,
+        "permissions": { "allow-greet": { "identifier": "allow-greet" } },
+        "permission_sets": {},
+        "global_scope_schema": null
+      }
+    }))
+    .unwrap();
+    assert_eq!(
+      permission_identifiers(&manifests),
+      ["allow-greet", "fs:default", "fs:allow-read"]
+    );
+  }
+}
@@ -0,0 +1,109 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Select};
+
+use crate::{
+  acl::{
+    capability_files, permission_identifiers, plugin_manifests, update_capability_file,
+    CapabilityFileEntry,
+  },
+  Result,
+};
+
+#[derive(Debug, Parser)]
+#[clap(about = "Add a permission to a capability")]
+pub struct Options {
+  /// Permission to add.
+  identifier: String,
+  /// Capability to add the permission to.
+  capability: Option<String>,
+}
+
+pub fn command(options: Options) -> Result<()> {
+  let manifests = plugin_manifests()?;
+  let available = permission_identifiers(&manifests);
+  if !available.contains(&options.identifier) {
+    anyhow::bail!(
+      "Permission {} not found, expected one of {}",
+      options.identifier,
+      available.join(", ")
+    );
+  }
+
+  add_permission(&options.identifier, options.capability.as_deref())
+}
+
+/// Adds the permission to the given capability, prompting for one when it is not provided.
+pub fn add_permission(identifier: &str, capability: Option<&str>) -> Result<()> {
+  let files = capability_files()?;
+  let (file, capability) = match capability {
+    Some(capability) => find_capability(&files, capability)
+      .ok_or_else(|| anyhow::anyhow!("capability {capability} not found"))?,
+    None => match select_capability(&files, identifier)? {
+      Some(selected) => selected,
+      None => return Ok(()),
+    },
+  };
+
+  if update_capability_file(&file.path, capability, |permissions| {
+    permissions.add(identifier)
+  })? {
+    log::info!(action = "Added"; "permission `{identifier}` to `{capability}` at {}", file.path.display());
+  } else {
+    log::info!("Permission `{identifier}` already found on `{capability}`");
+  }
+
+  Ok(())
+}
+
+fn find_capability<'a>(
+  files: &'a [CapabilityFileEntry],
+  identifier: &str,
+) -> Option<(&'a CapabilityFileEntry, &'a str)> {
+  files.iter().find_map(|file| {
+    file
+      .capabilities
+      .iter()
+      .find(|c| c.identifier == identifier)
+      .map(|c| (file, c.identifier.as_str()))
+  })
+}
+
+fn select_capability<'a>(
+  files: &'a [CapabilityFileEntry],
+  identifier: &str,
+) -> Result<Option<(&'a CapabilityFileEntry, &'a str)>> {
+  let capabilities = files
+    .iter()
+    .flat_map(|file| {
+      file
+        .capabilities
+        .iter()
+        .map(move |c| (file, c.identifier.as_str()))
+    })
+    .collect::<Vec<_>>();
+
+  match capabilities.len() {
+    0 => {
+      log::warn!("No capabilities found, create one with `tauri capability new`");
+      Ok(None)
+    }
+    1 => Ok(capabilities.into_iter().next()),
+    _ => {
+      let selected = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Choose the capability to add `{identifier}` to"))
+        .items(
+          &capabilities
+            .iter()
+            .map(|(_, capability)| capability)
+            .collect::<Vec<_>>(),
+        )
+        .default(0)
+        .interact_opt()?;
+      Ok(selected.map(|index| capabilities[index]))
+    }
+  }
+}
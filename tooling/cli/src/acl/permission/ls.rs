@@ -0,0 +1,71 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use clap::Parser;
+use colored::Colorize;
+use tauri_utils::acl::APP_ACL_KEY;
+
+use crate::{acl::plugin_manifests, Result};
+
+#[derive(Debug, Parser)]
+#[clap(about = "List permissions available to your application")]
+pub struct Options {
+  /// Name of the plugin to list permissions.
+  plugin: Option<String>,
+  /// Permission identifier filter.
+  #[clap(short, long)]
+  filter: Option<String>,
+}
+
+pub fn command(options: Options) -> Result<()> {
+  let manifests = plugin_manifests()?;
+
+  for (plugin, manifest) in &manifests {
+    let is_app = plugin == APP_ACL_KEY;
+    if options
+      .plugin
+      .as_ref()
+      .is_some_and(|p| p != plugin && !(is_app && p == "app"))
+    {
+      continue;
+    }
+
+    let mut permissions = Vec::new();
+    if let Some(default) = &manifest.default_permission {
+      permissions.push(("default", Some(default.description.as_str())));
+    }
+    for set in manifest.permission_sets.values() {
+      permissions.push((set.identifier.as_str(), Some(set.description.as_str())));
+    }
+    for permission in manifest.permissions.values() {
+      permissions.push((
+        permission.identifier.as_str(),
+        permission.description.as_deref(),
+      ));
+    }
+
+    for (id, description) in permissions {
+      let identifier = if is_app {
+        id.to_string()
+      } else {
+        format!("{plugin}:{id}")
+      };
+      if options
+        .filter
+        .as_ref()
+        .is_some_and(|f| !identifier.contains(f.as_str()))
+      {
+        continue;
+      }
+
+      println!(
+        "{}{}",
+        identifier.green(),
+        description.map(|d| format!(" - {d}")).unwrap_or_default()
+      );
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,231 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Input};
+use serde::Serialize;
+use tauri_utils::acl::Identifier;
+
+use crate::{acl::FileFormat, helpers::app_paths::tauri_dir, Result};
+
+/// Folder containing the permission files of an app or plugin.
+const PERMISSIONS_FOLDER_NAME: &str = "permissions";
+/// Path of the permissions JSON schema relative to the permissions folder.
+const PERMISSIONS_SCHEMA_PATH: &str = "schemas/schema.json";
+
+#[derive(Debug, Parser)]
+#[clap(about = "Create a new permission file")]
+pub struct Options {
+  /// Permission identifier.
+  identifier: Option<String>,
+  /// Permission description
+  #[clap(long)]
+  description: Option<String>,
+  /// List of commands to allow
+  #[clap(short, long, use_value_delimiter = true)]
+  allow: Option<Vec<String>>,
+  /// List of commands to deny
+  #[clap(short, long, use_value_delimiter = true)]
+  deny: Option<Vec<String>>,
+  /// Output file format.
+  #[clap(long, default_value_t = FileFormat::Toml)]
+  format: FileFormat,
+  /// The output file.
+  #[clap(short, long)]
+  out: Option<PathBuf>,
+  /// Skip prompting for values
+  #[clap(long)]
+  ci: bool,
+}
+
+pub fn command(mut options: Options) -> Result<()> {
+  options.ci = options.ci || std::env::var("CI").is_ok();
+  let theme = ColorfulTheme::default();
+
+  let identifier = match options.identifier {
+    Some(identifier) => identifier,
+    None if options.ci => anyhow::bail!("the permission identifier is required"),
+    None => Input::<String>::with_theme(&theme)
+      .with_prompt("What's the permission identifier?")
+      .interact_text()?,
+  };
+  if identifier.contains(':') {
+    anyhow::bail!(
+      "permission identifiers cannot have a prefix, the plugin name is added when the permission is referenced"
+    );
+  }
+  Identifier::try_from(identifier.clone())
+    .with_context(|| format!("invalid permission identifier {identifier}"))?;
+
+  let description = match options.description {
+    Some(description) => Some(description),
+    None if options.ci => None,
+    None => Some(
+      Input::<String>::with_theme(&theme)
+        .with_prompt("What's the permission description?")
+        .allow_empty(true)
+        .interact_text()?,
+    )
+    .filter(|d| !d.is_empty()),
+  };
+
+  let allow = options.allow.unwrap_or_default();
+  let deny = options.deny.unwrap_or_default();
+
+  let path = match options.out {
+    Some(out) => out,
+    None => permissions_dir()?.join(format!("{identifier}.{}", options.format.extension())),
+  };
+  if path.exists() {
+    anyhow::bail!("permission file {} already exists", path.display());
+  }
+
+  let contents = permission_file(options.format, &identifier, description, allow, deny)?;
+
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  std::fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+
+  log::info!(action = "Created"; "permission at {}", path.display());
+
+  Ok(())
+}
+
+#[derive(Serialize)]
+struct PermissionFile<'a> {
+  #[serde(rename = "$schema")]
+  schema: &'static str,
+  permission: [Permission<'a>; 1],
+}
+
+#[derive(Serialize)]
+struct Permission<'a> {
+  identifier: &'a str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  description: Option<String>,
+  commands: Commands,
+}
+
+#[derive(Serialize)]
+struct Commands {
+  allow: Vec<String>,
+  deny: Vec<String>,
+}
+
+/// The contents of a permission file with a single permission.
+fn permission_file(
+  format: FileFormat,
+  identifier: &str,
+  description: Option<String>,
+  allow: Vec<String>,
+  deny: Vec<String>,
+) -> Result<String> {
+  let contents = match format {
+    FileFormat::Toml => {
+      let mut contents = format!(
+        "\"$schema\" = \"{PERMISSIONS_SCHEMA_PATH}\"\n\n[[permission]]\nidentifier = {}\n",
+        toml::Value::from(identifier)
+      );
+      if let Some(description) = &description {
+        contents.push_str(&format!(
+          "description = {}\n",
+          toml::Value::from(description.as_str())
+        ));
+      }
+      if !allow.is_empty() {
+        contents.push_str(&format!("commands.allow = {}\n", toml::Value::from(allow)));
+      }
+      if !deny.is_empty() {
+        contents.push_str(&format!("commands.deny = {}\n", toml::Value::from(deny)));
+      }
+      contents
+    }
+    FileFormat::Json => format.serialize(&PermissionFile {
+      schema: PERMISSIONS_SCHEMA_PATH,
+      permission: [Permission {
+        identifier,
+        description,
+        commands: Commands { allow, deny },
+      }],
+    })?,
+  };
+  Ok(contents)
+}
+
+/// The permissions folder of the plugin on the current directory or the Tauri app.
+fn permissions_dir() -> Result<PathBuf> {
+  let cwd = std::env::current_dir()?;
+  let cargo_toml_path = cwd.join("Cargo.toml");
+
+  if cargo_toml_path.exists() {
+    let contents = std::fs::read_to_string(cargo_toml_path)?;
+    let cargo_toml: toml::Value = toml::from_str(&contents)?;
+    let is_plugin = cargo_toml
+      .get("build-dependencies")
+      .and_then(|deps| deps.get("tauri-plugin"))
+      .is_some();
+    if is_plugin {
+      return Ok(cwd.join(PERMISSIONS_FOLDER_NAME));
+    }
+  }
+
+  Ok(tauri_dir().join(PERMISSIONS_FOLDER_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn toml_permission_file() {
+    let contents = permission_file(
+      FileFormat::Toml,
+      "allow-greet",
+      Some("Allows greeting".into()),
+      vec!["greet".into()],
+      Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(
+      contents,
+      "\"$schema\" = \"schemas/schema.json\"\n\n[[permission]]\nidentifier = \"allow-greet\"\ndescription = \"Allows greeting\"\ncommands.allow = [\"greet\"]\n"
+    );
+    let file: tauri_utils::acl::plugin::PermissionFile = toml::from_str(&contents).unwrap();
+    assert_eq!(file.permission[0].commands.allow, ["greet"]);
+  }
+
+  #[test]
+  fn json_permission_file() {
+    let contents = permission_file(
+      FileFormat::Json,
+      "deny-greet",
+      None,
+      Vec::new(),
+      vec!["greet".into()],
+    )
+    .unwrap();
+    assert_eq!(
+      contents,
+      r#"{
+  "$schema": "schemas/schema.json",
+  "permission": [
+    {
+      "identifier": "deny-greet",
+      "commands": {
+        "allow": [],
+        "deny": [
+          "greet"
+        ]
+      }
+    }
+  ]
+}
+"#
+    );
+  }
+}
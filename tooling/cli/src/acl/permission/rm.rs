@@ -0,0 +1,59 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use clap::Parser;
+
+use crate::{
+  acl::{capability_files, update_capability_file},
+  Result,
+};
+
+#[derive(Debug, Parser)]
+#[clap(about = "Remove a permission from capabilities")]
+pub struct Options {
+  /// Permission to remove.
+  identifier: String,
+  /// Capability to remove the permission from. Removes it from all capabilities by default.
+  capability: Option<String>,
+}
+
+pub fn command(options: Options) -> Result<()> {
+  let mut found_capability = false;
+  let mut removed = false;
+
+  for file in capability_files()? {
+    for capability in &file.capabilities {
+      if options
+        .capability
+        .as_ref()
+        .is_some_and(|c| c != &capability.identifier)
+      {
+        continue;
+      }
+      found_capability = true;
+
+      if update_capability_file(&file.path, &capability.identifier, |permissions| {
+        permissions.remove(&options.identifier)
+      })? {
+        removed = true;
+        log::info!(action = "Removed"; "permission `{}` from `{}` at {}", options.identifier, capability.identifier, file.path.display());
+      }
+    }
+  }
+
+  if let Some(capability) = &options.capability {
+    if !found_capability {
+      anyhow::bail!("capability {capability} not found");
+    }
+  }
+
+  if !removed {
+    log::warn!(
+      "Permission `{}` is not referenced by any capability",
+      options.identifier
+    );
+  }
+
+  Ok(())
+}
@@ -0,0 +1,333 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use tauri_utils::{
+  acl::{
+    capability::Capability,
+    plugin::Manifest,
+    report::{Decision, Grant, Report, ReportChange, ReportCommand, ReportEntry, ReportScope},
+    APP_ACL_KEY,
+  },
+  platform::Target,
+};
+
+use crate::{
+  acl::{
+    capabilities_dir, capability_files, file_format, is_schema_file, parse_capability_file,
+    plugin_manifests, plugin_manifests_path,
+  },
+  helpers::app_paths::tauri_dir,
+  Result,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ReportFormat {
+  Text,
+  Json,
+}
+
+#[derive(Debug, Parser)]
+#[clap(about = "Print the resolved access control list of your application")]
+pub struct Options {
+  /// Output format.
+  #[clap(long, value_enum, default_value_t = ReportFormat::Text)]
+  format: ReportFormat,
+  /// Only print what the given window label is allowed to do.
+  #[clap(short, long)]
+  window: Option<String>,
+  /// Target platform to resolve the capabilities for. Defaults to the current platform.
+  #[clap(short, long, value_parser = parse_target)]
+  target: Option<Target>,
+  /// Compare the access control list of two git revisions.
+  /// When a single revision is provided it is compared against the working tree.
+  #[clap(long, num_args = 1..=2, value_names = ["FROM", "TO"])]
+  diff: Option<Vec<String>>,
+}
+
+fn parse_target(target: &str) -> std::result::Result<Target, String> {
+  match target.to_lowercase().as_str() {
+    "macos" => Ok(Target::MacOS),
+    "windows" => Ok(Target::Windows),
+    "linux" => Ok(Target::Linux),
+    "android" => Ok(Target::Android),
+    "ios" => Ok(Target::Ios),
+    _ => Err(format!(
+      "unknown target {target}, expected one of macos, windows, linux, android or ios"
+    )),
+  }
+}
+
+pub fn command(options: Options) -> Result<()> {
+  let target = options.target.unwrap_or_else(Target::current);
+
+  if let Some(revisions) = &options.diff {
+    let before = report(Some(&revisions[0]), target, options.window.as_deref())?;
+    let after = report(
+      revisions.get(1).map(|r| r.as_str()),
+      target,
+      options.window.as_deref(),
+    )?;
+    let changes = before.diff(&after);
+
+    match options.format {
+      ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&changes)?),
+      ReportFormat::Text => print_changes(&changes),
+    }
+  } else {
+    let report = report(None, target, options.window.as_deref())?;
+
+    match options.format {
+      ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+      ReportFormat::Text => print_report(&report),
+    }
+  }
+
+  Ok(())
+}
+
+/// Generates the report for the given git revision, or the working tree.
+fn report(revision: Option<&str>, target: Target, window: Option<&str>) -> Result<Report> {
+  let (manifests, capabilities) = match revision {
+    Some(revision) => (
+      manifests_at(revision)?,
+      capabilities_at(revision)
+        .with_context(|| format!("failed to read capabilities at {revision}"))?,
+    ),
+    None => {
+      let mut capabilities = BTreeMap::new();
+      for file in capability_files()? {
+        for capability in file.capabilities {
+          capabilities.insert(capability.identifier.clone(), capability);
+        }
+      }
+      (plugin_manifests()?, capabilities)
+    }
+  };
+
+  let mut report = Report::new(&manifests, &capabilities, target)?;
+  if let Some(window) = window {
+    report.entries = report.window(window);
+  }
+  Ok(report)
+}
+
+/// Reads the plugin manifests at the given revision, falling back to the current ones when they are not committed.
+fn manifests_at(revision: &str) -> Result<BTreeMap<String, Manifest>> {
+  match git_show(revision, &plugin_manifests_path()) {
+    Ok(contents) => serde_json::from_str(&contents).context("failed to parse the plugin manifests"),
+    Err(_) => {
+      log::warn!(
+        "Plugin manifests not found at {revision}, using the manifests generated by the last build"
+      );
+      plugin_manifests()
+    }
+  }
+}
+
+fn capabilities_at(revision: &str) -> Result<BTreeMap<String, Capability>> {
+  let output = git(&[
+    "ls-tree",
+    "-r",
+    "--name-only",
+    "--full-tree",
+    revision,
+    &repo_relative_path(&capabilities_dir())?,
+  ])?;
+
+  let mut capabilities = BTreeMap::new();
+  for path in output.lines().map(PathBuf::from) {
+    if is_schema_file(&path) {
+      continue;
+    }
+    let Some(format) = file_format(&path) else {
+      continue;
+    };
+
+    let contents = git(&["show", &format!("{revision}:{}", path.display())])?;
+    for capability in parse_capability_file(format, &contents)
+      .with_context(|| format!("failed to parse {}", path.display()))?
+    {
+      capabilities.insert(capability.identifier.clone(), capability);
+    }
+  }
+
+  Ok(capabilities)
+}
+
+fn git_show(revision: &str, path: &Path) -> Result<String> {
+  git(&["show", &format!("{revision}:{}", repo_relative_path(path)?)])
+}
+
+/// The path relative to the git repository root, using forward slashes.
+fn repo_relative_path(path: &Path) -> Result<String> {
+  // `git` runs on the Tauri directory, so the prefix is the Tauri directory path relative to the repository root
+  let prefix = git(&["rev-parse", "--show-prefix"])?;
+  let relative = path
+    .strip_prefix(tauri_dir())
+    .context("path is not inside the Tauri project")?;
+  Ok(format!(
+    "{}{}",
+    prefix.trim(),
+    relative.display().to_string().replace('\\', "/")
+  ))
+}
+
+fn git(args: &[&str]) -> Result<String> {
+  let output = Command::new("git")
+    .args(args)
+    .current_dir(tauri_dir())
+    .output()
+    .context("failed to run git")?;
+  if !output.status.success() {
+    anyhow::bail!(
+      "`git {}` failed: {}",
+      args.join(" "),
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn print_report(report: &Report) {
+  if report.entries.is_empty() && report.global_scopes.is_empty() {
+    log::warn!("No commands are allowed by the access control list");
+    return;
+  }
+
+  for entry in &report.entries {
+    print_entry(entry);
+  }
+
+  if !report.global_scopes.is_empty() {
+    println!("{}", "global scopes".bold());
+    for global_scope in &report.global_scopes {
+      println!("  {}", plugin_display(&global_scope.plugin).green());
+      print_scope(&global_scope.scope, "    ");
+      print_grants(&global_scope.granted_by, "    ");
+    }
+  }
+}
+
+fn print_entry(entry: &ReportEntry) {
+  println!(
+    "{} {} [{}]",
+    "window".bold(),
+    entry.window.bold(),
+    entry.context
+  );
+  for command in &entry.commands {
+    print_command(command, "  ");
+  }
+}
+
+fn print_command(command: &ReportCommand, indent: &str) {
+  let decision = match command.decision {
+    Decision::Allow => "allow".green(),
+    Decision::Deny => "deny ".red(),
+  };
+  println!("{indent}{decision} {}", command.name);
+  print_scope(&command.scope, &format!("{indent}  "));
+  print_grants(&command.granted_by, &format!("{indent}  "));
+}
+
+fn print_scope(scope: &ReportScope, indent: &str) {
+  for (label, values) in [("scope allow", &scope.allow), ("scope deny", &scope.deny)] {
+    for value in values {
+      println!(
+        "{indent}{} {}",
+        format!("{label}:").cyan(),
+        serde_json::to_string(value).unwrap_or_default()
+      );
+    }
+  }
+}
+
+fn print_grants(grants: &[Grant], indent: &str) {
+  for grant in grants {
+    println!(
+      "{indent}{} {} ({})",
+      "granted by:".dimmed(),
+      grant.capability,
+      grant.permission
+    );
+  }
+}
+
+fn print_changes(changes: &[ReportChange]) {
+  if changes.is_empty() {
+    log::info!("No access control list changes");
+    return;
+  }
+
+  for change in changes {
+    match change {
+      ReportChange::Added {
+        window,
+        context,
+        command,
+      } => {
+        println!("{} window {window} [{context}]", "+".green().bold());
+        print_command(command, "  ");
+      }
+      ReportChange::Removed {
+        window,
+        context,
+        command,
+      } => {
+        println!("{} window {window} [{context}]", "-".red().bold());
+        print_command(command, "  ");
+      }
+      ReportChange::Changed {
+        window,
+        context,
+        before,
+        after,
+      } => {
+        println!("{} window {window} [{context}]", "~".yellow().bold());
+        println!("  {}", "before:".dimmed());
+        print_command(before, "    ");
+        println!("  {}", "after:".dimmed());
+        print_command(after, "    ");
+      }
+      ReportChange::GlobalScope {
+        plugin,
+        before,
+        after,
+      } => {
+        println!(
+          "{} global scope {}",
+          "~".yellow().bold(),
+          plugin_display(plugin)
+        );
+        for (label, scope) in [("before:", before), ("after:", after)] {
+          println!("  {}", label.dimmed());
+          match scope {
+            Some(scope) => {
+              print_scope(&scope.scope, "    ");
+              print_grants(&scope.granted_by, "    ");
+            }
+            None => println!("    none"),
+          }
+        }
+      }
+    }
+  }
+}
+
+fn plugin_display(plugin: &str) -> &str {
+  if plugin == APP_ACL_KEY {
+    "app"
+  } else {
+    plugin
+  }
+}
@@ -5,9 +5,11 @@
 use anyhow::Context;
 use clap::Parser;
 use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, Confirm};
 use regex::Regex;
 
 use crate::{
+  acl::permission::add::add_permission,
   helpers::{
     app_paths::{app_dir, tauri_dir},
     cross_command,
@@ -110,6 +112,18 @@ pub fn command(options: Options) -> Result<()> {
     }
   }
 
+  // add the plugin default permission to a capability
+  if std::env::var("CI").is_err()
+    && Confirm::with_theme(&ColorfulTheme::default())
+      .with_prompt(format!(
+        "Add the `{plugin}:default` permission to a capability?"
+      ))
+      .default(true)
+      .interact()?
+  {
+    add_permission(&format!("{plugin}:default"), None)?;
+  }
+
   // add plugin init code to main.rs or lib.rs
   let plugin_init_fn = if metadata.builder {
     "Builder::new().build()"
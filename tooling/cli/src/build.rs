@@ -6,22 +6,16 @@ use crate::{
   helpers::{
     app_paths::{app_dir, tauri_dir},
     command_env,
-    config::{get as get_config, ConfigHandle, FrontendDist, HookCommand},
-    updater_signature::{secret_key as updater_secret_key, sign_file},
+    config::{get as get_config, Config, ConfigHandle, ConfigMetadata, FrontendDist, HookCommand},
+    sbom::SbomFormat,
   },
   interface::{AppInterface, AppSettings, Interface},
   CommandExt, ConfigValue, Result,
 };
 use anyhow::{bail, Context};
-use base64::Engine;
 use clap::{ArgAction, Parser};
-use log::{debug, error, info, warn};
-use std::{
-  env::{set_current_dir, var},
-  path::{Path, PathBuf},
-  process::Command,
-};
-use tauri_bundler::bundle::{bundle_project, Bundle, PackageType};
+use log::{debug, error, info};
+use std::{env::set_current_dir, process::Command};
 use tauri_utils::platform::Target;
 
 #[derive(Debug, Clone, Parser)]
@@ -62,11 +56,15 @@ pub struct Options {
   /// Skip prompting for values
   #[clap(long)]
   pub ci: bool,
+  /// Write a software bill of materials next to each bundle.
+  ///
+  /// It lists the Cargo and frontend lockfile dependencies and the bundled sidecars and resources with their hashes.
+  #[clap(long, value_enum)]
+  pub sbom: Option<SbomFormat>,
 }
 
 pub fn command(mut options: Options, verbosity: u8) -> Result<()> {
   options.ci = options.ci || std::env::var("CI").is_ok();
-  let ci = options.ci;
 
   let target = options
     .target
@@ -96,171 +94,14 @@ pub fn command(mut options: Options, verbosity: u8) -> Result<()> {
 
   let app_settings = interface.app_settings();
 
-  if config_.bundle.active {
-    let package_types = if let Some(names) = &options.bundles {
-      let mut types = vec![];
-      let mut skip = false;
-      for name in names {
-        if name == "none" {
-          skip = true;
-          break;
-        }
-
-        match PackageType::from_short_name(name) {
-          Some(package_type) => {
-            types.push(package_type);
-          }
-          None => {
-            return Err(anyhow::anyhow!(format!(
-              "Unsupported bundle format: {name}"
-            )));
-          }
-        }
-      }
-
-      if skip {
-        None
-      } else {
-        Some(types)
-      }
-    } else {
-      let targets = config_.bundle.targets.to_vec();
-      if targets.is_empty() {
-        None
-      } else {
-        Some(targets.into_iter().map(Into::into).collect())
-      }
-    };
-
-    let updater_pub_key = config_
-      .plugins
-      .0
-      .get("updater")
-      .and_then(|k| k.get("pubkey"))
-      .and_then(|v| v.as_str())
-      .map(|v| v.to_string());
-    if let Some(types) = &package_types {
-      if updater_pub_key
-        .as_ref()
-        .map(|v| !v.is_empty())
-        .unwrap_or(false)
-        && !types.contains(&PackageType::Updater)
-      {
-        warn!("`plugins > updater > pubkey` is set, but the bundle target list does not contain `updater`, so the updater artifacts won't be generated.");
-      }
-    }
-
-    // if we have a package to bundle, let's run the `before_bundle_command`.
-    if package_types.as_ref().map_or(true, |p| !p.is_empty()) {
-      if let Some(before_bundle) = config_.build.before_bundle_command.clone() {
-        run_hook(
-          "beforeBundleCommand",
-          before_bundle,
-          &interface,
-          options.debug,
-        )?;
-      }
-    }
-
-    let mut settings = app_settings
-      .get_bundler_settings(&options.into(), config_, out_dir, package_types)
-      .with_context(|| "failed to build bundler settings")?;
-
-    settings.set_log_level(match verbosity {
-      0 => log::Level::Error,
-      1 => log::Level::Info,
-      _ => log::Level::Trace,
-    });
-
-    // set env vars used by the bundler
-    #[cfg(target_os = "linux")]
-    {
-      if config_.bundle.linux.appimage.bundle_media_framework {
-        std::env::set_var("APPIMAGE_BUNDLE_GSTREAMER", "1");
-      }
-
-      if let Some(open) = config_.plugins.0.get("shell").and_then(|v| v.get("open")) {
-        if open.as_bool().is_some_and(|x| x) || open.is_string() {
-          std::env::set_var("APPIMAGE_BUNDLE_XDG_OPEN", "1");
-        }
-      }
-
-      if settings.deep_link_protocols().is_some() {
-        std::env::set_var("APPIMAGE_BUNDLE_XDG_MIME", "1");
-      }
-    }
-
-    let bundles = bundle_project(settings)
-      .map_err(|e| anyhow::anyhow!("{:#}", e))
-      .with_context(|| "failed to bundle project")?;
-
-    let updater_bundles: Vec<&Bundle> = bundles
-      .iter()
-      .filter(|bundle| bundle.package_type == PackageType::Updater)
-      .collect();
-
-    // If updater is active and we bundled it
-    if !updater_bundles.is_empty() {
-      if let Some(pubkey) = updater_pub_key {
-        // get the public key
-        // check if pubkey points to a file...
-        let maybe_path = Path::new(&pubkey);
-        let pubkey = if maybe_path.exists() {
-          std::fs::read_to_string(maybe_path)?
-        } else {
-          pubkey
-        };
-
-        // if no password provided we use an empty string
-        let password = var("TAURI_SIGNING_PRIVATE_KEY_PASSWORD").ok().or_else(|| {
-          if ci {
-            Some("".into())
-          } else {
-            None
-          }
-        });
-
-        // get the private key
-        let secret_key = match var("TAURI_SIGNING_PRIVATE_KEY") {
-        Ok(private_key) => {
-          // check if private_key points to a file...
-          let maybe_path = Path::new(&private_key);
-          let private_key = if maybe_path.exists() {
-            std::fs::read_to_string(maybe_path)?
-          } else {
-            private_key
-          };
-          updater_secret_key(private_key, password)
-        }
-        _ => Err(anyhow::anyhow!("A public key has been found, but no private key. Make sure to set `TAURI_SIGNING_PRIVATE_KEY` environment variable.")),
-      }?;
-
-        let pubkey = base64::engine::general_purpose::STANDARD.decode(pubkey)?;
-        let pub_key_decoded = String::from_utf8_lossy(&pubkey);
-        let public_key =
-          minisign::PublicKeyBox::from_string(&pub_key_decoded)?.into_public_key()?;
-
-        // make sure we have our package built
-        let mut signed_paths = Vec::new();
-        for elem in updater_bundles {
-          // we expect to have only one path in the vec but we iter if we add
-          // another type of updater package who require multiple file signature
-          for path in elem.bundle_paths.iter() {
-            // sign our path from environment variables
-            let (signature_path, signature) = sign_file(&secret_key, path)?;
-            if signature.keynum() != public_key.keynum() {
-              log::warn!(
-              "The updater secret key from `TAURI_PRIVATE_KEY` does not match the public key from `plugins > updater > pubkey`. If you are not rotating keys, this means your configuration is wrong and won't be accepted at runtime when performing update."
-            );
-            }
-            signed_paths.push(signature_path);
-          }
-        }
-
-        print_signed_updater_archive(&signed_paths)?;
-      }
-    }
-  }
+  crate::bundle::bundle(
+    &options.into(),
+    verbosity,
+    &interface,
+    &*app_settings,
+    config_,
+    out_dir,
+  )?;
 
   Ok(())
 }
@@ -277,30 +118,7 @@ pub fn setup(
   let config_guard = config.lock().unwrap();
   let config_ = config_guard.as_ref().unwrap();
 
-  let bundle_identifier_source = config_
-    .find_bundle_identifier_overwriter()
-    .unwrap_or_else(|| "tauri.conf.json".into());
-
-  if config_.identifier == "com.tauri.dev" {
-    error!(
-      "You must change the bundle identifier in `{} identifier`. The default value `com.tauri.dev` is not allowed as it must be unique across applications.",
-      bundle_identifier_source
-    );
-    std::process::exit(1);
-  }
-
-  if config_
-    .identifier
-    .chars()
-    .any(|ch| !(ch.is_alphanumeric() || ch == '-' || ch == '.'))
-  {
-    error!(
-      "The bundle identifier \"{}\" set in `{} identifier`. The bundle identifier string must contain only alphanumeric characters (A-Z, a-z, and 0-9), hyphens (-), and periods (.).",
-      config_.identifier,
-      bundle_identifier_source
-    );
-    std::process::exit(1);
-  }
+  validate_identifier(config_);
 
   if let Some(before_build) = config_.build.before_build_command.clone() {
     run_hook("beforeBuildCommand", before_build, interface, options.debug)?;
@@ -339,16 +157,65 @@ pub fn setup(
     options.runner = config_.build.runner.clone();
   }
 
-  options
-    .features
-    .get_or_insert(Vec::new())
-    .extend(config_.build.features.clone().unwrap_or_default());
-  interface.build_options(&mut options.args, &mut options.features, mobile);
+  setup_features(
+    interface,
+    config_,
+    &mut options.args,
+    &mut options.features,
+    mobile,
+  );
 
   Ok(())
 }
 
-fn run_hook(name: &str, hook: HookCommand, interface: &AppInterface, debug: bool) -> Result<()> {
+/// Exits the process if the bundle identifier is the default one or contains invalid characters.
+pub fn validate_identifier(config: &ConfigMetadata) {
+  let bundle_identifier_source = config
+    .find_bundle_identifier_overwriter()
+    .unwrap_or_else(|| "tauri.conf.json".into());
+
+  if config.identifier == "com.tauri.dev" {
+    error!(
+      "You must change the bundle identifier in `{} identifier`. The default value `com.tauri.dev` is not allowed as it must be unique across applications.",
+      bundle_identifier_source
+    );
+    std::process::exit(1);
+  }
+
+  if config
+    .identifier
+    .chars()
+    .any(|ch| !(ch.is_alphanumeric() || ch == '-' || ch == '.'))
+  {
+    error!(
+      "The bundle identifier \"{}\" set in `{} identifier`. The bundle identifier string must contain only alphanumeric characters (A-Z, a-z, and 0-9), hyphens (-), and periods (.).",
+      config.identifier,
+      bundle_identifier_source
+    );
+    std::process::exit(1);
+  }
+}
+
+/// Adds the features of `build.features` and the ones required by the configuration to the cargo features.
+pub fn setup_features(
+  interface: &AppInterface,
+  config: &Config,
+  args: &mut Vec<String>,
+  features: &mut Option<Vec<String>>,
+  mobile: bool,
+) {
+  features
+    .get_or_insert(Vec::new())
+    .extend(config.build.features.clone().unwrap_or_default());
+  interface.build_options(args, features, mobile);
+}
+
+pub fn run_hook(
+  name: &str,
+  hook: HookCommand,
+  interface: &AppInterface,
+  debug: bool,
+) -> Result<()> {
   let (script, script_cwd) = match hook {
     HookCommand::Script(s) if s.is_empty() => (None, None),
     HookCommand::Script(s) => (Some(s), None),
@@ -393,24 +260,3 @@ fn run_hook(name: &str, hook: HookCommand, interface: &AppInterface, debug: bool
 
   Ok(())
 }
-
-fn print_signed_updater_archive(output_paths: &[PathBuf]) -> crate::Result<()> {
-  use std::fmt::Write;
-  if !output_paths.is_empty() {
-    let pluralised = if output_paths.len() == 1 {
-      "updater signature"
-    } else {
-      "updater signatures"
-    };
-    let mut printable_paths = String::new();
-    for path in output_paths {
-      writeln!(
-        printable_paths,
-        "        {}",
-        tauri_utils::display_path(path)
-      )?;
-    }
-    info!( action = "Finished"; "{} {} at:\n{}", output_paths.len(), pluralised, printable_paths);
-  }
-  Ok(())
-}
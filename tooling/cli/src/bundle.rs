@@ -0,0 +1,440 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::{
+  build::{run_hook, setup_features, validate_identifier},
+  helpers::{
+    app_paths::{app_dir, tauri_dir},
+    config::{get as get_config, Config},
+    sbom::{Sbom, SbomFormat},
+    updater_signature::{decode_key, secret_key as updater_secret_key, sign_file},
+  },
+  interface::{rust::get_workspace_dir, AppInterface, AppSettings, Interface},
+  ConfigValue, Result,
+};
+use anyhow::{bail, Context};
+use clap::{ArgAction, Parser};
+use log::{info, warn};
+use minisign::SecretKey;
+use std::{
+  env::{set_current_dir, var},
+  path::{Path, PathBuf},
+};
+use tauri_bundler::bundle::{bundle_project, Bundle, PackageType};
+use tauri_utils::platform::Target;
+
+#[derive(Debug, Clone, Parser)]
+#[clap(
+  about = "Generate bundles and installers for your prebuilt app",
+  long_about = "Generate bundles and installers for your prebuilt app. Unlike `build`, it does not compile the app: the binaries must already exist in the target directory, for instance from a previous `tauri build --bundles none` run. This will run `build.beforeBundleCommand` before generating the bundles and installers of your app."
+)]
+pub struct Options {
+  /// Bundles the binaries built with the debug flag
+  #[clap(short, long)]
+  pub debug: bool,
+  /// Target triple the app was built for.
+  ///
+  /// It must be one of the values outputted by `$rustc --print target-list` or `universal-apple-darwin` for an universal macOS application.
+  #[clap(short, long)]
+  pub target: Option<String>,
+  /// Space or comma separated list of features the app was built with
+  #[clap(short, long, action = ArgAction::Append, num_args(0..))]
+  pub features: Option<Vec<String>>,
+  /// Space or comma separated list of bundles to package.
+  ///
+  /// Each bundle must be one of `deb`, `rpm`, `appimage`, `msi`, `app` or `dmg` on MacOS and `updater` on all platforms.
+  /// If `none` is specified, the bundler will be skipped.
+  ///
+  /// Note that the `updater` bundle is not automatically added so you must specify it if the updater is enabled.
+  #[clap(short, long, action = ArgAction::Append, num_args(0..), value_delimiter = ',')]
+  pub bundles: Option<Vec<String>>,
+  /// JSON string or path to JSON file to merge with tauri.conf.json
+  #[clap(short, long)]
+  pub config: Option<ConfigValue>,
+  /// Skip prompting for values
+  #[clap(long)]
+  pub ci: bool,
+  /// Write a software bill of materials next to each bundle.
+  ///
+  /// It lists the Cargo and frontend lockfile dependencies and the bundled sidecars and resources with their hashes.
+  #[clap(long, value_enum)]
+  pub sbom: Option<SbomFormat>,
+}
+
+impl From<crate::build::Options> for Options {
+  fn from(options: crate::build::Options) -> Self {
+    Self {
+      debug: options.debug,
+      target: options.target,
+      features: options.features,
+      bundles: options.bundles,
+      config: options.config,
+      ci: options.ci,
+      sbom: options.sbom,
+    }
+  }
+}
+
+pub fn command(mut options: Options, verbosity: u8) -> Result<()> {
+  options.ci = options.ci || std::env::var("CI").is_ok();
+
+  let target = options
+    .target
+    .as_deref()
+    .map(Target::from_triple)
+    .unwrap_or_else(Target::current);
+
+  let config = get_config(target, options.config.as_ref().map(|c| &c.0))?;
+
+  let interface = AppInterface::new(
+    config.lock().unwrap().as_ref().unwrap(),
+    options.target.clone(),
+  )?;
+
+  let tauri_path = tauri_dir();
+  set_current_dir(tauri_path).with_context(|| "failed to change current working directory")?;
+
+  let config_guard = config.lock().unwrap();
+  let config_ = config_guard.as_ref().unwrap();
+
+  validate_identifier(config_);
+  // use the same feature set as `tauri build` so the bundle settings match the built binaries
+  setup_features(
+    &interface,
+    config_,
+    &mut Vec::new(),
+    &mut options.features,
+    false,
+  );
+
+  let app_settings = interface.app_settings();
+  let bin_path = app_settings.app_binary_path(&options.clone().into())?;
+  let out_dir = bin_path.parent().unwrap();
+
+  bundle(
+    &options,
+    verbosity,
+    &interface,
+    &*app_settings,
+    config_,
+    out_dir,
+  )
+}
+
+/// Runs the bundler on the binaries found in `out_dir`, signing the updater bundles if needed.
+pub fn bundle<A: AppSettings>(
+  options: &Options,
+  verbosity: u8,
+  interface: &AppInterface,
+  app_settings: &A,
+  config: &Config,
+  out_dir: &Path,
+) -> Result<()> {
+  if !config.bundle.active {
+    return Ok(());
+  }
+
+  let package_types = package_types(options.bundles.as_deref(), config)?;
+
+  let updater_pub_key = config
+    .plugins
+    .0
+    .get("updater")
+    .and_then(|k| k.get("pubkey"))
+    .and_then(|v| v.as_str())
+    .map(|v| v.to_string());
+  if let Some(types) = &package_types {
+    if updater_pub_key
+      .as_ref()
+      .map(|v| !v.is_empty())
+      .unwrap_or(false)
+      && !types.contains(&PackageType::Updater)
+    {
+      warn!("`plugins > updater > pubkey` is set, but the bundle target list does not contain `updater`, so the updater artifacts won't be generated.");
+    }
+  }
+
+  // if we have a package to bundle, let's run the `before_bundle_command`.
+  if package_types.as_ref().map_or(true, |p| !p.is_empty()) {
+    if let Some(before_bundle) = config.build.before_bundle_command.clone() {
+      run_hook(
+        "beforeBundleCommand",
+        before_bundle,
+        interface,
+        options.debug,
+      )?;
+    }
+  }
+
+  let mut settings = app_settings
+    .get_bundler_settings(&options.clone().into(), config, out_dir, package_types)
+    .with_context(|| "failed to build bundler settings")?;
+
+  for binary in settings.binaries() {
+    let binary_path = settings.binary_path(binary);
+    if !binary_path.exists() {
+      bail!(
+        "failed to find the `{}` binary at {}, make sure the app was built for this target and profile before bundling it",
+        binary.name(),
+        tauri_utils::display_path(&binary_path)
+      );
+    }
+  }
+
+  settings.set_log_level(match verbosity {
+    0 => log::Level::Error,
+    1 => log::Level::Info,
+    _ => log::Level::Trace,
+  });
+
+  // set env vars used by the bundler
+  #[cfg(target_os = "linux")]
+  {
+    if config.bundle.linux.appimage.bundle_media_framework {
+      std::env::set_var("APPIMAGE_BUNDLE_GSTREAMER", "1");
+    }
+
+    if let Some(open) = config.plugins.0.get("shell").and_then(|v| v.get("open")) {
+      if open.as_bool().is_some_and(|x| x) || open.is_string() {
+        std::env::set_var("APPIMAGE_BUNDLE_XDG_OPEN", "1");
+      }
+    }
+
+    if settings.deep_link_protocols().is_some() {
+      std::env::set_var("APPIMAGE_BUNDLE_XDG_MIME", "1");
+    }
+  }
+
+  let sbom = match options.sbom {
+    Some(format) => Some((
+      format,
+      Sbom::collect(
+        &settings,
+        &get_workspace_dir()?.join("Cargo.lock"),
+        app_dir(),
+      )
+      .with_context(|| "failed to collect the SBOM")?,
+    )),
+    None => None,
+  };
+
+  let bundles = bundle_project(settings)
+    .map_err(|e| anyhow::anyhow!("{:#}", e))
+    .with_context(|| "failed to bundle project")?;
+
+  if let Some((format, sbom)) = &sbom {
+    let mut sbom_paths = Vec::new();
+    for bundle in &bundles {
+      for path in &bundle.bundle_paths {
+        sbom_paths.push(sbom.write(*format, path)?);
+      }
+    }
+    print_sbom_paths(&sbom_paths)?;
+  }
+
+  let updater_bundles: Vec<&Bundle> = bundles
+    .iter()
+    .filter(|bundle| bundle.package_type == PackageType::Updater)
+    .collect();
+
+  // If updater is active and we bundled it
+  if !updater_bundles.is_empty() {
+    if let Some(pubkey) = updater_pub_key {
+      // get the public key
+      // check if pubkey points to a file...
+      let maybe_path = Path::new(&pubkey);
+      let pubkey = if maybe_path.exists() {
+        std::fs::read_to_string(maybe_path)?
+      } else {
+        pubkey
+      };
+
+      let secret_key = signing_secret_key(options.ci)?;
+
+      let public_key =
+        minisign::PublicKeyBox::from_string(&decode_key(pubkey)?)?.into_public_key()?;
+
+      // make sure we have our package built
+      let mut signed_paths = Vec::new();
+      for elem in updater_bundles {
+        // we expect to have only one path in the vec but we iter if we add
+        // another type of updater package who require multiple file signature
+        for path in elem.bundle_paths.iter() {
+          // sign our path from environment variables
+          let (signature_path, signature) = sign_file(&secret_key, path)?;
+          if signature.keynum() != public_key.keynum() {
+            log::warn!(
+            "The updater secret key from `TAURI_PRIVATE_KEY` does not match the public key from `plugins > updater > pubkey`. If you are not rotating keys, this means your configuration is wrong and won't be accepted at runtime when performing update."
+          );
+          }
+          signed_paths.push(signature_path);
+        }
+      }
+
+      print_signed_updater_archive(&signed_paths)?;
+    }
+  }
+
+  Ok(())
+}
+
+/// The package types to bundle, from the `--bundles` option or the `bundle > targets` configuration.
+///
+/// Returns `None` to use the default package types of the platform, or if `none` is given.
+fn package_types(names: Option<&[String]>, config: &Config) -> Result<Option<Vec<PackageType>>> {
+  match names {
+    Some(names) if names.iter().any(|name| name == "none") => Ok(None),
+    Some(names) => names
+      .iter()
+      .map(|name| {
+        PackageType::from_short_name(name)
+          .ok_or_else(|| anyhow::anyhow!("Unsupported bundle format: {name}"))
+      })
+      .collect::<Result<Vec<_>>>()
+      .map(Some),
+    None => {
+      let targets = config.bundle.targets.to_vec();
+      if targets.is_empty() {
+        Ok(None)
+      } else {
+        Ok(Some(targets.into_iter().map(Into::into).collect()))
+      }
+    }
+  }
+}
+
+/// Reads the updater private key from the `TAURI_SIGNING_PRIVATE_KEY` environment variable, either the key or a path to it.
+fn signing_secret_key(ci: bool) -> Result<SecretKey> {
+  let Ok(private_key) = var("TAURI_SIGNING_PRIVATE_KEY") else {
+    bail!("A public key has been found, but no private key. Make sure to set `TAURI_SIGNING_PRIVATE_KEY` environment variable.");
+  };
+  // check if private_key points to a file...
+  let maybe_path = Path::new(&private_key);
+  let private_key = if maybe_path.exists() {
+    std::fs::read_to_string(maybe_path)?
+  } else {
+    private_key
+  };
+
+  // if no password provided we use an empty string
+  let password = var("TAURI_SIGNING_PRIVATE_KEY_PASSWORD")
+    .ok()
+    .or_else(|| ci.then(String::new));
+
+  updater_secret_key(private_key, password)
+}
+
+fn print_sbom_paths(output_paths: &[PathBuf]) -> crate::Result<()> {
+  use std::fmt::Write;
+  if !output_paths.is_empty() {
+    let mut printable_paths = String::new();
+    for path in output_paths {
+      writeln!(
+        printable_paths,
+        "        {}",
+        tauri_utils::display_path(path)
+      )?;
+    }
+    info!(action = "Finished"; "{} SBOM {} at:\n{}", output_paths.len(), if output_paths.len() == 1 { "document" } else { "documents" }, printable_paths);
+  }
+  Ok(())
+}
+
+fn print_signed_updater_archive(output_paths: &[PathBuf]) -> crate::Result<()> {
+  use std::fmt::Write;
+  if !output_paths.is_empty() {
+    let pluralised = if output_paths.len() == 1 {
+      "updater signature"
+    } else {
+      "updater signatures"
+    };
+    let mut printable_paths = String::new();
+    for path in output_paths {
+      writeln!(
+        printable_paths,
+        "        {}",
+        tauri_utils::display_path(path)
+      )?;
+    }
+    info!( action = "Finished"; "{} {} at:\n{}", output_paths.len(), pluralised, printable_paths);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn config(targets: serde_json::Value) -> Config {
+    serde_json::from_value(serde_json::json!({
+      "identifier": "com.tauri.test",
+      "bundle": { "targets": targets }
+    }))
+    .unwrap()
+  }
+
+  fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(ToString::to_string).collect()
+  }
+
+  #[test]
+  fn parses_cli_options() {
+    let options = Options::try_parse_from([
+      "bundle",
+      "--debug",
+      "--bundles",
+      "deb,appimage",
+      "--features",
+      "a",
+      "b",
+      "--ci",
+    ])
+    .unwrap();
+    assert!(options.debug);
+    assert!(options.ci);
+    assert_eq!(options.bundles, Some(names(&["deb", "appimage"])));
+    assert_eq!(options.features, Some(names(&["a", "b"])));
+    assert!(options.target.is_none());
+  }
+
+  #[test]
+  fn converts_build_options() {
+    let build_options = crate::build::Options::try_parse_from([
+      "build",
+      "--target",
+      "x86_64-unknown-linux-gnu",
+      "--bundles",
+      "rpm",
+    ])
+    .unwrap();
+    let options = Options::from(build_options);
+    assert!(!options.debug);
+    assert_eq!(options.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    assert_eq!(options.bundles, Some(names(&["rpm"])));
+  }
+
+  #[test]
+  fn package_types_from_cli() {
+    let config = config("all".into());
+    assert_eq!(
+      package_types(Some(&names(&["deb", "appimage"])), &config).unwrap(),
+      Some(vec![PackageType::Deb, PackageType::AppImage])
+    );
+    assert_eq!(
+      package_types(Some(&names(&["deb", "none"])), &config).unwrap(),
+      None
+    );
+    let error = package_types(Some(&names(&["zip"])), &config).unwrap_err();
+    assert_eq!(error.to_string(), "Unsupported bundle format: zip");
+  }
+
+  #[test]
+  fn package_types_from_config() {
+    assert_eq!(package_types(None, &config("all".into())).unwrap(), None);
+    assert_eq!(
+      package_types(None, &config(serde_json::json!(["rpm", "deb"]))).unwrap(),
+      Some(vec![PackageType::Rpm, PackageType::Deb])
+    );
+  }
+}
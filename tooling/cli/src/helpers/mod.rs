@@ -7,6 +7,7 @@ pub mod config;
 pub mod flock;
 pub mod framework;
 pub mod npm;
+pub mod sbom;
 pub mod template;
 pub mod updater_signature;
 pub mod web_dev_server;
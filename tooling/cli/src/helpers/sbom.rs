@@ -0,0 +1,766 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Software bill of materials generation for `tauri build`.
+//!
+//! Everything is read from lockfiles and the filesystem so it works offline.
+
+use crate::Result;
+use anyhow::Context;
+use base64::Engine;
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::{
+  collections::BTreeMap,
+  fs::{read_to_string, File},
+  io,
+  path::{Path, PathBuf},
+};
+use tauri_bundler::bundle::Settings;
+
+/// The SBOM document format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SbomFormat {
+  /// CycloneDX 1.5 JSON.
+  #[value(name = "cyclonedx")]
+  CycloneDx,
+  /// SPDX 2.3 JSON.
+  Spdx,
+}
+
+impl SbomFormat {
+  fn extension(self) -> &'static str {
+    match self {
+      Self::CycloneDx => "cdx.json",
+      Self::Spdx => "spdx.json",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum HashAlgorithm {
+  Sha1,
+  Sha256,
+  Sha384,
+  Sha512,
+}
+
+impl HashAlgorithm {
+  fn cyclonedx_name(self) -> &'static str {
+    match self {
+      Self::Sha1 => "SHA-1",
+      Self::Sha256 => "SHA-256",
+      Self::Sha384 => "SHA-384",
+      Self::Sha512 => "SHA-512",
+    }
+  }
+
+  fn spdx_name(self) -> &'static str {
+    match self {
+      Self::Sha1 => "SHA1",
+      Self::Sha256 => "SHA256",
+      Self::Sha384 => "SHA384",
+      Self::Sha512 => "SHA512",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Hash {
+  algorithm: HashAlgorithm,
+  /// Lowercase hex encoded digest.
+  value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Ecosystem {
+  Cargo,
+  Npm,
+}
+
+/// A third party package resolved from a lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Package {
+  ecosystem: Ecosystem,
+  name: String,
+  version: String,
+  hashes: Vec<Hash>,
+}
+
+impl Package {
+  fn purl(&self) -> String {
+    match self.ecosystem {
+      Ecosystem::Cargo => format!("pkg:cargo/{}@{}", self.name, self.version),
+      Ecosystem::Npm => format!(
+        "pkg:npm/{}@{}",
+        self.name.replacen('@', "%40", 1),
+        self.version
+      ),
+    }
+  }
+}
+
+/// A file shipped in the bundles, such as a sidecar or a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BundledFile {
+  name: String,
+  sha256: String,
+}
+
+/// The collected bill of materials, written once per bundle.
+#[derive(Debug)]
+pub struct Sbom {
+  name: String,
+  version: String,
+  packages: Vec<Package>,
+  files: Vec<BundledFile>,
+}
+
+impl Sbom {
+  /// Collects the Cargo and frontend lockfile packages and the sidecars and resources of the app.
+  pub fn collect(settings: &Settings, cargo_lock_path: &Path, app_dir: &Path) -> Result<Self> {
+    let mut packages = Vec::new();
+
+    if cargo_lock_path.exists() {
+      let lock = read_to_string(cargo_lock_path)
+        .with_context(|| format!("failed to read {}", cargo_lock_path.display()))?;
+      packages.extend(parse_cargo_lock(&lock)?);
+    } else {
+      log::warn!(
+        "{} not found, the SBOM will not include Rust dependencies",
+        cargo_lock_path.display()
+      );
+    }
+
+    packages.extend(frontend_packages(app_dir)?);
+
+    packages.sort();
+    packages.dedup();
+
+    let mut files = Vec::new();
+    for src in settings.external_binaries() {
+      let src = src?;
+      files.push(bundled_file(&src)?);
+    }
+    for src in settings.resource_files() {
+      let src = src?;
+      files.push(bundled_file(&src)?);
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Self {
+      name: settings.product_name().to_string(),
+      version: settings.version_string().to_string(),
+      packages,
+      files,
+    })
+  }
+
+  /// Writes the SBOM for the given bundle next to it, returning the path of the document.
+  pub fn write(&self, format: SbomFormat, bundle_path: &Path) -> Result<PathBuf> {
+    let bundle_hash = if bundle_path.is_file() {
+      Some(sha256_file(bundle_path)?)
+    } else {
+      None
+    };
+    let bundle_name = bundle_path
+      .file_name()
+      .map(|n| n.to_string_lossy().into_owned())
+      .unwrap_or_else(|| self.name.clone());
+
+    let document = match format {
+      SbomFormat::CycloneDx => self.cyclonedx(&bundle_name, bundle_hash)?,
+      SbomFormat::Spdx => self.spdx(&bundle_name, bundle_hash)?,
+    };
+
+    let mut path = bundle_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(format.extension());
+    let path = PathBuf::from(path);
+    std::fs::write(&path, serde_json::to_string_pretty(&document)?)
+      .with_context(|| format!("failed to write SBOM to {}", path.display()))?;
+    Ok(path)
+  }
+
+  fn cyclonedx(&self, bundle_name: &str, bundle_hash: Option<String>) -> Result<JsonValue> {
+    let hashes = |hashes: &[Hash]| {
+      hashes
+        .iter()
+        .map(|h| json!({ "alg": h.algorithm.cyclonedx_name(), "content": h.value }))
+        .collect::<Vec<_>>()
+    };
+
+    let mut component = json!({
+      "type": "application",
+      "bom-ref": bundle_name,
+      "name": self.name,
+      "version": self.version,
+    });
+    if let Some(hash) = bundle_hash {
+      component["hashes"] = json!([{ "alg": "SHA-256", "content": hash }]);
+    }
+
+    let mut components = Vec::new();
+    for package in &self.packages {
+      components.push(json!({
+        "type": "library",
+        "bom-ref": package.purl(),
+        "name": package.name,
+        "version": package.version,
+        "purl": package.purl(),
+        "hashes": hashes(&package.hashes),
+      }));
+    }
+    for file in &self.files {
+      components.push(json!({
+        "type": "file",
+        "bom-ref": format!("file:{}", file.name),
+        "name": file.name,
+        "hashes": [{ "alg": "SHA-256", "content": file.sha256 }],
+      }));
+    }
+
+    Ok(json!({
+      "bomFormat": "CycloneDX",
+      "specVersion": "1.5",
+      "version": 1,
+      "metadata": {
+        "timestamp": timestamp()?,
+        "tools": [{
+          "vendor": "Tauri Programme within The Commons Conservancy",
+          "name": "tauri-cli",
+          "version": env!("CARGO_PKG_VERSION"),
+        }],
+        "component": component,
+      },
+      "components": components,
+    }))
+  }
+
+  fn spdx(&self, bundle_name: &str, bundle_hash: Option<String>) -> Result<JsonValue> {
+    let checksums = |hashes: &[Hash]| {
+      hashes
+        .iter()
+        .map(|h| json!({ "algorithm": h.algorithm.spdx_name(), "checksumValue": h.value }))
+        .collect::<Vec<_>>()
+    };
+
+    let root_id = "SPDXRef-Package-app";
+    let mut root = json!({
+      "SPDXID": root_id,
+      "name": self.name,
+      "versionInfo": self.version,
+      "packageFileName": bundle_name,
+      "downloadLocation": "NOASSERTION",
+      "filesAnalyzed": false,
+    });
+    if let Some(hash) = bundle_hash {
+      root["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": hash }]);
+    }
+
+    let mut packages = vec![root];
+    let mut relationships = vec![json!({
+      "spdxElementId": "SPDXRef-DOCUMENT",
+      "relationshipType": "DESCRIBES",
+      "relatedSpdxElement": root_id,
+    })];
+
+    for (i, package) in self.packages.iter().enumerate() {
+      let id = format!("SPDXRef-Package-{i}");
+      packages.push(json!({
+        "SPDXID": id,
+        "name": package.name,
+        "versionInfo": package.version,
+        "downloadLocation": "NOASSERTION",
+        "filesAnalyzed": false,
+        "checksums": checksums(&package.hashes),
+        "externalRefs": [{
+          "referenceCategory": "PACKAGE-MANAGER",
+          "referenceType": "purl",
+          "referenceLocator": package.purl(),
+        }],
+      }));
+      relationships.push(json!({
+        "spdxElementId": root_id,
+        "relationshipType": "DEPENDS_ON",
+        "relatedSpdxElement": id,
+      }));
+    }
+
+    let mut files = Vec::new();
+    for (i, file) in self.files.iter().enumerate() {
+      let id = format!("SPDXRef-File-{i}");
+      files.push(json!({
+        "SPDXID": id,
+        "fileName": format!("./{}", file.name),
+        "checksums": [{ "algorithm": "SHA256", "checksumValue": file.sha256 }],
+      }));
+      relationships.push(json!({
+        "spdxElementId": root_id,
+        "relationshipType": "CONTAINS",
+        "relatedSpdxElement": id,
+      }));
+    }
+
+    Ok(json!({
+      "spdxVersion": "SPDX-2.3",
+      "dataLicense": "CC0-1.0",
+      "SPDXID": "SPDXRef-DOCUMENT",
+      "name": format!("{}-{}", self.name, self.version),
+      "documentNamespace": format!(
+        "https://spdx.org/spdxdocs/{}-{}-{}",
+        self.name.replace(' ', "-"),
+        self.version,
+        bundle_name.replace(' ', "-")
+      ),
+      "creationInfo": {
+        "created": timestamp()?,
+        "creators": [format!("Tool: tauri-cli-{}", env!("CARGO_PKG_VERSION"))],
+      },
+      "packages": packages,
+      "files": files,
+      "relationships": relationships,
+    }))
+  }
+}
+
+/// The document creation time, honoring `SOURCE_DATE_EPOCH` for reproducible output.
+fn timestamp() -> Result<String> {
+  let now = match std::env::var("SOURCE_DATE_EPOCH")
+    .ok()
+    .and_then(|v| v.trim().parse::<i64>().ok())
+  {
+    Some(epoch) => time::OffsetDateTime::from_unix_timestamp(epoch)?,
+    None => time::OffsetDateTime::now_utc().replace_nanosecond(0)?,
+  };
+  Ok(now.format(&time::format_description::well_known::Rfc3339)?)
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+  let mut file = File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+  let mut hasher = Sha256::new();
+  io::copy(&mut file, &mut hasher)?;
+  Ok(hex::encode(hasher.finalize()))
+}
+
+fn bundled_file(path: &Path) -> Result<BundledFile> {
+  Ok(BundledFile {
+    name: tauri_utils::resources::resource_relpath(path)
+      .to_string_lossy()
+      .replace('\\', "/"),
+    sha256: sha256_file(path)?,
+  })
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+  #[serde(default)]
+  package: Vec<CargoLockPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoLockPackage {
+  name: String,
+  version: String,
+  checksum: Option<String>,
+}
+
+fn parse_cargo_lock(contents: &str) -> Result<Vec<Package>> {
+  let lock: CargoLock = toml::from_str(contents).context("failed to parse Cargo.lock")?;
+  Ok(
+    lock
+      .package
+      .into_iter()
+      .map(|p| Package {
+        ecosystem: Ecosystem::Cargo,
+        name: p.name,
+        version: p.version,
+        hashes: p
+          .checksum
+          .map(|value| {
+            vec![Hash {
+              algorithm: HashAlgorithm::Sha256,
+              value,
+            }]
+          })
+          .unwrap_or_default(),
+      })
+      .collect(),
+  )
+}
+
+fn frontend_packages(app_dir: &Path) -> Result<Vec<Package>> {
+  let mut packages = Vec::new();
+  let mut found = false;
+
+  let package_lock = app_dir.join("package-lock.json");
+  if package_lock.exists() {
+    found = true;
+    let contents = read_to_string(&package_lock)?;
+    packages.extend(parse_package_lock(&contents)?);
+  }
+
+  let yarn_lock = app_dir.join("yarn.lock");
+  if yarn_lock.exists() {
+    found = true;
+    packages.extend(parse_yarn_lock(&read_to_string(&yarn_lock)?));
+  }
+
+  let pnpm_lock = app_dir.join("pnpm-lock.yaml");
+  if pnpm_lock.exists() {
+    found = true;
+    packages.extend(parse_pnpm_lock(&read_to_string(&pnpm_lock)?));
+  }
+
+  if !found && app_dir.join("package.json").exists() {
+    log::warn!("No supported frontend lockfile found, the SBOM will not include npm dependencies");
+  }
+
+  Ok(packages)
+}
+
+/// Parses a Subresource Integrity string such as `sha512-<base64>`.
+fn parse_integrity(integrity: &str) -> Vec<Hash> {
+  integrity
+    .split_whitespace()
+    .filter_map(|entry| {
+      let (algorithm, digest) = entry.split_once('-')?;
+      let algorithm = match algorithm {
+        "sha1" => HashAlgorithm::Sha1,
+        "sha256" => HashAlgorithm::Sha256,
+        "sha384" => HashAlgorithm::Sha384,
+        "sha512" => HashAlgorithm::Sha512,
+        _ => return None,
+      };
+      let digest = base64::engine::general_purpose::STANDARD
+        .decode(digest)
+        .ok()?;
+      Some(Hash {
+        algorithm,
+        value: hex::encode(digest),
+      })
+    })
+    .collect()
+}
+
+#[derive(Deserialize)]
+struct PackageLock {
+  /// lockfileVersion 2 and 3.
+  packages: Option<BTreeMap<String, PackageLockEntry>>,
+  /// lockfileVersion 1.
+  dependencies: Option<BTreeMap<String, PackageLockEntry>>,
+}
+
+#[derive(Deserialize)]
+struct PackageLockEntry {
+  version: Option<String>,
+  integrity: Option<String>,
+  #[serde(default)]
+  link: bool,
+  dependencies: Option<BTreeMap<String, JsonValue>>,
+}
+
+fn parse_package_lock(contents: &str) -> Result<Vec<Package>> {
+  let lock: PackageLock =
+    serde_json::from_str(contents).context("failed to parse package-lock.json")?;
+  let mut packages = Vec::new();
+
+  if let Some(entries) = lock.packages {
+    for (path, entry) in entries {
+      // the root project is stored with an empty key
+      let Some((_, name)) = path.rsplit_once("node_modules/") else {
+        continue;
+      };
+      if entry.link {
+        continue;
+      }
+      if let Some(version) = entry.version {
+        packages.push(Package {
+          ecosystem: Ecosystem::Npm,
+          name: name.to_string(),
+          version,
+          hashes: entry
+            .integrity
+            .as_deref()
+            .map(parse_integrity)
+            .unwrap_or_default(),
+        });
+      }
+    }
+  } else if let Some(dependencies) = lock.dependencies {
+    collect_package_lock_v1(dependencies, &mut packages);
+  }
+
+  Ok(packages)
+}
+
+fn collect_package_lock_v1(
+  dependencies: BTreeMap<String, PackageLockEntry>,
+  packages: &mut Vec<Package>,
+) {
+  for (name, entry) in dependencies {
+    if let Some(version) = entry.version {
+      packages.push(Package {
+        ecosystem: Ecosystem::Npm,
+        name,
+        version,
+        hashes: entry
+          .integrity
+          .as_deref()
+          .map(parse_integrity)
+          .unwrap_or_default(),
+      });
+    }
+    if let Some(nested) = entry.dependencies {
+      let nested = nested
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, serde_json::from_value(value).ok()?)))
+        .collect();
+      collect_package_lock_v1(nested, packages);
+    }
+  }
+}
+
+/// Splits `name@version` (where the name may be scoped) and returns the name.
+fn package_name(spec: &str) -> Option<&str> {
+  let (prefix_len, rest) = match spec.strip_prefix('@') {
+    Some(rest) => (1, rest),
+    None => (0, spec),
+  };
+  let index = rest.find('@')? + prefix_len;
+  Some(&spec[..index])
+}
+
+/// Parses both the classic (v1) and the berry `yarn.lock` formats.
+fn parse_yarn_lock(contents: &str) -> Vec<Package> {
+  let mut packages = Vec::new();
+  let mut current: Option<Package> = None;
+
+  for line in contents.lines() {
+    if line.trim().is_empty() || line.starts_with('#') {
+      continue;
+    }
+
+    if !line.starts_with(' ') {
+      if let Some(package) = current.take() {
+        packages.push(package);
+      }
+      // the first spec of `"a@^1.0.0", "a@^1.1.0":`
+      let spec = line
+        .trim_end_matches(':')
+        .split(", ")
+        .next()
+        .unwrap_or_default()
+        .trim_matches('"');
+      if spec.contains("@workspace:") || spec == "__metadata" {
+        continue;
+      }
+      current = package_name(spec).map(|name| Package {
+        ecosystem: Ecosystem::Npm,
+        name: name.to_string(),
+        version: String::new(),
+        hashes: Vec::new(),
+      });
+      continue;
+    }
+
+    if let Some(package) = current.as_mut() {
+      let line = line.trim();
+      let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+      let value = value.trim().trim_matches('"');
+      match key.trim_end_matches(':') {
+        "version" => package.version = value.to_string(),
+        "integrity" => package.hashes = parse_integrity(value),
+        _ => {}
+      }
+    }
+  }
+  if let Some(package) = current {
+    packages.push(package);
+  }
+
+  packages.retain(|p| !p.version.is_empty());
+  packages
+}
+
+/// Parses the `packages` section of a `pnpm-lock.yaml` file (lockfile v5 to v9).
+fn parse_pnpm_lock(contents: &str) -> Vec<Package> {
+  let mut packages = Vec::new();
+  let mut in_packages = false;
+
+  for line in contents.lines() {
+    if !line.starts_with(' ') && !line.trim().is_empty() {
+      in_packages = line.trim_end() == "packages:";
+      continue;
+    }
+    if !in_packages {
+      continue;
+    }
+
+    let indent = line.len() - line.trim_start().len();
+    let line = line.trim();
+    if indent == 2 && line.ends_with(':') {
+      let key = line
+        .trim_end_matches(':')
+        .trim_matches(|c| c == '"' || c == '\'');
+      let key = key.trim_start_matches('/');
+      // strip the peer dependencies suffix
+      let key = key.split('(').next().unwrap_or(key);
+      let (name, version) = match package_name(key) {
+        Some(name) => (name, &key[name.len() + 1..]),
+        // lockfile v5 uses `/name/version`
+        None => match key.rsplit_once('/') {
+          Some(split) => split,
+          None => continue,
+        },
+      };
+      // lockfile v5 appends the peer dependencies with `_`
+      let version = version.split('_').next().unwrap_or(version);
+      packages.push(Package {
+        ecosystem: Ecosystem::Npm,
+        name: name.to_string(),
+        version: version.to_string(),
+        hashes: Vec::new(),
+      });
+    } else if let Some(resolution) = line.strip_prefix("resolution: {integrity: ") {
+      if let Some(package) = packages.last_mut() {
+        let integrity = resolution
+          .split([',', '}'])
+          .next()
+          .unwrap_or_default()
+          .trim();
+        package.hashes = parse_integrity(integrity);
+      }
+    }
+  }
+
+  packages
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cargo_lock() {
+    let packages = parse_cargo_lock(
+      r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+
+[[package]]
+name = "serde"
+version = "1.0.195"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "63261df402c67811e9ac6def069e4786148c4563f4b50fd4bf30aa370d626b02"
+"#,
+    )
+    .unwrap();
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[1].purl(), "pkg:cargo/serde@1.0.195");
+    assert_eq!(packages[1].hashes[0].algorithm, HashAlgorithm::Sha256);
+  }
+
+  #[test]
+  fn package_lock() {
+    let packages = parse_package_lock(
+      r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "app" },
+    "node_modules/@tauri-apps/api": { "version": "2.0.0-beta.0", "integrity": "sha512-AAAA" },
+    "node_modules/a/node_modules/b": { "version": "1.0.0" },
+    "node_modules/local": { "link": true, "resolved": "../local" }
+  }
+}"#,
+    )
+    .unwrap();
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].purl(), "pkg:npm/%40tauri-apps/api@2.0.0-beta.0");
+    assert_eq!(packages[0].hashes[0].value, "000000");
+    assert_eq!(packages[1].name, "b");
+  }
+
+  #[test]
+  fn package_names() {
+    assert_eq!(package_name("vite@^5.0.0"), Some("vite"));
+    assert_eq!(
+      package_name("@tauri-apps/api@^2.0.0"),
+      Some("@tauri-apps/api")
+    );
+    assert_eq!(package_name("@tauri-apps/api"), None);
+    assert_eq!(package_name("vite"), None);
+    assert_eq!(package_name("@"), None);
+    assert_eq!(package_name(""), None);
+  }
+
+  #[test]
+  fn yarn_lock() {
+    let classic = r#"
+# yarn lockfile v1
+
+"@tauri-apps/api@^2.0.0-beta.0", "@tauri-apps/api@^2.0.0-beta.1":
+  version "2.0.0-beta.1"
+  resolved "https://registry.yarnpkg.com/@tauri-apps/api/-/api-2.0.0-beta.1.tgz"
+  integrity sha512-AAAA
+
+vite@^5.0.0:
+  version "5.0.12"
+"#;
+    let packages = parse_yarn_lock(classic);
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].name, "@tauri-apps/api");
+    assert_eq!(packages[0].version, "2.0.0-beta.1");
+    assert_eq!(packages[0].hashes.len(), 1);
+    assert_eq!(packages[1].name, "vite");
+
+    let berry = r#"
+__metadata:
+  version: 6
+
+"app@workspace:.":
+  version: 0.0.0-use.local
+
+"vite@npm:^5.0.0":
+  version: 5.0.12
+  checksum: 1234
+"#;
+    let packages = parse_yarn_lock(berry);
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].purl(), "pkg:npm/vite@5.0.12");
+  }
+
+  #[test]
+  fn pnpm_lock() {
+    let lock = r#"
+lockfileVersion: '6.0'
+
+dependencies:
+  vite:
+    specifier: ^5.0.0
+    version: 5.0.12
+
+packages:
+
+  /@tauri-apps/api@2.0.0-beta.1:
+    resolution: {integrity: sha512-AAAA}
+    dev: false
+
+  /vite@5.0.12(@types/node@20.0.0):
+    resolution: {integrity: sha512-AAAA, tarball: https://example.com}
+"#;
+    let packages = parse_pnpm_lock(lock);
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0].name, "@tauri-apps/api");
+    assert_eq!(packages[0].version, "2.0.0-beta.1");
+    assert_eq!(packages[1].name, "vite");
+    assert_eq!(packages[1].version, "5.0.12");
+    assert_eq!(packages[1].hashes.len(), 1);
+  }
+}
@@ -14,8 +14,10 @@
 use anyhow::Context;
 pub use anyhow::Result;
 
+mod acl;
 mod add;
 mod build;
+mod bundle;
 mod completions;
 mod dev;
 mod helpers;
@@ -134,6 +136,7 @@ enum Commands {
   Init(init::Options),
   Dev(dev::Options),
   Build(build::Options),
+  Bundle(bundle::Options),
   Android(mobile::android::Cli),
   #[cfg(target_os = "macos")]
   Ios(mobile::ios::Cli),
@@ -142,6 +145,9 @@ enum Commands {
   Info(info::Options),
   Add(add::Options),
   Plugin(plugin::Cli),
+  Permission(acl::permission::Cli),
+  Capability(acl::capability::Cli),
+  Acl(acl::Cli),
   Icon(icon::Options),
   Signer(signer::Cli),
   Completions(completions::Options),
@@ -239,12 +245,16 @@ where
 
   match cli.command {
     Commands::Build(options) => build::command(options, cli.verbose)?,
+    Commands::Bundle(options) => bundle::command(options, cli.verbose)?,
     Commands::Dev(options) => dev::command(options)?,
     Commands::Add(options) => add::command(options)?,
     Commands::Icon(options) => icon::command(options)?,
     Commands::Info(options) => info::command(options)?,
     Commands::Init(options) => init::command(options)?,
     Commands::Plugin(cli) => plugin::command(cli)?,
+    Commands::Permission(cli) => acl::permission::command(cli)?,
+    Commands::Capability(cli) => acl::capability::command(cli)?,
+    Commands::Acl(cli) => acl::command(cli)?,
     Commands::Signer(cli) => signer::command(cli)?,
     Commands::Completions(options) => completions::command(options, cli_)?,
     Commands::Android(c) => mobile::android::command(c, cli.verbose)?,
@@ -360,6 +360,7 @@ fn allowlist_to_permissions(
           Some(fs_denied)
         },
       },
+      min_version: None,
     });
   }
 
@@ -417,6 +418,7 @@ fn allowlist_to_permissions(
         allow: Some(allowed),
         deny: None,
       },
+      min_version: None,
     });
   }
 
@@ -456,6 +458,7 @@ fn allowlist_to_permissions(
         allow: Some(allowed),
         deny: None,
       },
+      min_version: None,
     });
   }
 
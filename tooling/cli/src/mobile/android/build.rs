@@ -77,6 +77,7 @@ impl From<Options> for BuildOptions {
       config: options.config,
       args: Vec::new(),
       ci: false,
+      sbom: None,
     }
   }
 }
@@ -73,6 +73,7 @@ impl From<Options> for BuildOptions {
       config: options.config,
       args: Vec::new(),
       ci: false,
+      sbom: None,
     }
   }
 }
@@ -0,0 +1,530 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Tauri specific WebDriver extension commands.
+//!
+//! The commands live under `/session/{session id}/tauri/` and are translated to
+//! standard WebDriver commands sent to the native driver. Most of them require the application
+//! to register the `tauri::webdriver` plugin (`webdriver` feature of the `tauri` crate).
+//!
+//! | Method | URI template                            | Command       |
+//! |--------|-----------------------------------------|---------------|
+//! | POST   | `/session/{id}/tauri/invoke`            | Invoke        |
+//! | POST   | `/session/{id}/tauri/emit`              | Emit          |
+//! | POST   | `/session/{id}/tauri/listen`            | Listen        |
+//! | GET    | `/session/{id}/tauri/events/{listener}` | Take Events   |
+//! | GET    | `/session/{id}/tauri/windows`           | Get Windows   |
+//! | POST   | `/session/{id}/tauri/window`            | Switch Window |
+
+use crate::cli::Args;
+use anyhow::Error;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE, HOST};
+use hyper::http::uri::Authority;
+use hyper::{Body, Client, Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+
+type HttpClient = Client<hyper::client::HttpConnector>;
+
+/// Invokes a command with the `(cmd, args)` arguments and resolves with `{ value }` or `{ error }`.
+const INVOKE_SCRIPT: &str = r#"
+const done = arguments[arguments.length - 1];
+const internals = window.__TAURI_INTERNALS__;
+if (!internals) {
+  done({ error: 'the Tauri IPC is not available in the current window' });
+} else {
+  internals.invoke(arguments[0], arguments[1]).then(
+    (value) => done({ value: value === undefined ? null : value }),
+    (error) => done({ error: error === undefined ? null : error })
+  );
+}
+"#;
+
+/// Registers an event buffer with the `(event)` argument and resolves with `{ value }` or `{ error }`.
+const LISTEN_SCRIPT: &str = r#"
+const done = arguments[arguments.length - 1];
+const plugin = window.__TAURI_INTERNALS__ && window.__TAURI_INTERNALS__.plugins.webdriver;
+if (!plugin) {
+  done({ error: 'the Tauri WebDriver plugin is not registered' });
+} else {
+  plugin.listen(arguments[0]).then(
+    (value) => done({ value: value }),
+    (error) => done({ error: error === undefined ? null : error })
+  );
+}
+"#;
+
+/// Drains the buffer of the `(listener id)` argument.
+const TAKE_EVENTS_SCRIPT: &str = r#"
+const plugin = window.__TAURI_INTERNALS__ && window.__TAURI_INTERNALS__.plugins.webdriver;
+if (!plugin) {
+  return { error: 'the Tauri WebDriver plugin is not registered' };
+}
+return { value: plugin.takeEvents(arguments[0]) };
+"#;
+
+/// Reads the window and webview labels of the current browsing context.
+const LABELS_SCRIPT: &str = r#"
+const metadata = window.__TAURI_INTERNALS__ && window.__TAURI_INTERNALS__.metadata;
+if (!metadata) {
+  return null;
+}
+return { window: metadata.currentWindow.label, webview: metadata.currentWebview.label };
+"#;
+
+/// A Tauri extension command.
+enum Command {
+  Invoke,
+  Emit,
+  Listen,
+  TakeEvents(String),
+  GetWindows,
+  SwitchWindow,
+}
+
+/// Matches the request against the extension commands, returning the session id and command.
+fn route(method: &Method, path: &str) -> Option<(String, Command)> {
+  let rest = path.strip_prefix("/session/")?;
+  let (session, rest) = rest.split_once('/')?;
+  let rest = rest.strip_prefix("tauri/")?;
+
+  let command = match (method, rest) {
+    (&Method::POST, "invoke") => Command::Invoke,
+    (&Method::POST, "emit") => Command::Emit,
+    (&Method::POST, "listen") => Command::Listen,
+    (&Method::GET, "windows") => Command::GetWindows,
+    (&Method::POST, "window") => Command::SwitchWindow,
+    (&Method::GET, rest) => match rest.strip_prefix("events/") {
+      Some(id) if !id.is_empty() && !id.contains('/') => Command::TakeEvents(id.into()),
+      _ => return None,
+    },
+    _ => return None,
+  };
+
+  Some((session.into(), command))
+}
+
+/// Whether the request targets one of the Tauri extension commands.
+pub fn is_extension_command(req: &Request<Body>) -> bool {
+  route(req.method(), req.uri().path()).is_some()
+}
+
+/// An error following the [WebDriver error](https://www.w3.org/TR/webdriver/#errors) format.
+#[derive(Debug)]
+struct WebDriverError {
+  status: StatusCode,
+  error: String,
+  message: String,
+}
+
+impl WebDriverError {
+  fn new(status: StatusCode, error: &str, message: impl Into<String>) -> Self {
+    Self {
+      status,
+      error: error.into(),
+      message: message.into(),
+    }
+  }
+
+  fn invalid_argument(message: impl Into<String>) -> Self {
+    Self::new(StatusCode::BAD_REQUEST, "invalid argument", message)
+  }
+
+  fn javascript(error: Value) -> Self {
+    let message = match error {
+      Value::String(message) => message,
+      Value::Null => "unknown error".into(),
+      error => error.to_string(),
+    };
+    Self::new(
+      StatusCode::INTERNAL_SERVER_ERROR,
+      "javascript error",
+      message,
+    )
+  }
+
+  fn into_response(self) -> Result<Response<Body>, Error> {
+    let body = json!({
+      "value": {
+        "error": self.error,
+        "message": self.message,
+        "stacktrace": "",
+      }
+    });
+    json_response(self.status, &body)
+  }
+}
+
+impl fmt::Display for WebDriverError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.error, self.message)
+  }
+}
+
+impl std::error::Error for WebDriverError {}
+
+fn json_response(status: StatusCode, body: &Value) -> Result<Response<Body>, Error> {
+  let bytes = serde_json::to_vec(body)?;
+  Response::builder()
+    .status(status)
+    .header(CONTENT_TYPE, "application/json; charset=utf-8")
+    .header(CONTENT_LENGTH, bytes.len())
+    .body(bytes.into())
+    .map_err(Into::into)
+}
+
+/// Client for the session on the native WebDriver server.
+struct NativeSession {
+  client: HttpClient,
+  base: String,
+}
+
+impl NativeSession {
+  /// Sends a command to the native driver and returns its `value`.
+  async fn send(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value, Error> {
+    let body = match body {
+      Some(body) => serde_json::to_vec(&body)?,
+      None => Vec::new(),
+    };
+    let req = Request::builder()
+      .method(method)
+      .uri(format!("{}{}", self.base, path))
+      .header(CONTENT_TYPE, "application/json; charset=utf-8")
+      .header(CONTENT_LENGTH, body.len())
+      .body(Body::from(body))?;
+
+    let res = self.client.request(req).await?;
+    let status = res.status();
+    let bytes = hyper::body::to_bytes(res.into_body()).await?;
+    let mut json: Value = serde_json::from_slice(&bytes)?;
+    let value = json.get_mut("value").map(Value::take).unwrap_or_default();
+
+    if status.is_success() {
+      Ok(value)
+    } else {
+      // forward the native error as is
+      let error = value
+        .get("error")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown error");
+      let message = value
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+      Err(WebDriverError::new(status, error, message).into())
+    }
+  }
+
+  /// Runs an asynchronous script resolving with `{ value }` or `{ error }`.
+  async fn execute_async(&self, script: &str, args: Value) -> Result<Value, Error> {
+    let result = self
+      .send(
+        Method::POST,
+        "/execute/async",
+        Some(json!({ "script": script, "args": args })),
+      )
+      .await?;
+    script_result(result)
+  }
+
+  /// Runs a synchronous script and returns its result.
+  async fn execute_sync(&self, script: &str, args: Value) -> Result<Value, Error> {
+    self
+      .send(
+        Method::POST,
+        "/execute/sync",
+        Some(json!({ "script": script, "args": args })),
+      )
+      .await
+  }
+
+  async fn invoke(&self, cmd: &str, args: Value) -> Result<Value, Error> {
+    self.execute_async(INVOKE_SCRIPT, json!([cmd, args])).await
+  }
+}
+
+fn script_result(mut result: Value) -> Result<Value, Error> {
+  match result.get_mut("error") {
+    Some(error) => Err(WebDriverError::javascript(error.take()).into()),
+    None => Ok(result.get_mut("value").map(Value::take).unwrap_or_default()),
+  }
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(body: &[u8]) -> Result<T, Error> {
+  serde_json::from_slice(body)
+    .map_err(|e| WebDriverError::invalid_argument(format!("invalid request body: {e}")).into())
+}
+
+#[derive(Deserialize)]
+struct InvokeParameters {
+  cmd: String,
+  #[serde(default)]
+  args: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct EmitParameters {
+  event: String,
+  #[serde(default)]
+  payload: Option<Value>,
+  #[serde(default)]
+  target: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct ListenParameters {
+  event: String,
+}
+
+#[derive(Deserialize)]
+struct SwitchWindowParameters {
+  label: String,
+}
+
+async fn run(session: &NativeSession, command: Command, body: &[u8]) -> Result<Value, Error> {
+  match command {
+    Command::Invoke => {
+      let params: InvokeParameters = parse_body(body)?;
+      session
+        .invoke(&params.cmd, params.args.unwrap_or_else(|| json!({})))
+        .await
+    }
+    Command::Emit => {
+      let params: EmitParameters = parse_body(body)?;
+      session
+        .invoke(
+          "plugin:webdriver|emit",
+          json!({ "event": params.event, "payload": params.payload, "target": params.target }),
+        )
+        .await
+    }
+    Command::Listen => {
+      let params: ListenParameters = parse_body(body)?;
+      session
+        .execute_async(LISTEN_SCRIPT, json!([params.event]))
+        .await
+    }
+    Command::TakeEvents(id) => {
+      let id = id
+        .parse::<u32>()
+        .map_err(|_| WebDriverError::invalid_argument(format!("invalid listener id `{id}`")))?;
+      let result = session
+        .execute_sync(TAKE_EVENTS_SCRIPT, json!([id]))
+        .await?;
+      script_result(result)
+    }
+    Command::GetWindows => session.invoke("plugin:webdriver|windows", json!({})).await,
+    Command::SwitchWindow => {
+      let params: SwitchWindowParameters = parse_body(body)?;
+      switch_window(session, &params.label).await
+    }
+  }
+}
+
+/// Switches to the browsing context of the webview (or window) with the given label.
+async fn switch_window(session: &NativeSession, label: &str) -> Result<Value, Error> {
+  let current = session.send(Method::GET, "/window", None).await?;
+  let handles = session.send(Method::GET, "/window/handles", None).await?;
+  let handles = handles.as_array().cloned().unwrap_or_default();
+
+  // webview labels take precedence over window labels since a window can host several webviews
+  let mut window_match = None;
+  for handle in handles {
+    session
+      .send(Method::POST, "/window", Some(json!({ "handle": handle })))
+      .await?;
+    let labels = session.execute_sync(LABELS_SCRIPT, json!([])).await?;
+    if labels.get("webview").and_then(Value::as_str) == Some(label) {
+      return Ok(Value::Null);
+    }
+    if window_match.is_none() && labels.get("window").and_then(Value::as_str) == Some(label) {
+      window_match.replace(handle);
+    }
+  }
+
+  let (handle, result) = match window_match {
+    Some(handle) => (handle, Ok(Value::Null)),
+    None => (
+      current,
+      Err(
+        WebDriverError::new(
+          StatusCode::NOT_FOUND,
+          "no such window",
+          format!("no window or webview with label `{label}`"),
+        )
+        .into(),
+      ),
+    ),
+  };
+  session
+    .send(Method::POST, "/window", Some(json!({ "handle": handle })))
+    .await?;
+  result
+}
+
+/// Handles a Tauri extension command by translating it to native WebDriver commands.
+pub async fn handle(
+  client: HttpClient,
+  req: Request<Body>,
+  args: &Args,
+) -> Result<Response<Body>, Error> {
+  let (session, command) =
+    route(req.method(), req.uri().path()).expect("request is an extension command");
+
+  let host: Authority = req
+    .headers()
+    .get(HOST)
+    .expect("hyper request has host")
+    .to_str()?
+    .parse()?;
+  let native = NativeSession {
+    client,
+    base: format!(
+      "http://{}:{}/session/{}",
+      host.host(),
+      args.native_port,
+      session
+    ),
+  };
+
+  let body = hyper::body::to_bytes(req.into_body()).await?;
+
+  match run(&native, command, &body).await {
+    Ok(value) => json_response(StatusCode::OK, &json!({ "value": value })),
+    Err(e) => match e.downcast::<WebDriverError>() {
+      Ok(e) => e.into_response(),
+      Err(e) => Err(e),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{convert::Infallible, net::SocketAddr};
+
+  use hyper::{
+    service::{make_service_fn, service_fn},
+    Server,
+  };
+
+  use super::*;
+
+  #[test]
+  fn routes() {
+    let command = |method, path| route(&method, path);
+    assert!(matches!(
+      command(Method::POST, "/session/1/tauri/invoke"),
+      Some((session, Command::Invoke)) if session == "1"
+    ));
+    assert!(matches!(
+      command(Method::GET, "/session/1/tauri/windows"),
+      Some((_, Command::GetWindows))
+    ));
+    assert!(matches!(
+      command(Method::GET, "/session/1/tauri/events/3"),
+      Some((_, Command::TakeEvents(id))) if id == "3"
+    ));
+
+    assert!(command(Method::GET, "/session/1/tauri/invoke").is_none());
+    assert!(command(Method::GET, "/session/1/tauri/events/").is_none());
+    assert!(command(Method::GET, "/session/1/tauri/events/3/4").is_none());
+    assert!(command(Method::POST, "/session/1/url").is_none());
+    assert!(command(Method::POST, "/status").is_none());
+  }
+
+  #[test]
+  fn script_results() {
+    assert_eq!(script_result(json!({ "value": 1 })).unwrap(), json!(1));
+    assert_eq!(script_result(json!({})).unwrap(), Value::Null);
+
+    let error = script_result(json!({ "error": "failed" }))
+      .unwrap_err()
+      .downcast::<WebDriverError>()
+      .unwrap();
+    assert_eq!(error.status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(error.to_string(), "javascript error: failed");
+  }
+
+  /// Starts a native driver that resolves every script with its arguments.
+  async fn native_driver() -> SocketAddr {
+    let make_service = make_service_fn(|_| async {
+      Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+        let path = req.uri().path().to_string();
+        let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        let response = if path == "/session/1/execute/async" {
+          let body: Value = serde_json::from_slice(&body).unwrap();
+          json_response(
+            StatusCode::OK,
+            &json!({ "value": { "value": body["args"] } }),
+          )
+        } else {
+          json_response(
+            StatusCode::NOT_FOUND,
+            &json!({ "value": { "error": "unknown command", "message": path } }),
+          )
+        };
+        Ok::<_, Infallible>(response.unwrap())
+      }))
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    addr
+  }
+
+  async fn request(
+    native: SocketAddr,
+    method: Method,
+    path: &str,
+    body: Value,
+  ) -> (StatusCode, Value) {
+    let args = Args {
+      port: 4444,
+      native_port: native.port(),
+      native_host: "127.0.0.1".into(),
+      native_driver: None,
+    };
+    let req = Request::builder()
+      .method(method)
+      .uri(path)
+      .header(HOST, "127.0.0.1:4444")
+      .body(Body::from(body.to_string()))
+      .unwrap();
+    let res = handle(Client::new(), req, &args).await.unwrap();
+    let status = res.status();
+    let body = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+  }
+
+  #[tokio::test]
+  async fn translates_to_native_commands() {
+    let native = native_driver().await;
+
+    let (status, body) = request(
+      native,
+      Method::POST,
+      "/session/1/tauri/invoke",
+      json!({ "cmd": "greet", "args": { "name": "tauri" } }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "value": ["greet", { "name": "tauri" }] }));
+
+    let (status, body) = request(native, Method::POST, "/session/1/tauri/invoke", json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["value"]["error"], "invalid argument");
+
+    // native errors are forwarded as is
+    let (status, body) = request(
+      native,
+      Method::GET,
+      "/session/1/tauri/events/1",
+      Value::Null,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["value"]["error"], "unknown command");
+  }
+}
@@ -14,6 +14,7 @@
 )]
 
 mod cli;
+mod extension;
 mod server;
 mod webdriver;
 
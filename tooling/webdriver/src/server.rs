@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::cli::Args;
+use crate::extension;
 use anyhow::Error;
 use futures_util::TryFutureExt;
 use hyper::header::CONTENT_LENGTH;
@@ -59,6 +60,11 @@ async fn handle(
   mut req: Request<Body>,
   args: Args,
 ) -> Result<Response<Body>, Error> {
+  // tauri specific commands are translated to native commands instead of forwarded
+  if extension::is_extension_command(&req) {
+    return extension::handle(client, req, &args).await;
+  }
+
   // manipulate a new session to convert options to the native driver format
   if let (&Method::POST, "/session") = (req.method(), req.uri().path()) {
     let (mut parts, body) = req.into_parts();